@@ -2,6 +2,8 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, ItemFn};
 
+mod syntax;
+
 #[proc_macro_attribute]
 pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
@@ -24,13 +26,8 @@ pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
             let ctx = rust_jsc::JSContext::from(__ctx_ref);
             let function = rust_jsc::JSObject::from_ref(__function, __ctx_ref);
             let this_object = rust_jsc::JSObject::from_ref(__this_object, __ctx_ref);
-            let arguments = if __arguments.is_null() || __argument_count == 0 {
-                vec![]
-            } else {
-                unsafe { std::slice::from_raw_parts(__arguments, __argument_count) }
-                    .iter()
-                    .map(|__inner_value| rust_jsc::JSValue::new(*__inner_value, __ctx_ref))
-                    .collect::<Vec<_>>()
+            let arguments = unsafe {
+                rust_jsc::buffer::SmallBuffer::arguments(__ctx_ref, __arguments, __argument_count)
             };
 
             let func: fn(
@@ -44,7 +41,81 @@ pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 #fn_name ::<#generic_params>
             };
 
-            let result = func(ctx, function, this_object, arguments.as_slice());
+            let __span = rust_jsc::trace::CallSpan::enter(
+                &ctx,
+                rust_jsc::trace::CallDirection::JsToNative,
+                &function,
+                __argument_count,
+            );
+            let result = match ctx
+                .check_budget()
+                .and_then(|()| rust_jsc::recursion::RecursionGuard::enter(&ctx))
+            {
+                Ok(_recursion) => func(ctx, function, this_object, &arguments),
+                Err(exception) => Err(exception),
+            };
+
+            match result {
+                Ok(value) => {
+                    *__exception = std::ptr::null_mut();
+                    value.into()
+                }
+                Err(exception) => {
+                    *__exception = rust_jsc::internal::JSValueRef::from(exception) as *mut _;
+                    std::ptr::null_mut()
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_attribute]
+pub fn getter(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let fn_name = &input.sig.ident;
+    let visibility = &input.vis;
+    let generics = &input.sig.generics;
+    let generic_params = &generics.params;
+    let where_clause = &generics.where_clause;
+
+    let expanded = quote! {
+        #visibility unsafe extern "C" fn #fn_name <#generic_params> (
+            __ctx_ref: rust_jsc::internal::JSContextRef,
+            __function: rust_jsc::internal::JSObjectRef,
+            __this_object: rust_jsc::internal::JSObjectRef,
+            __argument_count: usize,
+            __arguments: *const rust_jsc::internal::JSValueRef,
+            __exception: *mut rust_jsc::internal::JSValueRef,
+        ) -> *const rust_jsc::internal::OpaqueJSValue
+        #where_clause {
+            let ctx = rust_jsc::JSContext::from(__ctx_ref);
+            let function = rust_jsc::JSObject::from_ref(__function, __ctx_ref);
+            let this_object = rust_jsc::JSObject::from_ref(__this_object, __ctx_ref);
+
+            let func: fn(
+                rust_jsc::JSContext,
+                rust_jsc::JSObject,
+            ) -> rust_jsc::JSResult<rust_jsc::JSValue> = {
+                #input
+
+                #fn_name ::<#generic_params>
+            };
+
+            let __span = rust_jsc::trace::CallSpan::enter(
+                &ctx,
+                rust_jsc::trace::CallDirection::JsToNative,
+                &function,
+                __argument_count,
+            );
+            let result = match ctx
+                .check_budget()
+                .and_then(|()| rust_jsc::recursion::RecursionGuard::enter(&ctx))
+            {
+                Ok(_recursion) => func(ctx, this_object),
+                Err(exception) => Err(exception),
+            };
 
             match result {
                 Ok(value) => {
@@ -62,6 +133,74 @@ pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+#[proc_macro_attribute]
+pub fn setter(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let fn_name = &input.sig.ident;
+    let visibility = &input.vis;
+    let generics = &input.sig.generics;
+    let generic_params = &generics.params;
+    let where_clause = &generics.where_clause;
+
+    let expanded = quote! {
+        #visibility unsafe extern "C" fn #fn_name <#generic_params> (
+            __ctx_ref: rust_jsc::internal::JSContextRef,
+            __function: rust_jsc::internal::JSObjectRef,
+            __this_object: rust_jsc::internal::JSObjectRef,
+            __argument_count: usize,
+            __arguments: *const rust_jsc::internal::JSValueRef,
+            __exception: *mut rust_jsc::internal::JSValueRef,
+        ) -> *const rust_jsc::internal::OpaqueJSValue
+        #where_clause {
+            let ctx = rust_jsc::JSContext::from(__ctx_ref);
+            let function = rust_jsc::JSObject::from_ref(__function, __ctx_ref);
+            let this_object = rust_jsc::JSObject::from_ref(__this_object, __ctx_ref);
+            let value = if __arguments.is_null() || __argument_count == 0 {
+                rust_jsc::JSValue::undefined(&ctx)
+            } else {
+                rust_jsc::JSValue::new(*__arguments, __ctx_ref)
+            };
+
+            let func: fn(
+                rust_jsc::JSContext,
+                rust_jsc::JSObject,
+                rust_jsc::JSValue,
+            ) -> rust_jsc::JSResult<()> = {
+                #input
+
+                #fn_name ::<#generic_params>
+            };
+
+            let __span = rust_jsc::trace::CallSpan::enter(
+                &ctx,
+                rust_jsc::trace::CallDirection::JsToNative,
+                &function,
+                __argument_count,
+            );
+            let result = match ctx
+                .check_budget()
+                .and_then(|()| rust_jsc::recursion::RecursionGuard::enter(&ctx))
+            {
+                Ok(_recursion) => func(ctx, this_object, value),
+                Err(exception) => Err(exception),
+            };
+
+            match result {
+                Ok(()) => {
+                    *__exception = std::ptr::null_mut();
+                    rust_jsc::internal::JSValueMakeUndefined(__ctx_ref)
+                }
+                Err(exception) => {
+                    *__exception = rust_jsc::internal::JSValueRef::from(exception) as *mut _;
+                    std::ptr::null_mut()
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
 #[proc_macro_attribute]
 pub fn constructor(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
@@ -82,13 +221,8 @@ pub fn constructor(_attr: TokenStream, item: TokenStream) -> TokenStream {
         #where_clause {
             let ctx = rust_jsc::JSContext::from(__ctx_ref);
             let constructor = rust_jsc::JSObject::from_ref(__constructor, __ctx_ref);
-            let arguments = if __arguments.is_null() || __argument_count == 0 {
-                vec![]
-            } else {
-                unsafe { std::slice::from_raw_parts(__arguments, __argument_count) }
-                    .iter()
-                    .map(|__inner_value| rust_jsc::JSValue::new(*__inner_value, __ctx_ref))
-                    .collect::<Vec<_>>()
+            let arguments = unsafe {
+                rust_jsc::buffer::SmallBuffer::arguments(__ctx_ref, __arguments, __argument_count)
             };
 
             let func: fn(
@@ -101,7 +235,19 @@ pub fn constructor(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 #fn_name ::<#generic_params>
             };
 
-            let result = func(ctx, constructor, arguments.as_slice());
+            let __span = rust_jsc::trace::CallSpan::enter(
+                &ctx,
+                rust_jsc::trace::CallDirection::JsToNative,
+                &constructor,
+                __argument_count,
+            );
+            let result = match ctx
+                .check_budget()
+                .and_then(|()| rust_jsc::recursion::RecursionGuard::enter(&ctx))
+            {
+                Ok(_recursion) => func(ctx, constructor, &arguments),
+                Err(exception) => Err(exception),
+            };
 
             match result {
                 Ok(value) => {
@@ -233,6 +379,154 @@ pub fn has_instance(_attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+#[proc_macro_attribute]
+pub fn has_property(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let fn_name = &input.sig.ident;
+    let visibility = &input.vis;
+    let generics = &input.sig.generics;
+    let generic_params = &generics.params;
+    let where_clause = &generics.where_clause;
+
+    let expanded = quote! {
+        #visibility unsafe extern "C" fn #fn_name <#generic_params> (
+            __ctx_ref: rust_jsc::internal::JSContextRef,
+            __object: rust_jsc::internal::JSObjectRef,
+            __property_name: rust_jsc::internal::JSStringRef,
+        ) -> bool
+        #where_clause {
+            let ctx = rust_jsc::JSContext::from(__ctx_ref);
+            let object = rust_jsc::JSObject::from_ref(__object, __ctx_ref);
+            let property_name = rust_jsc::JSString::from(
+                rust_jsc::internal::JSStringRetain(__property_name),
+            );
+
+            let func: fn(
+                rust_jsc::JSContext,
+                rust_jsc::JSObject,
+                rust_jsc::JSString,
+            ) -> bool = {
+                #input
+
+                #fn_name ::<#generic_params>
+            };
+
+            func(ctx, object, property_name)
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_attribute]
+pub fn get_property(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let fn_name = &input.sig.ident;
+    let visibility = &input.vis;
+    let generics = &input.sig.generics;
+    let generic_params = &generics.params;
+    let where_clause = &generics.where_clause;
+
+    let expanded = quote! {
+        #visibility unsafe extern "C" fn #fn_name <#generic_params> (
+            __ctx_ref: rust_jsc::internal::JSContextRef,
+            __object: rust_jsc::internal::JSObjectRef,
+            __property_name: rust_jsc::internal::JSStringRef,
+            __exception: *mut rust_jsc::internal::JSValueRef,
+        ) -> *const rust_jsc::internal::OpaqueJSValue
+        #where_clause {
+            let ctx = rust_jsc::JSContext::from(__ctx_ref);
+            let object = rust_jsc::JSObject::from_ref(__object, __ctx_ref);
+            let property_name = rust_jsc::JSString::from(
+                rust_jsc::internal::JSStringRetain(__property_name),
+            );
+
+            let func: fn(
+                rust_jsc::JSContext,
+                rust_jsc::JSObject,
+                rust_jsc::JSString,
+            ) -> rust_jsc::JSResult<Option<rust_jsc::JSValue>> = {
+                #input
+
+                #fn_name ::<#generic_params>
+            };
+
+            let result = func(ctx, object, property_name);
+
+            match result {
+                Ok(Some(value)) => {
+                    *__exception = std::ptr::null_mut();
+                    value.into()
+                }
+                Ok(None) => {
+                    *__exception = std::ptr::null_mut();
+                    std::ptr::null_mut()
+                }
+                Err(exception) => {
+                    *__exception = rust_jsc::internal::JSValueRef::from(exception) as *mut _;
+                    std::ptr::null_mut()
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_attribute]
+pub fn set_property(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemFn);
+    let fn_name = &input.sig.ident;
+    let visibility = &input.vis;
+    let generics = &input.sig.generics;
+    let generic_params = &generics.params;
+    let where_clause = &generics.where_clause;
+
+    let expanded = quote! {
+        #visibility unsafe extern "C" fn #fn_name <#generic_params> (
+            __ctx_ref: rust_jsc::internal::JSContextRef,
+            __object: rust_jsc::internal::JSObjectRef,
+            __property_name: rust_jsc::internal::JSStringRef,
+            __value: rust_jsc::internal::JSValueRef,
+            __exception: *mut rust_jsc::internal::JSValueRef,
+        ) -> bool
+        #where_clause {
+            let ctx = rust_jsc::JSContext::from(__ctx_ref);
+            let object = rust_jsc::JSObject::from_ref(__object, __ctx_ref);
+            let property_name = rust_jsc::JSString::from(
+                rust_jsc::internal::JSStringRetain(__property_name),
+            );
+            let value = rust_jsc::JSValue::new(__value, __ctx_ref);
+
+            let func: fn(
+                rust_jsc::JSContext,
+                rust_jsc::JSObject,
+                rust_jsc::JSString,
+                rust_jsc::JSValue,
+            ) -> rust_jsc::JSResult<bool> = {
+                #input
+
+                #fn_name ::<#generic_params>
+            };
+
+            let result = func(ctx, object, property_name, value);
+
+            match result {
+                Ok(handled) => {
+                    *__exception = std::ptr::null_mut();
+                    handled
+                }
+                Err(exception) => {
+                    *__exception = rust_jsc::internal::JSValueRef::from(exception) as *mut _;
+                    false
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
 #[proc_macro_attribute]
 pub fn module_resolve(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
@@ -260,14 +554,19 @@ pub fn module_resolve(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 rust_jsc::JSValue,
                 rust_jsc::JSValue,
                 rust_jsc::JSValue,
-            ) -> rust_jsc::JSStringRetain = {
+            ) -> _ = {
                 #input
 
                 #fn_name ::<#generic_params>
             };
 
-            let result = func(ctx, key_value, referrer, script_fetcher);
-            rust_jsc::internal::JSStringRef::from(result)
+            let __specifier = key_value.clone();
+            let __importer = referrer.clone();
+            rust_jsc::loader::resolve_output(
+                &__specifier,
+                &__importer,
+                func(ctx, key_value, referrer, script_fetcher),
+            )
         }
     };
 
@@ -336,14 +635,21 @@ pub fn module_fetch(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 rust_jsc::JSValue,
                 rust_jsc::JSValue,
                 rust_jsc::JSValue,
-            ) -> rust_jsc::JSStringRetain = {
+            ) -> _ = {
                 #input
 
                 #fn_name ::<#generic_params>
             };
 
-            let result = func(ctx, key_value, attributes_value, script_fetcher);
-            rust_jsc::internal::JSStringRef::from(result)
+            if let Some(source) = rust_jsc::loader::resolve_error_source(&key_value) {
+                return source;
+            }
+
+            let __key = key_value.clone();
+            rust_jsc::loader::fetch_output(
+                &__key,
+                func(ctx, key_value, attributes_value, script_fetcher),
+            )
         }
     };
 
@@ -425,7 +731,10 @@ pub fn uncaught_exception(_attr: TokenStream, item: TokenStream) -> TokenStream
 }
 
 #[proc_macro_attribute]
-pub fn uncaught_exception_event_loop(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn uncaught_exception_event_loop(
+    _attr: TokenStream,
+    item: TokenStream,
+) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
     let fn_name = &input.sig.ident;
     let visibility = &input.vis;
@@ -455,4 +764,132 @@ pub fn uncaught_exception_event_loop(_attr: TokenStream, item: TokenStream) -> T
     };
 
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Embeds a JavaScript file in the binary as a `rust_jsc::script::EmbeddedScript`.
+///
+/// The path is relative to the directory of the crate's `Cargo.toml`. Files ending
+/// in `.mjs` are embedded as modules, any other file as a classic script. The file
+/// must exist, be valid UTF-8 and not contain NUL characters, or compilation fails.
+///
+/// Scripts and modules are also tokenized at compile time: an unterminated string,
+/// comment, template or regular expression literal, or an unbalanced bracket, fails
+/// compilation with the file and line of the error. The rest of the grammar is only
+/// checked by the engine, see `EmbeddedScript::check_syntax`.
+#[proc_macro]
+pub fn include_js(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as syn::LitStr);
+    let relative = path.value();
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let full_path = std::path::Path::new(&root).join(&relative);
+
+    let source = match std::fs::read_to_string(&full_path) {
+        Ok(source) => source,
+        Err(error) => {
+            let message = format!("failed to read {}: {}", full_path.display(), error);
+            return syn::Error::new(path.span(), message)
+                .to_compile_error()
+                .into();
+        }
+    };
+    if let Some(offset) = source.find('\0') {
+        let message = format!(
+            "{} contains a NUL character at byte {}",
+            full_path.display(),
+            offset
+        );
+        return syn::Error::new(path.span(), message)
+            .to_compile_error()
+            .into();
+    }
+    if let Err(error) = syntax::check(&source) {
+        let message =
+            format!("{}:{}: {}", full_path.display(), error.line, error.message);
+        return syn::Error::new(path.span(), message)
+            .to_compile_error()
+            .into();
+    }
+
+    let module = full_path
+        .extension()
+        .is_some_and(|extension| extension == "mjs");
+    let full_path = full_path.to_string_lossy();
+    let expanded = quote! {
+        rust_jsc::script::EmbeddedScript::new(include_str!(#full_path), #relative, #module)
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Derives `rust_jsc::convert::JSEnum`, `IntoJSValue` and `FromJSValue` for an enum
+/// whose variants have no fields.
+///
+/// Variants convert to their numeric value, the discriminant, and are converted
+/// from either their name or their numeric value.
+#[proc_macro_derive(JSEnum)]
+pub fn derive_js_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    let enum_name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let data = match &input.data {
+        syn::Data::Enum(data) => data,
+        _ => {
+            return syn::Error::new(
+                enum_name.span(),
+                "JSEnum can only be derived for enums",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, syn::Fields::Unit))
+    {
+        return syn::Error::new(
+            variant.ident.span(),
+            "JSEnum variants cannot have fields",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let name = enum_name.to_string();
+    let variant_names: Vec<String> = data
+        .variants
+        .iter()
+        .map(|variant| variant.ident.to_string())
+        .collect();
+    let variants: Vec<&syn::Ident> =
+        data.variants.iter().map(|variant| &variant.ident).collect();
+    let indexes = 0..variants.len();
+
+    let expanded = quote! {
+        impl #impl_generics rust_jsc::convert::JSEnum for #enum_name #type_generics #where_clause {
+            const NAME: &'static str = #name;
+            const VARIANTS: &'static [(&'static str, i64)] = &[
+                #((#variant_names, Self::#variants as i64)),*
+            ];
+        }
+
+        impl #impl_generics rust_jsc::convert::IntoJSValue for #enum_name #type_generics #where_clause {
+            fn into_js_value(self, ctx: &rust_jsc::JSContext) -> rust_jsc::JSValue {
+                rust_jsc::JSValue::number(ctx, self as i64 as f64)
+            }
+        }
+
+        impl #impl_generics rust_jsc::convert::FromJSValue for #enum_name #type_generics #where_clause {
+            fn from_js_value(value: &rust_jsc::JSValue) -> rust_jsc::JSResult<Self> {
+                let index = <Self as rust_jsc::convert::JSEnum>::variant_index(value)?;
+                match index {
+                    #(#indexes => Ok(Self::#variants),)*
+                    _ => unreachable!("JSEnum variant index out of range"),
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
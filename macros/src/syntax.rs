@@ -0,0 +1,257 @@
+//! A lexical check of JavaScript sources for `include_js!`.
+//!
+//! The engine cannot run inside the compiler, so the check only tokenizes the
+//! source: it finds unterminated strings, comments, template and regular expression
+//! literals and unbalanced brackets, the same way for scripts and modules. The rest
+//! of the grammar is left to the engine when the script is evaluated.
+
+/// An error found in a source, with its line starting at 1.
+pub(crate) struct SyntaxError {
+    pub(crate) line: usize,
+    pub(crate) message: String,
+}
+
+enum Frame {
+    /// An open `(`, `[` or `{` and its line.
+    Bracket(char, usize),
+    /// The `${` of a template literal and the line the literal starts on.
+    Substitution(usize),
+}
+
+/// Keywords after which a `/` starts a regular expression rather than a division.
+const REGEX_KEYWORDS: &[&str] = &[
+    "await",
+    "case",
+    "delete",
+    "do",
+    "else",
+    "in",
+    "instanceof",
+    "new",
+    "of",
+    "return",
+    "throw",
+    "typeof",
+    "void",
+    "yield",
+];
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    frames: Vec<Frame>,
+    /// Whether a `/` at this point starts a regular expression.
+    regex_allowed: bool,
+}
+
+/// Checks that the strings, comments, literals and brackets of a source are closed.
+pub(crate) fn check(source: &str) -> Result<(), SyntaxError> {
+    let mut lexer = Lexer {
+        chars: source.chars().peekable(),
+        line: 1,
+        frames: Vec::new(),
+        regex_allowed: true,
+    };
+
+    if source.starts_with("#!") {
+        lexer.skip_line();
+    }
+
+    lexer.run()
+}
+
+impl Lexer<'_> {
+    fn error<T>(
+        &self,
+        line: usize,
+        message: impl Into<String>,
+    ) -> Result<T, SyntaxError> {
+        Err(SyntaxError {
+            line,
+            message: message.into(),
+        })
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_line(&mut self) {
+        while self.chars.next_if(|&c| c != '\n').is_some() {}
+    }
+
+    fn run(&mut self) -> Result<(), SyntaxError> {
+        while let Some(c) = self.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '/' if self.chars.next_if_eq(&'/').is_some() => self.skip_line(),
+                '/' if self.chars.next_if_eq(&'*').is_some() => self.block_comment()?,
+                '/' if self.regex_allowed => {
+                    self.regex()?;
+                    self.regex_allowed = false;
+                }
+                '\'' | '"' => {
+                    self.string(c)?;
+                    self.regex_allowed = false;
+                }
+                '`' => self.template(self.line)?,
+                '(' | '[' | '{' => {
+                    self.frames.push(Frame::Bracket(c, self.line));
+                    self.regex_allowed = true;
+                }
+                ')' | ']' | '}' => self.close(c)?,
+                c if c.is_ascii_digit() => {
+                    self.word();
+                    self.regex_allowed = false;
+                }
+                '.' if self.chars.peek().is_some_and(char::is_ascii_digit) => {
+                    self.word();
+                    self.regex_allowed = false;
+                }
+                c if c == '_'
+                    || c == '$'
+                    || c == '#'
+                    || c == '\\'
+                    || c.is_alphanumeric() =>
+                {
+                    let mut word = String::from(c);
+                    word.push_str(&self.word());
+                    self.regex_allowed = REGEX_KEYWORDS.contains(&word.as_str());
+                }
+                '+' | '-' if self.chars.next_if_eq(&c).is_some() => {
+                    // `i++ / 2` divides, a prefix `++` before a regex is not valid.
+                    self.regex_allowed = false;
+                }
+                _ => self.regex_allowed = true,
+            }
+        }
+
+        match self.frames.pop() {
+            Some(Frame::Bracket(open, line)) => {
+                self.error(line, format!("this `{}` is never closed", open))
+            }
+            Some(Frame::Substitution(line)) => {
+                self.error(line, "unterminated template literal")
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Consumes the rest of an identifier, keyword or number.
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|&c| c == '_' || c == '$' || c == '.' || c.is_alphanumeric())
+        {
+            word.push(c);
+        }
+        word
+    }
+
+    fn block_comment(&mut self) -> Result<(), SyntaxError> {
+        let start = self.line;
+        while let Some(c) = self.next() {
+            if c == '*' && self.chars.next_if_eq(&'/').is_some() {
+                return Ok(());
+            }
+        }
+
+        self.error(start, "unterminated comment")
+    }
+
+    fn string(&mut self, quote: char) -> Result<(), SyntaxError> {
+        let start = self.line;
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => {
+                    self.next();
+                }
+                '\n' => break,
+                c if c == quote => return Ok(()),
+                _ => {}
+            }
+        }
+
+        self.error(start, "unterminated string literal")
+    }
+
+    /// Scans a template literal up to its end or to a `${`, whose expression is then
+    /// tokenized like any other code until the matching `}`.
+    fn template(&mut self, start: usize) -> Result<(), SyntaxError> {
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => {
+                    self.next();
+                }
+                '`' => {
+                    self.regex_allowed = false;
+                    return Ok(());
+                }
+                '$' if self.chars.next_if_eq(&'{').is_some() => {
+                    self.frames.push(Frame::Substitution(start));
+                    self.regex_allowed = true;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        self.error(start, "unterminated template literal")
+    }
+
+    fn regex(&mut self) -> Result<(), SyntaxError> {
+        let start = self.line;
+        let mut class = false;
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => {
+                    self.next();
+                }
+                '\n' => break,
+                '[' => class = true,
+                ']' => class = false,
+                '/' if !class => {
+                    self.word();
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        self.error(start, "unterminated regular expression literal")
+    }
+
+    fn close(&mut self, close: char) -> Result<(), SyntaxError> {
+        let line = self.line;
+        match self.frames.pop() {
+            Some(Frame::Substitution(start)) if close == '}' => self.template(start),
+            Some(Frame::Bracket(open, _))
+                if matches!((open, close), ('(', ')') | ('[', ']') | ('{', '}')) =>
+            {
+                // A `/` after a block starts a regex, after a call or index it divides.
+                self.regex_allowed = close == '}';
+                Ok(())
+            }
+            Some(Frame::Bracket(open, start)) => self.error(
+                line,
+                format!(
+                    "unexpected `{}`, the `{}` on line {} is not closed",
+                    close, open, start
+                ),
+            ),
+            Some(Frame::Substitution(start)) => self.error(
+                line,
+                format!(
+                    "unexpected `{}`, the template literal on line {} is not closed",
+                    close, start
+                ),
+            ),
+            None => self.error(line, format!("unexpected `{}`", close)),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::cell::Cell;
+
+use crate::{convert::FromJSValue, JSContext, JSError, JSResult, JSValue};
+
+/// A view over the arguments of a native callback, with typed accessors that
+/// report errors mentioning the function name.
+///
+/// # Example
+/// ```rust,ignore
+/// use rust_jsc::{args::Args, callback, JSContext, JSObject, JSResult, JSValue};
+///
+/// #[callback]
+/// fn repeat(
+///     ctx: JSContext,
+///     _function: JSObject,
+///     _this: JSObject,
+///     arguments: &[JSValue],
+/// ) -> JSResult<JSValue> {
+///     let args = Args::new(&ctx, "repeat", arguments);
+///     let text = args.get::<String>(0)?;
+///     let count = args.get_opt::<u32>(1)?.unwrap_or(1);
+///     Ok(JSValue::string(&ctx, text.repeat(count as usize)))
+/// }
+/// ```
+pub struct Args<'a> {
+    ctx: &'a JSContext,
+    name: &'a str,
+    arguments: &'a [JSValue],
+    consumed: Cell<usize>,
+}
+
+impl<'a> Args<'a> {
+    /// Creates a new view over the arguments of the function `name`.
+    pub fn new(ctx: &'a JSContext, name: &'a str, arguments: &'a [JSValue]) -> Self {
+        Self {
+            ctx,
+            name,
+            arguments,
+            consumed: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of arguments.
+    pub fn len(&self) -> usize {
+        self.arguments.len()
+    }
+
+    /// Returns `true` if the function was called without arguments.
+    pub fn is_empty(&self) -> bool {
+        self.arguments.is_empty()
+    }
+
+    /// Returns the argument at `index`.
+    ///
+    /// # Errors
+    /// A `TypeError` if the function was called with fewer arguments.
+    pub fn require(&self, index: usize) -> JSResult<&'a JSValue> {
+        let value = self.arguments.get(index).ok_or_else(|| {
+            self.type_error(format!(
+                "{} requires at least {} argument{}, but only {} {} passed",
+                self.name,
+                index + 1,
+                if index == 0 { "" } else { "s" },
+                self.arguments.len(),
+                if self.arguments.len() == 1 {
+                    "was"
+                } else {
+                    "were"
+                },
+            ))
+        })?;
+
+        self.consume(index);
+        Ok(value)
+    }
+
+    /// Returns the argument at `index` converted to `T`.
+    ///
+    /// # Errors
+    /// A `TypeError` if the argument is missing or cannot be converted to `T`.
+    pub fn get<T: FromJSValue>(&self, index: usize) -> JSResult<T> {
+        let value = self.require(index)?;
+        self.convert(index, value)
+    }
+
+    /// Returns the argument at `index` converted to `T`, or `None` if it is
+    /// missing or `undefined`.
+    ///
+    /// # Errors
+    /// A `TypeError` if the argument cannot be converted to `T`.
+    pub fn get_opt<T: FromJSValue>(&self, index: usize) -> JSResult<Option<T>> {
+        self.consume(index);
+        match self.arguments.get(index) {
+            Some(value) if !value.is_undefined() => self.convert(index, value).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the arguments after the last one read with `require`, `get` or `get_opt`,
+    /// e.g. the rest parameters of a variadic function.
+    pub fn remaining(&self) -> &'a [JSValue] {
+        let consumed = self.consumed.get().min(self.arguments.len());
+        &self.arguments[consumed..]
+    }
+
+    /// Returns all the arguments.
+    pub fn as_slice(&self) -> &'a [JSValue] {
+        self.arguments
+    }
+
+    fn consume(&self, index: usize) {
+        if index + 1 > self.consumed.get() {
+            self.consumed.set(index + 1);
+        }
+    }
+
+    fn convert<T: FromJSValue>(&self, index: usize, value: &JSValue) -> JSResult<T> {
+        T::from_js_value(value).map_err(|error| {
+            let reason = error
+                .message()
+                .map(|message| message.to_string())
+                .unwrap_or_default();
+            self.type_error(format!("{}: argument {}: {}", self.name, index, reason))
+        })
+    }
+
+    fn type_error(&self, message: String) -> JSError {
+        match JSError::new_typ(self.ctx, message) {
+            Ok(error) => error,
+            Err(error) => error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let ctx = JSContext::new();
+        let arguments = [JSValue::string(&ctx, "a"), JSValue::number(&ctx, 3.0)];
+        let args = Args::new(&ctx, "repeat", &arguments);
+
+        assert_eq!(args.len(), 2);
+        assert_eq!(args.get::<String>(0).unwrap(), "a");
+        assert_eq!(args.get::<u32>(1).unwrap(), 3);
+        assert_eq!(args.get_opt::<u32>(2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_arity_error() {
+        let ctx = JSContext::new();
+        let arguments = [JSValue::string(&ctx, "a")];
+        let args = Args::new(&ctx, "repeat", &arguments);
+
+        let error = args.get::<u32>(1).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert_eq!(
+            error.message().unwrap().to_string(),
+            "repeat requires at least 2 arguments, but only 1 was passed"
+        );
+    }
+
+    #[test]
+    fn test_conversion_error() {
+        let ctx = JSContext::new();
+        let arguments = [JSValue::string(&ctx, "a")];
+        let args = Args::new(&ctx, "repeat", &arguments);
+
+        let error = args.get::<u32>(0).unwrap_err();
+        assert!(error
+            .message()
+            .unwrap()
+            .to_string()
+            .starts_with("repeat: argument 0:"));
+    }
+
+    #[test]
+    fn test_remaining() {
+        let ctx = JSContext::new();
+        let arguments = [
+            JSValue::string(&ctx, "%s %s"),
+            JSValue::number(&ctx, 1.0),
+            JSValue::number(&ctx, 2.0),
+        ];
+        let args = Args::new(&ctx, "format", &arguments);
+
+        assert_eq!(args.remaining().len(), 3);
+        args.get::<String>(0).unwrap();
+        assert_eq!(args.remaining().len(), 2);
+        args.get_opt::<f64>(5).unwrap();
+        assert!(args.remaining().is_empty());
+    }
+}
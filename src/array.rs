@@ -1,8 +1,10 @@
 use std::ops::Deref;
 
-use rust_jsc_sys::{JSObjectMakeArray, JSValueRef};
+use rust_jsc_sys::{JSObjectMakeArray, JSValueMakeNumber, JSValueRef};
 
-use crate::{JSArray, JSContext, JSError, JSObject, JSResult, JSValue};
+use crate::{
+    buffer::SmallBuffer, JSArray, JSContext, JSError, JSObject, JSResult, JSValue,
+};
 
 impl JSArray {
     pub fn new(object: JSObject) -> Self {
@@ -39,15 +41,74 @@ impl JSArray {
     /// The new `JSArray` object.
     pub fn new_array(ctx: &JSContext, args: &[JSValue]) -> JSResult<Self> {
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
 
         let result = unsafe {
             JSObjectMakeArray(ctx.inner, args.len(), args.as_ptr(), &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
+        }
+
+        Ok(Self::new(JSObject::from_ref(result, ctx.inner)))
+    }
+
+    /// Creates a new `JSArray` object from a slice of numbers in one call, without
+    /// wrapping each element in a `JSValue`.
+    ///
+    /// # Arguments
+    /// - `ctx`: The JavaScript context to create the array in.
+    /// - `values`: The numbers to initialize the array with.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{JSArray, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// let array = JSArray::from_f64_slice(&ctx, &[1.5, 2.0, 3.0]).unwrap();
+    /// assert_eq!(array.as_string().unwrap(), "1.5,2,3");
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the array.
+    /// A `JSError` will be returned.
+    ///
+    /// # Returns
+    /// The new `JSArray` object.
+    pub fn from_f64_slice(ctx: &JSContext, values: &[f64]) -> JSResult<Self> {
+        Self::from_numbers(ctx, values.iter().copied())
+    }
+
+    /// Creates a new `JSArray` object from a slice of `i32` numbers.
+    /// See [`JSArray::from_f64_slice`].
+    pub fn from_i32_slice(ctx: &JSContext, values: &[i32]) -> JSResult<Self> {
+        Self::from_numbers(ctx, values.iter().map(|value| f64::from(*value)))
+    }
+
+    /// Creates a new `JSArray` object from a slice of `u32` numbers.
+    /// See [`JSArray::from_f64_slice`].
+    pub fn from_u32_slice(ctx: &JSContext, values: &[u32]) -> JSResult<Self> {
+        Self::from_numbers(ctx, values.iter().map(|value| f64::from(*value)))
+    }
+
+    fn from_numbers(
+        ctx: &JSContext,
+        values: impl Iterator<Item = f64>,
+    ) -> JSResult<Self> {
+        // Numbers are not allocated on the heap, so the references stay valid while
+        // the buffer is built.
+        let values: Vec<JSValueRef> = values
+            .map(|value| unsafe { JSValueMakeNumber(ctx.inner, value) })
+            .collect();
+
+        let mut exception: JSValueRef = std::ptr::null_mut();
+        let result = unsafe {
+            JSObjectMakeArray(ctx.inner, values.len(), values.as_ptr(), &mut exception)
+        };
+
+        if !exception.is_null() {
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self::new(JSObject::from_ref(result, ctx.inner)))
@@ -228,6 +289,23 @@ mod tests {
         assert_eq!(array.as_string().unwrap(), "1,2,3");
     }
 
+    #[test]
+    fn test_array_from_slices() {
+        let ctx = JSContext::new();
+        let array = JSArray::from_f64_slice(&ctx, &[0.5, -1.0, f64::NAN]).unwrap();
+        assert_eq!(array.as_string().unwrap(), "0.5,-1,NaN");
+
+        let array = JSArray::from_i32_slice(&ctx, &[i32::MIN, 0]).unwrap();
+        assert_eq!(array.get(0).unwrap().as_number().unwrap(), i32::MIN as f64);
+
+        let array = JSArray::from_u32_slice(&ctx, &[u32::MAX]).unwrap();
+        assert_eq!(array.length().unwrap(), 1.0);
+        assert_eq!(array.get(0).unwrap().as_number().unwrap(), u32::MAX as f64);
+
+        let array = JSArray::from_f64_slice(&ctx, &[]).unwrap();
+        assert_eq!(array.length().unwrap(), 0.0);
+    }
+
     #[test]
     fn test_array_get() {
         let ctx = JSContext::new();
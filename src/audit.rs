@@ -0,0 +1,137 @@
+//! Stale value detection, enabled with the `value-audit` feature.
+//!
+//! Every context created by the crate gets a generation when it is created, and
+//! loses it when its global object is collected. Values are tagged with the generation
+//! of their context and checked against it when they are used, so a value used after
+//! its context was destroyed panics with a description of the bug instead of reading
+//! freed memory. Without the feature the functions do nothing.
+
+#[cfg(feature = "value-audit")]
+use rust_jsc_sys::{JSContextGetGlobalContext, JSContextRef};
+#[cfg(feature = "value-audit")]
+use std::{collections::HashMap, sync::Mutex};
+
+/// The context a value belongs to: its global context and that context's generation.
+#[cfg(feature = "value-audit")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tag {
+    global: usize,
+    generation: u64,
+}
+
+/// The generation of each live context created by the crate, by global context.
+#[cfg(feature = "value-audit")]
+static CONTEXTS: Mutex<Option<HashMap<usize, u64>>> = Mutex::new(None);
+
+/// Records the generation of a context the crate created.
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+pub(crate) fn context_created(global: usize, generation: u64) {
+    #[cfg(feature = "value-audit")]
+    if let Ok(mut contexts) = CONTEXTS.lock() {
+        contexts
+            .get_or_insert_with(HashMap::new)
+            .insert(global, generation);
+    }
+    #[cfg(not(feature = "value-audit"))]
+    let _ = (global, generation);
+}
+
+/// Forgets the generation of a context whose global object was collected.
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+pub(crate) fn context_finalized(global: usize, generation: u64) {
+    #[cfg(feature = "value-audit")]
+    if let Ok(mut contexts) = CONTEXTS.lock() {
+        let contexts = contexts.get_or_insert_with(HashMap::new);
+        // A newer context may have been created at the same address.
+        if contexts.get(&global) == Some(&generation) {
+            contexts.remove(&global);
+        }
+    }
+    #[cfg(not(feature = "value-audit"))]
+    let _ = (global, generation);
+}
+
+/// Returns the tag of a value created in the context.
+#[cfg(feature = "value-audit")]
+pub(crate) fn tag(ctx: JSContextRef) -> Tag {
+    if ctx.is_null() {
+        return Tag {
+            global: 0,
+            generation: 0,
+        };
+    }
+
+    let global = unsafe { JSContextGetGlobalContext(ctx) } as usize;
+    let generation = CONTEXTS
+        .lock()
+        .ok()
+        .and_then(|contexts| contexts.as_ref()?.get(&global).copied())
+        .unwrap_or(0);
+    Tag { global, generation }
+}
+
+/// Panics if the context of the value was destroyed since the value was created.
+/// Values of contexts the crate did not create, such as those wrapped with
+/// `JSContext::from`, have no generation and are not checked.
+#[cfg(feature = "value-audit")]
+pub(crate) fn check(tag: Tag, operation: &str) {
+    if tag.generation == 0 {
+        return;
+    }
+
+    let current = CONTEXTS
+        .lock()
+        .ok()
+        .and_then(|contexts| contexts.as_ref()?.get(&tag.global).copied());
+    if current != Some(tag.generation) {
+        panic!(
+            "{} used a value of context generation {} after the context was destroyed; \
+             keep a JSContext alive for as long as its values are used",
+            operation, tag.generation
+        );
+    }
+}
+
+/// Panics, with the `value-audit` feature, if the context of the value was destroyed.
+pub(crate) fn check_value(value: &crate::JSValue, operation: &str) {
+    #[cfg(feature = "value-audit")]
+    check(value.tag, operation);
+    #[cfg(not(feature = "value-audit"))]
+    let _ = (value, operation);
+}
+
+#[cfg(all(test, feature = "value-audit"))]
+mod tests {
+    use crate::{JSContext, JSValue};
+
+    #[test]
+    fn test_value_used_after_context() {
+        let ctx = JSContext::new();
+        let value = JSValue::number(&ctx, 42.0);
+        let handle = JSContext::from(ctx.inner);
+        drop(handle);
+        assert_eq!(value.as_number().unwrap(), 42.0);
+
+        drop(ctx);
+        let result = std::panic::catch_unwind(|| value.as_number());
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(
+            message.starts_with("JSValue::as_number used a value of context generation")
+        );
+    }
+
+    #[test]
+    fn test_transient_handles_keep_values_valid() {
+        let raw = unsafe { rust_jsc_sys::JSGlobalContextCreate(std::ptr::null_mut()) };
+        let value = {
+            let handle = JSContext::from(raw as rust_jsc_sys::JSContextRef);
+            JSValue::number(&handle, 7.0)
+        };
+        let handle = JSContext::from(raw as rust_jsc_sys::JSContextRef);
+        assert_eq!(value.as_number().unwrap(), 7.0);
+        drop(handle);
+        assert_eq!(value.as_number().unwrap(), 7.0);
+        unsafe { rust_jsc_sys::JSGlobalContextRelease(raw) };
+    }
+}
@@ -0,0 +1,228 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{JSArray, JSContext, JSFunction, JSObject, JSResult, JSValue};
+
+type BatchTask = Box<dyn FnOnce(&JSContext) -> JSResult<JSValue>>;
+
+enum Operation {
+    Evaluate(String),
+    Call {
+        function: JSObject,
+        this: Option<JSObject>,
+        arguments: Vec<JSValue>,
+    },
+    Task(Option<BatchTask>),
+}
+
+/// A list of operations queued with [`JSContext::batch`].
+///
+/// The queued values are protected from garbage collection until the batch runs.
+pub struct Batch {
+    operations: Vec<Operation>,
+}
+
+impl Batch {
+    /// Queues the evaluation of a script.
+    ///
+    /// # Arguments
+    /// - `script`: The source of the script.
+    pub fn eval(&mut self, script: impl Into<String>) -> &mut Self {
+        self.operations.push(Operation::Evaluate(script.into()));
+        self
+    }
+
+    /// Queues a call of a function.
+    ///
+    /// # Arguments
+    /// - `function`: The function to call.
+    /// - `this`: The `this` object, or `None` for `undefined`.
+    /// - `arguments`: The arguments to pass to the function.
+    pub fn call(
+        &mut self,
+        function: &JSObject,
+        this: Option<&JSObject>,
+        arguments: &[JSValue],
+    ) -> &mut Self {
+        function.protect();
+        if let Some(this) = this {
+            this.protect();
+        }
+        for argument in arguments {
+            argument.protect();
+        }
+
+        self.operations.push(Operation::Call {
+            function: function.clone(),
+            this: this.cloned(),
+            arguments: arguments.to_vec(),
+        });
+        self
+    }
+
+    /// Queues a host closure, for work that depends on the results of earlier operations.
+    ///
+    /// # Arguments
+    /// - `task`: The closure to run, its result is added to the results of the batch.
+    pub fn run<F>(&mut self, task: F) -> &mut Self
+    where
+        F: FnOnce(&JSContext) -> JSResult<JSValue> + 'static,
+    {
+        self.operations.push(Operation::Task(Some(Box::new(task))));
+        self
+    }
+
+    /// Returns the number of queued operations.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns true if no operation is queued.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+impl Operation {
+    fn execute(&mut self, ctx: &JSContext) -> JSResult<JSValue> {
+        match self {
+            Operation::Evaluate(script) => ctx.evaluate_script(script, None),
+            Operation::Call {
+                function,
+                this,
+                arguments,
+            } => function.call(this.as_ref(), arguments),
+            Operation::Task(task) => match task.take() {
+                Some(task) => task(ctx),
+                None => Ok(JSValue::undefined(ctx)),
+            },
+        }
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Operation::Call {
+            function,
+            this,
+            arguments,
+        } = self
+        {
+            function.unprotect();
+            if let Some(this) = this {
+                this.unprotect();
+            }
+            for argument in arguments.iter() {
+                argument.unprotect();
+            }
+        }
+    }
+}
+
+impl JSContext {
+    /// Queues operations with the closure and runs them all under one acquisition of
+    /// the virtual machine lock. The operations run from inside a native function, so
+    /// each of them re-enters the lock the batch already holds instead of taking it,
+    /// and pending promise jobs run once, after the last operation.
+    ///
+    /// # Arguments
+    /// - `queue`: A closure that queues the operations on the batch.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{JSContext, JSValue};
+    ///
+    /// let ctx = JSContext::new();
+    /// let results = ctx
+    ///     .batch(|b| {
+    ///         b.eval("globalThis.square = (n) => n * n");
+    ///         b.run(|ctx| ctx.evaluate_script("square(4)", None));
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(results[1].as_number().unwrap(), 16.0);
+    /// ```
+    ///
+    /// # Errors
+    /// The first exception thrown by an operation; the remaining operations do not run.
+    ///
+    /// # Returns
+    /// The result of each operation, in order.
+    pub fn batch<F>(&self, queue: F) -> JSResult<Vec<JSValue>>
+    where
+        F: FnOnce(&mut Batch),
+    {
+        let mut batch = Batch {
+            operations: Vec::new(),
+        };
+        queue(&mut batch);
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let operations = Rc::new(Cell::new(Some(batch.operations)));
+        let queued = operations.clone();
+        let function = JSFunction::closure(self, move |ctx, _this, _arguments| {
+            let results = JSArray::new_array(ctx, &[])?;
+            for mut operation in queued.take().unwrap_or_default() {
+                results.push(&operation.execute(ctx)?)?;
+            }
+
+            Ok(results.into())
+        });
+
+        let results = function.call(None, &[]);
+        // Unprotect the operations here rather than when the function is finalized.
+        drop(operations.take());
+        let results = JSArray::new(results?.as_object()?);
+        let length = results.length()? as u32;
+        (0..length).map(|index| results.get(index)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch() {
+        let ctx = JSContext::new();
+        let add = ctx.evaluate_script("(a, b) => a + b", None).unwrap();
+        let add = add.as_object().unwrap();
+
+        let results = ctx
+            .batch(|b| {
+                b.eval("globalThis.log = []; 1")
+                    .call(
+                        &add,
+                        None,
+                        &[JSValue::number(&ctx, 2.0), JSValue::number(&ctx, 3.0)],
+                    )
+                    .eval("Promise.resolve().then(() => log.push('job')); log.length")
+                    .run(|ctx| ctx.evaluate_script("log.length", None));
+            })
+            .unwrap();
+
+        let results: Vec<f64> = results
+            .iter()
+            .map(|value| value.as_number().unwrap())
+            .collect();
+        // Promise jobs run when the batch releases the lock.
+        assert_eq!(results, vec![1.0, 5.0, 0.0, 0.0]);
+        let log = ctx.evaluate_script("log.length", None).unwrap();
+        assert_eq!(log.as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_batch_error() {
+        let ctx = JSContext::new();
+        let result = ctx.batch(|b| {
+            b.eval("globalThis.ran = []")
+                .eval("throw new TypeError('stop')")
+                .eval("ran.push(1)");
+        });
+
+        let error = result.err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        let ran = ctx.evaluate_script("ran.length", None).unwrap();
+        assert_eq!(ran.as_number().unwrap(), 0.0);
+    }
+}
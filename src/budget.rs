@@ -0,0 +1,157 @@
+//! Execution budgets for the work done on behalf of one hook or evaluation, so an
+//! expensive one cannot starve the rest of the host loop.
+
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+use crate::{builtins::named_error, JSContext, JSResult};
+
+/// The innermost budget of a context, stored as a context extension.
+#[derive(Default)]
+struct ExecutionBudget {
+    deadline: Cell<Option<Instant>>,
+    budget: Cell<Duration>,
+}
+
+/// Restores the enclosing budget when a [`JSContext::with_budget`] call returns or
+/// unwinds.
+struct BudgetScope<'a> {
+    ctx: &'a JSContext,
+    deadline: Option<Instant>,
+    budget: Duration,
+}
+
+impl Drop for BudgetScope<'_> {
+    fn drop(&mut self) {
+        if let Some(current) = self.ctx.get_extension::<ExecutionBudget>() {
+            current.deadline.set(self.deadline);
+            current.budget.set(self.budget);
+        }
+    }
+}
+
+impl JSContext {
+    /// Runs `work` with an execution budget. Once the budget is exhausted, every
+    /// native function the script calls, whether defined with `#[callback]`,
+    /// `#[constructor]` or as a closure, throws a `TimeoutError` instead of running,
+    /// and [`JSContext::check_budget`] fails, so long host operations can stop too.
+    ///
+    /// Budgets nest: an inner budget never outlasts the one around it.
+    ///
+    /// The engine cannot interrupt a script that never calls into the host, such as
+    /// `while (true) {}`, so the budget only bounds scripts at their next native call.
+    ///
+    /// # Arguments
+    /// - `budget`: How long `work` may run.
+    /// - `work`: The work, e.g. an evaluation or a call to a hook.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let result = ctx.with_budget(Duration::from_millis(50), || {
+    ///     ctx.evaluate_script("onRequest(request)", None)
+    /// });
+    /// ```
+    pub fn with_budget<T>(&self, budget: Duration, work: impl FnOnce() -> T) -> T {
+        let current = match self.get_extension::<ExecutionBudget>() {
+            Some(current) => current,
+            None => {
+                self.set_extension(ExecutionBudget::default());
+                self.get_extension::<ExecutionBudget>()
+                    .expect("the budget extension was just set")
+            }
+        };
+
+        let _scope = BudgetScope {
+            ctx: self,
+            deadline: current.deadline.get(),
+            budget: current.budget.get(),
+        };
+        let deadline = Instant::now().checked_add(budget);
+        match (current.deadline.get(), deadline) {
+            (Some(outer), Some(inner)) if outer <= inner => {}
+            (Some(_), None) => {}
+            _ => {
+                current.deadline.set(deadline);
+                current.budget.set(budget);
+            }
+        }
+
+        work()
+    }
+
+    /// Returns the time left in the innermost budget, or `None` outside of
+    /// [`JSContext::with_budget`].
+    pub fn remaining_budget(&self) -> Option<Duration> {
+        let deadline = self.get_extension::<ExecutionBudget>()?.deadline.get()?;
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Checks that the budget of the current work is not exhausted.
+    ///
+    /// # Errors
+    /// A `TimeoutError` if the budget set by [`JSContext::with_budget`] is exhausted.
+    pub fn check_budget(&self) -> JSResult<()> {
+        let Some(current) = self.get_extension::<ExecutionBudget>() else {
+            return Ok(());
+        };
+        match current.deadline.get() {
+            Some(deadline) if Instant::now() >= deadline => {
+                let message = format!(
+                    "The execution budget of {} ms was exhausted",
+                    current.budget.get().as_millis()
+                );
+                Err(named_error(self, "TimeoutError", &message))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JSFunction, JSObject, JSValue};
+
+    #[test]
+    fn test_with_budget() {
+        let ctx = JSContext::new();
+        assert!(ctx.remaining_budget().is_none());
+        assert!(ctx.check_budget().is_ok());
+
+        let tick: JSObject = JSFunction::closure(&ctx, |ctx, _, _| {
+            std::thread::sleep(Duration::from_millis(5));
+            Ok(JSValue::undefined(ctx))
+        })
+        .into();
+        ctx.global_object()
+            .set_property("tick", &tick.into(), Default::default())
+            .unwrap();
+
+        let result = ctx.with_budget(Duration::from_millis(20), || {
+            ctx.evaluate_script("let calls = 0; while (true) { tick(); calls++; }", None)
+        });
+        let error = result.unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TimeoutError");
+        let calls = ctx.evaluate_script("calls", None).unwrap();
+        assert!(calls.as_number().unwrap() < 10.0);
+
+        ctx.evaluate_script("tick()", None).unwrap();
+        assert!(ctx.remaining_budget().is_none());
+
+        ctx.with_budget(Duration::from_secs(60), || {
+            ctx.with_budget(Duration::from_secs(3600), || {
+                assert!(ctx.remaining_budget().unwrap() <= Duration::from_secs(60));
+            });
+            ctx.with_budget(Duration::ZERO, || {
+                assert!(ctx.check_budget().is_err());
+            });
+            assert!(ctx.check_budget().is_ok());
+        });
+    }
+}
@@ -0,0 +1,125 @@
+//! Stack buffers for marshaling call arguments.
+//!
+//! Most calls into and out of JavaScript pass a handful of arguments, so the
+//! buffers keep up to [`INLINE_CAPACITY`] values inline and only allocate for
+//! longer argument lists. The generated callback shims use them, which is why the
+//! module is public.
+
+use std::ops::Deref;
+
+use rust_jsc_sys::{JSContextRef, JSValueRef};
+
+use crate::JSValue;
+
+/// The number of values a buffer keeps on the stack before spilling to the heap.
+pub const INLINE_CAPACITY: usize = 8;
+
+/// A list of values that lives on the stack when it is short.
+pub struct SmallBuffer<T>(Storage<T>);
+
+/// The values of a [`SmallBuffer`]. The inline length is never above
+/// [`INLINE_CAPACITY`], which is why the variants are private.
+enum Storage<T> {
+    Inline([T; INLINE_CAPACITY], usize),
+    Heap(Vec<T>),
+}
+
+impl<T: Clone> SmallBuffer<T> {
+    /// Collects `len` values, filling the unused inline slots with `filler`.
+    /// The filler is never exposed through the slice.
+    fn collect(len: usize, filler: T, values: impl Iterator<Item = T>) -> Self {
+        if len > INLINE_CAPACITY {
+            return Self(Storage::Heap(values.collect()));
+        }
+
+        let mut inline: [T; INLINE_CAPACITY] = std::array::from_fn(|_| filler.clone());
+        let mut count = 0;
+        for (slot, value) in inline.iter_mut().zip(values) {
+            *slot = value;
+            count += 1;
+        }
+
+        Self(Storage::Inline(inline, count))
+    }
+}
+
+impl<T> Deref for SmallBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match &self.0 {
+            Storage::Inline(values, len) => &values[..*len],
+            Storage::Heap(values) => values,
+        }
+    }
+}
+
+impl SmallBuffer<JSValue> {
+    /// Wraps the raw arguments a native callback receives.
+    ///
+    /// # Safety
+    /// `arguments` must be null or point to `argument_count` values, as passed by
+    /// JavaScriptCore to a callback.
+    pub unsafe fn arguments(
+        ctx: JSContextRef,
+        arguments: *const JSValueRef,
+        argument_count: usize,
+    ) -> Self {
+        if arguments.is_null() || argument_count == 0 {
+            return Self(Storage::Inline(
+                std::array::from_fn(|_| JSValue::new(std::ptr::null(), ctx)),
+                0,
+            ));
+        }
+
+        let values = std::slice::from_raw_parts(arguments, argument_count);
+        Self::collect(
+            argument_count,
+            JSValue::new(std::ptr::null(), ctx),
+            values.iter().map(|value| JSValue::new(*value, ctx)),
+        )
+    }
+}
+
+impl SmallBuffer<JSValueRef> {
+    /// Collects the references of the values, to pass them to the C API.
+    pub(crate) fn refs(values: &[JSValue]) -> Self {
+        Self::collect(
+            values.len(),
+            std::ptr::null(),
+            values.iter().map(|value| value.inner),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JSContext;
+
+    #[test]
+    fn test_small_buffer() {
+        let ctx = JSContext::new();
+        let values: Vec<JSValue> = (0..INLINE_CAPACITY + 1)
+            .map(|index| JSValue::number(&ctx, index as f64))
+            .collect();
+
+        let short = SmallBuffer::refs(&values[..3]);
+        assert!(matches!(short.0, Storage::Inline(_, 3)));
+        assert_eq!(short.len(), 3);
+        assert_eq!(short[2], values[2].inner);
+
+        let long = SmallBuffer::refs(&values);
+        assert!(matches!(long.0, Storage::Heap(_)));
+        assert_eq!(long.len(), INLINE_CAPACITY + 1);
+
+        let refs: Vec<JSValueRef> = values.iter().map(|value| value.inner).collect();
+        let arguments =
+            unsafe { SmallBuffer::arguments(ctx.inner, refs.as_ptr(), refs.len()) };
+        assert_eq!(arguments.len(), refs.len());
+        assert_eq!(arguments[4].as_number().unwrap(), 4.0);
+
+        let empty = unsafe { SmallBuffer::arguments(ctx.inner, std::ptr::null(), 0) };
+        assert!(empty.is_empty());
+    }
+}
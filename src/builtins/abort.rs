@@ -0,0 +1,459 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    context_data::ContextData, JSContext, JSFunction, JSObject, JSResult, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// Defines `AbortController` and `AbortSignal`. The bindings are created once per
+/// context and held by the crate, so signals made from Rust and from scripts share
+/// the same classes while `signalAbort` stays out of reach of scripts.
+const ABORT: &str = r#"(function () {
+    const illegal = Symbol("illegal");
+    const states = new WeakMap();
+
+    const abortError = () => {
+        const error = new Error("The operation was aborted.");
+        error.name = "AbortError";
+        return error;
+    };
+
+    const signalAbort = (signal, reason) => {
+        const state = states.get(signal);
+        if (state.aborted) {
+            return;
+        }
+        state.aborted = true;
+        state.reason = reason === undefined ? abortError() : reason;
+
+        const event = { type: "abort", target: signal, currentTarget: signal };
+        const listeners = [...state.listeners];
+        if (typeof signal.onabort === "function") {
+            listeners.unshift({ listener: signal.onabort, once: false });
+        }
+
+        let failure = null;
+        for (const entry of listeners) {
+            if (entry.once) {
+                signal.removeEventListener("abort", entry.listener);
+            }
+            try {
+                if (typeof entry.listener === "function") {
+                    entry.listener.call(signal, event);
+                } else {
+                    entry.listener.handleEvent(event);
+                }
+            } catch (error) {
+                failure ??= { error };
+            }
+        }
+
+        if (failure !== null) {
+            throw failure.error;
+        }
+    };
+
+    class AbortSignal {
+        constructor(token) {
+            if (token !== illegal) {
+                throw new TypeError("Illegal constructor");
+            }
+            states.set(this, { aborted: false, reason: undefined, listeners: [] });
+            this.onabort = null;
+        }
+
+        get aborted() {
+            return states.get(this).aborted;
+        }
+
+        get reason() {
+            return states.get(this).reason;
+        }
+
+        throwIfAborted() {
+            const state = states.get(this);
+            if (state.aborted) {
+                throw state.reason;
+            }
+        }
+
+        addEventListener(type, listener, options) {
+            if (type !== "abort" || listener === null || listener === undefined) {
+                return;
+            }
+            const listeners = states.get(this).listeners;
+            if (listeners.some((entry) => entry.listener === listener)) {
+                return;
+            }
+            const once = typeof options === "object" && options !== null && Boolean(options.once);
+            listeners.push({ listener, once });
+        }
+
+        removeEventListener(type, listener) {
+            if (type !== "abort") {
+                return;
+            }
+            const listeners = states.get(this).listeners;
+            const index = listeners.findIndex((entry) => entry.listener === listener);
+            if (index !== -1) {
+                listeners.splice(index, 1);
+            }
+        }
+
+        static abort(reason) {
+            const signal = new AbortSignal(illegal);
+            signalAbort(signal, reason);
+            return signal;
+        }
+
+        static any(signals) {
+            const signal = new AbortSignal(illegal);
+            for (const source of signals) {
+                if (source.aborted) {
+                    signalAbort(signal, source.reason);
+                    return signal;
+                }
+            }
+            for (const source of signals) {
+                source.addEventListener("abort", () => signalAbort(signal, source.reason), {
+                    once: true,
+                });
+            }
+            return signal;
+        }
+    }
+
+    class AbortController {
+        #signal = new AbortSignal(illegal);
+
+        get signal() {
+            return this.#signal;
+        }
+
+        abort(reason) {
+            signalAbort(this.#signal, reason);
+        }
+    }
+
+    return { AbortController, AbortSignal, signalAbort };
+})()"#;
+
+fn bindings(ctx: &JSContext) -> JSResult<JSObject> {
+    ContextData::shim(ctx, "abort", ABORT)
+}
+
+/// Installs the `AbortController` and `AbortSignal` globals in the context.
+///
+/// `AbortSignal.timeout` is not provided, as it needs timers from the embedder.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_abort(&ctx).unwrap();
+/// ctx.evaluate_script("new AbortController().signal.aborted", None).unwrap();
+/// ```
+///
+/// # Errors
+/// If the globals cannot be defined.
+pub fn install_abort(ctx: &JSContext) -> JSResult<()> {
+    let bindings = bindings(ctx)?;
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let global_object = ctx.global_object();
+    for name in ["AbortController", "AbortSignal"] {
+        global_object.set_property(name, &bindings.get_property(name)?, descriptor)?;
+    }
+
+    Ok(())
+}
+
+/// A JavaScript `AbortSignal`.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::builtins::{AbortSignal, AbortToken};
+/// use rust_jsc::JSContext;
+///
+/// let ctx = JSContext::new();
+/// let token = AbortToken::new();
+/// let signal = AbortSignal::from_rust(&ctx, &token).unwrap();
+/// ctx.global_object()
+///     .set_property("signal", signal.object(), Default::default())
+///     .unwrap();
+///
+/// // Scripts see the signal abort when the host cancels the token.
+/// token.abort().unwrap();
+/// assert!(signal.is_aborted().unwrap());
+/// ```
+#[derive(Clone)]
+pub struct AbortSignal {
+    object: JSObject,
+}
+
+impl AbortSignal {
+    /// Wraps an `AbortSignal` object, e.g. one passed to a native function.
+    pub fn from_object(object: JSObject) -> Self {
+        Self { object }
+    }
+
+    /// Creates a signal that aborts when the token is aborted.
+    /// If the token is already aborted, so is the signal.
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the signal.
+    pub fn from_rust(ctx: &JSContext, token: &AbortToken) -> JSResult<Self> {
+        let bindings = bindings(ctx)?;
+        let controller = bindings
+            .get_property("AbortController")?
+            .as_object()?
+            .call_as_constructor(&[])?;
+        let signal = controller.get_property("signal")?.as_object()?;
+
+        if token.is_aborted() {
+            abort_signal(&signal)?;
+        } else {
+            signal.protect();
+            token.state.borrow_mut().signals.push(signal.clone());
+        }
+
+        Ok(Self { object: signal })
+    }
+
+    /// Returns the signal object.
+    pub fn object(&self) -> &JSObject {
+        &self.object
+    }
+
+    /// Returns `true` if the signal has been aborted.
+    pub fn is_aborted(&self) -> JSResult<bool> {
+        Ok(self.object.get_property("aborted")?.as_boolean())
+    }
+
+    /// Returns the abort reason, or `undefined` if the signal has not been aborted.
+    pub fn reason(&self) -> JSResult<JSValue> {
+        self.object.get_property("reason")
+    }
+
+    /// Calls the listener with the abort reason when the signal is aborted.
+    ///
+    /// # Errors
+    /// If the listener cannot be added.
+    pub fn on_abort<F>(&self, listener: F) -> JSResult<()>
+    where
+        F: Fn(&JSContext, &JSValue) -> JSResult<()> + 'static,
+    {
+        let ctx = JSContext::from(self.object.ctx);
+        let listener = JSFunction::closure(&ctx, move |ctx, this, _arguments| {
+            listener(ctx, &this.get_property("reason")?)?;
+            Ok(JSValue::undefined(ctx))
+        });
+
+        self.object
+            .get_property("addEventListener")?
+            .as_object()?
+            .call(
+                Some(&self.object),
+                &[JSValue::string(&ctx, "abort"), listener.into()],
+            )?;
+        Ok(())
+    }
+}
+
+fn abort_signal(signal: &JSObject) -> JSResult<()> {
+    let ctx = JSContext::from(signal.ctx);
+    bindings(&ctx)?
+        .get_property("signalAbort")?
+        .as_object()?
+        .call(None, &[signal.clone().into()])?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct AbortTokenState {
+    aborted: bool,
+    signals: Vec<JSObject>,
+}
+
+impl Drop for AbortTokenState {
+    fn drop(&mut self) {
+        for signal in self.signals.drain(..) {
+            signal.unprotect();
+        }
+    }
+}
+
+/// A host-side cancellation token that propagates into JavaScript through
+/// [`AbortSignal::from_rust`], e.g. to cancel a `fetch` when the embedder shuts down.
+///
+/// Clones share the same state.
+#[derive(Clone, Default)]
+pub struct AbortToken {
+    state: Rc<RefCell<AbortTokenState>>,
+}
+
+impl AbortToken {
+    /// Creates a token that has not been aborted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the token has been aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.state.borrow().aborted
+    }
+
+    /// Aborts the token and every signal created from it, with an `AbortError`
+    /// as the reason. Aborting a token more than once does nothing.
+    ///
+    /// # Errors
+    /// The first exception thrown by an abort listener. All the signals are
+    /// aborted regardless.
+    pub fn abort(&self) -> JSResult<()> {
+        let signals = {
+            let mut state = self.state.borrow_mut();
+            if state.aborted {
+                return Ok(());
+            }
+
+            state.aborted = true;
+            std::mem::take(&mut state.signals)
+        };
+
+        let mut result = Ok(());
+        for signal in signals {
+            let aborted = abort_signal(&signal);
+            signal.unprotect();
+            if result.is_ok() {
+                result = aborted;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::builtins::{
+        install_fetch, HttpBackend, HttpRequest, HttpResponder, HttpResponse,
+    };
+
+    #[test]
+    fn test_abort_controller() {
+        let ctx = JSContext::new();
+        install_abort(&ctx).unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                const controller = new AbortController();
+                const events = [];
+                controller.signal.addEventListener("abort", (event) => events.push(event.type));
+                controller.signal.onabort = () => events.push("onabort");
+                controller.abort();
+                controller.abort();
+                let constructed = true;
+                try { new AbortSignal(); } catch (error) { constructed = false; }
+                events.join(",") + "|" + controller.signal.reason.name + "|" + constructed
+                    + "|" + AbortSignal.abort("why").reason
+            "#,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "onabort,abort|AbortError|false|why"
+        );
+    }
+
+    #[test]
+    fn test_bindings_hidden() {
+        let ctx = JSContext::new();
+        install_abort(&ctx).unwrap();
+        let token = AbortToken::new();
+        AbortSignal::from_rust(&ctx, &token).unwrap();
+
+        let result = ctx
+            .evaluate_script("globalThis[Symbol.for('rust_jsc.abort')]", None)
+            .unwrap();
+        assert!(result.is_undefined());
+    }
+
+    #[test]
+    fn test_from_rust() {
+        let ctx = JSContext::new();
+        let token = AbortToken::new();
+        let signal = AbortSignal::from_rust(&ctx, &token).unwrap();
+        ctx.global_object()
+            .set_property("signal", signal.object(), Default::default())
+            .unwrap();
+        ctx.evaluate_script(
+            "globalThis.seen = null; signal.addEventListener('abort', () => seen = signal.reason.name)",
+            None,
+        )
+        .unwrap();
+
+        let called = Rc::new(Cell::new(false));
+        let flag = called.clone();
+        signal
+            .on_abort(move |_ctx, reason| {
+                assert!(reason.is_object());
+                flag.set(true);
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(!signal.is_aborted().unwrap());
+        token.abort().unwrap();
+        assert!(token.is_aborted());
+        assert!(signal.is_aborted().unwrap());
+        assert!(called.get());
+
+        let seen = ctx.evaluate_script("seen", None).unwrap();
+        assert_eq!(seen.as_string().unwrap().to_string(), "AbortError");
+
+        let signal = AbortSignal::from_rust(&ctx, &token).unwrap();
+        assert!(signal.is_aborted().unwrap());
+    }
+
+    #[test]
+    fn test_abort_fetch() {
+        struct Pending(Rc<RefCell<Option<HttpResponder>>>);
+
+        impl HttpBackend for Pending {
+            fn fetch(&self, _request: HttpRequest, responder: HttpResponder) {
+                *self.0.borrow_mut() = Some(responder);
+            }
+        }
+
+        let ctx = JSContext::new();
+        let pending = Rc::new(RefCell::new(None));
+        install_fetch(&ctx, Pending(pending.clone())).unwrap();
+
+        let token = AbortToken::new();
+        let signal = AbortSignal::from_rust(&ctx, &token).unwrap();
+        ctx.global_object()
+            .set_property("signal", signal.object(), Default::default())
+            .unwrap();
+        ctx.evaluate_script(
+            r#"
+            globalThis.result = null;
+            fetch("https://example.com", { signal })
+                .then(() => { result = "resolved"; }, (error) => { result = error.name; });
+        "#,
+            None,
+        )
+        .unwrap();
+
+        token.abort().unwrap();
+        let responder = pending.borrow_mut().take().unwrap();
+        responder.respond(HttpResponse::new(200, "late")).unwrap();
+
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "AbortError");
+    }
+}
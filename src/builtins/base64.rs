@@ -0,0 +1,370 @@
+use super::named_error;
+use crate::{
+    JSContext, JSError, JSFunction, JSResult, JSString, JSTypedArray, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+const ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// An error returned when decoding malformed base64 or hex input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input contains a character outside of the alphabet, at the given byte offset.
+    InvalidCharacter(usize),
+    /// The input has a length that cannot be produced by the encoder.
+    InvalidLength,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter(offset) => {
+                write!(f, "Invalid character at offset {}", offset)
+            }
+            DecodeError::InvalidLength => write!(f, "Invalid input length"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encodes the bytes with the standard base64 alphabet, with padding.
+///
+/// # Example
+/// ```
+/// use rust_jsc::builtins::base64;
+///
+/// assert_eq!(base64::encode(b"hello"), "aGVsbG8=");
+/// ```
+pub fn encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = match chunk {
+            [a, b, c] => u32::from_be_bytes([0, *a, *b, *c]),
+            [a, b] => u32::from_be_bytes([0, *a, *b, 0]),
+            [a] => u32::from_be_bytes([0, *a, 0, 0]),
+            _ => unreachable!(),
+        };
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - index * 6)) & 0x3f;
+                output.push(ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+/// Decodes base64 input with the forgiving algorithm used by `atob`: ASCII
+/// whitespace is ignored and padding is optional.
+///
+/// # Example
+/// ```
+/// use rust_jsc::builtins::base64;
+///
+/// assert_eq!(base64::decode("aGVs bG8").unwrap(), b"hello");
+/// assert!(base64::decode("a").is_err());
+/// ```
+///
+/// # Errors
+/// A `DecodeError` if the input is not valid base64.
+pub fn decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut sextets = Vec::with_capacity(input.len());
+    let mut padding = 0;
+    for (offset, byte) in input.bytes().enumerate() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+
+        if byte == b'=' {
+            padding += 1;
+            continue;
+        }
+
+        let sextet = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(DecodeError::InvalidCharacter(offset)),
+        };
+
+        // Padding is only allowed at the end.
+        if padding > 0 {
+            return Err(DecodeError::InvalidCharacter(offset));
+        }
+
+        sextets.push(sextet);
+    }
+
+    if padding > 2 || (padding > 0 && (sextets.len() + padding) % 4 != 0) {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    if sextets.len() % 4 == 1 {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    let mut output = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, sextet)| {
+                group | (*sextet as u32) << (18 - index * 6)
+            });
+        let bytes = group.to_be_bytes();
+        output.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+
+    Ok(output)
+}
+
+/// Encodes the bytes as lowercase hexadecimal.
+///
+/// # Example
+/// ```
+/// use rust_jsc::builtins::base64;
+///
+/// assert_eq!(base64::encode_hex(&[0xca, 0xfe]), "cafe");
+/// ```
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut output = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        output.push(DIGITS[(byte >> 4) as usize] as char);
+        output.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+
+    output
+}
+
+/// Decodes hexadecimal input, in either case.
+///
+/// # Errors
+/// A `DecodeError` if the input is not valid hexadecimal.
+pub fn decode_hex(input: &str) -> Result<Vec<u8>, DecodeError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    let digit = |offset: usize, byte: u8| match byte {
+        b'0'..=b'9' => Ok(byte - b'0'),
+        b'a'..=b'f' => Ok(byte - b'a' + 10),
+        b'A'..=b'F' => Ok(byte - b'A' + 10),
+        _ => Err(DecodeError::InvalidCharacter(offset)),
+    };
+
+    input
+        .as_bytes()
+        .chunks(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let offset = index * 2;
+            Ok(digit(offset, pair[0])? << 4 | digit(offset + 1, pair[1])?)
+        })
+        .collect()
+}
+
+/// Installs the `atob` and `btoa` globals in the context.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_base64(&ctx).unwrap();
+/// let result = ctx.evaluate_script("atob(btoa('hello'))", None).unwrap();
+/// ```
+///
+/// # Errors
+/// If the globals cannot be defined.
+pub fn install_base64(ctx: &JSContext) -> JSResult<()> {
+    let atob = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let input = match arguments.first() {
+            Some(value) => value.as_string()?.to_string(),
+            None => return Err(JSError::new_typ(ctx, "atob requires 1 argument")?),
+        };
+
+        let bytes = decode(&input).map_err(|error| {
+            named_error(
+                ctx,
+                "InvalidCharacterError",
+                &format!(
+                    "The string to be decoded is not correctly encoded: {}",
+                    error
+                ),
+            )
+        })?;
+        // The result is a binary string, which may contain NUL characters.
+        let output: Vec<u16> = bytes.into_iter().map(u16::from).collect();
+        Ok(JSValue::string(ctx, JSString::from_utf16(&output)))
+    });
+
+    let btoa = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let input = match arguments.first() {
+            Some(value) => value.as_string()?.to_utf16(),
+            None => return Err(JSError::new_typ(ctx, "btoa requires 1 argument")?),
+        };
+
+        let bytes = input
+            .into_iter()
+            .map(u8::try_from)
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| {
+                named_error(
+                    ctx,
+                    "InvalidCharacterError",
+                    "The string to be encoded contains characters outside of the Latin1 range",
+                )
+            })?;
+        Ok(JSValue::string(ctx, encode(&bytes)))
+    });
+
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let global_object = ctx.global_object();
+    global_object.set_property("atob", &atob, descriptor)?;
+    global_object.set_property("btoa", &btoa, descriptor)
+}
+
+impl JSTypedArray {
+    /// Encodes the bytes of the typed array as base64.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{JSContext, JSTypedArray};
+    ///
+    /// let ctx = JSContext::new();
+    /// let typed_array = JSTypedArray::from_base64(&ctx, "aGVsbG8=").unwrap();
+    /// assert_eq!(typed_array.to_base64().unwrap(), "aGVsbG8=");
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading the bytes.
+    /// A `JSError` will be returned.
+    pub fn to_base64(&self) -> JSResult<String> {
+        Ok(encode(&self.to_bytes()?))
+    }
+
+    /// Decodes base64 input into a new `Uint8Array`.
+    ///
+    /// # Errors
+    /// A `SyntaxError` if the input is not valid base64.
+    pub fn from_base64(ctx: &JSContext, input: &str) -> JSResult<Self> {
+        let bytes = decode(input).map_err(|error| syntax_error(ctx, error))?;
+        Self::from_slice(ctx, &bytes)
+    }
+
+    /// Encodes the bytes of the typed array as lowercase hexadecimal.
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading the bytes.
+    /// A `JSError` will be returned.
+    pub fn to_hex(&self) -> JSResult<String> {
+        Ok(encode_hex(&self.to_bytes()?))
+    }
+
+    /// Decodes hexadecimal input into a new `Uint8Array`.
+    ///
+    /// # Errors
+    /// A `SyntaxError` if the input is not valid hexadecimal.
+    pub fn from_hex(ctx: &JSContext, input: &str) -> JSResult<Self> {
+        let bytes = decode_hex(input).map_err(|error| syntax_error(ctx, error))?;
+        Self::from_slice(ctx, &bytes)
+    }
+}
+
+fn syntax_error(ctx: &JSContext, error: DecodeError) -> JSError {
+    let message = JSValue::string(ctx, error.to_string());
+    let result = ctx
+        .global_object()
+        .get_property("SyntaxError")
+        .and_then(|constructor| constructor.as_object())
+        .and_then(|constructor| constructor.call_as_constructor(&[message]));
+
+    match result {
+        Ok(object) => JSError::from(object),
+        Err(error) => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(&[0xff, 0xfe]), "//4=");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("Zg==").unwrap(), b"f");
+        assert_eq!(decode("Zm8").unwrap(), b"fo");
+        assert_eq!(decode(" Zm9v\nYmFy ").unwrap(), b"foobar");
+        assert_eq!(decode("//4=").unwrap(), &[0xff, 0xfe]);
+        assert_eq!(decode("Zm9v!"), Err(DecodeError::InvalidCharacter(4)));
+        assert_eq!(decode("Z"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode("Zg="), Err(DecodeError::InvalidLength));
+        assert!(decode("Zg==Zg==").is_err());
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode_hex(&[0x00, 0xca, 0xfe]), "00cafe");
+        assert_eq!(decode_hex("00CAfe").unwrap(), &[0x00, 0xca, 0xfe]);
+        assert_eq!(decode_hex("abc"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode_hex("zz"), Err(DecodeError::InvalidCharacter(0)));
+    }
+
+    #[test]
+    fn test_atob_btoa() {
+        let ctx = JSContext::new();
+        install_base64(&ctx).unwrap();
+
+        let result = ctx.evaluate_script("btoa('hello')", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "aGVsbG8=");
+        let result = ctx.evaluate_script("atob('aGVsbG8=')", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "hello");
+
+        let result = ctx
+            .evaluate_script(
+                "atob(btoa('a\\0b')) === 'a\\0b' && atob('AP8=') === '\\0\\xff'",
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+
+        let result = ctx
+            .evaluate_script("try { btoa('\\u20ac') } catch (e) { e.name }", None)
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "InvalidCharacterError"
+        );
+    }
+
+    #[test]
+    fn test_typed_array_base64() {
+        let ctx = JSContext::new();
+        let typed_array = JSTypedArray::from_base64(&ctx, "aGVsbG8=").unwrap();
+        assert_eq!(typed_array.as_vec::<u8>().unwrap(), b"hello");
+        assert_eq!(typed_array.to_base64().unwrap(), "aGVsbG8=");
+        assert_eq!(typed_array.to_hex().unwrap(), "68656c6c6f");
+
+        let error = JSTypedArray::from_base64(&ctx, "!").unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "SyntaxError");
+    }
+}
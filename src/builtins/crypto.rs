@@ -0,0 +1,177 @@
+use super::named_error;
+use crate::{
+    JSContext, JSError, JSFunction, JSObject, JSResult, JSTypedArray, JSTypedArrayType,
+    JSValue, PropertyDescriptorBuilder,
+};
+
+/// The most bytes `getRandomValues` fills in one call, as in the Web Crypto specification.
+const MAX_RANDOM_BYTES: usize = 65536;
+
+/// Fills the buffer with bytes from the operating system's secure random source.
+#[cfg(unix)]
+fn fill_random(bytes: &mut [u8]) -> std::io::Result<()> {
+    use std::io::Read;
+
+    std::fs::File::open("/dev/urandom")?.read_exact(bytes)
+}
+
+#[cfg(not(unix))]
+fn fill_random(_bytes: &mut [u8]) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "no secure random source on this platform",
+    ))
+}
+
+/// Formats 16 random bytes as a version 4 UUID.
+fn format_uuid(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = super::base64::encode_hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn random_error(ctx: &JSContext, error: std::io::Error) -> JSError {
+    named_error(
+        ctx,
+        "OperationError",
+        &format!("Failed to read random bytes: {}", error),
+    )
+}
+
+/// Installs the `crypto` global with `getRandomValues` and `randomUUID`, backed by
+/// the operating system's secure random source.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_crypto(&ctx).unwrap();
+/// ctx.evaluate_script("crypto.getRandomValues(new Uint32Array(4))", None)
+///     .unwrap();
+/// ```
+///
+/// # Errors
+/// If the global cannot be defined.
+pub fn install_crypto(ctx: &JSContext) -> JSResult<()> {
+    let get_random_values = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let not_typed_array =
+            || JSError::new_typ(ctx, "getRandomValues requires an integer typed array");
+        let value = match arguments.first() {
+            Some(value) if value.is_object() => value,
+            _ => return Err(not_typed_array()?),
+        };
+
+        let typed_array = JSTypedArray::from_value(value)?;
+        match typed_array.array_type()? {
+            JSTypedArrayType::ArrayBuffer | JSTypedArrayType::None => {
+                return Err(not_typed_array()?);
+            }
+            JSTypedArrayType::Float32Array | JSTypedArrayType::Float64Array => {
+                return Err(named_error(
+                    ctx,
+                    "TypeMismatchError",
+                    "getRandomValues requires an integer typed array",
+                ));
+            }
+            _ => {}
+        }
+
+        let byte_len = typed_array.byte_len()?;
+        if byte_len > MAX_RANDOM_BYTES {
+            return Err(named_error(
+                ctx,
+                "QuotaExceededError",
+                &format!(
+                    "The typed array's byte length ({}) exceeds the number of bytes of entropy available via this API ({})",
+                    byte_len, MAX_RANDOM_BYTES
+                ),
+            ));
+        }
+
+        if byte_len > 0 {
+            fill_random(typed_array.bytes::<u8>()?)
+                .map_err(|error| random_error(ctx, error))?;
+        }
+
+        Ok(value.clone())
+    });
+
+    let random_uuid = JSFunction::closure(ctx, |ctx, _this, _arguments| {
+        let mut bytes = [0u8; 16];
+        fill_random(&mut bytes).map_err(|error| random_error(ctx, error))?;
+        Ok(JSValue::string(ctx, format_uuid(bytes)))
+    });
+
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let crypto = JSObject::new(ctx);
+    crypto.set_property("getRandomValues", &get_random_values, descriptor)?;
+    crypto.set_property("randomUUID", &random_uuid, descriptor)?;
+    ctx.global_object()
+        .set_property("crypto", &crypto, descriptor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_uuid() {
+        let uuid = format_uuid([0xff; 16]);
+        assert_eq!(uuid, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        assert_eq!(format_uuid([0; 16]), "00000000-0000-4000-8000-000000000000");
+    }
+
+    #[test]
+    fn test_get_random_values() {
+        let ctx = JSContext::new();
+        install_crypto(&ctx).unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                const array = new Uint32Array(64);
+                const same = crypto.getRandomValues(array) === array;
+                const filled = array.some((value) => value !== 0);
+                let mismatch = null;
+                try { crypto.getRandomValues(new Float64Array(1)); } catch (e) { mismatch = e.name; }
+                let quota = null;
+                try { crypto.getRandomValues(new Uint8Array(65537)); } catch (e) { quota = e.name; }
+                [same, filled, mismatch, quota].join(",")
+            "#,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "true,true,TypeMismatchError,QuotaExceededError"
+        );
+    }
+
+    #[test]
+    fn test_random_uuid() {
+        let ctx = JSContext::new();
+        install_crypto(&ctx).unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                const pattern = /^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/;
+                const first = crypto.randomUUID();
+                pattern.test(first) && first !== crypto.randomUUID()
+            "#,
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+    }
+}
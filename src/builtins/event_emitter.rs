@@ -0,0 +1,288 @@
+use crate::{JSContext, JSFunction, JSObject, JSResult, JSValue};
+
+/// Defines `on`, `once`, `off`, `emit` and `listenerCount` on the target object.
+/// Listeners live in a `Map` captured by the methods, so they are not reachable from
+/// the target itself.
+const EVENT_EMITTER: &str = r#"(function (target) {
+    const listeners = new Map();
+    const apply = Reflect.apply;
+    const define = (name, value) => {
+        Object.defineProperty(target, name, {
+            value,
+            writable: true,
+            configurable: true,
+            enumerable: false,
+        });
+    };
+
+    define("on", function on(event, listener) {
+        if (typeof listener !== "function") {
+            throw new TypeError("The listener must be a function");
+        }
+
+        let list = listeners.get(event);
+        if (list === undefined) {
+            list = [];
+            listeners.set(event, list);
+        }
+
+        list.push(listener);
+        return this;
+    });
+
+    define("once", function once(event, listener) {
+        if (typeof listener !== "function") {
+            throw new TypeError("The listener must be a function");
+        }
+
+        const wrapper = function (...args) {
+            target.off(event, wrapper);
+            return apply(listener, this, args);
+        };
+        wrapper.listener = listener;
+        return target.on(event, wrapper);
+    });
+
+    define("off", function off(event, listener) {
+        const list = listeners.get(event);
+        if (list === undefined) {
+            return this;
+        }
+
+        const index = list.findIndex(
+            (item) => item === listener || item.listener === listener,
+        );
+        if (index !== -1) {
+            list.splice(index, 1);
+        }
+        if (list.length === 0) {
+            listeners.delete(event);
+        }
+
+        return this;
+    });
+
+    define("emit", function emit(event, ...args) {
+        const list = listeners.get(event);
+        if (list === undefined) {
+            return false;
+        }
+
+        for (const listener of [...list]) {
+            apply(listener, target, args);
+        }
+
+        return true;
+    });
+
+    define("listenerCount", function listenerCount(event) {
+        const list = listeners.get(event);
+        return list === undefined ? 0 : list.length;
+    });
+
+    return target;
+})"#;
+
+/// An event emitter installed on a JavaScript object.
+///
+/// The object gets `on`, `once`, `off`, `emit` and `listenerCount` methods, with the
+/// same semantics as Node's `EventEmitter`, and listeners can be added and events
+/// emitted from both JavaScript and Rust.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins::EventEmitter, JSContext, JSObject, JSValue};
+///
+/// let ctx = JSContext::new();
+/// let process = JSObject::new(&ctx);
+/// let emitter = EventEmitter::install(&process).unwrap();
+/// ctx.global_object()
+///     .set_property("process", &process, Default::default())
+///     .unwrap();
+///
+/// ctx.evaluate_script("process.on('exit', (code) => console.log(code))", None)
+///     .unwrap();
+/// emitter.emit("exit", &[JSValue::number(&ctx, 0.0)]).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct EventEmitter {
+    target: JSObject,
+}
+
+impl EventEmitter {
+    /// Creates a new plain object with the event emitter methods.
+    ///
+    /// # Errors
+    /// If the methods cannot be installed.
+    pub fn new(ctx: &JSContext) -> JSResult<Self> {
+        Self::install(&JSObject::new(ctx))
+    }
+
+    /// Installs the event emitter methods on the given object.
+    ///
+    /// # Errors
+    /// If the methods cannot be installed, e.g. the object is frozen.
+    pub fn install(target: &JSObject) -> JSResult<Self> {
+        let ctx = JSContext::from(target.value.ctx);
+        let install = ctx.evaluate_script(EVENT_EMITTER, None)?.as_object()?;
+        install.call(None, std::slice::from_ref(&target.value))?;
+
+        Ok(Self {
+            target: target.clone(),
+        })
+    }
+
+    /// Returns the object the event emitter is installed on.
+    pub fn object(&self) -> &JSObject {
+        &self.target
+    }
+
+    /// Adds a Rust listener for the event.
+    ///
+    /// # Returns
+    /// The JavaScript function wrapping the listener, to be passed to [`EventEmitter::off`].
+    pub fn on<F>(&self, event: &str, listener: F) -> JSResult<JSFunction>
+    where
+        F: Fn(&JSContext, &[JSValue]) -> JSResult<()> + 'static,
+    {
+        let ctx = JSContext::from(self.target.value.ctx);
+        let listener = JSFunction::closure(&ctx, move |ctx, _this, arguments| {
+            listener(ctx, arguments)?;
+            Ok(JSValue::undefined(ctx))
+        });
+
+        self.call(
+            "on",
+            &[JSValue::string(&ctx, event), listener.clone().into()],
+        )?;
+        Ok(listener)
+    }
+
+    /// Adds a JavaScript listener for the event.
+    ///
+    /// # Errors
+    /// If `listener` is not a function.
+    pub fn add_listener(&self, event: &str, listener: &JSFunction) -> JSResult<()> {
+        let ctx = JSContext::from(self.target.value.ctx);
+        self.call(
+            "on",
+            &[JSValue::string(&ctx, event), listener.clone().into()],
+        )?;
+        Ok(())
+    }
+
+    /// Removes a listener added from JavaScript or Rust.
+    pub fn off(&self, event: &str, listener: &JSFunction) -> JSResult<()> {
+        let ctx = JSContext::from(self.target.value.ctx);
+        self.call(
+            "off",
+            &[JSValue::string(&ctx, event), listener.clone().into()],
+        )?;
+        Ok(())
+    }
+
+    /// Calls the listeners of the event, in the order they were added.
+    /// An exception thrown by a listener stops the emission and is returned.
+    ///
+    /// # Returns
+    /// `true` if the event had listeners, otherwise `false`.
+    pub fn emit(&self, event: &str, arguments: &[JSValue]) -> JSResult<bool> {
+        let ctx = JSContext::from(self.target.value.ctx);
+        let mut emit_arguments = Vec::with_capacity(arguments.len() + 1);
+        emit_arguments.push(JSValue::string(&ctx, event));
+        emit_arguments.extend_from_slice(arguments);
+
+        Ok(self.call("emit", &emit_arguments)?.as_boolean())
+    }
+
+    /// Returns the number of listeners of the event.
+    pub fn listener_count(&self, event: &str) -> JSResult<u32> {
+        let ctx = JSContext::from(self.target.value.ctx);
+        let count = self.call("listenerCount", &[JSValue::string(&ctx, event)])?;
+        Ok(count.as_number()? as u32)
+    }
+
+    fn call(&self, method: &str, arguments: &[JSValue]) -> JSResult<JSValue> {
+        self.target
+            .get_property(method)?
+            .as_object()?
+            .call(Some(&self.target), arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn test_emit_from_rust() {
+        let ctx = JSContext::new();
+        let emitter = EventEmitter::new(&ctx).unwrap();
+        let received = Rc::new(RefCell::new(Vec::new()));
+
+        let sink = received.clone();
+        let listener = emitter
+            .on("data", move |_ctx, arguments| {
+                sink.borrow_mut().push(arguments[0].as_number()?);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(emitter.listener_count("data").unwrap(), 1);
+
+        assert!(emitter.emit("data", &[JSValue::number(&ctx, 1.0)]).unwrap());
+        emitter.off("data", &listener).unwrap();
+        assert!(!emitter.emit("data", &[JSValue::number(&ctx, 2.0)]).unwrap());
+        assert_eq!(received.borrow().as_slice(), &[1.0]);
+    }
+
+    #[test]
+    fn test_emit_from_js() {
+        let ctx = JSContext::new();
+        let emitter = EventEmitter::new(&ctx).unwrap();
+        ctx.global_object()
+            .set_property("emitter", emitter.object(), Default::default())
+            .unwrap();
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        emitter
+            .on("message", move |_ctx, arguments| {
+                sink.borrow_mut()
+                    .push(arguments[0].as_string()?.to_string());
+                Ok(())
+            })
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                let count = 0;
+                emitter.once("message", () => count++);
+                emitter.emit("message", "first");
+                emitter.emit("message", "second");
+                count
+            "#,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(result.as_number().unwrap(), 1.0);
+        assert_eq!(received.borrow().as_slice(), &["first", "second"]);
+    }
+
+    #[test]
+    fn test_listener_error() {
+        let ctx = JSContext::new();
+        let emitter = EventEmitter::new(&ctx).unwrap();
+        let listener = ctx
+            .evaluate_script("(() => { throw new Error('boom'); })", None)
+            .unwrap();
+        emitter
+            .add_listener("data", &JSFunction::from(listener.as_object().unwrap()))
+            .unwrap();
+
+        assert!(emitter.emit("data", &[]).is_err());
+    }
+}
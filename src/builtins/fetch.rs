@@ -0,0 +1,675 @@
+use std::rc::Rc;
+
+use crate::{
+    JSArray, JSContext, JSError, JSFunction, JSObject, JSPromise,
+    JSPromiseResolvingFunctions, JSResult, JSString, JSTypedArray, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// Defines `Headers`, `Request`, `Response` and `fetch` on top of the native `send`
+/// function, which also receives the factory for the `Response` objects it resolves.
+/// Bodies are kept as `Uint8Array`s in a `WeakMap`, so they are not reachable from
+/// scripts except through the body methods.
+const FETCH: &str = r#"(function (send, encodeText, decodeText) {
+    const bodies = new WeakMap();
+    const headerLists = new WeakMap();
+
+    const normalizeName = (name) => {
+        name = String(name);
+        if (!/^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/.test(name)) {
+            throw new TypeError(`Invalid header name: '${name}'`);
+        }
+        return name.toLowerCase();
+    };
+    const normalizeValue = (value) => String(value).replace(/^[\t\n\r ]+|[\t\n\r ]+$/g, "");
+
+    class Headers {
+        constructor(init) {
+            headerLists.set(this, new Map());
+            if (init === undefined || init === null) {
+                return;
+            }
+            if (init instanceof Headers) {
+                init.forEach((value, name) => this.append(name, value));
+            } else if (typeof init[Symbol.iterator] === "function") {
+                for (const pair of init) {
+                    const [name, value, ...rest] = pair;
+                    if (rest.length !== 0 || pair.length !== 2) {
+                        throw new TypeError("Header pairs must contain exactly two items");
+                    }
+                    this.append(name, value);
+                }
+            } else {
+                for (const name of Object.keys(init)) {
+                    this.append(name, init[name]);
+                }
+            }
+        }
+
+        append(name, value) {
+            const list = headerLists.get(this);
+            name = normalizeName(name);
+            const values = list.get(name);
+            if (values === undefined) {
+                list.set(name, [normalizeValue(value)]);
+            } else {
+                values.push(normalizeValue(value));
+            }
+        }
+
+        set(name, value) {
+            headerLists.get(this).set(normalizeName(name), [normalizeValue(value)]);
+        }
+
+        get(name) {
+            const values = headerLists.get(this).get(normalizeName(name));
+            return values === undefined ? null : values.join(", ");
+        }
+
+        has(name) {
+            return headerLists.get(this).has(normalizeName(name));
+        }
+
+        delete(name) {
+            headerLists.get(this).delete(normalizeName(name));
+        }
+
+        forEach(callback, thisArg) {
+            for (const [name, value] of this) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries() {
+            const names = [...headerLists.get(this).keys()].sort();
+            for (const name of names) {
+                yield [name, this.get(name)];
+            }
+        }
+
+        *keys() {
+            for (const [name] of this.entries()) {
+                yield name;
+            }
+        }
+
+        *values() {
+            for (const [, value] of this.entries()) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+    }
+
+    const toBody = (body) => {
+        if (body === undefined || body === null) {
+            return null;
+        }
+        if (typeof body === "string") {
+            return encodeText(body);
+        }
+        if (ArrayBuffer.isView(body)) {
+            return new Uint8Array(body.buffer, body.byteOffset, body.byteLength).slice();
+        }
+        if (body instanceof ArrayBuffer) {
+            return new Uint8Array(body.slice(0));
+        }
+        if (typeof URLSearchParams === "function" && body instanceof URLSearchParams) {
+            return encodeText(body.toString());
+        }
+        return encodeText(String(body));
+    };
+
+    class Body {
+        #used = false;
+
+        get bodyUsed() {
+            return this.#used;
+        }
+
+        #consume() {
+            if (this.#used) {
+                return Promise.reject(new TypeError("Body has already been consumed"));
+            }
+            this.#used = true;
+            const bytes = bodies.get(this);
+            return Promise.resolve(bytes === null ? new Uint8Array(0) : bytes);
+        }
+
+        arrayBuffer() {
+            return this.#consume().then((bytes) => bytes.slice().buffer);
+        }
+
+        bytes() {
+            return this.#consume().then((bytes) => bytes.slice());
+        }
+
+        text() {
+            return this.#consume().then((bytes) => decodeText(bytes));
+        }
+
+        json() {
+            return this.text().then((text) => JSON.parse(text));
+        }
+    }
+
+    class Request extends Body {
+        constructor(input, init = {}) {
+            super();
+            const source = input instanceof Request ? input : null;
+            this.url = source === null ? String(input) : source.url;
+            this.method = String(init.method ?? source?.method ?? "GET").toUpperCase();
+            this.headers = new Headers(init.headers ?? source?.headers);
+            this.signal = init.signal ?? source?.signal ?? null;
+
+            let body = source === null ? null : bodies.get(source);
+            if (init.body !== undefined) {
+                body = toBody(init.body);
+            }
+            if (body !== null && (this.method === "GET" || this.method === "HEAD")) {
+                throw new TypeError("Request with GET/HEAD method cannot have body");
+            }
+            bodies.set(this, body);
+        }
+
+        clone() {
+            return new Request(this);
+        }
+    }
+
+    class Response extends Body {
+        constructor(body = null, init = {}) {
+            super();
+            const status = init.status ?? 200;
+            if (status < 200 || status > 599) {
+                throw new RangeError(`Invalid status code: ${status}`);
+            }
+            this.status = status;
+            this.statusText = String(init.statusText ?? "");
+            this.headers = new Headers(init.headers);
+            this.url = "";
+            this.type = "default";
+            bodies.set(this, toBody(body));
+        }
+
+        get ok() {
+            return this.status >= 200 && this.status <= 299;
+        }
+
+        clone() {
+            const response = new Response(bodies.get(this), {
+                statusText: this.statusText,
+                headers: this.headers,
+            });
+            response.status = this.status;
+            response.url = this.url;
+            response.type = this.type;
+            return response;
+        }
+
+        static json(data, init = {}) {
+            const headers = new Headers(init.headers);
+            if (!headers.has("content-type")) {
+                headers.set("content-type", "application/json");
+            }
+            return new Response(JSON.stringify(data), { ...init, headers });
+        }
+
+        static error() {
+            const response = new Response(null, { status: 200 });
+            response.status = 0;
+            response.type = "error";
+            return response;
+        }
+    }
+
+    function fetch(input, init) {
+        try {
+            const request = new Request(input, init);
+            const signal = request.signal;
+            if (signal !== null && signal.aborted) {
+                return Promise.reject(signal.reason);
+            }
+            const response = send(
+                request.method,
+                request.url,
+                [...request.headers],
+                bodies.get(request) ?? undefined,
+                makeResponse,
+            );
+            if (signal === null || typeof signal.addEventListener !== "function") {
+                return response;
+            }
+
+            // Settling later from the backend is a no-op once the signal has
+            // rejected the promise.
+            return new Promise((resolve, reject) => {
+                const onAbort = () => reject(signal.reason);
+                signal.addEventListener("abort", onAbort, { once: true });
+                response
+                    .then(resolve, reject)
+                    .finally(() => signal.removeEventListener("abort", onAbort));
+            });
+        } catch (error) {
+            return Promise.reject(error);
+        }
+    }
+
+    const makeResponse = (status, statusText, headers, body, url) => {
+        const response = new Response(null, { statusText, headers });
+        response.status = status;
+        response.url = url;
+        response.type = "basic";
+        bodies.set(response, body);
+        return response;
+    };
+
+    return { fetch, Headers, Request, Response };
+})"#;
+
+/// An HTTP request made by a script through `fetch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The upper-cased request method, e.g. `GET`.
+    pub method: String,
+    /// The URL, as passed to `fetch`.
+    pub url: String,
+    /// The headers, with lower-cased names, in sorted order.
+    pub headers: Vec<(String, String)>,
+    /// The request body, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+/// An HTTP response returned to a script by an [`HttpBackend`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// The final URL of the response, after redirects.
+    /// Leave empty to use the URL of the request.
+    pub url: String,
+}
+
+impl HttpResponse {
+    /// Creates a response with the status and body and no headers.
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a header to the response.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Performs the HTTP requests made with `fetch`.
+///
+/// The backend is implemented by the embedder, e.g. on top of reqwest or hyper, or
+/// as a stub in tests. Each request comes with an [`HttpResponder`] that settles the
+/// promise returned by `fetch`, either right away or later from the thread that
+/// runs the context, e.g. when the embedder's event loop completes the request.
+pub trait HttpBackend: 'static {
+    fn fetch(&self, request: HttpRequest, responder: HttpResponder);
+}
+
+/// Settles the promise of a `fetch` call.
+///
+/// Dropping the responder without calling [`HttpResponder::respond`] or
+/// [`HttpResponder::reject`] leaves the promise pending forever.
+pub struct HttpResponder {
+    resolver: JSPromiseResolvingFunctions,
+    make_response: JSObject,
+    url: String,
+}
+
+impl HttpResponder {
+    fn new(
+        resolver: JSPromiseResolvingFunctions,
+        make_response: JSObject,
+        url: String,
+    ) -> Self {
+        // The responder may outlive the call to `fetch`, so the functions it holds
+        // have to be protected from garbage collection until it is dropped.
+        resolver.resolve.protect();
+        resolver.reject.protect();
+        make_response.protect();
+
+        Self {
+            resolver,
+            make_response,
+            url,
+        }
+    }
+
+    /// Resolves the promise with a `Response` object.
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the `Response` object.
+    pub fn respond(self, response: HttpResponse) -> JSResult<()> {
+        let ctx = &JSContext::from(self.make_response.ctx);
+        let headers = response
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                JSArray::new_array(
+                    ctx,
+                    &[
+                        JSValue::string(ctx, name.as_str()),
+                        JSValue::string(ctx, value.as_str()),
+                    ],
+                )
+                .map(JSValue::from)
+            })
+            .collect::<JSResult<Vec<_>>>()?;
+
+        let url = if response.url.is_empty() {
+            &self.url
+        } else {
+            &response.url
+        };
+
+        let arguments = [
+            JSValue::number(ctx, response.status as f64),
+            JSValue::string(ctx, response.status_text),
+            JSArray::new_array(ctx, &headers)?.into(),
+            JSTypedArray::from_slice(ctx, &response.body)?.into(),
+            JSValue::string(ctx, url.as_str()),
+        ];
+        let response = self.make_response.call(None, &arguments)?;
+        self.resolver.resolve(None, &[response])?;
+        Ok(())
+    }
+
+    /// Rejects the promise with a `TypeError`, like a network error does.
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the error.
+    pub fn reject(self, message: &str) -> JSResult<()> {
+        let error = JSError::new_typ(
+            &JSContext::from(self.make_response.ctx),
+            format!("Failed to fetch: {}", message),
+        )?;
+        self.resolver.reject(None, &[error.into()])?;
+        Ok(())
+    }
+
+    /// Rejects the promise with the given value, e.g. the reason of an abort signal.
+    ///
+    /// # Errors
+    /// If an exception is thrown while rejecting the promise.
+    pub fn reject_with(self, reason: &JSValue) -> JSResult<()> {
+        self.resolver.reject(None, std::slice::from_ref(reason))?;
+        Ok(())
+    }
+}
+
+impl Drop for HttpResponder {
+    fn drop(&mut self) {
+        self.resolver.resolve.unprotect();
+        self.resolver.reject.unprotect();
+        self.make_response.unprotect();
+    }
+}
+
+fn string_pair(value: &JSValue) -> JSResult<(String, String)> {
+    let pair = value.as_object()?;
+    let name = pair.get_property_at_index(0)?.as_string()?.to_string();
+    let value = pair.get_property_at_index(1)?.as_string()?.to_string();
+    Ok((name, value))
+}
+
+/// Installs `fetch`, `Headers`, `Request` and `Response` globals in the context,
+/// with requests performed by the backend.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::builtins::{install_fetch, HttpBackend, HttpRequest, HttpResponder, HttpResponse};
+/// use rust_jsc::JSContext;
+///
+/// struct Stub;
+///
+/// impl HttpBackend for Stub {
+///     fn fetch(&self, request: HttpRequest, responder: HttpResponder) {
+///         responder.respond(HttpResponse::new(200, request.url)).unwrap();
+///     }
+/// }
+///
+/// let ctx = JSContext::new();
+/// install_fetch(&ctx, Stub).unwrap();
+/// ctx.evaluate_script("fetch('https://example.com').then((r) => r.text())", None)
+///     .unwrap();
+/// ```
+///
+/// # Errors
+/// If the globals cannot be defined.
+pub fn install_fetch(ctx: &JSContext, backend: impl HttpBackend) -> JSResult<()> {
+    let encode_text = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let text = match arguments.first() {
+            Some(value) => value.as_string()?.to_string(),
+            None => String::new(),
+        };
+
+        Ok(JSTypedArray::from_slice(ctx, text.as_bytes())?.into())
+    });
+
+    let decode_text = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let bytes = match arguments.first() {
+            Some(value) => JSTypedArray::from_value(value)?.to_bytes()?,
+            None => Vec::new(),
+        };
+
+        // The body may contain NUL characters, which `&str` conversions reject.
+        let text: Vec<u16> = String::from_utf8_lossy(&bytes).encode_utf16().collect();
+        Ok(JSValue::string(ctx, JSString::from_utf16(&text)))
+    });
+
+    let backend = Rc::new(backend);
+    let send = JSFunction::closure(ctx, move |ctx, _this, arguments| {
+        let (promise, resolver) = JSPromise::new_pending(ctx)?;
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| JSValue::undefined(ctx))
+        };
+
+        let headers = argument(2).as_object()?;
+        let length = headers.get_property("length")?.as_number()? as u32;
+        let headers = (0..length)
+            .map(|index| string_pair(&headers.get_property_at_index(index)?))
+            .collect::<JSResult<Vec<_>>>()?;
+
+        let body = argument(3);
+        let body = if body.is_undefined() {
+            None
+        } else {
+            Some(JSTypedArray::from_value(&body)?.to_bytes()?)
+        };
+
+        let request = HttpRequest {
+            method: argument(0).as_string()?.to_string(),
+            url: argument(1).as_string()?.to_string(),
+            headers,
+            body,
+        };
+
+        let responder =
+            HttpResponder::new(resolver, argument(4).as_object()?, request.url.clone());
+        backend.fetch(request, responder);
+
+        Ok(promise.into())
+    });
+
+    let globals = ctx
+        .evaluate_script(FETCH, None)?
+        .as_object()?
+        .call(None, &[send.into(), encode_text.into(), decode_text.into()])?
+        .as_object()?;
+
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let global_object = ctx.global_object();
+    for name in ["fetch", "Headers", "Request", "Response"] {
+        global_object.set_property(name, &globals.get_property(name)?, descriptor)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    struct Echo;
+
+    impl HttpBackend for Echo {
+        fn fetch(&self, request: HttpRequest, responder: HttpResponder) {
+            if request.url.starts_with("https://offline.test") {
+                responder.reject("offline").unwrap();
+                return;
+            }
+
+            let body = format!(
+                "{} {} {}",
+                request.method,
+                request.url,
+                String::from_utf8(request.body.unwrap_or_default()).unwrap()
+            );
+            let mut response = HttpResponse::new(201, body).header("x-echo", "yes");
+            if let Some((_, value)) =
+                request.headers.iter().find(|(name, _)| name == "x-request")
+            {
+                response = response.header("x-request", value.as_str());
+            }
+
+            responder.respond(response).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_fetch() {
+        let ctx = JSContext::new();
+        install_fetch(&ctx, Echo).unwrap();
+
+        ctx.evaluate_script(
+            r#"
+            globalThis.result = null;
+            fetch("https://example.com/items", {
+                method: "post",
+                headers: { "X-Request": "1" },
+                body: "payload",
+            }).then(async (response) => {
+                result = [
+                    response.status,
+                    response.ok,
+                    response.url,
+                    response.headers.get("x-echo"),
+                    response.headers.get("x-request"),
+                    await response.text(),
+                    response.bodyUsed,
+                ].join("|");
+            });
+        "#,
+            None,
+        )
+        .unwrap();
+
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "201|true|https://example.com/items|yes|1|POST https://example.com/items payload|true"
+        );
+    }
+
+    #[test]
+    fn test_fetch_network_error() {
+        let ctx = JSContext::new();
+        install_fetch(&ctx, Echo).unwrap();
+
+        ctx.evaluate_script(
+            r#"
+            globalThis.result = null;
+            fetch("https://offline.test").catch((error) => {
+                result = `${error.name}: ${error.message}`;
+            });
+        "#,
+            None,
+        )
+        .unwrap();
+
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "TypeError: Failed to fetch: offline"
+        );
+    }
+
+    #[test]
+    fn test_fetch_deferred() {
+        struct Deferred(Rc<RefCell<Vec<HttpResponder>>>);
+
+        impl HttpBackend for Deferred {
+            fn fetch(&self, _request: HttpRequest, responder: HttpResponder) {
+                self.0.borrow_mut().push(responder);
+            }
+        }
+
+        let ctx = JSContext::new();
+        let pending = Rc::new(RefCell::new(Vec::new()));
+        install_fetch(&ctx, Deferred(pending.clone())).unwrap();
+
+        ctx.evaluate_script(
+            r#"
+            globalThis.result = null;
+            fetch("https://example.com")
+                .then((response) => response.json())
+                .then((value) => { result = value.answer; });
+        "#,
+            None,
+        )
+        .unwrap();
+        assert!(ctx.evaluate_script("result", None).unwrap().is_null());
+
+        let responder = pending.borrow_mut().pop().unwrap();
+        responder
+            .respond(HttpResponse::new(200, r#"{"answer":42}"#))
+            .unwrap();
+
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_headers_and_response() {
+        let ctx = JSContext::new();
+        install_fetch(&ctx, Echo).unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                const headers = new Headers([["B", "2"], ["a", "1"]]);
+                headers.append("b", "3");
+                const response = Response.json({ ok: true }, { status: 202 });
+                [...headers].join(";") + "|" + response.headers.get("content-type")
+                    + "|" + response.status
+            "#,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "a,1;b,2, 3|application/json|202"
+        );
+    }
+}
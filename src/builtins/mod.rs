@@ -0,0 +1,44 @@
+//! Reusable host utilities that runtimes built on top of the crate commonly need.
+
+pub mod abort;
+pub mod base64;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod event_emitter;
+pub mod fetch;
+pub mod streams;
+pub mod structured_clone;
+#[cfg(feature = "text-encoding")]
+pub mod text_encoding;
+
+pub use abort::{install_abort, AbortSignal, AbortToken};
+pub use base64::install_base64;
+#[cfg(feature = "crypto")]
+pub use crypto::install_crypto;
+pub use event_emitter::EventEmitter;
+pub use fetch::{install_fetch, HttpBackend, HttpRequest, HttpResponder, HttpResponse};
+pub use streams::{
+    install_streams, PullHandle, PullRequest, ReadSource, ReadableStream,
+    ReadableStreamSource, WritableStream, WritableStreamSink, WriteHandle, WriteRequest,
+    WriteSink,
+};
+pub use structured_clone::install_structured_clone;
+#[cfg(feature = "text-encoding")]
+pub use text_encoding::install_text_encoding;
+
+use crate::{JSContext, JSError, JSValue};
+
+/// Creates an `Error` with the given name, for the `DOMException` names the web
+/// APIs throw, e.g. `InvalidCharacterError`.
+pub(crate) fn named_error(ctx: &JSContext, name: &str, message: &str) -> JSError {
+    let error = match JSError::with_message(ctx, message) {
+        Ok(error) => error,
+        Err(error) => return error,
+    };
+
+    let name = JSValue::string(ctx, name);
+    match error.object.set_property("name", &name, Default::default()) {
+        Ok(()) => error,
+        Err(error) => error,
+    }
+}
@@ -0,0 +1,1257 @@
+use std::{
+    cell::RefCell,
+    io::{self, Read, Write},
+    rc::Rc,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::{
+    context_data::ContextData,
+    promise::{run_blocking, Deferred, DeferredHandle},
+    JSContext, JSError, JSFunction, JSObject, JSPromise, JSResult, JSTypedArray, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// Defines `ReadableStream` and `WritableStream` with the queueing and backpressure
+/// of the WHATWG Streams standard: a readable stream pulls from its source only
+/// while its queue is below the high water mark, and a writable stream's
+/// `writer.ready` stays pending while its queue is full. Byte streams, tees and
+/// transform streams are not provided.
+const STREAMS: &str = r#"(() => {
+    const sizeOf = (strategy) => (typeof strategy.size === "function" ? strategy.size : () => 1);
+    const waiter = () => {
+        const entry = {};
+        entry.promise = new Promise((resolve, reject) => {
+            entry.resolve = resolve;
+            entry.reject = reject;
+        });
+        entry.promise.catch(() => {});
+        return entry;
+    };
+
+    class ReadableStream {
+        #source;
+        #controller;
+        #queue = [];
+        #queueSize = 0;
+        #highWaterMark;
+        #size;
+        #state = "readable";
+        #storedError;
+        #closeRequested = false;
+        #started = false;
+        #pulling = false;
+        #pullAgain = false;
+        #requests = [];
+        #locked = false;
+        #closed = waiter();
+
+        constructor(source = {}, strategy = {}) {
+            this.#source = source;
+            this.#highWaterMark = strategy.highWaterMark ?? 1;
+            this.#size = sizeOf(strategy);
+            const stream = this;
+            this.#controller = {
+                enqueue: (chunk) => stream.#enqueue(chunk),
+                close: () => stream.#close(),
+                error: (error) => stream.#error(error),
+                get desiredSize() {
+                    return stream.#desiredSize();
+                },
+            };
+
+            let started;
+            try {
+                started = source.start?.(this.#controller);
+            } catch (error) {
+                this.#error(error);
+                return;
+            }
+            Promise.resolve(started).then(() => {
+                this.#started = true;
+                this.#pullIfNeeded();
+            }, (error) => this.#error(error));
+        }
+
+        get locked() {
+            return this.#locked;
+        }
+
+        #desiredSize() {
+            if (this.#state === "errored") {
+                return null;
+            }
+            return this.#state === "closed" ? 0 : this.#highWaterMark - this.#queueSize;
+        }
+
+        #enqueue(chunk) {
+            if (this.#state !== "readable" || this.#closeRequested) {
+                throw new TypeError("The stream is not readable");
+            }
+            if (this.#requests.length > 0) {
+                this.#requests.shift().resolve({ value: chunk, done: false });
+            } else {
+                const size = this.#size(chunk);
+                this.#queue.push({ chunk, size });
+                this.#queueSize += size;
+            }
+            this.#pullIfNeeded();
+        }
+
+        #close() {
+            if (this.#state !== "readable" || this.#closeRequested) {
+                throw new TypeError("The stream is not readable");
+            }
+            this.#closeRequested = true;
+            if (this.#queue.length === 0) {
+                this.#finish();
+            }
+        }
+
+        #finish() {
+            this.#state = "closed";
+            for (const request of this.#requests.splice(0)) {
+                request.resolve({ value: undefined, done: true });
+            }
+            this.#closed.resolve();
+        }
+
+        #error(error) {
+            if (this.#state !== "readable") {
+                return;
+            }
+            this.#state = "errored";
+            this.#storedError = error;
+            this.#queue = [];
+            this.#queueSize = 0;
+            for (const request of this.#requests.splice(0)) {
+                request.reject(error);
+            }
+            this.#closed.reject(error);
+        }
+
+        #pullIfNeeded() {
+            if (!this.#started || this.#state !== "readable" || this.#closeRequested) {
+                return;
+            }
+            if (this.#requests.length === 0 && this.#desiredSize() <= 0) {
+                return;
+            }
+            if (this.#pulling) {
+                this.#pullAgain = true;
+                return;
+            }
+
+            this.#pulling = true;
+            let pulled;
+            try {
+                pulled = this.#source.pull?.(this.#controller);
+            } catch (error) {
+                this.#error(error);
+                return;
+            }
+            Promise.resolve(pulled).then(() => {
+                this.#pulling = false;
+                if (this.#pullAgain) {
+                    this.#pullAgain = false;
+                    this.#pullIfNeeded();
+                }
+            }, (error) => this.#error(error));
+        }
+
+        #read() {
+            if (this.#queue.length > 0) {
+                const { chunk, size } = this.#queue.shift();
+                this.#queueSize -= size;
+                if (this.#closeRequested && this.#queue.length === 0) {
+                    this.#finish();
+                } else {
+                    this.#pullIfNeeded();
+                }
+                return Promise.resolve({ value: chunk, done: false });
+            }
+            if (this.#state === "closed") {
+                return Promise.resolve({ value: undefined, done: true });
+            }
+            if (this.#state === "errored") {
+                return Promise.reject(this.#storedError);
+            }
+            return new Promise((resolve, reject) => {
+                this.#requests.push({ resolve, reject });
+                this.#pullIfNeeded();
+            });
+        }
+
+        #cancel(reason) {
+            if (this.#state === "closed") {
+                return Promise.resolve();
+            }
+            if (this.#state === "errored") {
+                return Promise.reject(this.#storedError);
+            }
+            this.#queue = [];
+            this.#queueSize = 0;
+            this.#finish();
+            try {
+                return Promise.resolve(this.#source.cancel?.(reason)).then(() => undefined);
+            } catch (error) {
+                return Promise.reject(error);
+            }
+        }
+
+        cancel(reason) {
+            if (this.#locked) {
+                return Promise.reject(new TypeError("The stream is locked"));
+            }
+            return this.#cancel(reason);
+        }
+
+        getReader() {
+            if (this.#locked) {
+                throw new TypeError("The stream is locked");
+            }
+            this.#locked = true;
+            const stream = this;
+            let released = false;
+            const releasedError = () => Promise.reject(new TypeError("The reader was released"));
+            return {
+                read: () => (released ? releasedError() : stream.#read()),
+                cancel: (reason) => (released ? releasedError() : stream.#cancel(reason)),
+                releaseLock() {
+                    if (!released) {
+                        released = true;
+                        stream.#locked = false;
+                    }
+                },
+                closed: stream.#closed.promise,
+            };
+        }
+
+        async pipeTo(destination) {
+            const reader = this.getReader();
+            const writer = destination.getWriter();
+            try {
+                while (true) {
+                    const { value, done } = await reader.read();
+                    if (done) {
+                        break;
+                    }
+                    await writer.ready;
+                    await writer.write(value);
+                }
+                await writer.close();
+            } catch (error) {
+                await writer.abort(error).catch(() => {});
+                await reader.cancel(error).catch(() => {});
+                throw error;
+            } finally {
+                reader.releaseLock();
+                writer.releaseLock();
+            }
+        }
+
+        async *values() {
+            const reader = this.getReader();
+            try {
+                while (true) {
+                    const { value, done } = await reader.read();
+                    if (done) {
+                        return;
+                    }
+                    yield value;
+                }
+            } finally {
+                reader.releaseLock();
+            }
+        }
+
+        [Symbol.asyncIterator]() {
+            return this.values();
+        }
+    }
+
+    class WritableStream {
+        #sink;
+        #controller;
+        #queue = [];
+        #queueSize = 0;
+        #highWaterMark;
+        #size;
+        #state = "writable";
+        #storedError;
+        #started = false;
+        #writing = false;
+        #closeRequest = null;
+        #locked = false;
+        #closed = waiter();
+        #ready = waiter();
+        #backpressure = false;
+
+        constructor(sink = {}, strategy = {}) {
+            this.#sink = sink;
+            this.#highWaterMark = strategy.highWaterMark ?? 1;
+            this.#size = sizeOf(strategy);
+            this.#controller = { error: (error) => this.#error(error) };
+            this.#ready.resolve();
+            this.#updateReady();
+
+            let started;
+            try {
+                started = sink.start?.(this.#controller);
+            } catch (error) {
+                this.#error(error);
+                return;
+            }
+            Promise.resolve(started).then(() => {
+                this.#started = true;
+                this.#advance();
+            }, (error) => this.#error(error));
+        }
+
+        get locked() {
+            return this.#locked;
+        }
+
+        #desiredSize() {
+            if (this.#state === "errored") {
+                return null;
+            }
+            return this.#state === "closed" ? 0 : this.#highWaterMark - this.#queueSize;
+        }
+
+        #updateReady() {
+            const backpressure = this.#state === "writable" && this.#desiredSize() <= 0;
+            if (backpressure && !this.#backpressure) {
+                this.#ready = waiter();
+            } else if (!backpressure && this.#backpressure) {
+                this.#ready.resolve();
+            }
+            this.#backpressure = backpressure;
+        }
+
+        #write(chunk) {
+            if (this.#state === "errored") {
+                return Promise.reject(this.#storedError);
+            }
+            if (this.#state !== "writable" || this.#closeRequest !== null) {
+                return Promise.reject(new TypeError("The stream is closing"));
+            }
+            let size;
+            try {
+                size = this.#size(chunk);
+            } catch (error) {
+                return Promise.reject(error);
+            }
+            return new Promise((resolve, reject) => {
+                this.#queue.push({ chunk, size, resolve, reject });
+                this.#queueSize += size;
+                this.#updateReady();
+                this.#advance();
+            });
+        }
+
+        #advance() {
+            if (!this.#started || this.#writing || this.#state !== "writable") {
+                return;
+            }
+            if (this.#queue.length === 0) {
+                if (this.#closeRequest !== null) {
+                    this.#finish();
+                }
+                return;
+            }
+
+            const { chunk, size, resolve, reject } = this.#queue[0];
+            this.#writing = true;
+            let written;
+            try {
+                written = this.#sink.write?.(chunk, this.#controller);
+            } catch (error) {
+                written = Promise.reject(error);
+            }
+            Promise.resolve(written).then(() => {
+                this.#writing = false;
+                this.#queue.shift();
+                this.#queueSize -= size;
+                resolve();
+                this.#updateReady();
+                this.#advance();
+            }, (error) => {
+                this.#writing = false;
+                this.#queue.shift();
+                reject(error);
+                this.#error(error);
+            });
+        }
+
+        #finish() {
+            this.#writing = true;
+            let closed;
+            try {
+                closed = this.#sink.close?.();
+            } catch (error) {
+                closed = Promise.reject(error);
+            }
+            Promise.resolve(closed).then(() => {
+                this.#writing = false;
+                this.#state = "closed";
+                this.#closeRequest.resolve();
+                this.#closed.resolve();
+            }, (error) => {
+                this.#writing = false;
+                this.#closeRequest.reject(error);
+                this.#error(error);
+            });
+        }
+
+        #error(error) {
+            if (this.#state !== "writable") {
+                return;
+            }
+            this.#state = "errored";
+            this.#storedError = error;
+            for (const entry of this.#queue.splice(0)) {
+                entry.reject(error);
+            }
+            this.#queueSize = 0;
+            if (this.#closeRequest !== null) {
+                this.#closeRequest.reject(error);
+            }
+            this.#closed.reject(error);
+            if (this.#backpressure) {
+                this.#ready.reject(error);
+            } else {
+                this.#ready = waiter();
+                this.#ready.reject(error);
+            }
+        }
+
+        #close() {
+            if (this.#state !== "writable" || this.#closeRequest !== null) {
+                return Promise.reject(new TypeError("The stream is closed or closing"));
+            }
+            const request = waiter();
+            this.#closeRequest = request;
+            this.#advance();
+            return request.promise;
+        }
+
+        #abort(reason) {
+            if (this.#state !== "writable") {
+                return Promise.resolve();
+            }
+            this.#error(reason);
+            try {
+                return Promise.resolve(this.#sink.abort?.(reason)).then(() => undefined);
+            } catch (error) {
+                return Promise.reject(error);
+            }
+        }
+
+        abort(reason) {
+            if (this.#locked) {
+                return Promise.reject(new TypeError("The stream is locked"));
+            }
+            return this.#abort(reason);
+        }
+
+        close() {
+            if (this.#locked) {
+                return Promise.reject(new TypeError("The stream is locked"));
+            }
+            return this.#close();
+        }
+
+        getWriter() {
+            if (this.#locked) {
+                throw new TypeError("The stream is locked");
+            }
+            this.#locked = true;
+            const stream = this;
+            let released = false;
+            const releasedError = () => Promise.reject(new TypeError("The writer was released"));
+            return {
+                write: (chunk) => (released ? releasedError() : stream.#write(chunk)),
+                close: () => (released ? releasedError() : stream.#close()),
+                abort: (reason) => (released ? releasedError() : stream.#abort(reason)),
+                releaseLock() {
+                    if (!released) {
+                        released = true;
+                        stream.#locked = false;
+                    }
+                },
+                get ready() {
+                    return stream.#ready.promise;
+                },
+                get desiredSize() {
+                    return stream.#desiredSize();
+                },
+                closed: stream.#closed.promise,
+            };
+        }
+    }
+
+    const toBytes = (chunk) => {
+        if (ArrayBuffer.isView(chunk)) {
+            return new Uint8Array(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+        }
+        if (chunk instanceof ArrayBuffer) {
+            return new Uint8Array(chunk);
+        }
+        throw new TypeError("The chunk must be an ArrayBuffer or an ArrayBuffer view");
+    };
+
+    // Adapts the host functions of a Rust source: `pull` returns a promise of a chunk
+    // or null, and the stream waits for it before pulling again.
+    const hostSource = (pull, cancel, highWaterMark) => new ReadableStream({
+        pull(controller) {
+            return pull().then((chunk) => {
+                if (chunk === null) {
+                    controller.close();
+                } else {
+                    controller.enqueue(chunk);
+                }
+            });
+        },
+        cancel,
+    }, { highWaterMark });
+
+    // Adapts the host functions of a Rust sink: `write` and `close` return promises.
+    const hostSink = (write, close, abort, highWaterMark) => new WritableStream({
+        write: (chunk) => write(toBytes(chunk)),
+        close,
+        abort,
+    }, { highWaterMark });
+
+    return { ReadableStream, WritableStream, hostSource, hostSink, toBytes };
+})()"#;
+
+/// Returns the classes and host adapters of [`STREAMS`], evaluated once per context
+/// and held by the crate, so scripts cannot reach or replace the adapters.
+fn bindings(ctx: &JSContext) -> JSResult<JSObject> {
+    ContextData::shim(ctx, "streams", STREAMS)
+}
+
+fn io_error(ctx: &JSContext, error: io::Error) -> JSError {
+    match JSError::with_message(ctx, error.to_string()) {
+        Ok(error) => error,
+        Err(error) => error,
+    }
+}
+
+/// Installs the `ReadableStream` and `WritableStream` globals in the context.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_streams(&ctx).unwrap();
+/// ctx.evaluate_script("new ReadableStream().locked", None).unwrap();
+/// ```
+///
+/// # Errors
+/// If the globals cannot be defined.
+pub fn install_streams(ctx: &JSContext) -> JSResult<()> {
+    let bindings = bindings(ctx)?;
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let global_object = ctx.global_object();
+    for name in ["ReadableStream", "WritableStream"] {
+        global_object.set_property(name, &bindings.get_property(name)?, descriptor)?;
+    }
+
+    Ok(())
+}
+
+/// The Rust side of a [`ReadableStream`], which produces the chunks scripts read.
+///
+/// Sources are asynchronous: `pull` answers a [`PullRequest`] whenever the chunk is
+/// ready, right away or later from another thread through [`PullRequest::send`], so
+/// waiting for I/O does not block the context thread.
+pub trait ReadableStreamSource: 'static {
+    /// Requests the next chunk, to be answered through the request.
+    ///
+    /// The stream calls it only while its queue is below the high water mark, and
+    /// not again until the request is answered, so a script that stops reading
+    /// stops the source.
+    fn pull(&mut self, ctx: &JSContext, request: PullRequest);
+
+    /// Called when a script cancels the stream; no more chunks are pulled.
+    fn cancel(&mut self, _ctx: &JSContext) {}
+}
+
+/// The Rust side of a [`WritableStream`], which receives the chunks scripts write.
+///
+/// Sinks are asynchronous: each write is done once its [`WriteRequest`] is
+/// answered, right away or later from another thread through
+/// [`WriteRequest::send`].
+pub trait WritableStreamSink: 'static {
+    /// Consumes the next chunk. The chunks are written one at a time, in order, and
+    /// the writes that wait meanwhile count toward the high water mark, which
+    /// scripts see through `writer.ready`. An error errors the stream.
+    fn write(&mut self, ctx: &JSContext, chunk: Vec<u8>, request: WriteRequest);
+
+    /// Called once all the chunks are written and a script closes the stream. An
+    /// error rejects the close.
+    fn close(&mut self, ctx: &JSContext, request: WriteRequest) {
+        let _ = request.settle(ctx, Ok(()));
+    }
+
+    /// Called when a script aborts the stream; the pending chunks are discarded.
+    fn abort(&mut self, _ctx: &JSContext) {}
+}
+
+/// A pending pull of a [`ReadableStreamSource`]. Until it is answered the stream
+/// pulls no more; dropping it leaves the stream waiting.
+#[derive(Debug)]
+pub struct PullRequest {
+    deferred: Deferred,
+}
+
+impl PullRequest {
+    /// Answers the pull with the next chunk, `None` to close the stream, or an
+    /// error to error it.
+    ///
+    /// # Errors
+    /// If the chunk cannot be created or settling the pull throws.
+    pub fn settle(
+        self,
+        ctx: &JSContext,
+        chunk: JSResult<Option<Vec<u8>>>,
+    ) -> JSResult<()> {
+        match chunk.and_then(|chunk| chunk_value(ctx, chunk)) {
+            Ok(chunk) => self.deferred.resolve(&chunk),
+            Err(error) => self.deferred.reject(&error.into()),
+        }
+    }
+
+    /// Returns a `Send` handle to answer the pull from another thread. The answer
+    /// takes effect the next time [`JSContext::run_deferred`] is called.
+    pub fn send(self) -> PullHandle {
+        PullHandle(self.deferred.handle())
+    }
+}
+
+/// A [`PullRequest`] sent to another thread.
+#[derive(Debug)]
+pub struct PullHandle(DeferredHandle);
+
+impl PullHandle {
+    /// Answers the pull with the next chunk, `None` to close the stream, or an
+    /// error to error it.
+    pub fn settle(self, chunk: io::Result<Option<Vec<u8>>>) {
+        self.0.settle(move |ctx| match chunk {
+            Ok(chunk) => chunk_value(ctx, chunk),
+            Err(error) => Err(io_error(ctx, error)),
+        });
+    }
+}
+
+/// A pending write or close of a [`WritableStreamSink`]. Until it is answered the
+/// stream writes no more; dropping it leaves the stream waiting.
+#[derive(Debug)]
+pub struct WriteRequest {
+    deferred: Deferred,
+}
+
+impl WriteRequest {
+    /// Completes the write, or fails it with the error.
+    ///
+    /// # Errors
+    /// If settling the write throws.
+    pub fn settle(self, ctx: &JSContext, result: JSResult<()>) -> JSResult<()> {
+        match result {
+            Ok(()) => self.deferred.resolve(&JSValue::undefined(ctx)),
+            Err(error) => self.deferred.reject(&error.into()),
+        }
+    }
+
+    /// Returns a `Send` handle to complete the write from another thread. The
+    /// result takes effect the next time [`JSContext::run_deferred`] is called.
+    pub fn send(self) -> WriteHandle {
+        WriteHandle(self.deferred.handle())
+    }
+}
+
+/// A [`WriteRequest`] sent to another thread.
+#[derive(Debug)]
+pub struct WriteHandle(DeferredHandle);
+
+impl WriteHandle {
+    /// Completes the write, or fails it with the error.
+    pub fn settle(self, result: io::Result<()>) {
+        self.0.settle(move |ctx| match result {
+            Ok(()) => Ok(JSValue::undefined(ctx)),
+            Err(error) => Err(io_error(ctx, error)),
+        });
+    }
+}
+
+/// Returns a chunk as a `Uint8Array`, or `null` for the end of the stream.
+fn chunk_value(ctx: &JSContext, chunk: Option<Vec<u8>>) -> JSResult<JSValue> {
+    match chunk {
+        Some(chunk) => Ok(JSTypedArray::from_slice(ctx, &chunk)?.into()),
+        None => Ok(JSValue::null(ctx)),
+    }
+}
+
+/// Locks the reader or writer of a blocking adapter on a worker thread.
+fn lock<T>(shared: &Mutex<T>) -> io::Result<MutexGuard<'_, T>> {
+    shared
+        .lock()
+        .map_err(|_| io::Error::other("A previous read or write panicked"))
+}
+
+/// A [`ReadableStreamSource`] that reads chunks from a blocking [`io::Read`], such
+/// as a file. The reads run on the worker threads of
+/// [`JSContext::spawn_blocking`], so the chunks arrive through
+/// [`JSContext::run_deferred`].
+#[derive(Debug)]
+pub struct ReadSource<R> {
+    reader: Arc<Mutex<R>>,
+    chunk_size: usize,
+}
+
+impl<R: Read + Send + 'static> ReadSource<R> {
+    /// The size of the chunks read by default.
+    pub const CHUNK_SIZE: usize = 16 * 1024;
+
+    /// Creates a source that reads chunks of up to [`ReadSource::CHUNK_SIZE`] bytes.
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, Self::CHUNK_SIZE)
+    }
+
+    /// Creates a source that reads chunks of up to `chunk_size` bytes.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader: Arc::new(Mutex::new(reader)),
+            chunk_size: chunk_size.max(1),
+        }
+    }
+}
+
+impl<R: Read + Send + 'static> ReadableStreamSource for ReadSource<R> {
+    fn pull(&mut self, _ctx: &JSContext, request: PullRequest) {
+        let reader = self.reader.clone();
+        let chunk_size = self.chunk_size;
+        let handle = request.send();
+        run_blocking(Box::new(move || {
+            let read = || {
+                let mut reader = lock(&reader)?;
+                let mut chunk = vec![0; chunk_size];
+                loop {
+                    match reader.read(&mut chunk) {
+                        Ok(0) => return Ok(None),
+                        Ok(length) => {
+                            chunk.truncate(length);
+                            return Ok(Some(chunk));
+                        }
+                        Err(error) if error.kind() == io::ErrorKind::Interrupted => {
+                            continue
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+            };
+            handle.settle(read());
+        }));
+    }
+}
+
+/// A [`WritableStreamSink`] that writes chunks to a blocking [`io::Write`], flushed
+/// when the stream is closed. The writes run on the worker threads of
+/// [`JSContext::spawn_blocking`], so they complete through
+/// [`JSContext::run_deferred`].
+#[derive(Debug)]
+pub struct WriteSink<W> {
+    writer: Arc<Mutex<W>>,
+}
+
+impl<W: Write + Send + 'static> WriteSink<W> {
+    /// Creates a sink that writes to the writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+}
+
+impl<W: Write + Send + 'static> WritableStreamSink for WriteSink<W> {
+    fn write(&mut self, _ctx: &JSContext, chunk: Vec<u8>, request: WriteRequest) {
+        let writer = self.writer.clone();
+        let handle = request.send();
+        run_blocking(Box::new(move || {
+            handle.settle(lock(&writer).and_then(|mut writer| writer.write_all(&chunk)));
+        }));
+    }
+
+    fn close(&mut self, _ctx: &JSContext, request: WriteRequest) {
+        let writer = self.writer.clone();
+        let handle = request.send();
+        run_blocking(Box::new(move || {
+            handle.settle(lock(&writer).and_then(|mut writer| writer.flush()));
+        }));
+    }
+}
+
+/// A JavaScript `ReadableStream`.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::builtins::{ReadSource, ReadableStream};
+/// use rust_jsc::JSContext;
+///
+/// let ctx = JSContext::new();
+/// let file = std::fs::File::open("/srv/data.bin").unwrap();
+/// let stream = ReadableStream::from_source(&ctx, ReadSource::new(file), 4).unwrap();
+/// ctx.global_object()
+///     .set_property("input", stream.object(), Default::default())
+///     .unwrap();
+/// // for await (const chunk of input) { ... }, with the chunks read on worker
+/// // threads and delivered by the event loop calling `ctx.run_deferred()`.
+/// ```
+#[derive(Clone)]
+pub struct ReadableStream {
+    object: JSObject,
+}
+
+impl ReadableStream {
+    /// Wraps a `ReadableStream` object, e.g. one passed to a native function.
+    pub fn from_object(object: JSObject) -> Self {
+        Self { object }
+    }
+
+    /// Creates a stream of `Uint8Array` chunks pulled from the source.
+    ///
+    /// # Arguments
+    /// - `source`: The source of the chunks.
+    /// - `high_water_mark`: How many chunks the stream pulls ahead of the reader.
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the stream.
+    pub fn from_source(
+        ctx: &JSContext,
+        source: impl ReadableStreamSource,
+        high_water_mark: usize,
+    ) -> JSResult<Self> {
+        let source = Rc::new(RefCell::new(source));
+
+        let pulled = source.clone();
+        let pull = JSFunction::closure(ctx, move |ctx, _this, _arguments| {
+            let (promise, deferred) = JSPromise::deferred(ctx)?;
+            pulled.borrow_mut().pull(ctx, PullRequest { deferred });
+            Ok(promise.into())
+        });
+        let cancel = JSFunction::closure(ctx, move |ctx, _this, _arguments| {
+            source.borrow_mut().cancel(ctx);
+            Ok(JSValue::undefined(ctx))
+        });
+
+        let object = bindings(ctx)?
+            .get_property("hostSource")?
+            .as_object()?
+            .call(
+                None,
+                &[
+                    pull.into(),
+                    cancel.into(),
+                    JSValue::number(ctx, high_water_mark as f64),
+                ],
+            )?;
+        Ok(Self {
+            object: object.as_object()?,
+        })
+    }
+
+    /// Returns the stream object.
+    pub fn object(&self) -> &JSObject {
+        &self.object
+    }
+
+    /// Pipes the stream into the destination, as `stream.pipeTo(destination)`.
+    ///
+    /// # Errors
+    /// If an exception is thrown while starting the pipe.
+    ///
+    /// # Returns
+    /// A promise that resolves once every chunk is written and the destination is
+    /// closed.
+    pub fn pipe_to(&self, destination: &WritableStream) -> JSResult<JSValue> {
+        self.object
+            .get_property("pipeTo")?
+            .as_object()?
+            .call(Some(&self.object), &[destination.object.clone().into()])
+    }
+
+    /// Reads the stream to the end from Rust, e.g. a stream created by a script.
+    /// The chunks must be `ArrayBuffer`s or `ArrayBuffer` views.
+    ///
+    /// # Arguments
+    /// - `budget`: The longest time to wait for each chunk, see
+    ///   [`JSContext::run_until_settled_with`]. The chunks of Rust sources that
+    ///   arrive through [`JSContext::run_deferred`] are settled meanwhile.
+    ///
+    /// # Errors
+    /// If the stream is locked or errors, a chunk is not made of bytes, or a chunk
+    /// does not arrive within the budget.
+    pub fn read_to_end(&self, budget: Duration) -> JSResult<Vec<u8>> {
+        let ctx = JSContext::from(self.object.ctx);
+        let to_bytes = bindings(&ctx)?.get_property("toBytes")?.as_object()?;
+        let reader = self
+            .object
+            .get_property("getReader")?
+            .as_object()?
+            .call(Some(&self.object), &[])?
+            .as_object()?;
+
+        let result = read_chunks(&ctx, &reader, &to_bytes, budget);
+        // The lock is released whether or not the stream was read to the end.
+        let released = reader
+            .get_property("releaseLock")
+            .and_then(|release| release.as_object())
+            .and_then(|release| release.call(Some(&reader), &[]));
+        let bytes = result?;
+        released?;
+        Ok(bytes)
+    }
+}
+
+/// Reads the chunks of a stream reader until the end, as bytes.
+fn read_chunks(
+    ctx: &JSContext,
+    reader: &JSObject,
+    to_bytes: &JSObject,
+    budget: Duration,
+) -> JSResult<Vec<u8>> {
+    let read = reader.get_property("read")?.as_object()?;
+    let mut bytes = Vec::new();
+    loop {
+        let promise = read.call(Some(reader), &[])?;
+        let next = ctx
+            .run_until_settled_with(&promise, budget, |ctx| {
+                ctx.run_deferred()?;
+                Ok(ctx.has_pending_deferreds())
+            })?
+            .as_object()?;
+        if next.get_property("done")?.as_boolean() {
+            return Ok(bytes);
+        }
+
+        let view = to_bytes.call(None, &[next.get_property("value")?])?;
+        bytes.extend_from_slice(&JSTypedArray::from_value(&view)?.to_bytes()?);
+    }
+}
+
+/// A JavaScript `WritableStream`.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::builtins::{WritableStream, WriteSink};
+/// use rust_jsc::JSContext;
+///
+/// let ctx = JSContext::new();
+/// let file = std::fs::File::create("/srv/out.bin").unwrap();
+/// let stream = WritableStream::from_sink(&ctx, WriteSink::new(file), 4).unwrap();
+/// ctx.global_object()
+///     .set_property("output", stream.object(), Default::default())
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct WritableStream {
+    object: JSObject,
+}
+
+impl WritableStream {
+    /// Wraps a `WritableStream` object, e.g. one passed to a native function.
+    pub fn from_object(object: JSObject) -> Self {
+        Self { object }
+    }
+
+    /// Creates a stream that writes its chunks to the sink. Chunks must be
+    /// `ArrayBuffer`s or `ArrayBuffer` views.
+    ///
+    /// # Arguments
+    /// - `sink`: The destination of the chunks.
+    /// - `high_water_mark`: How many chunks may wait for the sink before
+    ///   `writer.ready` stays pending.
+    ///
+    /// # Errors
+    /// If an exception is thrown while creating the stream.
+    pub fn from_sink(
+        ctx: &JSContext,
+        sink: impl WritableStreamSink,
+        high_water_mark: usize,
+    ) -> JSResult<Self> {
+        let sink = Rc::new(RefCell::new(sink));
+
+        let written = sink.clone();
+        let write = JSFunction::closure(ctx, move |ctx, _this, arguments| {
+            let chunk = match arguments.first() {
+                Some(chunk) => JSTypedArray::from_value(chunk)?.to_bytes()?,
+                None => Vec::new(),
+            };
+            let (promise, deferred) = JSPromise::deferred(ctx)?;
+            written
+                .borrow_mut()
+                .write(ctx, chunk, WriteRequest { deferred });
+            Ok(promise.into())
+        });
+        let closed = sink.clone();
+        let close = JSFunction::closure(ctx, move |ctx, _this, _arguments| {
+            let (promise, deferred) = JSPromise::deferred(ctx)?;
+            closed.borrow_mut().close(ctx, WriteRequest { deferred });
+            Ok(promise.into())
+        });
+        let abort = JSFunction::closure(ctx, move |ctx, _this, _arguments| {
+            sink.borrow_mut().abort(ctx);
+            Ok(JSValue::undefined(ctx))
+        });
+
+        let object = bindings(ctx)?.get_property("hostSink")?.as_object()?.call(
+            None,
+            &[
+                write.into(),
+                close.into(),
+                abort.into(),
+                JSValue::number(ctx, high_water_mark as f64),
+            ],
+        )?;
+        Ok(Self {
+            object: object.as_object()?,
+        })
+    }
+
+    /// Returns the stream object.
+    pub fn object(&self) -> &JSObject {
+        &self.object
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pump(ctx: &JSContext) -> JSResult<bool> {
+        ctx.run_deferred()?;
+        Ok(ctx.has_pending_deferreds())
+    }
+
+    #[test]
+    fn test_readable_stream_source() {
+        let ctx = JSContext::new();
+        install_streams(&ctx).unwrap();
+
+        struct Counter(Rc<RefCell<u8>>);
+
+        impl ReadableStreamSource for Counter {
+            fn pull(&mut self, ctx: &JSContext, request: PullRequest) {
+                let mut pulls = self.0.borrow_mut();
+                *pulls += 1;
+                let chunk = (*pulls <= 3).then(|| vec![*pulls; 2]);
+                request.settle(ctx, Ok(chunk)).unwrap();
+            }
+        }
+
+        let pulls = Rc::new(RefCell::new(0));
+        let stream =
+            ReadableStream::from_source(&ctx, Counter(pulls.clone()), 1).unwrap();
+        ctx.global_object()
+            .set_property("input", stream.object(), Default::default())
+            .unwrap();
+
+        // Only the high water mark is pulled ahead while nothing reads.
+        ctx.evaluate_script("void 0", None).unwrap();
+        assert_eq!(*pulls.borrow(), 1);
+
+        let promise = ctx
+            .evaluate_script(
+                r#"(async () => {
+                    const chunks = [];
+                    for await (const chunk of input) {
+                        chunks.push(Array.from(chunk).join(""));
+                    }
+                    return chunks.join(",") + "|" + input.locked;
+                })()"#,
+                None,
+            )
+            .unwrap();
+        let result = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(1), pump)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "11,22,33|false");
+    }
+
+    #[test]
+    fn test_writable_stream_sink() {
+        let ctx = JSContext::new();
+        install_streams(&ctx).unwrap();
+
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Shared {
+            fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(bytes);
+                Ok(bytes.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let output = Shared::default();
+        let stream =
+            WritableStream::from_sink(&ctx, WriteSink::new(output.clone()), 1).unwrap();
+        ctx.global_object()
+            .set_property("output", stream.object(), Default::default())
+            .unwrap();
+
+        let promise = ctx
+            .evaluate_script(
+                r#"(async () => {
+                    const writer = output.getWriter();
+                    const sizes = [writer.desiredSize];
+                    const first = writer.write(new Uint8Array([104, 105]));
+                    sizes.push(writer.desiredSize);
+                    await first;
+                    await writer.ready;
+                    await writer.write(new Uint8Array([33]).buffer);
+                    const rejected = await writer.write("text").then(() => false, () => true);
+                    return sizes.join(",") + "|" + rejected;
+                })()"#,
+                None,
+            )
+            .unwrap();
+        let result = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(1), pump)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "1,0|true");
+        assert_eq!(output.0.lock().unwrap().as_slice(), b"hi!");
+    }
+
+    #[test]
+    fn test_pipe_and_read_to_end() {
+        let ctx = JSContext::new();
+        install_streams(&ctx).unwrap();
+
+        let source =
+            ReadSource::with_chunk_size(io::Cursor::new(b"streamed".to_vec()), 3);
+        let readable = ReadableStream::from_source(&ctx, source, 2).unwrap();
+        let destination = Rc::new(RefCell::new(Vec::new()));
+
+        struct Collect(Rc<RefCell<Vec<u8>>>);
+
+        impl WritableStreamSink for Collect {
+            fn write(&mut self, ctx: &JSContext, chunk: Vec<u8>, request: WriteRequest) {
+                self.0.borrow_mut().extend_from_slice(&chunk);
+                request.settle(ctx, Ok(())).unwrap();
+            }
+        }
+
+        let writable =
+            WritableStream::from_sink(&ctx, Collect(destination.clone()), 1).unwrap();
+        let promise = readable.pipe_to(&writable).unwrap();
+        ctx.run_until_settled_with(&promise, Duration::from_secs(1), pump)
+            .unwrap();
+        assert_eq!(destination.borrow().as_slice(), b"streamed");
+
+        let stream = ctx
+            .evaluate_script(
+                r#"new ReadableStream({
+                    start(controller) {
+                        controller.enqueue(new Uint8Array([1, 2]));
+                        controller.enqueue(new Uint16Array([0x0403]));
+                        controller.close();
+                    },
+                })"#,
+                None,
+            )
+            .unwrap();
+        let stream = ReadableStream::from_object(stream.as_object().unwrap());
+        let bytes = stream.read_to_end(Duration::from_secs(1)).unwrap();
+        assert_eq!(bytes, vec![1, 2, 3, 4]);
+        assert!(!stream.object().get_property("locked").unwrap().as_boolean());
+
+        let stream = ctx
+            .evaluate_script(
+                r#"new ReadableStream({
+                    start(controller) {
+                        controller.enqueue("text");
+                    },
+                })"#,
+                None,
+            )
+            .unwrap();
+        let stream = ReadableStream::from_object(stream.as_object().unwrap());
+        assert!(stream.read_to_end(Duration::from_secs(1)).is_err());
+        assert!(!stream.object().get_property("locked").unwrap().as_boolean());
+    }
+
+    #[test]
+    fn test_source_answered_from_another_thread() {
+        let ctx = JSContext::new();
+        install_streams(&ctx).unwrap();
+
+        struct Delayed(u8);
+
+        impl ReadableStreamSource for Delayed {
+            fn pull(&mut self, _ctx: &JSContext, request: PullRequest) {
+                self.0 += 1;
+                let chunk = (self.0 <= 2).then(|| vec![self.0]);
+                let handle = request.send();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(5));
+                    handle.settle(Ok(chunk));
+                });
+            }
+        }
+
+        let stream = ReadableStream::from_source(&ctx, Delayed(0), 1).unwrap();
+        let bytes = stream.read_to_end(Duration::from_secs(1)).unwrap();
+        assert_eq!(bytes, vec![1, 2]);
+
+        let reader = ReadSource::with_chunk_size(io::Cursor::new(b"pooled".to_vec()), 4);
+        let stream = ReadableStream::from_source(&ctx, reader, 1).unwrap();
+        let bytes = stream.read_to_end(Duration::from_secs(1)).unwrap();
+        assert_eq!(bytes, b"pooled");
+
+        struct Failing;
+
+        impl Write for Failing {
+            fn write(&mut self, _bytes: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let stream = WritableStream::from_sink(&ctx, WriteSink::new(Failing), 1).unwrap();
+        ctx.global_object()
+            .set_property("failing", stream.object(), Default::default())
+            .unwrap();
+        let promise = ctx
+            .evaluate_script(
+                "failing.getWriter().write(new Uint8Array([1])).catch((error) => error.message)",
+                None,
+            )
+            .unwrap();
+        let message = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(1), pump)
+            .unwrap();
+        assert_eq!(message.as_string().unwrap().to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_bindings_hidden() {
+        let ctx = JSContext::new();
+        install_streams(&ctx).unwrap();
+        let bindings = ctx
+            .evaluate_script("typeof globalThis[Symbol.for('rust_jsc.streams')]", None)
+            .unwrap();
+        assert_eq!(bindings.as_string().unwrap().to_string(), "undefined");
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    context_data::ContextData, JSArrayBuffer, JSContext, JSObject, JSResult, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// The structured clone algorithm of the HTML specification, for the types
+/// JavaScriptCore provides. Transferred buffers are copied into the clone and
+/// detached afterwards, so the clone never observes a detached buffer. Properties are
+/// defined rather than assigned, so a `__proto__` key stays a data property.
+const STRUCTURED_CLONE: &str = r#"(function () {
+    const transfer = ArrayBuffer.prototype.transfer;
+    const toString = Object.prototype.toString;
+    const defineProperty = Object.defineProperty;
+    const errors = {
+        Error, EvalError, RangeError, ReferenceError, SyntaxError, TypeError, URIError,
+    };
+    const unsupported = new Set([
+        "[object Promise]", "[object WeakMap]", "[object WeakSet]", "[object WeakRef]",
+        "[object FinalizationRegistry]", "[object Symbol]",
+    ]);
+
+    const dataCloneError = (message) => {
+        const error = new Error(message);
+        error.name = "DataCloneError";
+        return error;
+    };
+
+    return function structuredClone(value, options) {
+        const transferList =
+            options == null || options.transfer === undefined ? [] : Array.from(options.transfer);
+        const transferred = new Set();
+        if (transferList.length > 0 && typeof transfer !== "function") {
+            throw dataCloneError("ArrayBuffer transfer is not supported");
+        }
+        for (const buffer of transferList) {
+            if (toString.call(buffer) !== "[object ArrayBuffer]") {
+                throw dataCloneError("Value is not transferable");
+            }
+            if (transferred.has(buffer)) {
+                throw dataCloneError("ArrayBuffer is transferred more than once");
+            }
+            if (buffer.detached) {
+                throw dataCloneError("ArrayBuffer is detached");
+            }
+            transferred.add(buffer);
+        }
+
+        const memory = new Map();
+        const copyProperties = (source, target) => {
+            for (const key of Object.keys(source)) {
+                defineProperty(target, key, {
+                    value: clone(source[key]),
+                    writable: true,
+                    enumerable: true,
+                    configurable: true,
+                });
+            }
+            return target;
+        };
+
+        const clone = (input) => {
+            const type = typeof input;
+            if (type === "symbol") {
+                throw dataCloneError("Symbol values cannot be cloned");
+            }
+            if (type === "function") {
+                throw dataCloneError("Functions cannot be cloned");
+            }
+            if (type !== "object" || input === null) {
+                return input;
+            }
+            if (memory.has(input)) {
+                return memory.get(input);
+            }
+
+            const tag = toString.call(input);
+            let output;
+            switch (tag) {
+                case "[object Boolean]":
+                case "[object Number]":
+                case "[object String]":
+                case "[object BigInt]":
+                    output = Object(input.valueOf());
+                    break;
+                case "[object Date]":
+                    output = new Date(input.getTime());
+                    break;
+                case "[object RegExp]":
+                    output = new RegExp(input.source, input.flags);
+                    break;
+                case "[object ArrayBuffer]":
+                    if (input.detached) {
+                        throw dataCloneError("ArrayBuffer is detached");
+                    }
+                    output = input.slice(0);
+                    break;
+                case "[object DataView]":
+                    output = new DataView(clone(input.buffer), input.byteOffset, input.byteLength);
+                    break;
+                case "[object Map]":
+                    output = new Map();
+                    memory.set(input, output);
+                    for (const [key, entry] of input) {
+                        output.set(clone(key), clone(entry));
+                    }
+                    return output;
+                case "[object Set]":
+                    output = new Set();
+                    memory.set(input, output);
+                    for (const entry of input) {
+                        output.add(clone(entry));
+                    }
+                    return output;
+                case "[object Error]": {
+                    const Constructor = errors[input.name] || Error;
+                    output = new Constructor();
+                    memory.set(input, output);
+                    if (input.message !== undefined) {
+                        output.message = String(input.message);
+                    }
+                    if ("cause" in input) {
+                        output.cause = clone(input.cause);
+                    }
+                    if (typeof input.stack === "string") {
+                        output.stack = input.stack;
+                    }
+                    return output;
+                }
+                case "[object Array]":
+                    output = new Array(input.length);
+                    memory.set(input, output);
+                    return copyProperties(input, output);
+                default:
+                    if (ArrayBuffer.isView(input)) {
+                        const Constructor = globalThis[input[Symbol.toStringTag]];
+                        output = new Constructor(clone(input.buffer), input.byteOffset, input.length);
+                        break;
+                    }
+                    if (unsupported.has(tag)) {
+                        throw dataCloneError(`${tag.slice(8, -1)} objects cannot be cloned`);
+                    }
+
+                    output = {};
+                    memory.set(input, output);
+                    return copyProperties(input, output);
+            }
+
+            memory.set(input, output);
+            return output;
+        };
+
+        const result = clone(value);
+        for (const buffer of transferred) {
+            transfer.call(buffer);
+        }
+
+        return result;
+    };
+})()"#;
+
+/// Installs the `structuredClone` global in the context.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_structured_clone(&ctx).unwrap();
+/// ctx.evaluate_script("structuredClone(new Map([[1, { a: 1 }]]))", None).unwrap();
+/// ```
+///
+/// # Errors
+/// If the global cannot be defined.
+pub fn install_structured_clone(ctx: &JSContext) -> JSResult<()> {
+    let structured_clone = ContextData::shim(ctx, "structuredClone", STRUCTURED_CLONE)?;
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    ctx.global_object()
+        .set_property("structuredClone", &structured_clone, descriptor)
+}
+
+impl JSContext {
+    /// Creates a deep copy of the value with the structured clone algorithm, like
+    /// `structuredClone(value, { transfer })` in JavaScript.
+    ///
+    /// Supported are primitives, plain objects and arrays, `Date`, `RegExp`, `Map`,
+    /// `Set`, errors, primitive wrappers, `ArrayBuffer`, typed arrays and `DataView`,
+    /// with cycles and shared references preserved. Functions, symbols, promises and
+    /// weak collections are rejected with a `DataCloneError`.
+    ///
+    /// # Arguments
+    /// - `value`: The value to clone.
+    /// - `transfer`: Array buffers to transfer; they are detached after the clone.
+    ///   The engine must support `ArrayBuffer.prototype.transfer` to detach them.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("({ list: [1, 2, 3] })", None).unwrap();
+    /// let copy = ctx.structured_clone(&value, &[]).unwrap();
+    /// assert!(copy != value);
+    /// ```
+    ///
+    /// # Errors
+    /// A `DataCloneError` if the value contains something that cannot be cloned, or
+    /// if buffers are given to transfer and the engine cannot detach them.
+    pub fn structured_clone(
+        &self,
+        value: &JSValue,
+        transfer: &[JSArrayBuffer],
+    ) -> JSResult<JSValue> {
+        let structured_clone =
+            ContextData::shim(self, "structuredClone", STRUCTURED_CLONE)?;
+
+        let mut arguments = vec![value.clone()];
+        if !transfer.is_empty() {
+            let list = self.evaluate_script("[]", None)?.as_object()?;
+            for (index, buffer) in transfer.iter().enumerate() {
+                list.set_property_at_index(index as u32, &buffer.object)?;
+            }
+
+            let options = JSObject::new(self);
+            options.set_property("transfer", &list, Default::default())?;
+            arguments.push(options.into());
+        }
+
+        structured_clone.call(None, &arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_clone_global() {
+        let ctx = JSContext::new();
+        install_structured_clone(&ctx).unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"
+                const source = { date: new Date(0), map: new Map([["k", [1, 2]]]) };
+                source.self = source;
+                const copy = structuredClone(source);
+                copy !== source
+                    && copy.self === copy
+                    && copy.date instanceof Date
+                    && copy.date.getTime() === 0
+                    && copy.map.get("k")[1] === 2
+                    && copy.map.get("k") !== source.map.get("k")
+            "#,
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+    }
+
+    #[test]
+    fn test_structured_clone_proto_key() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script(r#"JSON.parse('{"__proto__": {"polluted": true}}')"#, None)
+            .unwrap();
+        let copy = ctx.structured_clone(&value, &[]).unwrap();
+        ctx.global_object()
+            .set_property("copy", &copy, Default::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                "Object.getPrototypeOf(copy) === Object.prototype \
+                    && Object.hasOwn(copy, '__proto__') && copy.polluted === undefined",
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+    }
+
+    #[test]
+    fn test_structured_clone_rejects_functions() {
+        let ctx = JSContext::new();
+        let value = ctx.evaluate_script("({ f() {} })", None).unwrap();
+        let error = ctx.structured_clone(&value, &[]).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "DataCloneError");
+    }
+
+    #[test]
+    fn test_structured_clone_transfer() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script("globalThis.bytes = new Uint8Array([1, 2, 3]); bytes", None)
+            .unwrap();
+        let buffer = ctx
+            .evaluate_script("bytes.buffer", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let buffer = JSArrayBuffer::from_object(buffer);
+
+        let copy = ctx
+            .structured_clone(&value, std::slice::from_ref(&buffer))
+            .unwrap();
+        ctx.global_object()
+            .set_property("copy", &copy, Default::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script("copy.length === 3 && copy[2] === 3", None)
+            .unwrap();
+        assert!(result.as_boolean());
+        assert!(buffer.is_detached());
+    }
+}
@@ -0,0 +1,354 @@
+use std::borrow::Cow;
+
+use crate::{
+    JSContext, JSError, JSFunction, JSObject, JSResult, JSString, JSTypedArray, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// Defines the `TextEncoder` and `TextDecoder` classes on top of the native UTF-8
+/// functions. Decoder state for streaming lives in private fields.
+const TEXT_ENCODING: &str = r#"(function (encode, encodeInto, decode) {
+    const labels = new Set([
+        "unicode-1-1-utf-8", "unicode11utf8", "unicode20utf8", "utf-8", "utf8",
+        "x-unicode20utf8",
+    ]);
+    const toString = Object.prototype.toString;
+
+    const toBytes = (input) => {
+        if (ArrayBuffer.isView(input)) {
+            return new Uint8Array(input.buffer, input.byteOffset, input.byteLength);
+        }
+        const tag = toString.call(input);
+        if (tag === "[object ArrayBuffer]" || tag === "[object SharedArrayBuffer]") {
+            return new Uint8Array(input);
+        }
+        throw new TypeError(
+            "The provided value is not of type '(ArrayBuffer or ArrayBufferView)'",
+        );
+    };
+
+    class TextEncoder {
+        get encoding() {
+            return "utf-8";
+        }
+
+        encode(input = "") {
+            return encode(String(input));
+        }
+
+        encodeInto(source, destination) {
+            if (!(destination instanceof Uint8Array)) {
+                throw new TypeError("The destination must be a Uint8Array");
+            }
+            return encodeInto(String(source), destination);
+        }
+    }
+
+    class TextDecoder {
+        #fatal;
+        #ignoreBOM;
+        #pending = new Uint8Array(0);
+        #bomSeen = false;
+
+        constructor(label = "utf-8", options = {}) {
+            const encoding = String(label).trim().toLowerCase();
+            if (!labels.has(encoding)) {
+                throw new RangeError(`The encoding label provided ('${label}') is invalid.`);
+            }
+            this.#fatal = Boolean(options && options.fatal);
+            this.#ignoreBOM = Boolean(options && options.ignoreBOM);
+        }
+
+        get encoding() {
+            return "utf-8";
+        }
+
+        get fatal() {
+            return this.#fatal;
+        }
+
+        get ignoreBOM() {
+            return this.#ignoreBOM;
+        }
+
+        decode(input, options = {}) {
+            const stream = Boolean(options && options.stream);
+            let bytes = input === undefined ? new Uint8Array(0) : toBytes(input);
+            if (this.#pending.length > 0) {
+                const joined = new Uint8Array(this.#pending.length + bytes.length);
+                joined.set(this.#pending);
+                joined.set(bytes, this.#pending.length);
+                bytes = joined;
+            } else {
+                bytes = bytes.slice();
+            }
+
+            let text;
+            try {
+                const result = decode(bytes, this.#fatal, stream);
+                text = result.text;
+                this.#pending = bytes.slice(bytes.length - result.pending);
+            } catch (error) {
+                this.#pending = new Uint8Array(0);
+                this.#bomSeen = false;
+                throw error;
+            }
+
+            if (!this.#ignoreBOM && !this.#bomSeen && text.length > 0) {
+                if (text.charCodeAt(0) === 0xfeff) {
+                    text = text.slice(1);
+                }
+                this.#bomSeen = true;
+            }
+            if (!stream) {
+                this.#bomSeen = false;
+            }
+
+            return text;
+        }
+    }
+
+    return { TextEncoder, TextDecoder };
+})"#;
+
+/// Returns the number of bytes at the end of `bytes` that start a UTF-8 sequence
+/// but do not complete it, so they can be decoded with the next chunk.
+fn incomplete_suffix(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    for index in (start..bytes.len()).rev() {
+        // Skip continuation bytes until the lead byte of the last sequence.
+        if bytes[index] & 0xc0 == 0x80 {
+            continue;
+        }
+
+        return match std::str::from_utf8(&bytes[index..]) {
+            Err(error) if error.error_len().is_none() => bytes.len() - index,
+            _ => 0,
+        };
+    }
+
+    0
+}
+
+/// Encodes the UTF-16 code units as UTF-8, replacing unpaired surrogates with
+/// U+FFFD like `TextEncoder` does.
+fn encode_utf16(characters: &[u16]) -> String {
+    char::decode_utf16(characters.iter().copied())
+        .map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn argument<'a>(
+    ctx: &JSContext,
+    arguments: &'a [JSValue],
+    index: usize,
+) -> JSResult<&'a JSValue> {
+    match arguments.get(index) {
+        Some(value) => Ok(value),
+        None => Err(JSError::new_typ(ctx, "Not enough arguments")?),
+    }
+}
+
+/// Installs the `TextEncoder` and `TextDecoder` globals in the context.
+///
+/// Only UTF-8 is supported, which is the only encoding `TextEncoder` produces and
+/// the default of `TextDecoder`; other labels throw a `RangeError`.
+/// Streaming decodes, `fatal` and `ignoreBOM` behave as in the Encoding specification.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{builtins, JSContext};
+///
+/// let ctx = JSContext::new();
+/// builtins::install_text_encoding(&ctx).unwrap();
+/// let result = ctx
+///     .evaluate_script("new TextDecoder().decode(new TextEncoder().encode('hé'))", None)
+///     .unwrap();
+/// ```
+///
+/// # Errors
+/// If the globals cannot be defined.
+pub fn install_text_encoding(ctx: &JSContext) -> JSResult<()> {
+    let encode = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let input = argument(ctx, arguments, 0)?.as_string()?.to_utf16();
+        let bytes = encode_utf16(&input).into_bytes();
+
+        Ok(JSTypedArray::from_slice(ctx, &bytes)?.into())
+    });
+
+    let encode_into = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let input = argument(ctx, arguments, 0)?.as_string()?.to_utf16();
+        let destination = JSTypedArray::from_value(argument(ctx, arguments, 1)?)?;
+
+        let mut read = 0;
+        let mut written = 0;
+        if destination.byte_len()? > 0 {
+            let bytes = destination.bytes::<u8>()?;
+            for character in char::decode_utf16(input.iter().copied()) {
+                let character = character.unwrap_or(char::REPLACEMENT_CHARACTER);
+                if written + character.len_utf8() > bytes.len() {
+                    break;
+                }
+
+                character.encode_utf8(&mut bytes[written..]);
+                written += character.len_utf8();
+                read += character.len_utf16();
+            }
+        }
+
+        let result = JSObject::new(ctx);
+        result.set_property(
+            "read",
+            &JSValue::number(ctx, read as f64),
+            Default::default(),
+        )?;
+        result.set_property(
+            "written",
+            &JSValue::number(ctx, written as f64),
+            Default::default(),
+        )?;
+        Ok(result.into())
+    });
+
+    let decode = JSFunction::closure(ctx, |ctx, _this, arguments| {
+        let bytes = JSTypedArray::from_value(argument(ctx, arguments, 0)?)?.to_bytes()?;
+        let fatal = argument(ctx, arguments, 1)?.as_boolean();
+        let stream = argument(ctx, arguments, 2)?.as_boolean();
+
+        let pending = if stream { incomplete_suffix(&bytes) } else { 0 };
+        let complete = &bytes[..bytes.len() - pending];
+        let text = if fatal {
+            match std::str::from_utf8(complete) {
+                Ok(text) => Cow::Borrowed(text),
+                Err(_) => {
+                    return Err(JSError::new_typ(
+                        ctx,
+                        "The encoded data was not valid utf-8",
+                    )?)
+                }
+            }
+        } else {
+            String::from_utf8_lossy(complete)
+        };
+
+        // Decoded text may contain NUL characters, which `&str` conversions reject.
+        let text: Vec<u16> = text.encode_utf16().collect();
+        let result = JSObject::new(ctx);
+        result.set_property(
+            "text",
+            &JSValue::string(ctx, JSString::from_utf16(&text)),
+            Default::default(),
+        )?;
+        result.set_property(
+            "pending",
+            &JSValue::number(ctx, pending as f64),
+            Default::default(),
+        )?;
+        Ok(result.into())
+    });
+
+    let classes = ctx
+        .evaluate_script(TEXT_ENCODING, None)?
+        .as_object()?
+        .call(None, &[encode.into(), encode_into.into(), decode.into()])?;
+    let classes = classes.as_object()?;
+
+    let descriptor = PropertyDescriptorBuilder::new().enumerable(false).build();
+    let global_object = ctx.global_object();
+    for name in ["TextEncoder", "TextDecoder"] {
+        global_object.set_property(name, &classes.get_property(name)?, descriptor)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(ctx: &JSContext, script: &str) -> JSValue {
+        ctx.evaluate_script(script, None).unwrap()
+    }
+
+    #[test]
+    fn test_incomplete_suffix() {
+        assert_eq!(incomplete_suffix(b"abc"), 0);
+        assert_eq!(incomplete_suffix(&[0x61, 0xe2, 0x82]), 2);
+        assert_eq!(incomplete_suffix(&[0xe2, 0x82, 0xac]), 0);
+        assert_eq!(incomplete_suffix(&[0xf0]), 1);
+        assert_eq!(incomplete_suffix(&[0x61, 0xff]), 0);
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let ctx = JSContext::new();
+        install_text_encoding(&ctx).unwrap();
+
+        let result = evaluate(
+            &ctx,
+            r#"
+            const bytes = new TextEncoder().encode("hé€\0");
+            bytes.length === 7
+                && bytes[1] === 0xc3
+                && new TextDecoder().decode(bytes) === "hé€\0"
+                && new TextDecoder().decode(new Uint8Array([0xef, 0xbb, 0xbf, 0x61])) === "a"
+                && new TextEncoder().encode("\ud800")[0] === 0xef
+        "#,
+        );
+        assert!(result.as_boolean());
+    }
+
+    #[test]
+    fn test_encode_into() {
+        let ctx = JSContext::new();
+        install_text_encoding(&ctx).unwrap();
+
+        let result = evaluate(
+            &ctx,
+            r#"
+            const destination = new Uint8Array(4);
+            const { read, written } = new TextEncoder().encodeInto("a€b", destination);
+            read === 2 && written === 4 && destination[3] === 0xac
+        "#,
+        );
+        assert!(result.as_boolean());
+    }
+
+    #[test]
+    fn test_decode_stream_and_fatal() {
+        let ctx = JSContext::new();
+        install_text_encoding(&ctx).unwrap();
+
+        let result = evaluate(
+            &ctx,
+            r#"
+            const decoder = new TextDecoder();
+            const first = decoder.decode(new Uint8Array([0x61, 0xe2, 0x82]), { stream: true });
+            const second = decoder.decode(new Uint8Array([0xac]));
+            first === "a"
+                && second === "€"
+                && new TextDecoder().decode(new Uint8Array([0xff])) === "�"
+        "#,
+        );
+        assert!(result.as_boolean());
+
+        let result = evaluate(
+            &ctx,
+            r#"
+            try {
+                new TextDecoder("utf-8", { fatal: true }).decode(new Uint8Array([0xff]));
+                "no error";
+            } catch (error) {
+                error.name;
+            }
+        "#,
+        );
+        assert_eq!(result.as_string().unwrap().to_string(), "TypeError");
+
+        let result = evaluate(
+            &ctx,
+            "try { new TextDecoder('latin1') } catch (e) { e.name }",
+        );
+        assert_eq!(result.as_string().unwrap().to_string(), "RangeError");
+    }
+}
@@ -0,0 +1,325 @@
+//! Byte buffers that cross into JavaScript as a `Uint8Array`, rather than as an
+//! array of numbers that takes a value per byte.
+//!
+//! With the `serde` feature, the module is also a `#[serde(with = "rust_jsc::bytes")]`
+//! helper: [`serialize`] writes a byte field with `serialize_bytes` instead of as a
+//! sequence of numbers, and [`deserialize`] reads it back from bytes or a sequence.
+//!
+//! ```ignore
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Upload {
+//!     name: String,
+//!     #[serde(with = "rust_jsc::bytes")]
+//!     body: Vec<u8>,
+//! }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    convert::{type_error, FromJSValue, IntoJSValue},
+    JSArrayBuffer, JSContext, JSResult, JSTypedArray, JSTypedArrayType, JSValue,
+};
+
+/// An owned byte buffer converted to and from a JavaScript `Uint8Array`.
+///
+/// Wrap `Vec<u8>` arguments, return values and fields in `Bytes` to pass them with
+/// [`IntoJSValue`] and [`FromJSValue`], e.g. with [`crate::JSObject::call_with`].
+/// The bytes are copied in both directions. Use
+/// [`IntoJSValue::try_into_js_value`] to handle a failure to create the array
+/// rather than panic.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{bytes::Bytes, convert::FromJSValue, JSContext};
+///
+/// let ctx = JSContext::new();
+/// let value = ctx.value(Bytes::from(vec![1, 2, 3])).unwrap();
+/// assert!(value.is_typed_array());
+/// assert_eq!(Bytes::from_js_value(&value).unwrap().as_slice(), &[1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    /// Returns the bytes as a slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the underlying vector.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Bytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        bytes.0
+    }
+}
+
+impl IntoJSValue for Bytes {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        self.as_slice().into_js_value(ctx)
+    }
+
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+        self.as_slice().try_into_js_value(ctx)
+    }
+}
+
+impl IntoJSValue for &Bytes {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        self.as_slice().into_js_value(ctx)
+    }
+
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+        self.as_slice().try_into_js_value(ctx)
+    }
+}
+
+/// # Panics
+/// If the `Uint8Array` cannot be created, see [`IntoJSValue::try_into_js_value`].
+impl IntoJSValue for &[u8] {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        match self.try_into_js_value(ctx) {
+            Ok(value) => value,
+            Err(error) => panic!(
+                "Failed to convert {} bytes to JavaScript: {}",
+                self.len(),
+                error
+                    .message()
+                    .map(|message| message.to_string())
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+        JSTypedArray::from_slice(ctx, self).map(Into::into)
+    }
+}
+
+impl FromJSValue for Bytes {
+    /// Copies the bytes of a `Uint8Array`, a `Uint8ClampedArray` or an `ArrayBuffer`.
+    /// Other typed arrays are rejected rather than reinterpreted as bytes.
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        const EXPECTED: &str = "a Uint8Array or an ArrayBuffer";
+        if !value.is_object() {
+            return Err(type_error(value, EXPECTED));
+        }
+
+        let typed_array = JSTypedArray::from_value(value)?;
+        match typed_array.array_type()? {
+            JSTypedArrayType::Uint8Array | JSTypedArrayType::Uint8ClampedArray => {
+                Ok(Self(typed_array.to_bytes()?))
+            }
+            JSTypedArrayType::ArrayBuffer => {
+                let buffer = JSArrayBuffer::from_object(value.as_object()?);
+                if buffer.len()? == 0 {
+                    return Ok(Self::default());
+                }
+                Ok(Self(buffer.bytes()?.to_vec()))
+            }
+            _ => Err(type_error(value, EXPECTED)),
+        }
+    }
+}
+
+/// Serializes a byte field with `serialize_bytes`, for
+/// `#[serde(with = "rust_jsc::bytes")]` on `Vec<u8>`, [`Bytes`] or other
+/// `AsRef<[u8]>` fields.
+///
+/// # Errors
+/// The error of the serializer.
+#[cfg(feature = "serde")]
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: serde::Serializer,
+{
+    serializer.serialize_bytes(bytes.as_ref())
+}
+
+/// Deserializes a byte field written by [`serialize`], accepting bytes as well as a
+/// sequence of numbers, for formats that have no bytes type.
+///
+/// # Errors
+/// The error of the deserializer, or an invalid type error if the value is neither
+/// bytes nor a sequence of bytes.
+#[cfg(feature = "serde")]
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Vec<u8>>,
+    D: serde::Deserializer<'de>,
+{
+    deserializer.deserialize_byte_buf(BytesVisitor).map(T::from)
+}
+
+#[cfg(feature = "serde")]
+struct BytesVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("bytes or a sequence of bytes")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        let ctx = JSContext::new();
+        let value = ctx.value(Bytes::from(vec![0, 127, 255])).unwrap();
+        assert!(value.is_typed_array());
+        ctx.global_object()
+            .set_property("bytes", &value, Default::default())
+            .unwrap();
+        let result = ctx
+            .evaluate_script(
+                "bytes instanceof Uint8Array && bytes.join(',') === '0,127,255'",
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+
+        let convert = |script: &str| {
+            Bytes::from_js_value(&ctx.evaluate_script(script, None).unwrap())
+        };
+        assert_eq!(convert("bytes").unwrap().as_slice(), &[0, 127, 255]);
+        assert_eq!(
+            convert("new Uint8Array([1, 2, 3, 4]).subarray(1, 3)")
+                .unwrap()
+                .as_slice(),
+            &[2, 3]
+        );
+        assert_eq!(
+            convert("new Uint8ClampedArray([300])").unwrap().as_slice(),
+            &[255]
+        );
+        assert_eq!(
+            convert("new Uint8Array([5, 6]).buffer").unwrap().as_slice(),
+            &[5, 6]
+        );
+        assert!(convert("new Uint8Array(0)").unwrap().is_empty());
+        assert!(convert("new ArrayBuffer(0)").unwrap().is_empty());
+
+        for script in ["[1, 2]", "new Uint16Array([1])", "'ab'", "null"] {
+            let error = convert(script).unwrap_err();
+            assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        }
+
+        let empty = ctx.value(Bytes::default()).unwrap();
+        assert_eq!(JSTypedArray::from_value(&empty).unwrap().len().unwrap(), 0);
+        let value = [1u8, 2].as_slice().try_into_js_value(&ctx).unwrap();
+        assert_eq!(Bytes::from_js_value(&value).unwrap().as_slice(), &[1, 2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_with() {
+        use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Upload {
+            #[serde(with = "crate::bytes")]
+            body: Vec<u8>,
+            #[serde(with = "crate::bytes")]
+            digest: Bytes,
+        }
+
+        let upload = Upload {
+            body: vec![1, 2, 255],
+            digest: Bytes::from(vec![7]),
+        };
+        assert_tokens(
+            &upload,
+            &[
+                Token::Struct {
+                    name: "Upload",
+                    len: 2,
+                },
+                Token::Str("body"),
+                Token::Bytes(&[1, 2, 255]),
+                Token::Str("digest"),
+                Token::Bytes(&[7]),
+                Token::StructEnd,
+            ],
+        );
+        assert_de_tokens(
+            &upload,
+            &[
+                Token::Struct {
+                    name: "Upload",
+                    len: 2,
+                },
+                Token::Str("body"),
+                Token::Seq { len: Some(3) },
+                Token::U8(1),
+                Token::U8(2),
+                Token::U8(255),
+                Token::SeqEnd,
+                Token::Str("digest"),
+                Token::ByteBuf(&[7]),
+                Token::StructEnd,
+            ],
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Cancellation of long host operations started by scripts, such as native
+//! functions that read files or wait on the network.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{builtins::named_error, JSContext, JSResult};
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    reason: Mutex<Option<String>>,
+}
+
+/// A token that tells host operations started from a context to stop, e.g. when
+/// the embedder shuts the context down.
+///
+/// Get the token of a context with [`JSContext::host_cancellation`] inside a
+/// callback. It is `Send`, so it can be moved into work running on other threads,
+/// such as the work of [`JSContext::spawn_blocking`]. Clones share the same state.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{callback, JSContext, JSObject, JSResult, JSValue};
+///
+/// #[callback]
+/// fn copy_files(
+///     ctx: JSContext,
+///     _function: JSObject,
+///     _this: JSObject,
+///     _arguments: &[JSValue],
+/// ) -> JSResult<JSValue> {
+///     let cancellation = ctx.host_cancellation();
+///     for file in std::fs::read_dir("/srv/in").unwrap() {
+///         cancellation.check(&ctx)?;
+///         // Copy the file.
+///     }
+///     Ok(JSValue::undefined(&ctx))
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct HostCancellation {
+    state: Arc<CancellationState>,
+}
+
+impl HostCancellation {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns the reason the token was cancelled with, if it was.
+    pub fn reason(&self) -> Option<String> {
+        self.state
+            .reason
+            .lock()
+            .ok()
+            .and_then(|reason| reason.clone())
+    }
+
+    /// Cancels the token. Only the first reason is kept.
+    ///
+    /// # Arguments
+    /// - `reason`: Why the operations are cancelled.
+    pub fn cancel(&self, reason: impl Into<String>) {
+        if let Ok(mut slot) = self.state.reason.lock() {
+            if slot.is_none() {
+                *slot = Some(reason.into());
+            }
+        }
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns an error to throw back to the script if the token has been cancelled.
+    ///
+    /// # Errors
+    /// An `AbortError` with the reason as its message.
+    pub fn check(&self, ctx: &JSContext) -> JSResult<()> {
+        if !self.is_cancelled() {
+            return Ok(());
+        }
+
+        let reason = self.reason().unwrap_or_default();
+        Err(named_error(ctx, "AbortError", &reason))
+    }
+}
+
+impl std::fmt::Debug for HostCancellation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostCancellation")
+            .field("cancelled", &self.is_cancelled())
+            .field("reason", &self.reason())
+            .finish()
+    }
+}
+
+/// The current token of a context, stored as a context extension.
+#[derive(Default)]
+struct ContextCancellation(RefCell<HostCancellation>);
+
+impl Drop for ContextCancellation {
+    fn drop(&mut self) {
+        self.0.borrow().cancel("The context was released");
+    }
+}
+
+impl JSContext {
+    /// Returns the cancellation token of the host operations of the context,
+    /// cancelled by [`JSContext::cancel_host_operations`],
+    /// [`JSContext::reset_global_state`] or when the context is released.
+    pub fn host_cancellation(&self) -> HostCancellation {
+        if let Some(current) = self.get_extension::<ContextCancellation>() {
+            return current.0.borrow().clone();
+        }
+
+        let current = ContextCancellation::default();
+        let token = current.0.borrow().clone();
+        self.set_extension(current);
+        token
+    }
+
+    /// Cancels the token of the host operations in flight, so they stop at their
+    /// next check, and gives operations started afterwards a fresh token.
+    ///
+    /// # Arguments
+    /// - `reason`: Why the operations are cancelled, the message of the
+    ///   `AbortError` of [`HostCancellation::check`].
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let token = ctx.host_cancellation();
+    /// ctx.cancel_host_operations("The server is shutting down");
+    /// assert!(token.is_cancelled());
+    /// assert!(!ctx.host_cancellation().is_cancelled());
+    /// ```
+    pub fn cancel_host_operations(&self, reason: &str) {
+        if let Some(current) = self.get_extension::<ContextCancellation>() {
+            current.0.replace(HostCancellation::new()).cancel(reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_cancellation() {
+        let ctx = JSContext::new();
+        let token = ctx.host_cancellation();
+        assert!(!token.is_cancelled());
+        assert!(token.check(&ctx).is_ok());
+
+        let worker = token.clone();
+        let handle = std::thread::spawn(move || {
+            while !worker.is_cancelled() {
+                std::thread::yield_now();
+            }
+            worker.reason()
+        });
+        ctx.cancel_host_operations("shutting down");
+        assert_eq!(handle.join().unwrap().as_deref(), Some("shutting down"));
+
+        let error = token.check(&ctx).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "AbortError");
+        assert_eq!(error.message().unwrap().to_string(), "shutting down");
+
+        token.cancel("ignored");
+        assert_eq!(token.reason().as_deref(), Some("shutting down"));
+
+        let mut ctx = ctx;
+        let token = ctx.host_cancellation();
+        assert!(!token.is_cancelled());
+        ctx.reset_global_state();
+        assert!(token.is_cancelled());
+        assert!(!ctx.host_cancellation().is_cancelled());
+    }
+}
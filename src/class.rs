@@ -1,16 +1,24 @@
-use std::ffi::CString;
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    ffi::CString,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+};
 
 use rust_jsc_sys::{
     kJSClassDefinitionEmpty, JSClassCreate, JSClassDefinition, JSClassRelease,
-    JSClassRetain, JSObjectCallAsConstructorCallback, JSObjectCallAsFunctionCallback,
-    JSObjectConvertToTypeCallback, JSObjectDeletePropertyCallback,
-    JSObjectFinalizeCallback, JSObjectGetPropertyCallback,
-    JSObjectGetPropertyNamesCallback, JSObjectHasInstanceCallback,
-    JSObjectHasPropertyCallback, JSObjectInitializeCallback, JSObjectMake,
-    JSObjectSetPropertyCallback,
+    JSClassRetain, JSContextRef, JSObjectCallAsConstructorCallback,
+    JSObjectCallAsFunctionCallback, JSObjectConvertToTypeCallback,
+    JSObjectDeletePropertyCallback, JSObjectFinalizeCallback,
+    JSObjectGetPropertyCallback, JSObjectGetPropertyNamesCallback,
+    JSObjectHasInstanceCallback, JSObjectHasPropertyCallback, JSObjectInitializeCallback,
+    JSObjectMake, JSObjectMakeConstructor, JSObjectRef, JSObjectSetPropertyCallback,
 };
 
-use crate::{JSClass, JSContext, JSObject, JSResult};
+use crate::{JSClass, JSContext, JSError, JSObject, JSResult, JSValue};
 
 #[derive(Debug)]
 pub enum ClassError {
@@ -21,17 +29,18 @@ pub enum ClassError {
 pub struct JSClassBuilder {
     definition: JSClassDefinition,
     name: String,
+    class_name: CString,
 }
 
 impl JSClassBuilder {
     pub fn new(name: &str) -> Self {
-        let mut definition = unsafe { kJSClassDefinitionEmpty };
+        let definition = unsafe { kJSClassDefinitionEmpty };
 
         let class_name = CString::new(name).unwrap();
-        definition.className = class_name.as_ptr();
         Self {
             definition,
             name: name.to_string(),
+            class_name,
         }
     }
 
@@ -46,6 +55,8 @@ impl JSClassBuilder {
     }
 
     pub fn parent_class(mut self, parent_class: &JSClass) -> Self {
+        // Objects of the subclass run the callbacks of the parent.
+        parent_class.pin_counter();
         self.definition.parentClass = parent_class.inner;
         self
     }
@@ -123,20 +134,34 @@ impl JSClassBuilder {
         self
     }
 
-    pub fn build(self) -> Result<JSClass, ClassError> {
+    pub fn build(mut self) -> Result<JSClass, ClassError> {
+        // The class name must outlive the definition, so it is only attached here.
+        self.definition.className = self.class_name.as_ptr();
+        let counter = InstanceCounter::claim(&self.name, &self.definition);
+        if let Some(slot) = counter {
+            self.definition.initialize = Some(COUNTED_INITIALIZERS[slot]);
+            self.definition.finalize = Some(COUNTED_FINALIZERS[slot]);
+        }
         let class = unsafe { JSClassCreate(&self.definition) };
         if class.is_null() {
+            if let Some(slot) = counter {
+                InstanceCounter::release(slot);
+            }
             return Err(ClassError::CreateFailed);
         }
 
         let class = unsafe { JSClassRetain(class) };
         if class.is_null() {
+            if let Some(slot) = counter {
+                InstanceCounter::release(slot);
+            }
             return Err(ClassError::RetainFailed);
         }
 
         Ok(JSClass {
             inner: class,
             name: self.name,
+            counter,
         })
     }
 }
@@ -210,6 +235,109 @@ impl JSClass {
         &self.name
     }
 
+    /// Returns the number of objects of the class, including objects of classes
+    /// that inherit from it, that have been created and not yet finalized, in all
+    /// contexts.
+    ///
+    /// The count is kept by the initialize and finalize callbacks the builder wraps.
+    /// At most [`COUNTED_CLASSES`] classes are counted at a time. A counter is
+    /// returned once the class is dropped and its last object is finalized, unless a
+    /// constructor, prototype or subclass was made from the class: those can create
+    /// objects of the class later, so the counter is kept for the rest of the process.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{JSClass, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// let class = JSClass::builder("Counted").build().unwrap();
+    /// let _object = class.object::<()>(&ctx, None);
+    /// if let Some(count) = class.live_instance_count() {
+    ///     assert_eq!(count, 1);
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    /// The number of live objects, or `None` if the class is not counted.
+    pub fn live_instance_count(&self) -> Option<usize> {
+        self.counter
+            .map(|slot| COUNTERS[slot].live.load(Ordering::Relaxed))
+    }
+
+    /// Returns the number of live objects of every counted class, by class name.
+    /// Classes with the same name are added together.
+    ///
+    /// Classes are shared by all contexts, so the counts cover the whole process.
+    /// A count that keeps growing in a long-running embedder points at objects that
+    /// are still reachable, e.g. from a global or a protected value.
+    pub fn live_instance_report() -> BTreeMap<String, usize> {
+        let mut report = BTreeMap::new();
+        for counter in COUNTERS.iter() {
+            let state = counter.state.lock().unwrap();
+            if let Some(callbacks) = &state.callbacks {
+                *report.entry(callbacks.name.clone()).or_insert(0) +=
+                    counter.live.load(Ordering::Relaxed);
+            }
+        }
+
+        report
+    }
+
+    /// Keeps the counter of the class for the rest of the process, because the
+    /// engine can now create objects of the class without it.
+    pub(crate) fn pin_counter(&self) {
+        if let Some(slot) = self.counter {
+            COUNTERS[slot].state.lock().unwrap().pinned = true;
+        }
+    }
+
+    /// Returns the prototype shared by the objects of the class in the given context.
+    /// Methods and symbols (e.g. `Symbol.toStringTag` or `Symbol.iterator`) installed
+    /// on it are visible from every object created with [`JSClass::object`].
+    ///
+    /// Classes built with `JSClassAttribute::NoAutomaticPrototype` have no shared
+    /// prototype: their objects inherit from `Object.prototype`, so an error is
+    /// returned instead of letting callers add methods to every object.
+    ///
+    /// # Arguments
+    /// - `ctx`: The JavaScript context.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{JSClass, JSContext, JSValue};
+    ///
+    /// let ctx = JSContext::default();
+    /// let class = JSClass::builder("Test").build().unwrap();
+    ///
+    /// let prototype = class.prototype(&ctx).unwrap();
+    /// let tag = JSValue::string(&ctx, "Test");
+    /// prototype.set_property("kind", &tag, Default::default()).unwrap();
+    ///
+    /// let object = class.object::<()>(&ctx, None);
+    /// assert_eq!(object.get_property("kind").unwrap(), tag);
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the class has no automatic prototype, or any error that occurs
+    /// while reading the prototype.
+    pub fn prototype(&self, ctx: &JSContext) -> JSResult<JSObject> {
+        self.pin_counter();
+        let constructor = unsafe { JSObjectMakeConstructor(ctx.inner, self.inner, None) };
+        let prototype = JSObject::from_ref(constructor, ctx.inner)
+            .get_property("prototype")?
+            .as_object()?;
+
+        let object: JSObject = ctx.global_constructor("Object")?.into();
+        if JSValue::from(prototype.clone()) == object.get_property("prototype")? {
+            return Err(JSError::new_typ(
+                ctx,
+                format!("Class {} has no automatic prototype", self.name),
+            )?);
+        }
+
+        Ok(prototype)
+    }
+
     /// Creates a new object of the class.
     /// The object will be created in the given context.
     /// The object will have the given data associated with it.
@@ -289,9 +417,184 @@ impl JSClass {
     }
 }
 
+/// A class that is created on first use and lives for the rest of the process.
+/// Used for the classes backing crate internals, which are shared by all contexts.
+pub(crate) struct StaticClass(OnceLock<SyncClass>);
+
+struct SyncClass(JSClass);
+
+// SAFETY: JSClassRef is an immutable, thread-safe reference counted object.
+unsafe impl Send for SyncClass {}
+unsafe impl Sync for SyncClass {}
+
+impl StaticClass {
+    pub(crate) const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    pub(crate) fn get_or_init(&self, init: impl FnOnce() -> JSClass) -> &JSClass {
+        &self.0.get_or_init(|| SyncClass(init())).0
+    }
+}
+
+/// The number of classes whose live objects are counted, see
+/// [`JSClass::live_instance_count`].
+pub const COUNTED_CLASSES: usize = 64;
+
+/// The live object count of a class and the callbacks the counting callbacks call.
+/// A counter is claimed by one class and returned once the class is dropped and its
+/// last object is finalized, because objects of the class can outlive it.
+struct InstanceCounter {
+    state: Mutex<CounterState>,
+    live: AtomicUsize,
+}
+
+struct CounterState {
+    callbacks: Option<CountedCallbacks>,
+    /// Whether the class was dropped.
+    released: bool,
+    /// Whether the engine can create objects of the class without it, through a
+    /// constructor or a subclass, so the counter can never be returned.
+    pinned: bool,
+}
+
+struct CountedCallbacks {
+    name: String,
+    initialize: JSObjectInitializeCallback,
+    finalize: JSObjectFinalizeCallback,
+}
+
+impl InstanceCounter {
+    /// Claims a free counter for the class, returning its slot.
+    fn claim(name: &str, definition: &JSClassDefinition) -> Option<usize> {
+        COUNTERS.iter().position(|counter| {
+            let mut state = counter.state.lock().unwrap();
+            if state.callbacks.is_some() {
+                return false;
+            }
+
+            state.callbacks = Some(CountedCallbacks {
+                name: name.to_string(),
+                initialize: definition.initialize,
+                finalize: definition.finalize,
+            });
+            state.released = false;
+            state.pinned = false;
+            counter.live.store(0, Ordering::Relaxed);
+            true
+        })
+    }
+
+    /// Marks the class of the counter as dropped, freeing the counter if no object
+    /// of the class is alive.
+    fn release(slot: usize) {
+        let counter = &COUNTERS[slot];
+        let mut state = counter.state.lock().unwrap();
+        state.released = true;
+        counter.free_if_unused(&mut state);
+    }
+
+    fn free_if_unused(&self, state: &mut CounterState) {
+        if state.released && !state.pinned && self.live.load(Ordering::Relaxed) == 0 {
+            state.callbacks = None;
+        }
+    }
+
+    fn initialize(&self) -> JSObjectInitializeCallback {
+        let state = self.state.lock().unwrap();
+        state.callbacks.as_ref().and_then(|c| c.initialize)
+    }
+
+    fn finalize(&self) -> JSObjectFinalizeCallback {
+        let state = self.state.lock().unwrap();
+        state.callbacks.as_ref().and_then(|c| c.finalize)
+    }
+}
+
+static COUNTERS: [InstanceCounter; COUNTED_CLASSES] = [const {
+    InstanceCounter {
+        state: Mutex::new(CounterState {
+            callbacks: None,
+            released: false,
+            pinned: false,
+        }),
+        live: AtomicUsize::new(0),
+    }
+}; COUNTED_CLASSES];
+
+unsafe extern "C" fn counted_initialize<const SLOT: usize>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+) {
+    let counter = &COUNTERS[SLOT];
+    counter.live.fetch_add(1, Ordering::Relaxed);
+    if let Some(initialize) = counter.initialize() {
+        initialize(ctx, object);
+    }
+}
+
+unsafe extern "C" fn counted_finalize<const SLOT: usize>(object: JSObjectRef) {
+    let counter = &COUNTERS[SLOT];
+    if let Some(finalize) = counter.finalize() {
+        FINALIZING.with(|depth| depth.set(depth.get() + 1));
+        finalize(object);
+        FINALIZING.with(|depth| depth.set(depth.get() - 1));
+    }
+
+    let mut state = counter.state.lock().unwrap();
+    counter.live.fetch_sub(1, Ordering::Relaxed);
+    counter.free_if_unused(&mut state);
+}
+
+thread_local! {
+    /// The number of finalizers running on the thread.
+    static FINALIZING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Panics, in debug builds, if a finalizer of a class is running on the thread.
+///
+/// Finalizers run while the engine collects garbage, when it cannot run scripts or
+/// allocate objects: a call from there corrupts the heap or deadlocks rather than
+/// throwing, so it is turned into a panic that names the operation.
+pub(crate) fn debug_assert_not_finalizing(operation: &str) {
+    #[cfg(debug_assertions)]
+    if FINALIZING.with(Cell::get) > 0 {
+        panic!(
+            "{} was called from a finalizer; the engine cannot run JavaScript while \
+             it collects garbage, defer the work until after the collection",
+            operation
+        );
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = operation;
+}
+
+macro_rules! counted_callbacks {
+    ($callback:ident: $($slot:literal)*) => {
+        [$($callback::<$slot>),*]
+    };
+}
+
+type Initialize = unsafe extern "C" fn(JSContextRef, JSObjectRef);
+type Finalize = unsafe extern "C" fn(JSObjectRef);
+
+static COUNTED_INITIALIZERS: [Initialize; COUNTED_CLASSES] = counted_callbacks!(
+    counted_initialize: 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22
+    23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49
+    50 51 52 53 54 55 56 57 58 59 60 61 62 63
+);
+static COUNTED_FINALIZERS: [Finalize; COUNTED_CLASSES] = counted_callbacks!(
+    counted_finalize: 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22
+    23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49
+    50 51 52 53 54 55 56 57 58 59 60 61 62 63
+);
+
 impl Drop for JSClass {
     fn drop(&mut self) {
         unsafe { JSClassRelease(self.inner) };
+        if let Some(slot) = self.counter {
+            InstanceCounter::release(slot);
+        }
     }
 }
 
@@ -503,4 +806,86 @@ mod tests {
         assert!(result);
         assert_eq!(*object.get_private_data::<i32>().unwrap(), 42);
     }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_call_from_finalizer() {
+        let ctx = JSContext::default();
+        super::FINALIZING.with(|depth| depth.set(1));
+        let result = std::panic::catch_unwind(|| ctx.evaluate_script("1", None).is_ok());
+        super::FINALIZING.with(|depth| depth.set(0));
+        assert!(result.is_err());
+        assert!(ctx.evaluate_script("1", None).is_ok());
+    }
+
+    #[test]
+    fn test_class_live_instance_count() {
+        let ctx = JSContext::default();
+        let class = JSClass::builder("LiveCount").build().unwrap();
+        assert_eq!(class.live_instance_count(), Some(0));
+
+        let _first = class.object::<()>(&ctx, None);
+        let _second = class.object::<()>(&ctx, None);
+        assert_eq!(class.live_instance_count(), Some(2));
+        assert_eq!(JSClass::live_instance_report()["LiveCount"], 2);
+    }
+
+    #[test]
+    fn test_class_counter_released() {
+        let ctx = JSContext::default();
+        let class = JSClass::builder("Released").build().unwrap();
+        assert!(class.counter.is_some());
+        drop(class);
+        assert!(!JSClass::live_instance_report().contains_key("Released"));
+
+        let class = JSClass::builder("Pinned").build().unwrap();
+        class.prototype(&ctx).unwrap();
+        drop(class);
+        assert_eq!(JSClass::live_instance_report()["Pinned"], 0);
+    }
+
+    #[test]
+    fn test_class_prototype() {
+        let ctx = JSContext::default();
+        let class = JSClass::builder("Test").build().unwrap();
+
+        let prototype = class.prototype(&ctx).unwrap();
+        let to_string_tag = ctx.evaluate_script("Symbol.toStringTag", None).unwrap();
+        prototype
+            .set(
+                &to_string_tag,
+                &JSValue::string(&ctx, "Test"),
+                Default::default(),
+            )
+            .unwrap();
+
+        let first = class.object::<()>(&ctx, None);
+        let second = class.object::<()>(&ctx, None);
+        ctx.global_object()
+            .set_property("first", &first, Default::default())
+            .unwrap();
+        ctx.global_object()
+            .set_property("second", &second, Default::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                "Object.getPrototypeOf(first) === Object.getPrototypeOf(second) && String(first)",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "[object Test]");
+    }
+
+    #[test]
+    fn test_class_prototype_without_automatic_prototype() {
+        let ctx = JSContext::default();
+        let class = JSClass::builder("Bare")
+            .set_attributes(JSClassAttribute::NoAutomaticPrototype.into())
+            .build()
+            .unwrap();
+
+        let error = class.prototype(&ctx).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
 }
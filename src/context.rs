@@ -1,47 +1,690 @@
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+};
+
 use rust_jsc_sys::{
-    JSAPIModuleLoader, JSCheckScriptSyntax, JSContextGetGlobalContext,
-    JSContextGetGlobalObject, JSContextGetGroup, JSContextGetSharedData,
-    JSContextGroupCreate, JSContextGroupRef, JSContextGroupRelease, JSContextRef,
-    JSContextSetSharedData, JSEvaluateScript, JSGarbageCollect,
-    JSGetMemoryUsageStatistics, JSGlobalContextCopyName, JSGlobalContextCreate,
-    JSGlobalContextCreateInGroup, JSGlobalContextIsInspectable, JSGlobalContextRef,
-    JSGlobalContextRelease, JSGlobalContextRetain, JSGlobalContextSetInspectable,
-    JSGlobalContextSetName, JSGlobalContextSetUncaughtExceptionAtEventLoopCallback,
+    JSAPIModuleLoader, JSCheckScriptSyntax, JSClassRef, JSClassRelease, JSClassRetain,
+    JSContextGetGlobalContext, JSContextGetGlobalObject, JSContextGetGroup,
+    JSContextGroupCreate, JSContextGroupRef, JSContextGroupRelease, JSContextGroupRetain,
+    JSContextRef, JSEvaluateScript, JSGarbageCollect, JSGetMemoryUsageStatistics,
+    JSGlobalContextCopyName, JSGlobalContextCreate, JSGlobalContextCreateInGroup,
+    JSGlobalContextIsInspectable, JSGlobalContextRef, JSGlobalContextRelease,
+    JSGlobalContextRetain, JSGlobalContextSetInspectable, JSGlobalContextSetName,
+    JSGlobalContextSetUncaughtExceptionAtEventLoopCallback,
     JSGlobalContextSetUncaughtExceptionHandler,
     JSGlobalContextSetUnhandledRejectionCallback, JSLinkAndEvaluateModule,
     JSLoadAndEvaluateModule, JSLoadAndEvaluateModuleFromSource, JSLoadModule,
-    JSLoadModuleFromSource, JSSetAPIModuleLoader, JSSetSyntheticModuleKeys, JSStringRef,
-    JSUncaughtExceptionAtEventLoop, JSUncaughtExceptionHandler, JSValueRef,
+    JSLoadModuleFromSource, JSObjectCallAsFunctionCallback, JSSetAPIModuleLoader,
+    JSSetSyntheticModuleKeys, JSStringRef, JSStringRelease,
+    JSUncaughtExceptionAtEventLoop, JSUncaughtExceptionHandler, JSValueMakeBoolean,
+    JSValueMakeNull, JSValueMakeUndefined, JSValueRef,
 };
 
 use crate::{
-    JSClass, JSContext, JSContextGroup, JSObject, JSResult, JSString, JSStringRetain,
-    JSValue,
+    class::{debug_assert_not_finalizing, ClassError, JSClassBuilder},
+    context_data::ContextData,
+    loader::ModuleError,
+    memory::HeapStatistics,
+    JSClass, JSContext, JSContextGroup, JSError, JSFunction, JSObject, JSResult,
+    JSRetainedString, JSString, JSValue, PromiseRejectionEvent, PromiseRejectionKind,
+    PropertyDescriptorBuilder,
 };
 
+type GlobalInitializer = Box<dyn FnOnce(&JSContext) -> JSResult<JSValue>>;
+
+/// The references of `undefined`, `null`, `false` and `true`, in that order. They are
+/// immediate values encoded in the reference itself on 64-bit targets, so they are
+/// the same in every context and never collected. On other targets they can be
+/// cells of a context, so they are made on every call instead.
+#[cfg(target_pointer_width = "64")]
+static SINGLETONS: std::sync::OnceLock<[usize; 4]> = std::sync::OnceLock::new();
+
+/// The identifier of the next context created by the crate, which tells a context
+/// apart from an earlier one at the same address.
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+static NEXT_CONTEXT_ID: std::sync::atomic::AtomicU64 =
+    std::sync::atomic::AtomicU64::new(1);
+
+/// The identifier and the thread of each context created by the crate, by global
+/// context, checked in debug builds by [`debug_assert_context_thread`]. Entries are
+/// removed when the global object of the context is collected.
+#[cfg(debug_assertions)]
+static CONTEXT_THREADS: std::sync::Mutex<
+    Option<HashMap<usize, (u64, std::thread::ThreadId)>>,
+> = std::sync::Mutex::new(None);
+
+/// Registers a context created by the crate: records its thread and its audit
+/// generation, see [`crate::audit`], until its global object is collected.
+fn register_context(ctx: &JSContext) {
+    #[cfg(any(debug_assertions, feature = "value-audit"))]
+    {
+        let global = ctx.inner as usize;
+        let id = NEXT_CONTEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        #[cfg(debug_assertions)]
+        if let Ok(mut threads) = CONTEXT_THREADS.lock() {
+            threads
+                .get_or_insert_with(Default::default)
+                .insert(global, (id, std::thread::current().id()));
+        }
+        crate::audit::context_created(global, id);
+
+        crate::context_data::on_finalize(ctx, move || {
+            #[cfg(debug_assertions)]
+            if let Ok(mut threads) = CONTEXT_THREADS.lock() {
+                let threads = threads.get_or_insert_with(Default::default);
+                // A newer context may have been created at the same address.
+                if threads.get(&global).is_some_and(|(known, _)| *known == id) {
+                    threads.remove(&global);
+                }
+            }
+            crate::audit::context_finalized(global, id);
+        });
+    }
+    #[cfg(not(any(debug_assertions, feature = "value-audit")))]
+    let _ = ctx;
+}
+
+/// Panics, in debug builds, if the context is used from another thread than the
+/// one it was created on.
+///
+/// The Rust state of a context, such as its extensions, is not thread-safe, so a
+/// context moved across threads with `unsafe` code corrupts memory silently; the
+/// panic names the operation and both threads instead.
+pub(crate) fn debug_assert_context_thread(ctx: JSContextRef, operation: &str) {
+    #[cfg(debug_assertions)]
+    {
+        let global = unsafe { JSContextGetGlobalContext(ctx) } as usize;
+        let owner = CONTEXT_THREADS.lock().ok().and_then(|threads| {
+            threads.as_ref()?.get(&global).map(|(_, thread)| *thread)
+        });
+        let current = std::thread::current().id();
+        if let Some(owner) = owner.filter(|owner| *owner != current) {
+            panic!(
+                "{} was called on thread {:?}, but the context was created on thread \
+                 {:?}; a context must only be used on the thread that created it",
+                operation, current, owner
+            );
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = (ctx, operation);
+}
+
+type ExceptionHandler = dyn Fn(&JSContext, Option<&str>, JSError);
+type ExceptionHook = dyn Fn(&JSError, &JSContext);
+
+/// Marks a context whose classic scripts are evaluated in strict mode, see
+/// [`JSContext::set_strict_mode`].
+struct StrictMode;
+
+/// Marks a context whose module loader was set with [`JSContext::set_module_loader`]
+/// rather than by the crate.
+pub(crate) struct CustomModuleLoader;
+
+/// The class the global object of a context was created with, retained so
+/// [`JSContext::reset_global_state`] creates the new global object with it.
+struct GlobalClass(JSClassRef);
+
+impl Drop for GlobalClass {
+    fn drop(&mut self) {
+        unsafe { JSClassRelease(self.0) };
+    }
+}
+
+/// The closures of [`JSContext::on_uncaught_exception`],
+/// [`JSContext::on_uncaught_exception_at_event_loop`] and
+/// [`JSContext::set_exception_hook`], stored as a context extension.
+#[derive(Default)]
+pub(crate) struct ExceptionHandlers {
+    uncaught: RefCell<Option<Rc<ExceptionHandler>>>,
+    event_loop: RefCell<Option<Rc<ExceptionHandler>>>,
+    hook: RefCell<Option<Rc<ExceptionHook>>>,
+    /// Set while the hook runs, so exceptions thrown by the hook are not reported.
+    reporting: Cell<bool>,
+}
+
+impl ExceptionHandlers {
+    /// Calls the exception hook of the context, if one is set.
+    pub(crate) fn report(ctx: &JSContext, error: &JSError) {
+        let Some(handlers) = ctx.get_extension::<ExceptionHandlers>() else {
+            return;
+        };
+        let hook = handlers.hook.borrow().clone();
+        if let Some(hook) = hook {
+            if handlers.reporting.replace(true) {
+                return;
+            }
+            hook(error, ctx);
+            handlers.reporting.set(false);
+        }
+    }
+
+    fn get(ctx: &JSContext) -> Rc<ExceptionHandlers> {
+        match ctx.get_extension::<ExceptionHandlers>() {
+            Some(handlers) => handlers,
+            None => {
+                ctx.set_extension(ExceptionHandlers::default());
+                ctx.get_extension::<ExceptionHandlers>()
+                    .expect("Exception handlers must be set")
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn uncaught_exception_trampoline(
+    ctx: JSContextRef,
+    filename: JSStringRef,
+    exception: JSValueRef,
+) {
+    let context = JSContext::from(ctx);
+    let handler = context
+        .get_extension::<ExceptionHandlers>()
+        .and_then(|handlers| handlers.uncaught.borrow().clone());
+    if let Some(handler) = handler {
+        let filename =
+            (!filename.is_null()).then(|| JSRetainedString::from(filename).to_string());
+        let error = JSError::from_exception(exception, ctx);
+        handler(&context, filename.as_deref(), error);
+    }
+}
+
+unsafe extern "C" fn event_loop_exception_trampoline(
+    ctx: JSContextRef,
+    exception: JSValueRef,
+) {
+    let context = JSContext::from(ctx);
+    let handler = context
+        .get_extension::<ExceptionHandlers>()
+        .and_then(|handlers| handlers.event_loop.borrow().clone());
+    if let Some(handler) = handler {
+        let error = JSError::from_exception(exception, ctx);
+        handler(&context, None, error);
+    }
+}
+
+/// A builder that collects the configuration of a context and creates it in one call.
+///
+/// The configuration is applied in a fixed order: name and inspector settings,
+/// module loader and virtual modules, VM options, classes and finally globals, so
+/// globals can rely on everything else being in place.
+///
+/// The VM options are the ones the crate controls per context, strict mode and the
+/// recursion limit. The engine wide options of JavaScriptCore, such as the JIT
+/// tiers, are not exposed by its C API and cannot be set here.
+pub struct ContextBuilder<'a> {
+    group: Option<&'a JSContextGroup>,
+    global_class: Option<&'a JSClass>,
+    name: Option<String>,
+    inspectable: Option<bool>,
+    module_loader: Option<JSAPIModuleLoader>,
+    virtual_modules: Vec<JSRetainedString>,
+    strict_mode: bool,
+    recursion_limit: Option<usize>,
+    classes: Vec<&'a JSClass>,
+    globals: Vec<(String, GlobalInitializer)>,
+}
+
+impl<'a> ContextBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            group: None,
+            global_class: None,
+            name: None,
+            inspectable: None,
+            module_loader: None,
+            virtual_modules: Vec::new(),
+            strict_mode: false,
+            recursion_limit: None,
+            classes: Vec::new(),
+            globals: Vec::new(),
+        }
+    }
+
+    /// Creates the context in the given group, so it shares the virtual machine
+    /// with the other contexts of the group.
+    pub fn group(mut self, group: &'a JSContextGroup) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Uses the given class for the global object.
+    pub fn global_class(mut self, class: &'a JSClass) -> Self {
+        self.global_class = Some(class);
+        self
+    }
+
+    /// Sets the name of the context, shown in the inspector.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets whether the context can be inspected by a remote inspector.
+    pub fn inspectable(mut self, inspectable: bool) -> Self {
+        self.inspectable = Some(inspectable);
+        self
+    }
+
+    /// Sets the module loader, see [`JSContext::set_module_loader`].
+    pub fn module_loader(mut self, module_loader: JSAPIModuleLoader) -> Self {
+        self.module_loader = Some(module_loader);
+        self
+    }
+
+    /// Adds a virtual module key, see [`JSContext::set_virtual_module_keys`].
+    pub fn virtual_module(mut self, key: impl Into<JSRetainedString>) -> Self {
+        self.virtual_modules.push(key.into());
+        self
+    }
+
+    /// Evaluates classic scripts in strict mode, see [`JSContext::set_strict_mode`].
+    pub fn strict_mode(mut self, strict: bool) -> Self {
+        self.strict_mode = strict;
+        self
+    }
+
+    /// Limits the depth of native calls, see [`JSContext::set_recursion_limit`].
+    pub fn recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = Some(limit);
+        self
+    }
+
+    /// Registers the class on the global object, see [`JSClass::register`].
+    pub fn class(mut self, class: &'a JSClass) -> Self {
+        self.classes.push(class);
+        self
+    }
+
+    /// Defines a global property whose value is created once the context exists.
+    pub fn global<F>(mut self, name: &str, value: F) -> Self
+    where
+        F: FnOnce(&JSContext) -> JSResult<JSValue> + 'static,
+    {
+        self.globals.push((name.to_string(), Box::new(value)));
+        self
+    }
+
+    /// Defines a global function backed by a native callback.
+    pub fn function(self, name: &str, callback: JSObjectCallAsFunctionCallback) -> Self {
+        let function_name = name.to_string();
+        self.global(name, move |ctx| {
+            Ok(JSFunction::callback(ctx, Some(function_name.as_str()), callback).into())
+        })
+    }
+
+    /// Creates the context.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::{JSContext, JSValue};
+    ///
+    /// let ctx = JSContext::builder()
+    ///     .name("worker")
+    ///     .virtual_module("@rust-jsc")
+    ///     .global("version", |ctx| Ok(JSValue::string(ctx, "1.0.0")))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If creating one of the globals fails. The partially configured context is
+    /// dropped and never returned.
+    pub fn build(self) -> JSResult<JSContext> {
+        let class = self
+            .global_class
+            .map(|class| class.inner)
+            .unwrap_or(std::ptr::null_mut());
+        let inner = match self.group {
+            Some(group) => unsafe {
+                JSGlobalContextCreateInGroup(group.context_group, class)
+            },
+            None => unsafe { JSGlobalContextCreate(class) },
+        };
+        let ctx = JSContext { inner };
+        register_context(&ctx);
+        if let Some(group) = self.group {
+            GroupMembership::register(group.context_group, &ctx);
+        }
+
+        if let Some(class) = self.global_class {
+            ctx.remember_global_class(class);
+        }
+
+        if let Some(name) = &self.name {
+            ctx.set_name(name);
+        }
+
+        if let Some(inspectable) = self.inspectable {
+            ctx.set_inspectable(inspectable);
+        }
+
+        if let Some(module_loader) = self.module_loader {
+            ctx.set_module_loader(module_loader);
+        }
+
+        if !self.virtual_modules.is_empty() {
+            ctx.set_virtual_module_keys(&self.virtual_modules);
+        }
+
+        ctx.set_strict_mode(self.strict_mode);
+        ctx.set_recursion_limit(self.recursion_limit);
+
+        for class in self.classes {
+            class.register(&ctx)?;
+        }
+
+        let global_object = ctx.global_object();
+        for (name, initializer) in self.globals {
+            let value = initializer(&ctx)?;
+            global_object.set_property(name.as_str(), &value, Default::default())?;
+        }
+
+        Ok(ctx)
+    }
+}
+
+impl Default for ContextBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Wraps `Promise.prototype.then` to report promises that get a handler after
+/// they have been reported as unhandled. Installing it twice only swaps the reporter.
+const PROMISE_REJECTION_TRACKER: &str = r#"(function (report) {
+    const key = Symbol.for("rust_jsc.promiseRejectionTracker");
+    const then = Promise.prototype.then;
+    if (typeof then[key] === "function") {
+        return then[key](report);
+    }
+
+    const pending = new WeakMap();
+    const apply = Reflect.apply;
+    let reporter = report;
+
+    const tracked = {
+        then(onFulfilled, onRejected) {
+            if (pending.has(this)) {
+                const reason = pending.get(this);
+                pending.delete(this);
+                reporter(this, reason, true);
+            }
+
+            return apply(then, this, [onFulfilled, onRejected]);
+        },
+    }.then;
+
+    const install = (next) => {
+        reporter = next;
+        return (promise, reason) => {
+            pending.set(promise, reason);
+            reporter(promise, reason, false);
+        };
+    };
+
+    Object.defineProperty(tracked, key, { value: install });
+    Object.defineProperty(Promise.prototype, "then", {
+        value: tracked,
+        writable: true,
+        configurable: true,
+    });
+
+    return install(report);
+})"#;
+
+/// The Rust state of a context group, kept while the group has Rust handles or
+/// registered contexts.
+#[derive(Default)]
+struct GroupEntry {
+    /// The name of [`JSContextGroup::set_name`].
+    name: Option<String>,
+    /// The number of `JSContextGroup` handles to the group.
+    handles: usize,
+    /// The global contexts created in the group, in creation order.
+    contexts: Vec<usize>,
+}
+
+impl GroupEntry {
+    fn is_unused(&self) -> bool {
+        self.handles == 0 && self.contexts.is_empty()
+    }
+}
+
+thread_local! {
+    /// The context groups of the thread, by group.
+    static GROUPS: RefCell<HashMap<usize, GroupEntry>> = RefCell::new(HashMap::new());
+}
+
+/// Updates the entry of a group, removing it once it is unused. Does nothing while
+/// the thread is shutting down.
+fn update_group<R>(
+    group: JSContextGroupRef,
+    update: impl FnOnce(&mut GroupEntry) -> R,
+) -> Option<R> {
+    GROUPS
+        .try_with(|groups| {
+            let mut groups = groups.borrow_mut();
+            let entry = groups.entry(group as usize).or_default();
+            let result = update(entry);
+            if entry.is_unused() {
+                groups.remove(&(group as usize));
+            }
+            result
+        })
+        .ok()
+}
+
+thread_local! {
+    /// The group and the number of `JSContext` handles of each context listed in a
+    /// group, by global context.
+    static MEMBERS: RefCell<HashMap<usize, (JSContextGroupRef, usize)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The listing of the contexts created in a group. A context is listed from its
+/// creation until its last `JSContext` handle is dropped, so
+/// [`JSContextGroup::contexts`] never returns a context the engine may have collected.
+struct GroupMembership;
+
+impl GroupMembership {
+    /// Lists a context created in the group, with its first handle.
+    fn register(group: JSContextGroupRef, ctx: &JSContext) {
+        update_group(group, |entry| entry.contexts.push(ctx.inner as usize));
+        let _ = MEMBERS.try_with(|members| {
+            members.borrow_mut().insert(ctx.inner as usize, (group, 1))
+        });
+    }
+
+    /// Counts a new handle to the context, if it is listed.
+    fn retained(ctx: JSGlobalContextRef) {
+        let _ = MEMBERS.try_with(|members| {
+            if let Some((_, handles)) = members.borrow_mut().get_mut(&(ctx as usize)) {
+                *handles += 1;
+            }
+        });
+    }
+
+    /// Counts a dropped handle to the context, removing it from its group with the
+    /// last one.
+    fn released(ctx: JSGlobalContextRef) {
+        let context = ctx as usize;
+        let unlisted = MEMBERS
+            .try_with(|members| {
+                let mut members = members.borrow_mut();
+                let (group, handles) = members.get_mut(&context)?;
+                *handles -= 1;
+                let group = *group;
+                (*handles == 0).then(|| {
+                    members.remove(&context);
+                    group
+                })
+            })
+            .ok()
+            .flatten();
+
+        if let Some(group) = unlisted {
+            update_group(group, |entry| {
+                entry.contexts.retain(|registered| *registered != context)
+            });
+        }
+    }
+
+    /// Lists the global context that replaced a listed one in its place, moving the
+    /// handle of the context over to it.
+    fn replace(ctx: &JSContext, previous: JSGlobalContextRef) {
+        let group = MEMBERS
+            .try_with(|members| {
+                members
+                    .borrow()
+                    .get(&(previous as usize))
+                    .map(|(group, _)| *group)
+            })
+            .ok()
+            .flatten();
+        let Some(group) = group else {
+            return;
+        };
+
+        update_group(group, |entry| {
+            let position = entry
+                .contexts
+                .iter()
+                .position(|context| *context == previous as usize)
+                .map_or(entry.contexts.len(), |position| position + 1);
+            entry.contexts.insert(position, ctx.inner as usize);
+        });
+        let _ = MEMBERS.try_with(|members| {
+            members.borrow_mut().insert(ctx.inner as usize, (group, 1))
+        });
+        Self::released(previous);
+    }
+}
+
 impl JSContextGroup {
+    /// Creates a new context in the group, sharing the virtual machine with the
+    /// other contexts of the group.
     pub fn new_context(&self) -> JSContext {
         let ctx = unsafe {
             JSGlobalContextCreateInGroup(self.context_group, std::ptr::null_mut())
         };
-        JSContext::from(ctx)
+        self.adopt_context(ctx)
     }
 
+    /// Creates a new context in the group, with the given class for the global object.
     pub fn new_context_with_class(&self, class: &JSClass) -> JSContext {
         let ctx =
             unsafe { JSGlobalContextCreateInGroup(self.context_group, class.inner) };
-        JSContext::from(ctx)
+        let ctx = self.adopt_context(ctx);
+        ctx.remember_global_class(class);
+        ctx
+    }
+
+    /// Takes ownership of a context just created in the group and registers it.
+    fn adopt_context(&self, ctx: JSGlobalContextRef) -> JSContext {
+        let ctx = JSContext { inner: ctx };
+        register_context(&ctx);
+        GroupMembership::register(self.context_group, &ctx);
+        ctx
     }
 
     /// Creates a new `JSContextGroup` object.
     pub fn new() -> Self {
         let context_group = unsafe { JSContextGroupCreate() };
-        Self { context_group }
+        Self::from(context_group)
+    }
+
+    /// Sets the name of the group, to tell the virtual machines of an application
+    /// apart in logs and statistics. The name is kept as long as the group has
+    /// `JSContextGroup` handles or contexts created through them.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the group.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContextGroup;
+    ///
+    /// let group = JSContextGroup::new();
+    /// group.set_name("workers");
+    /// assert_eq!(group.name().as_deref(), Some("workers"));
+    /// ```
+    pub fn set_name(&self, name: &str) {
+        update_group(self.context_group, |entry| {
+            entry.name = Some(name.to_string())
+        });
+    }
+
+    /// Returns the name of the group, if one was set with [`JSContextGroup::set_name`].
+    pub fn name(&self) -> Option<String> {
+        GROUPS
+            .try_with(|groups| {
+                groups
+                    .borrow()
+                    .get(&(self.context_group as usize))
+                    .and_then(|entry| entry.name.clone())
+            })
+            .ok()
+            .flatten()
+    }
+
+    /// Returns the contexts created in the group with [`JSContextGroup::new_context`],
+    /// [`JSContextGroup::new_context_with_class`] or a [`ContextBuilder`] given the
+    /// group, in creation order.
+    ///
+    /// A context is listed until the last `JSContext` handle to it is dropped; the
+    /// returned handles keep the contexts alive and listed.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContextGroup;
+    ///
+    /// let group = JSContextGroup::new();
+    /// let first = group.new_context();
+    /// let second = group.new_context();
+    /// assert_eq!(group.contexts().len(), 2);
+    /// ```
+    pub fn contexts(&self) -> Vec<JSContext> {
+        let contexts = GROUPS
+            .try_with(|groups| {
+                groups
+                    .borrow()
+                    .get(&(self.context_group as usize))
+                    .map(|entry| entry.contexts.clone())
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        contexts
+            .into_iter()
+            .map(|context| JSContext::from(context as JSContextRef))
+            .collect()
+    }
+
+    /// Returns the heap statistics of the virtual machine of the group, which the
+    /// contexts of the group share.
+    ///
+    /// # Returns
+    /// The statistics, or `None` if the group has no registered context to read them
+    /// from, see [`JSContextGroup::contexts`].
+    ///
+    /// # Errors
+    /// If the statistics cannot be read.
+    pub fn heap_statistics(&self) -> JSResult<Option<HeapStatistics>> {
+        match self.contexts().first() {
+            Some(ctx) => ctx.heap_statistics().map(Some),
+            None => Ok(None),
+        }
     }
 }
 
 impl From<JSContextGroupRef> for JSContextGroup {
     fn from(group: JSContextGroupRef) -> Self {
+        update_group(group, |entry| entry.handles += 1);
         Self {
             context_group: group,
         }
@@ -50,6 +693,7 @@ impl From<JSContextGroupRef> for JSContextGroup {
 
 impl Drop for JSContextGroup {
     fn drop(&mut self) {
+        update_group(self.context_group, |entry| entry.handles -= 1);
         unsafe {
             JSContextGroupRelease(self.context_group);
         }
@@ -58,7 +702,11 @@ impl Drop for JSContextGroup {
 
 impl std::fmt::Debug for JSContextGroup {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("JSContextGroup").finish()
+        let mut debug = f.debug_struct("JSContextGroup");
+        if let Some(name) = self.name() {
+            debug.field("name", &name);
+        }
+        debug.finish()
     }
 }
 
@@ -75,12 +723,79 @@ impl JSContext {
     /// ```
     pub fn new() -> Self {
         let ctx = unsafe { JSGlobalContextCreate(std::ptr::null_mut()) };
-        Self { inner: ctx }
+        let ctx = Self { inner: ctx };
+        register_context(&ctx);
+        ctx
     }
 
     pub fn new_with_class(class: &JSClass) -> Self {
         let ctx = unsafe { JSGlobalContextCreate(class.inner) };
-        Self { inner: ctx }
+        let ctx = Self { inner: ctx };
+        register_context(&ctx);
+        ctx.remember_global_class(class);
+        ctx
+    }
+
+    /// Keeps the class of the global object for [`JSContext::reset_global_state`].
+    fn remember_global_class(&self, class: &JSClass) {
+        unsafe { JSClassRetain(class.inner) };
+        self.set_extension(GlobalClass(class.inner));
+    }
+
+    /// Creates a new context whose global object has a class configured by the
+    /// closure, e.g. with property interceptors that resolve globals lazily from
+    /// Rust instead of defining them up front.
+    ///
+    /// The interceptors are declared with the [`has_property`], [`get_property`] and
+    /// [`set_property`] macros. They are consulted before the properties defined on
+    /// the global object; returning `None` or `false` falls back to them.
+    ///
+    /// [`has_property`]: crate::has_property
+    /// [`get_property`]: crate::get_property
+    /// [`set_property`]: crate::set_property
+    ///
+    /// # Arguments
+    /// - `configure`: A closure that configures the class of the global object.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{get_property, JSContext, JSObject, JSResult, JSString, JSValue};
+    ///
+    /// #[get_property]
+    /// fn env(ctx: JSContext, _global: JSObject, name: JSString) -> JSResult<Option<JSValue>> {
+    ///     let value = std::env::var(format!("APP_{}", name)).ok();
+    ///     Ok(value.map(|value| JSValue::string(&ctx, value)))
+    /// }
+    ///
+    /// let ctx = JSContext::new_with(|global| global.get_property(Some(env))).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the class of the global object cannot be created.
+    pub fn new_with<F>(configure: F) -> Result<Self, ClassError>
+    where
+        F: FnOnce(JSClassBuilder) -> JSClassBuilder,
+    {
+        // The global object retains its class, so the class can be released here.
+        let class = configure(JSClass::builder("GlobalObject")).build()?;
+        Ok(Self::new_with_class(&class))
+    }
+
+    /// Returns a [`ContextBuilder`] to configure a new context in one call.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{JSContext, JSValue};
+    ///
+    /// let ctx = JSContext::builder()
+    ///     .name("main")
+    ///     .inspectable(true)
+    ///     .global("answer", |ctx| Ok(JSValue::number(ctx, 42.0)))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder<'a>() -> ContextBuilder<'a> {
+        ContextBuilder::new()
     }
 
     /// Garbage collects the JavaScript execution context.
@@ -168,13 +883,116 @@ impl JSContext {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(())
     }
 
+    /// Registers a handler that is called with a [`PromiseRejectionEvent`] when a
+    /// promise is rejected without a handler (`PromiseRejectionKind::Unhandled`), and
+    /// again when a handler is attached to it later (`PromiseRejectionKind::Handled`).
+    /// Registering a new handler replaces the previous one.
+    ///
+    /// Late handlers are detected through `Promise.prototype.then`, so handlers
+    /// attached with `await` are not reported as `Handled`.
+    ///
+    /// # Arguments
+    /// - `handler`: The closure to call for each rejection event.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::{JSContext, PromiseRejectionKind};
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.on_promise_rejection(|_ctx, event| match event.kind {
+    ///     PromiseRejectionKind::Unhandled => println!("unhandled: {:?}", event.reason),
+    ///     PromiseRejectionKind::Handled => println!("handled later"),
+    /// })
+    /// .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the rejection tracker cannot be installed in the context.
+    pub fn on_promise_rejection<F>(&self, handler: F) -> JSResult<()>
+    where
+        F: Fn(&JSContext, PromiseRejectionEvent) + 'static,
+    {
+        let report = JSFunction::closure(self, move |ctx, _this, arguments| {
+            let undefined = JSValue::undefined(ctx);
+            let promise = arguments.first().unwrap_or(&undefined).as_object()?;
+            let reason = arguments.get(1).unwrap_or(&undefined).clone();
+            let kind = match arguments.get(2).map(JSValue::as_boolean) {
+                Some(true) => PromiseRejectionKind::Handled,
+                _ => PromiseRejectionKind::Unhandled,
+            };
+
+            handler(
+                ctx,
+                PromiseRejectionEvent {
+                    kind,
+                    promise,
+                    reason,
+                },
+            );
+            Ok(undefined)
+        });
+
+        let tracker = self
+            .evaluate_script(PROMISE_REJECTION_TRACKER, None)?
+            .as_object()?;
+        let callback = tracker.call(None, &[report.into()])?.as_object()?;
+        self.set_unhandled_rejection_callback(callback)
+    }
+
+    /// Queues a closure to run as a microtask, like `queueMicrotask` in JavaScript.
+    /// Microtasks run once control returns from the outermost call into the context.
+    /// An error returned by the task is reported as an unhandled promise rejection.
+    ///
+    /// # Arguments
+    /// - `task`: The closure to run.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.queue_microtask(|ctx| {
+    ///     ctx.evaluate_script("globalThis.ran = true", None)?;
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the task cannot be queued.
+    pub fn queue_microtask<F>(&self, task: F) -> JSResult<()>
+    where
+        F: FnOnce(&JSContext) -> JSResult<()> + 'static,
+    {
+        let task = std::cell::Cell::new(Some(task));
+        let function = JSFunction::closure(self, move |ctx, _this, _arguments| {
+            if let Some(task) = task.take() {
+                task(ctx)?;
+            }
+
+            Ok(JSValue::undefined(ctx))
+        });
+
+        let promise: JSObject = self.global_constructor("Promise")?.into();
+        let resolved = promise
+            .get_property("resolve")?
+            .as_object()?
+            .call(Some(&promise), &[])?
+            .as_object()?;
+        resolved
+            .get_property("then")?
+            .as_object()?
+            .call(Some(&resolved), &[function.into()])?;
+
+        Ok(())
+    }
+
     /// Sets a callback function that is called when an exception is not caught.
     /// The callback is called with the exception value.
     /// The callback is called on the context thread.
@@ -183,7 +1001,7 @@ impl JSContext {
     /// - `handler`: A native function
     ///
     /// # Examples
-    /// 
+    ///
     /// ```ignore
     /// use rust_jsc::JSContext;
     ///
@@ -233,6 +1051,92 @@ impl JSContext {
         };
     }
 
+    /// Sets a closure that is called when an exception is not caught, in place of
+    /// the handler of [`JSContext::set_uncaught_exception_handler`]. Unlike a native
+    /// function, the closure can capture the state it reports to.
+    ///
+    /// # Arguments
+    /// - `handler`: A closure called with the context, the file name of the script
+    ///   that threw, if known, and the exception.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let errors = Rc::new(RefCell::new(Vec::new()));
+    /// let log = errors.clone();
+    /// ctx.on_uncaught_exception(move |_ctx, filename, error| {
+    ///     log.borrow_mut().push((filename.map(String::from), error.message().unwrap().to_string()));
+    /// });
+    /// ```
+    pub fn on_uncaught_exception<F>(&self, handler: F)
+    where
+        F: Fn(&JSContext, Option<&str>, JSError) + 'static,
+    {
+        ExceptionHandlers::get(self)
+            .uncaught
+            .replace(Some(Rc::new(handler)));
+        self.set_uncaught_exception_handler(Some(uncaught_exception_trampoline));
+    }
+
+    /// Sets a closure that is called when an exception is not caught at the event
+    /// loop, in place of the callback of
+    /// [`JSContext::set_uncaught_exception_at_event_loop_callback`].
+    ///
+    /// # Arguments
+    /// - `handler`: A closure called with the context, `None` for the file name and
+    ///   the exception.
+    pub fn on_uncaught_exception_at_event_loop<F>(&self, handler: F)
+    where
+        F: Fn(&JSContext, Option<&str>, JSError) + 'static,
+    {
+        ExceptionHandlers::get(self)
+            .event_loop
+            .replace(Some(Rc::new(handler)));
+        self.set_uncaught_exception_at_event_loop_callback(Some(
+            event_loop_exception_trampoline,
+        ));
+    }
+
+    /// Sets a closure that is called with every exception the API returns as a
+    /// [`JSError`], whether or not the caller handles it, for logging or counting the
+    /// errors thrown by scripts. Exceptions thrown while the hook runs are not
+    /// reported to it.
+    ///
+    /// # Arguments
+    /// - `hook`: A closure called with the error and the context.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::{cell::Cell, rc::Rc};
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let errors = Rc::new(Cell::new(0));
+    /// let count = errors.clone();
+    /// ctx.set_exception_hook(move |_error, _ctx| count.set(count.get() + 1));
+    ///
+    /// let _ = ctx.evaluate_script("null.key", None);
+    /// assert_eq!(errors.get(), 1);
+    /// ```
+    pub fn set_exception_hook<F>(&self, hook: F)
+    where
+        F: Fn(&JSError, &JSContext) + 'static,
+    {
+        ExceptionHandlers::get(self)
+            .hook
+            .replace(Some(Rc::new(hook)));
+    }
+
+    /// Removes the closure set with [`JSContext::set_exception_hook`].
+    pub fn clear_exception_hook(&self) {
+        if let Some(handlers) = self.get_extension::<ExceptionHandlers>() {
+            handlers.hook.replace(None);
+        }
+    }
+
     /// Checks the syntax of a JavaScript script.
     ///
     /// # Arguments
@@ -257,7 +1161,9 @@ impl JSContext {
         script: &str,
         starting_line_number: i32,
     ) -> JSResult<bool> {
-        let script: JSString = script.into();
+        debug_assert_not_finalizing("JSContext::check_syntax");
+        debug_assert_context_thread(self.inner, "JSContext::check_syntax");
+        let script: JSString = self.strict_source(script).as_ref().into();
         let source_url = std::ptr::null_mut();
         let mut exception: JSValueRef = std::ptr::null_mut();
         let result = unsafe {
@@ -271,18 +1177,68 @@ impl JSContext {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(result)
     }
 
+    /// Returns the group of the context, whose virtual machine the context shares
+    /// with the other contexts of the group.
     pub fn group(&self) -> JSContextGroup {
-        let group = unsafe { JSContextGetGroup(self.inner) };
+        let group = unsafe { JSContextGroupRetain(JSContextGetGroup(self.inner)) };
         JSContextGroup::from(group)
     }
 
+    /// Replaces the global object with a fresh one, created in the same context group.
+    /// Parsed code and the heap of the virtual machine are kept, so this is much
+    /// cheaper than creating a new group.
+    ///
+    /// The name, the inspectable flag and the shared data are carried over, and the new
+    /// global object is created with the class of the old one if the context was
+    /// created with a class by this crate. Any other setting applied after creation,
+    /// such as the module loader or the virtual module keys, must be applied again.
+    /// Other `JSContext` handles keep referring to the old global object.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let mut ctx = JSContext::new();
+    /// ctx.evaluate_script("globalThis.leak = 1", None).unwrap();
+    /// ctx.reset_global_state();
+    /// assert!(ctx.evaluate_script("leak", None).is_err());
+    /// ```
+    pub fn reset_global_state(&mut self) {
+        self.cancel_host_operations("The context was reset");
+        let inspectable = self.is_inspectable();
+        let context_data = ContextData::shared(self);
+        let class = self
+            .get_extension::<GlobalClass>()
+            .map_or(std::ptr::null_mut(), |class| class.0);
+        let inner = unsafe {
+            let group = JSContextGetGroup(self.inner);
+            let inner = JSGlobalContextCreateInGroup(group, class);
+
+            let name = JSGlobalContextCopyName(self.inner);
+            if !name.is_null() {
+                JSGlobalContextSetName(inner, name);
+                JSStringRelease(name);
+            }
+
+            JSGlobalContextRelease(self.inner);
+            inner
+        };
+
+        let previous = std::mem::replace(&mut self.inner, inner);
+        register_context(self);
+        GroupMembership::replace(self, previous);
+        self.set_inspectable(inspectable);
+        if let Some(context_data) = context_data {
+            ContextData::attach(self, context_data);
+        }
+    }
+
     /// Gets the global object of the JavaScript execution context.
     ///
     /// # Examples
@@ -301,6 +1257,71 @@ impl JSContext {
         JSObject::from_ref(unsafe { JSContextGetGlobalObject(self.inner) }, self.inner)
     }
 
+    /// Returns `undefined`. Unlike [`JSValue::undefined`], the value is made once per
+    /// process on 64-bit targets, so hot callbacks do not cross into the engine for it.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(ctx.undefined().is_undefined());
+    /// ```
+    pub fn undefined(&self) -> JSValue {
+        self.singleton(0)
+    }
+
+    /// Returns `null`, made once per process like [`JSContext::undefined`].
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(ctx.null().is_null());
+    /// ```
+    pub fn null(&self) -> JSValue {
+        self.singleton(1)
+    }
+
+    /// Returns `true` or `false`, made once per process like [`JSContext::undefined`].
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(ctx.bool(true).as_boolean());
+    /// ```
+    pub fn bool(&self, value: bool) -> JSValue {
+        self.singleton(2 + value as usize)
+    }
+
+    /// Returns `undefined`, `null`, `false` or `true` by index, see `SINGLETONS`.
+    fn singleton(&self, index: usize) -> JSValue {
+        #[cfg(target_pointer_width = "64")]
+        let inner = {
+            let singletons = SINGLETONS.get_or_init(|| {
+                [0, 1, 2, 3].map(|index| self.make_singleton(index) as usize)
+            });
+            singletons[index] as JSValueRef
+        };
+        #[cfg(not(target_pointer_width = "64"))]
+        let inner = self.make_singleton(index);
+
+        JSValue::new(inner, self.inner)
+    }
+
+    fn make_singleton(&self, index: usize) -> JSValueRef {
+        unsafe {
+            match index {
+                0 => JSValueMakeUndefined(self.inner),
+                1 => JSValueMakeNull(self.inner),
+                _ => JSValueMakeBoolean(self.inner, index == 3),
+            }
+        }
+    }
+
     /// Evaluates a JavaScript module.
     ///
     /// # Examples
@@ -316,14 +1337,22 @@ impl JSContext {
     ///
     /// It will use a file system module loader to load the module.
     ///
-    pub fn evaluate_module(&self, filename: &str) -> JSResult<()> {
-        let filename: JSString = filename.into();
+    /// # Errors
+    /// A [`ModuleError`] with the exception thrown while loading or evaluating the
+    /// module, the module it comes from, its importers and the phase that failed.
+    /// With the loader of [`JSContext::set_module_root`], a `ReferenceError` caused by
+    /// an import cycle names the modules of the cycle in its message.
+    pub fn evaluate_module(&self, filename: &str) -> Result<(), ModuleError> {
+        debug_assert_not_finalizing("JSContext::evaluate_module");
+        debug_assert_context_thread(self.inner, "JSContext::evaluate_module");
+        let key: JSString = filename.into();
         let mut exception: JSValueRef = std::ptr::null_mut();
-        unsafe { JSLoadAndEvaluateModule(self.inner, filename.inner, &mut exception) };
+        self.clear_module_failure();
+        unsafe { JSLoadAndEvaluateModule(self.inner, key.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            let error = JSError::from_exception(exception, self.inner);
+            return Err(self.module_error(filename, error));
         }
 
         Ok(())
@@ -351,13 +1380,14 @@ impl JSContext {
     /// assert!(result.is_ok());
     /// ```
     pub fn load_module(&self, key: &str) -> JSResult<()> {
+        debug_assert_not_finalizing("JSContext::load_module");
+        debug_assert_context_thread(self.inner, "JSContext::load_module");
         let module_key: JSString = key.into();
         let mut exception: JSValueRef = std::ptr::null_mut();
         unsafe { JSLoadModule(self.inner, module_key.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(())
@@ -390,6 +1420,8 @@ impl JSContext {
     /// assert!(result.is_undefined());
     /// ```
     pub fn link_and_evaluate_module(&self, key: &str) -> JSValue {
+        debug_assert_not_finalizing("JSContext::link_and_evaluate_module");
+        debug_assert_context_thread(self.inner, "JSContext::link_and_evaluate_module");
         let module_key: JSString = key.into();
         let result = unsafe { JSLinkAndEvaluateModule(self.inner, module_key.inner) };
 
@@ -434,8 +1466,7 @@ impl JSContext {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(())
@@ -468,6 +1499,8 @@ impl JSContext {
         source_url: &str,
         starting_line_number: Option<i32>,
     ) -> JSResult<()> {
+        debug_assert_not_finalizing("JSContext::evaluate_module_from_source");
+        debug_assert_context_thread(self.inner, "JSContext::evaluate_module_from_source");
         let source: JSString = source.into();
         let source_url: JSString = source_url.into();
         let mut exception: JSValueRef = std::ptr::null_mut();
@@ -483,8 +1516,7 @@ impl JSContext {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(())
@@ -498,6 +1530,13 @@ impl JSContext {
     /// # Arguments
     /// - `module_loader`: A module loader.
     pub fn set_module_loader(&self, module_loader: JSAPIModuleLoader) {
+        self.install_module_loader(module_loader);
+        self.set_extension(CustomModuleLoader);
+    }
+
+    /// Sets the module loader without marking it as set by the embedder, for the
+    /// loader of the crate.
+    pub(crate) fn install_module_loader(&self, module_loader: JSAPIModuleLoader) {
         unsafe { JSSetAPIModuleLoader(self.inner, module_loader) };
     }
 
@@ -510,15 +1549,15 @@ impl JSContext {
     /// # Examples
     ///
     /// ```
-    /// use rust_jsc::{JSContext, JSStringRetain};
+    /// use rust_jsc::{JSContext, JSRetainedString};
     ///
     /// let ctx = JSContext::new();
     /// let keys = &[
-    ///    JSStringRetain::from("@rust-jsc"),
+    ///    JSRetainedString::from("@rust-jsc"),
     /// ];
     /// ctx.set_virtual_module_keys(keys);
     /// ```
-    pub fn set_virtual_module_keys(&self, keys: &[JSStringRetain]) {
+    pub fn set_virtual_module_keys(&self, keys: &[JSRetainedString]) {
         let keys: Vec<JSStringRef> = keys.iter().map(|key| key.0).collect();
         unsafe {
             JSSetSyntheticModuleKeys(self.inner, keys.len(), keys.as_ptr());
@@ -548,29 +1587,127 @@ impl JSContext {
         script: &str,
         starting_line_number: Option<i32>,
     ) -> JSResult<JSValue> {
-        let script: JSString = script.into();
+        self.evaluate_script_at(script, None, starting_line_number)
+    }
+
+    /// Evaluates a JavaScript script with a source URL, which the engine reports in
+    /// the `sourceURL` and the stack of errors thrown by the script.
+    ///
+    /// The source URL is also the referrer of the dynamic `import()` calls of the
+    /// script, so with the loader of [`JSContext::set_module_root`] relative
+    /// specifiers resolve next to it. A `file://` URL is treated as its path.
+    ///
+    /// # Arguments
+    /// - `script`: A JavaScript script.
+    /// - `source_url`: The URL of the script, such as its path.
+    /// - `starting_line_number`: The line number to start parsing the script.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let error = ctx
+    ///     .evaluate_script_with_source_url("throw new Error('boom')", "/srv/app.js", Some(1))
+    ///     .unwrap_err();
+    /// let url = error.as_object().unwrap().get_property("sourceURL").unwrap();
+    /// assert_eq!(url.as_string().unwrap().to_string(), "/srv/app.js");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if the script has a syntax error or throws.
+    pub fn evaluate_script_with_source_url(
+        &self,
+        script: &str,
+        source_url: &str,
+        starting_line_number: Option<i32>,
+    ) -> JSResult<JSValue> {
+        self.evaluate_script_at(script, Some(source_url), starting_line_number)
+    }
+
+    fn evaluate_script_at(
+        &self,
+        script: &str,
+        source_url: Option<&str>,
+        starting_line_number: Option<i32>,
+    ) -> JSResult<JSValue> {
+        debug_assert_not_finalizing("JSContext::evaluate_script");
+        debug_assert_context_thread(self.inner, "JSContext::evaluate_script");
+        let script: JSString = self.strict_source(script).as_ref().into();
         let this_object = std::ptr::null_mut();
-        let source_url = std::ptr::null_mut();
+        let source_url: Option<JSString> = source_url.map(Into::into);
         let mut exception: JSValueRef = std::ptr::null_mut();
         let result = unsafe {
             JSEvaluateScript(
                 self.inner,
                 script.inner,
                 this_object,
-                source_url,
+                source_url
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), |url| url.inner),
                 starting_line_number.unwrap_or(0),
                 &mut exception,
             )
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.inner);
-            return Err(value.into());
+            return Err(JSError::from_exception(exception, self.inner));
         }
 
         Ok(JSValue::new(result, self.inner))
     }
 
+    /// Sets whether the classic scripts evaluated in the context, with
+    /// [`JSContext::evaluate_script`] and the functions built on it, run in strict
+    /// mode, as if they started with `"use strict";`. Undeclared assignments then
+    /// throw instead of creating globals, and functions called without a receiver
+    /// get an undefined `this` instead of the global object.
+    ///
+    /// The directive is inserted at the start of the first line, after a hashbang,
+    /// so line numbers are unchanged but columns on that line are shifted. Modules
+    /// are always strict.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_strict_mode(true);
+    /// assert!(ctx.evaluate_script("leaked = 1", None).is_err());
+    /// ```
+    pub fn set_strict_mode(&self, strict: bool) {
+        if strict {
+            self.set_extension(StrictMode);
+        } else {
+            self.remove_extension::<StrictMode>();
+        }
+    }
+
+    /// Returns `true` if classic scripts are evaluated in strict mode, see
+    /// [`JSContext::set_strict_mode`].
+    pub fn is_strict_mode(&self) -> bool {
+        self.get_extension::<StrictMode>().is_some()
+    }
+
+    /// Returns the script with a `"use strict";` directive if the context is in
+    /// strict mode.
+    fn strict_source<'a>(&self, script: &'a str) -> Cow<'a, str> {
+        if !self.is_strict_mode() {
+            return Cow::Borrowed(script);
+        }
+
+        let (hashbang, body) = match script.starts_with("#!") {
+            true => {
+                script.split_at(script.find('\n').map_or(script.len(), |end| end + 1))
+            }
+            false => ("", script),
+        };
+        // `void 0` replaces the directive as the completion value, so a script of
+        // declarations only still evaluates to `undefined`.
+        Cow::Owned(format!("{}\"use strict\"; void 0; {}", hashbang, body))
+    }
+
     /// Checks if a context is inspectable.
     ///
     /// # Examples
@@ -613,72 +1750,246 @@ impl JSContext {
     /// ctx.set_name("KedoJS");
     /// assert_eq!(ctx.get_name().to_string(), "KedoJS");
     /// ```
-    pub fn set_name(&self, name: &str) {
-        let name: JSString = name.into();
-        unsafe { JSGlobalContextSetName(self.inner, name.inner) }
+    pub fn set_name(&self, name: &str) {
+        let name: JSString = name.into();
+        unsafe { JSGlobalContextSetName(self.inner, name.inner) }
+    }
+
+    /// Gets a copy of the name of a context.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let name = ctx.get_name();
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// Returns a `JSString` object.
+    pub fn get_name(&self) -> JSString {
+        let name = unsafe { JSGlobalContextCopyName(self.inner) };
+        name.into()
+    }
+
+    /// Sets shared data for a context, replacing the previous one.
+    /// The context keeps a reference to the data until its global object is
+    /// garbage collected, or the data is replaced.
+    ///
+    /// # Arguments
+    /// - `data`: A shared data.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_shared_data(Arc::new(10));
+    /// ```
+    pub fn set_shared_data<T: 'static>(&self, data: Arc<T>) {
+        let context_data = ContextData::get(self);
+        context_data.shared.replace(Some(Box::new(data)));
+    }
+
+    /// Gets shared data for a context.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_shared_data(Arc::new(10));
+    /// let shared_data = ctx.get_shared_data::<i32>().unwrap();
+    /// assert_eq!(*shared_data, 10);
+    /// ```
+    ///
+    /// # Returns
+    /// A new reference to the shared data, or `None` if no data was set
+    /// or the data is not of type `T`.
+    pub fn get_shared_data<T: 'static>(&self) -> Option<Arc<T>> {
+        let shared = ContextData::try_get(self)?.shared.borrow();
+        shared.as_ref()?.downcast_ref::<Arc<T>>().cloned()
     }
 
-    /// Gets a copy of the name of a context.
+    /// Stores a value in the context, keyed by its type. Independent subsystems can
+    /// keep their own state this way without colliding. The previous value of the
+    /// same type is returned.
     ///
-    /// # Examples
+    /// # Arguments
+    /// - `value`: The value to store.
     ///
-    /// ```no_run
+    /// # Examples
+    /// ```ignore
     /// use rust_jsc::JSContext;
     ///
+    /// struct LoaderState {
+    ///     root: String,
+    /// }
+    ///
     /// let ctx = JSContext::new();
-    /// let name = ctx.get_name();
+    /// ctx.set_extension(LoaderState { root: "/srv".to_string() });
+    /// let state = ctx.get_extension::<LoaderState>().unwrap();
+    /// assert_eq!(state.root, "/srv");
     /// ```
+    pub fn set_extension<T: 'static>(&self, value: T) -> Option<Rc<T>> {
+        let previous = ContextData::get(self)
+            .extensions
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Box::new(Rc::new(value)))?;
+        previous.downcast::<Rc<T>>().ok().map(|value| *value)
+    }
+
+    /// Gets the value of type `T` stored with [`JSContext::set_extension`].
     ///
     /// # Returns
+    /// A new reference to the value, or `None` if no value of type `T` was set.
+    pub fn get_extension<T: 'static>(&self) -> Option<Rc<T>> {
+        let extensions = ContextData::try_get(self)?.extensions.borrow();
+        extensions
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<Rc<T>>()
+            .cloned()
+    }
+
+    /// Removes the value of type `T` stored with [`JSContext::set_extension`].
     ///
-    /// Returns a `JSString` object.
-    pub fn get_name(&self) -> JSString {
-        let name = unsafe { JSGlobalContextCopyName(self.inner) };
-        name.into()
+    /// # Returns
+    /// The removed value, or `None` if no value of type `T` was set.
+    pub fn remove_extension<T: 'static>(&self) -> Option<Rc<T>> {
+        let previous = ContextData::try_get(self)?
+            .extensions
+            .borrow_mut()
+            .remove(&TypeId::of::<T>())?;
+        previous.downcast::<Rc<T>>().ok().map(|value| *value)
     }
 
-    /// Sets shared data for a context.
+    /// Gets a constructor of the global object by name, such as `Uint8Array` or `Map`.
+    /// The first lookup is cached, so later calls do not read the global object again,
+    /// and keep returning the original constructor even if a script replaces the global.
     ///
     /// # Arguments
-    /// - `data`: A shared data.
+    /// - `name`: The name of the constructor on the global object.
     ///
     /// # Examples
-    /// ```no_run
+    /// ```ignore
     /// use rust_jsc::JSContext;
     ///
     /// let ctx = JSContext::new();
-    /// let data = Box::new(10);
-    /// ctx.set_shared_data(data);
+    /// let map = ctx.global_constructor("Map").unwrap();
+    /// let value = ctx.evaluate_script("new Map()", None).unwrap();
+    /// assert!(value.is_instance_of(&map).unwrap());
     /// ```
-    pub fn set_shared_data<T>(&self, data: Box<T>) {
-        let data_ptr = Box::into_raw(data);
-        unsafe { JSContextSetSharedData(self.inner, data_ptr as _) }
+    ///
+    /// # Errors
+    /// A `TypeError` if the global property is not a constructor.
+    pub fn global_constructor(&self, name: &str) -> JSResult<JSFunction> {
+        if let Some(data) = ContextData::try_get(self) {
+            if let Some(constructor) = data.constructors.borrow().get(name) {
+                return Ok(JSFunction::from(constructor.clone()));
+            }
+        }
+
+        let value = self.global_object().get_property(name)?;
+        let constructor = match value.as_object() {
+            Ok(object) if object.is_contructor() => object,
+            _ => {
+                let message = format!("{} is not a constructor", name);
+                return Err(JSError::new_typ(self, message)?);
+            }
+        };
+
+        ContextData::cache_constructor(self, name, &constructor);
+        Ok(JSFunction::from(constructor))
     }
 
-    /// Gets shared data for a context.
+    /// Registers a global property whose value is created on first access, so
+    /// contexts with large host APIs start without building the parts scripts never
+    /// use.
+    ///
+    /// The property is a configurable, non-enumerable getter until it is read; the
+    /// first read calls the initializer and replaces the getter with a writable data
+    /// property holding the value. Assigning the property before it is read replaces
+    /// it without calling the initializer.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the global property.
+    /// - `initializer`: A closure that creates the value.
     ///
     /// # Examples
-    /// ```
-    /// use rust_jsc::JSContext;
+    /// ```ignore
+    /// use rust_jsc::{JSContext, JSObject};
     ///
     /// let ctx = JSContext::new();
-    /// let data = Box::new(10);
-    /// ctx.set_shared_data(data);
-    /// let shared_data = ctx.get_shared_data::<i32>().unwrap();
-    /// assert_eq!(*shared_data, 10);
+    /// ctx.register_lazy_global("heavyApi", |ctx| Ok(JSObject::new(ctx).into()))
+    ///     .unwrap();
+    /// let result = ctx.evaluate_script("typeof heavyApi", None).unwrap();
+    /// assert_eq!(result.as_string().unwrap(), "object");
     /// ```
     ///
-    /// # Returns
-    pub fn get_shared_data<T>(&self) -> Option<Box<T>> {
-        let data_ptr = unsafe { JSContextGetSharedData(self.inner) };
+    /// # Errors
+    /// If the property cannot be defined. Errors of the initializer are thrown to
+    /// the script that reads the property; later reads throw a `TypeError`.
+    pub fn register_lazy_global<F>(&self, name: &str, initializer: F) -> JSResult<()>
+    where
+        F: FnOnce(&JSContext) -> JSResult<JSValue> + 'static,
+    {
+        let initializer = Rc::new(RefCell::new(Some(initializer)));
+
+        let getter = {
+            let initializer = initializer.clone();
+            let name = name.to_string();
+            JSFunction::closure(self, move |ctx, _this, _arguments| {
+                let Some(initializer) = initializer.borrow_mut().take() else {
+                    let message = format!(
+                        "{} is not available: its initializer is running or failed",
+                        name
+                    );
+                    return Err(JSError::new_typ(ctx, message)?);
+                };
+
+                let value = initializer(ctx)?;
+                materialize_global(ctx, &name, &value)?;
+                Ok(value)
+            })
+        };
 
-        if data_ptr.is_null() {
-            return None;
-        }
-        Some(unsafe { Box::from_raw(data_ptr as *mut T) })
+        let setter = {
+            let name = name.to_string();
+            JSFunction::closure(self, move |ctx, _this, arguments| {
+                initializer.borrow_mut().take();
+                let value = match arguments.first() {
+                    Some(value) => value.clone(),
+                    None => ctx.undefined(),
+                };
+                materialize_global(ctx, &name, &value)?;
+                Ok(ctx.undefined())
+            })
+        };
+
+        self.global_object().define_accessor_functions(
+            name.into(),
+            Some(getter.into()),
+            Some(setter.into()),
+            PropertyDescriptorBuilder::new().enumerable(false).build(),
+        )
     }
 }
 
+/// Replaces the getter of a lazy global with a data property holding its value.
+fn materialize_global(ctx: &JSContext, name: &str, value: &JSValue) -> JSResult<()> {
+    let global_object = ctx.global_object();
+    global_object.delete_property(name)?;
+    global_object.set_property(
+        name,
+        value,
+        PropertyDescriptorBuilder::new().enumerable(false).build(),
+    )
+}
+
 impl std::fmt::Debug for JSContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("JSContext").finish()
@@ -697,6 +2008,7 @@ impl From<JSContextRef> for JSContext {
         unsafe {
             JSGlobalContextRetain(global_context);
         }
+        GroupMembership::retained(global_context);
 
         Self {
             inner: global_context,
@@ -706,6 +2018,7 @@ impl From<JSContextRef> for JSContext {
 
 impl Drop for JSContext {
     fn drop(&mut self) {
+        GroupMembership::released(self.inner);
         unsafe {
             JSGlobalContextRelease(self.inner);
         }
@@ -737,8 +2050,8 @@ mod tests {
         _key: JSValue,
         _referrer: JSValue,
         _script_fetcher: JSValue,
-    ) -> JSStringRetain {
-        JSStringRetain::from("@rust-jsc")
+    ) -> &'static str {
+        "@rust-jsc"
     }
 
     #[module_evaluate]
@@ -793,7 +2106,7 @@ mod tests {
         key: JSValue,
         _referrer: JSValue,
         _script_fetcher: JSValue,
-    ) -> JSStringRetain {
+    ) -> JSRetainedString {
         let key_value = key.as_string().unwrap();
         // resolve path to file system
         let test_module_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/modules");
@@ -801,7 +2114,7 @@ mod tests {
         let path = std::path::Path::new(test_module_dir).join(key_value.to_string());
         let module_path = std::fs::canonicalize(path).unwrap();
 
-        JSStringRetain::from(module_path.to_str().unwrap())
+        JSRetainedString::from(module_path.to_str().unwrap())
     }
 
     #[module_fetch]
@@ -810,19 +2123,40 @@ mod tests {
         _key: JSValue,
         _attributes_value: JSValue,
         _script_fetcher: JSValue,
-    ) -> JSStringRetain {
+    ) -> std::io::Result<String> {
         // read file content
         let path_key = _key.as_string().unwrap().to_string();
         println!("Path key: {:?}", path_key);
-        // check if the path is a file
-        let file_content = match std::fs::read_to_string(&path_key) {
-            Ok(content) => content,
-            Err(error) => {
-                unreachable!("Error reading file: {:?}", error);
-            }
-        };
+        std::fs::read_to_string(&path_key)
+    }
+
+    #[module_resolve]
+    fn module_loader_resolve_checked(
+        ctx: JSContext,
+        key: JSValue,
+        _referrer: JSValue,
+        _script_fetcher: JSValue,
+    ) -> JSResult<std::path::PathBuf> {
+        let key = key.as_string()?.to_string();
+        if key == "forbidden" {
+            return Err(JSError::new_typ(&ctx, "Access to 'forbidden' is denied")?);
+        }
 
-        JSStringRetain::from(file_content)
+        let test_module_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/modules");
+        Ok(std::path::Path::new(test_module_dir).join(key))
+    }
+
+    #[module_resolve]
+    fn module_loader_resolve_existing(
+        _ctx: JSContext,
+        key: JSValue,
+        _referrer: JSValue,
+        _script_fetcher: JSValue,
+    ) -> Option<std::path::PathBuf> {
+        let test_module_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/modules");
+        let path =
+            std::path::Path::new(test_module_dir).join(key.as_string().ok()?.to_string());
+        path.exists().then_some(path)
     }
 
     #[module_import_meta]
@@ -864,6 +2198,144 @@ mod tests {
         assert_eq!(format!("{:?}", ctx), "JSContext");
     }
 
+    #[has_property]
+    fn service_has(_ctx: JSContext, _global: JSObject, name: JSString) -> bool {
+        name.to_string().starts_with("svc_")
+    }
+
+    #[get_property]
+    fn service_get(
+        ctx: JSContext,
+        _global: JSObject,
+        name: JSString,
+    ) -> JSResult<Option<JSValue>> {
+        let name = name.to_string();
+        if !name.starts_with("svc_") {
+            return Ok(None);
+        }
+
+        Ok(Some(JSValue::string(&ctx, format!("service:{}", name))))
+    }
+
+    #[set_property]
+    fn service_set(
+        _ctx: JSContext,
+        _global: JSObject,
+        name: JSString,
+        _value: JSValue,
+    ) -> JSResult<bool> {
+        Ok(name.to_string().starts_with("readonly_"))
+    }
+
+    #[test]
+    fn test_new_with_global_interceptors() {
+        let ctx = JSContext::new_with(|global| {
+            global
+                .has_property(Some(service_has))
+                .get_property(Some(service_get))
+                .set_property(Some(service_set))
+        })
+        .unwrap();
+
+        let result = ctx.evaluate_script("svc_db", None).unwrap();
+        assert_eq!(result.as_string().unwrap(), "service:svc_db");
+        let result = ctx.evaluate_script("typeof missing", None).unwrap();
+        assert_eq!(result.as_string().unwrap(), "undefined");
+
+        let result = ctx
+            .evaluate_script("globalThis.plain = 2; plain", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 2.0);
+        let result = ctx
+            .evaluate_script(
+                "globalThis.readonly_x = 1; 'readonly_x' in globalThis",
+                None,
+            )
+            .unwrap();
+        assert!(!result.as_boolean());
+    }
+
+    #[test]
+    fn test_register_lazy_global() {
+        let ctx = JSContext::new();
+        let calls = Rc::new(Cell::new(0));
+        let count = calls.clone();
+        ctx.register_lazy_global("heavyApi", move |ctx| {
+            count.set(count.get() + 1);
+            ctx.evaluate_script("({ version: 3 })", None)
+        })
+        .unwrap();
+        ctx.register_lazy_global("unused", |_ctx| panic!("unused global was built"))
+            .unwrap();
+        ctx.register_lazy_global("replaced", |_ctx| panic!("replaced global was built"))
+            .unwrap();
+        assert_eq!(calls.get(), 0);
+
+        let result = ctx
+            .evaluate_script("heavyApi.version + heavyApi.version", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 6.0);
+        assert_eq!(calls.get(), 1);
+
+        let result = ctx
+            .evaluate_script(
+                "Object.getOwnPropertyDescriptor(globalThis, 'heavyApi').writable",
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+        let result = ctx
+            .evaluate_script("Object.keys(globalThis).includes('unused')", None)
+            .unwrap();
+        assert!(!result.as_boolean());
+
+        let result = ctx.evaluate_script("replaced = 5; replaced", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_js_context_group_registry() {
+        let group = JSContextGroup::new();
+        assert!(group.name().is_none());
+        assert!(group.contexts().is_empty());
+        assert!(group.heap_statistics().unwrap().is_none());
+
+        group.set_name("workers");
+        assert_eq!(
+            format!("{:?}", group),
+            "JSContextGroup { name: \"workers\" }"
+        );
+
+        let first = group.new_context();
+        let mut second = JSContext::builder().group(&group).build().unwrap();
+        first.evaluate_script("globalThis.id = 1", None).unwrap();
+        second.evaluate_script("globalThis.id = 2", None).unwrap();
+        assert_eq!(first.group().name().as_deref(), Some("workers"));
+
+        let ids = |group: &JSContextGroup| {
+            group
+                .contexts()
+                .iter()
+                .map(|ctx| ctx.evaluate_script("globalThis.id ?? 0", None).unwrap())
+                .map(|id| id.as_number().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&group), vec![1.0, 2.0]);
+
+        second.reset_global_state();
+        assert_eq!(ids(&group), vec![1.0, 0.0]);
+
+        let statistics = group.heap_statistics().unwrap().unwrap();
+        assert!(statistics.global_object_count >= 2);
+        assert!(statistics.heap_size <= statistics.heap_capacity);
+
+        let handle = JSContext::from(first.inner as JSContextRef);
+        drop(first);
+        assert_eq!(ids(&group), vec![1.0, 0.0]);
+        drop(handle);
+        assert_eq!(ids(&group), vec![0.0]);
+    }
+
     #[test]
     fn test_js_context_garbage_collect() {
         let ctx = JSContext::new();
@@ -910,6 +2382,42 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_strict_mode() {
+        let ctx = JSContext::new();
+        assert!(!ctx.is_strict_mode());
+        ctx.evaluate_script("sloppy = 1", None).unwrap();
+
+        ctx.set_strict_mode(true);
+        assert!(ctx.is_strict_mode());
+        let error = ctx.evaluate_script("leaked = 1", None).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "ReferenceError");
+        let this = ctx
+            .evaluate_script("(function () { return this; })()", None)
+            .unwrap();
+        assert!(this.is_undefined());
+        let result = ctx
+            .evaluate_script("#!/usr/bin/env js\n1 + 1", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 2.0);
+        let error = ctx
+            .evaluate_script("\nthrow new Error('line')", Some(1))
+            .unwrap_err();
+        let line = error.as_object().unwrap().get_property("line").unwrap();
+        assert_eq!(line.as_number().unwrap(), 2.0);
+        assert!(ctx.check_syntax("with (Math) { max(1, 2); }", 0).is_err());
+        for script in ["", "var declared = 1;", "function declared() {}"] {
+            let result = ctx.evaluate_script(script, None).unwrap();
+            assert!(result.is_undefined(), "{:?}", script);
+        }
+        let result = ctx.evaluate_script("1; var declared = 2;", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
+
+        ctx.set_strict_mode(false);
+        assert!(ctx.check_syntax("with (Math) { max(1, 2); }", 0).unwrap());
+        ctx.evaluate_script("leaked = 1", None).unwrap();
+    }
+
     #[test]
     // #[should_panic]
     fn test_js_context_evaluate_module_source() {
@@ -919,26 +2427,74 @@ mod tests {
         assert!(result.is_ok());
     }
 
-    // #[test]
-    // fn test_shared_data() {
-    //     let ctx = JSContext::new();
-    //     let data = Box::new(10);
-    //     ctx.set_shared_data(data.clone());
+    #[test]
+    fn test_shared_data() {
+        let ctx = JSContext::new();
+        let data = Arc::new(10);
+        ctx.set_shared_data(data.clone());
+        assert_eq!(Arc::strong_count(&data), 2);
+
+        let shared_data = ctx.get_shared_data::<i32>().unwrap();
+        assert_eq!(*shared_data, 10);
+        let shared_data = ctx.get_shared_data::<i32>().unwrap();
+        assert_eq!(*shared_data, 10);
+        assert!(ctx.get_shared_data::<String>().is_none());
+
+        ctx.set_shared_data(Arc::new(20));
+        assert_eq!(Arc::strong_count(&data), 1);
+        assert_eq!(*ctx.get_shared_data::<i32>().unwrap(), 20);
+    }
+
+    #[test]
+    fn test_extensions() {
+        struct Loader(&'static str);
+        struct Console(u32);
+
+        let ctx = JSContext::new();
+        assert!(ctx.get_extension::<Loader>().is_none());
+
+        assert!(ctx.set_extension(Loader("/srv")).is_none());
+        ctx.set_extension(Console(1));
+        assert_eq!(ctx.get_extension::<Loader>().unwrap().0, "/srv");
+        assert_eq!(ctx.get_extension::<Console>().unwrap().0, 1);
+
+        let previous = ctx.set_extension(Console(2)).unwrap();
+        assert_eq!(previous.0, 1);
+        assert_eq!(ctx.get_extension::<Console>().unwrap().0, 2);
+
+        assert_eq!(ctx.remove_extension::<Loader>().unwrap().0, "/srv");
+        assert!(ctx.get_extension::<Loader>().is_none());
+        assert!(ctx.get_extension::<Console>().is_some());
+    }
+
+    #[test]
+    fn test_shared_data_null() {
+        let ctx = JSContext::new();
+        let shared_data = ctx.get_shared_data::<i32>();
+        assert!(shared_data.is_none());
+    }
 
-    //     let mut shared_data = ManuallyDrop::new(ctx.get_shared_data::<i32>().unwrap());
-    //     assert_eq!(*shared_data.as_mut(), 10);
+    #[test]
+    fn test_shared_data_released_with_context() {
+        let data = Arc::new(10);
+        let group = JSContextGroup::new();
+        {
+            let ctx = group.new_context();
+            ctx.set_shared_data(data.clone());
+            assert_eq!(Arc::strong_count(&data), 2);
+        }
 
-    //     let shared_data = ctx.get_shared_data::<i32>().unwrap();
-    //     assert_eq!(*shared_data, 10);
-    //     // unsafe { ManuallyDrop::drop(&mut shared_data) };
-    // }
+        drop(group);
+        assert_eq!(Arc::strong_count(&data), 1);
+    }
 
-    // #[test]
-    // fn test_shared_data_null() {
-    //     let ctx = JSContext::new();
-    //     let shared_data = ctx.get_shared_data::<i32>();
-    //     assert!(shared_data.is_none());
-    // }
+    #[test]
+    fn test_shared_data_survives_reset() {
+        let mut ctx = JSContext::new();
+        ctx.set_shared_data(Arc::new(10));
+        ctx.reset_global_state();
+        assert_eq!(*ctx.get_shared_data::<i32>().unwrap(), 10);
+    }
 
     #[test]
     fn test_inspectable() {
@@ -950,7 +2506,7 @@ mod tests {
     #[test]
     fn test_virtual_module() {
         let ctx = JSContext::new();
-        let keys = &[JSStringRetain::from("@rust-jsc")];
+        let keys = &[JSRetainedString::from("@rust-jsc")];
         ctx.set_virtual_module_keys(keys);
 
         let callbacks = JSAPIModuleLoader {
@@ -1000,7 +2556,7 @@ mod tests {
     #[test]
     fn test_virtual_module_no_default() {
         let ctx = JSContext::new();
-        let keys = &[JSStringRetain::from("@rust-jsc")];
+        let keys = &[JSRetainedString::from("@rust-jsc")];
         ctx.set_virtual_module_keys(keys);
 
         let callbacks = JSAPIModuleLoader {
@@ -1066,6 +2622,75 @@ mod tests {
         assert_eq!(result_value.as_string().unwrap(), "Hello World KEDO");
     }
 
+    #[test]
+    fn test_module_loader_results() {
+        let ctx = JSContext::new();
+        ctx.set_module_loader(JSAPIModuleLoader {
+            disableBuiltinFileSystemLoader: true,
+            moduleLoaderResolve: Some(module_loader_resolve_checked),
+            moduleLoaderEvaluate: Some(module_loader_evaluate_virtual),
+            moduleLoaderFetch: Some(module_loader_fetch),
+            moduleLoaderCreateImportMetaProperties: Some(
+                module_loader_create_import_meta_properties,
+            ),
+        });
+
+        ctx.evaluate_module_from_source(
+            "import { myFunction } from 'script.js'; globalThis.loaded = typeof myFunction;",
+            "main.js",
+            None,
+        )
+        .unwrap();
+        let loaded = ctx.evaluate_script("loaded", None).unwrap();
+        assert_eq!(loaded.as_string().unwrap(), "function");
+
+        let error = ctx
+            .evaluate_module_from_source("import 'forbidden';", "forbidden.js", None)
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert_eq!(
+            error.message().unwrap().to_string(),
+            "Access to 'forbidden' is denied"
+        );
+
+        let error = ctx
+            .evaluate_module_from_source("import 'missing.js';", "missing.js", None)
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert!(error
+            .message()
+            .unwrap()
+            .to_string()
+            .starts_with("Cannot find module '"));
+    }
+
+    #[test]
+    fn test_module_loader_not_found() {
+        let ctx = JSContext::new();
+        ctx.set_module_loader(JSAPIModuleLoader {
+            disableBuiltinFileSystemLoader: true,
+            moduleLoaderResolve: Some(module_loader_resolve_existing),
+            moduleLoaderEvaluate: Some(module_loader_evaluate_virtual),
+            moduleLoaderFetch: Some(module_loader_fetch),
+            moduleLoaderCreateImportMetaProperties: Some(
+                module_loader_create_import_meta_properties,
+            ),
+        });
+
+        ctx.evaluate_module_from_source("import 'script.js';", "main.js", None)
+            .unwrap();
+
+        let error = ctx
+            .evaluate_module_from_source("import 'nowhere.js';", "main.js", None)
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert!(error
+            .message()
+            .unwrap()
+            .to_string()
+            .starts_with("Cannot find module 'nowhere.js'"));
+    }
+
     #[test]
     fn test_set_unhandled_rejection_callback() {
         let ctx = JSContext::new();
@@ -1093,6 +2718,108 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_on_uncaught_exception() {
+        let ctx = JSContext::new();
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        let log = reported.clone();
+        ctx.on_uncaught_exception(move |_ctx, filename, error| {
+            log.borrow_mut().push((
+                filename.map(String::from),
+                error.message().unwrap().to_string(),
+            ));
+        });
+
+        let result = ctx.evaluate_module_from_source(
+            "throw new Error('reported');",
+            "on_uncaught_exception.js",
+            None,
+        );
+        assert!(result.is_err());
+        let reported = reported.borrow();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].1, "reported");
+    }
+
+    #[test]
+    fn test_on_uncaught_exception_not_an_object() {
+        let ctx = JSContext::new();
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        let log = reported.clone();
+        ctx.on_uncaught_exception(move |_ctx, _filename, error| {
+            log.borrow_mut().push(error.message().unwrap().to_string());
+        });
+
+        for (index, thrown) in ["null", "undefined", "7"].iter().enumerate() {
+            let result = ctx.evaluate_module_from_source(
+                &format!("throw {};", thrown),
+                &format!("throw_primitive_{}.js", index),
+                None,
+            );
+            assert!(result.is_err());
+        }
+        assert_eq!(*reported.borrow(), vec!["null", "undefined", "7"]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_context_thread_affinity() {
+        struct SendContext(JSContextRef);
+        unsafe impl Send for SendContext {}
+
+        let ctx = JSContext::new();
+        let raw = SendContext(ctx.inner);
+        let result = std::thread::spawn(move || {
+            let raw = raw;
+            let ctx = JSContext::from(raw.0);
+            let _ = ctx.evaluate_script("1", None);
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(ctx.evaluate_script("1", None).is_ok());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_context_thread_pruned() {
+        let record = |global: usize| {
+            CONTEXT_THREADS
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|threads| threads.get(&global).map(|(id, _)| *id))
+        };
+
+        let ctx = JSContext::new();
+        let global = ctx.inner as usize;
+        let id = record(global).unwrap();
+        // The context has its own virtual machine, which collects the global object
+        // when the last reference is released.
+        drop(ctx);
+        assert_ne!(record(global), Some(id));
+    }
+
+    #[test]
+    fn test_exception_hook() {
+        let ctx = JSContext::new();
+        let names = Rc::new(RefCell::new(Vec::new()));
+        let log = names.clone();
+        ctx.set_exception_hook(move |error, ctx| {
+            log.borrow_mut().push(error.name().unwrap().to_string());
+            // Exceptions thrown by the hook are not reported again.
+            let _ = ctx.evaluate_script("null.key", None);
+        });
+
+        let result = ctx.evaluate_script("try { null.key } catch {} undefined.key", None);
+        assert!(result.is_err());
+        assert!(ctx.check_syntax("function (", 1).is_err());
+        assert_eq!(*names.borrow(), vec!["TypeError", "SyntaxError"]);
+
+        ctx.clear_exception_hook();
+        let _ = ctx.evaluate_script("null.key", None);
+        assert_eq!(names.borrow().len(), 2);
+    }
+
     #[test]
     fn test_set_uncaught_exception_at_event_loop_callback() {
         let ctx = JSContext::new();
@@ -1151,4 +2878,172 @@ mod tests {
             protected_global_object_count
         );
     }
+
+    #[test]
+    fn test_context_builder() {
+        let group = JSContextGroup::new();
+        let ctx = JSContext::builder()
+            .group(&group)
+            .name("builder")
+            .virtual_module("@rust-jsc")
+            .global("answer", |ctx| Ok(JSValue::number(ctx, 42.0)))
+            .global("greeting", |ctx| Ok(JSValue::string(ctx, "hello")))
+            .build()
+            .unwrap();
+
+        assert_eq!(ctx.get_name().to_string(), "builder");
+        assert!(!ctx.is_strict_mode());
+        let result = ctx.evaluate_script("answer + 1", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 43.0);
+        let result = ctx.evaluate_script("greeting", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "hello");
+    }
+
+    #[test]
+    fn test_context_builder_vm_options() {
+        let ctx = JSContext::builder()
+            .strict_mode(true)
+            .recursion_limit(64)
+            .build()
+            .unwrap();
+
+        assert!(ctx.is_strict_mode());
+        assert_eq!(ctx.recursion_limit(), Some(64));
+        assert!(ctx.evaluate_script("leaked = 1", None).is_err());
+    }
+
+    #[test]
+    fn test_context_builder_global_error() {
+        let result = JSContext::builder()
+            .global("broken", |ctx| {
+                ctx.evaluate_script("throw new Error('broken')", None)
+            })
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_reset_global_state() {
+        let mut ctx = JSContext::new();
+        ctx.set_name("reset");
+        ctx.evaluate_script("globalThis.leak = 1", None).unwrap();
+
+        ctx.reset_global_state();
+        assert_eq!(ctx.get_name().to_string(), "reset");
+        assert!(ctx.evaluate_script("leak", None).is_err());
+    }
+
+    #[test]
+    fn test_reset_global_state_keeps_global_class() {
+        let mut ctx = JSContext::new_with(|global| {
+            global
+                .has_property(Some(service_has))
+                .get_property(Some(service_get))
+        })
+        .unwrap();
+
+        ctx.reset_global_state();
+        let result = ctx.evaluate_script("svc_db", None).unwrap();
+        assert_eq!(result.as_string().unwrap(), "service:svc_db");
+    }
+
+    #[test]
+    fn test_singletons() {
+        let ctx = JSContext::new();
+        assert!(ctx.undefined().is_undefined());
+        assert!(ctx.null().is_null());
+        assert!(ctx.bool(true).as_boolean());
+        assert!(ctx.bool(false).is_boolean());
+        assert!(!ctx.bool(false).as_boolean());
+        assert_eq!(ctx.undefined().inner, JSValue::undefined(&ctx).inner);
+        assert_eq!(ctx.bool(true).inner, ctx.bool(true).inner);
+        assert!(ContextData::try_get(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_context_data_reveals_nothing() {
+        let ctx = JSContext::new();
+        ctx.set_extension(1u8);
+        ctx.global_constructor("Map").unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                r#"Object.getOwnPropertySymbols(globalThis).every((key) =>
+                    Reflect.ownKeys(globalThis[key])
+                        .every((name) => name === "length" || name === "name"))"#,
+                None,
+            )
+            .unwrap();
+        assert!(result.as_boolean());
+    }
+
+    #[test]
+    fn test_global_constructor() {
+        let mut ctx = JSContext::new();
+        let uint8_array: JSObject = ctx.global_constructor("Uint8Array").unwrap().into();
+        let value = ctx.evaluate_script("new Uint8Array(2)", None).unwrap();
+        assert!(value.is_instance_of(&uint8_array).unwrap());
+
+        // The cache keeps the original constructor after the global is replaced.
+        ctx.evaluate_script("globalThis.Uint8Array = function () {}", None)
+            .unwrap();
+        let cached = ctx.global_constructor("Uint8Array").unwrap();
+        let array = cached
+            .call_constructor(&[JSValue::number(&ctx, 3.0)])
+            .unwrap();
+        assert!(array.is_instance_of(&uint8_array).unwrap());
+
+        let error = ctx.global_constructor("Math").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        ctx.reset_global_state();
+        let value = ctx.evaluate_script("new Uint8Array(2)", None).unwrap();
+        let uint8_array: JSObject = ctx.global_constructor("Uint8Array").unwrap().into();
+        assert!(value.is_instance_of(&uint8_array).unwrap());
+    }
+
+    #[test]
+    fn test_on_promise_rejection() {
+        let ctx = JSContext::new();
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        ctx.on_promise_rejection(move |_ctx, event| {
+            let reason = event.reason.as_string().unwrap().to_string();
+            recorded.borrow_mut().push((event.kind, reason));
+        })
+        .unwrap();
+
+        ctx.evaluate_script("globalThis.rejected = Promise.reject('boom')", None)
+            .unwrap();
+        assert_eq!(
+            events.borrow().as_slice(),
+            &[(PromiseRejectionKind::Unhandled, "boom".to_string())]
+        );
+
+        ctx.evaluate_script("rejected.catch(() => {})", None)
+            .unwrap();
+        assert_eq!(
+            events.borrow().as_slice(),
+            &[
+                (PromiseRejectionKind::Unhandled, "boom".to_string()),
+                (PromiseRejectionKind::Handled, "boom".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queue_microtask() {
+        let ctx = JSContext::new();
+        ctx.queue_microtask(|ctx| {
+            let value = JSValue::boolean(ctx, true);
+            ctx.global_object()
+                .set_property("ran", &value, Default::default())
+        })
+        .unwrap();
+
+        let result = ctx
+            .evaluate_script("globalThis.ran === true", None)
+            .unwrap();
+        assert!(result.as_boolean());
+    }
 }
@@ -0,0 +1,225 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+
+use rust_jsc_sys::{
+    JSContextGetSharedData, JSContextSetSharedData, JSObjectGetPrivate, JSObjectMake,
+    JSObjectRef,
+};
+
+use crate::{
+    class::StaticClass, JSClass, JSContext, JSObject, JSResult, JSValue,
+    PropertyDescriptorBuilder,
+};
+
+/// The Rust state attached to a context.
+///
+/// The context shared-data pointer points to it, and it is owned by a holder object
+/// stored on the global object under a symbol, see [`hide`]: when the global object is collected the holder is
+/// finalized and the state, including everything it references, is dropped.
+#[derive(Default)]
+pub(crate) struct ContextData {
+    /// The value of [`JSContext::set_shared_data`], an `Arc<T>`.
+    pub(crate) shared: RefCell<Option<Box<dyn Any>>>,
+    /// The values of [`JSContext::set_extension`], each an `Rc<T>` keyed by `TypeId::of::<T>()`.
+    pub(crate) extensions: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    /// The constructors cached by [`JSContext::global_constructor`], keyed by name.
+    pub(crate) constructors: RefCell<HashMap<String, JSObject>>,
+    /// The objects of the crate's shim scripts cached by [`ContextData::shim`], keyed
+    /// by name.
+    pub(crate) shims: RefCell<HashMap<&'static str, JSObject>>,
+    /// The function of [`KEEP_ALIVE`] that keeps the cached constructors and shims
+    /// reachable from the global object.
+    pub(crate) keep_alive: RefCell<Option<JSObject>>,
+}
+
+static HOLDER_CLASS: StaticClass = StaticClass::new();
+
+fn holder_class() -> &'static JSClass {
+    HOLDER_CLASS.get_or_init(|| {
+        JSClass::builder("ContextData")
+            .set_finalize(Some(holder_finalize))
+            .build()
+            .expect("Failed to create context data class")
+    })
+}
+
+unsafe extern "C" fn holder_finalize(object: JSObjectRef) {
+    let data = JSObjectGetPrivate(object) as *mut Rc<ContextData>;
+    if !data.is_null() {
+        drop(Box::from_raw(data));
+    }
+}
+
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+static FINALIZER_CLASS: StaticClass = StaticClass::new();
+
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+fn finalizer_class() -> &'static JSClass {
+    FINALIZER_CLASS.get_or_init(|| {
+        JSClass::builder("ContextFinalizer")
+            .set_finalize(Some(finalizer_finalize))
+            .build()
+            .expect("Failed to create context finalizer class")
+    })
+}
+
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+unsafe extern "C" fn finalizer_finalize(object: JSObjectRef) {
+    let finalized = JSObjectGetPrivate(object) as *mut Box<dyn FnOnce()>;
+    if !finalized.is_null() {
+        Box::from_raw(finalized)();
+    }
+}
+
+/// Calls the closure when the global object of the context is collected. The closure
+/// runs while the engine finalizes objects, so it must not use the engine.
+#[cfg(any(debug_assertions, feature = "value-audit"))]
+pub(crate) fn on_finalize(ctx: &JSContext, finalized: impl FnOnce() + 'static) {
+    let finalized: Box<dyn FnOnce()> = Box::new(finalized);
+    let holder = unsafe {
+        JSObjectMake(
+            ctx.inner,
+            finalizer_class().inner,
+            Box::into_raw(Box::new(finalized)) as *mut std::ffi::c_void,
+        )
+    };
+    let holder = JSObject::from_ref(holder, ctx.inner);
+    hide(ctx, "rust_jsc.finalizer", &holder).expect("Failed to attach context finalizer");
+}
+
+impl ContextData {
+    /// Returns the state of the context, if any was attached.
+    pub(crate) fn try_get(ctx: &JSContext) -> Option<&ContextData> {
+        let data = unsafe { JSContextGetSharedData(ctx.inner) } as *const ContextData;
+        // SAFETY: the holder object keeps the data alive as long as the global object,
+        // which the context keeps alive while it is borrowed.
+        unsafe { data.as_ref() }
+    }
+
+    /// Returns the state of the context, attaching an empty one on first use.
+    pub(crate) fn get(ctx: &JSContext) -> &ContextData {
+        if let Some(data) = Self::try_get(ctx) {
+            return data;
+        }
+
+        Self::attach(ctx, Rc::default());
+        Self::try_get(ctx).expect("Context data must be attached")
+    }
+
+    /// Returns a new reference to the state of the context, if any was attached.
+    pub(crate) fn shared(ctx: &JSContext) -> Option<Rc<ContextData>> {
+        let data = unsafe { JSContextGetSharedData(ctx.inner) } as *const ContextData;
+        if data.is_null() {
+            return None;
+        }
+
+        // SAFETY: the pointer was created with `Rc::as_ptr` and the holder still owns
+        // a strong reference.
+        unsafe {
+            Rc::increment_strong_count(data);
+            Some(Rc::from_raw(data))
+        }
+    }
+
+    /// Attaches the state to the context, so that its global object owns a reference.
+    ///
+    /// The cached constructors and shims belong to the previous global object, so they
+    /// are dropped.
+    pub(crate) fn attach(ctx: &JSContext, data: Rc<ContextData>) {
+        data.constructors.borrow_mut().clear();
+        data.shims.borrow_mut().clear();
+        data.keep_alive.replace(None);
+
+        let pointer = Rc::as_ptr(&data) as *mut std::ffi::c_void;
+        let holder = unsafe {
+            JSObjectMake(
+                ctx.inner,
+                holder_class().inner,
+                Box::into_raw(Box::new(data)) as *mut std::ffi::c_void,
+            )
+        };
+        let holder = JSObject::from_ref(holder, ctx.inner);
+
+        hide(ctx, "rust_jsc.contextData", &holder)
+            .expect("Failed to attach context data");
+
+        unsafe { JSContextSetSharedData(ctx.inner, pointer) };
+    }
+
+    /// Keeps the object alive as long as the global object of the context.
+    fn keep_alive(ctx: &JSContext, object: &JSObject) -> JSResult<()> {
+        let keep_alive = Self::get(ctx).keep_alive.borrow().clone();
+        let keep_alive = match keep_alive {
+            Some(keep_alive) => keep_alive,
+            None => {
+                let keep_alive = ctx.evaluate_script(KEEP_ALIVE, None)?.as_object()?;
+                hide(ctx, "rust_jsc.keepAlive", &keep_alive)?;
+                Self::get(ctx).keep_alive.replace(Some(keep_alive.clone()));
+                keep_alive
+            }
+        };
+
+        keep_alive.call(None, &[object.clone().into()])?;
+        Ok(())
+    }
+
+    /// Caches a constructor of the context under its global name.
+    pub(crate) fn cache_constructor(ctx: &JSContext, name: &str, constructor: &JSObject) {
+        Self::keep_alive(ctx, constructor).expect("Failed to cache constructor");
+        Self::get(ctx)
+            .constructors
+            .borrow_mut()
+            .insert(name.to_string(), constructor.clone());
+    }
+
+    /// Evaluates one of the crate's shim scripts, which evaluate to an object, once
+    /// per context. Later calls with the same name return the cached object without
+    /// parsing or running the script again.
+    pub(crate) fn shim(
+        ctx: &JSContext,
+        name: &'static str,
+        source: &str,
+    ) -> JSResult<JSObject> {
+        if let Some(shim) = Self::get(ctx).shims.borrow().get(name) {
+            return Ok(shim.clone());
+        }
+
+        let shim = ctx.evaluate_script(source, None)?.as_object()?;
+        Self::keep_alive(ctx, &shim)?;
+        Self::get(ctx).shims.borrow_mut().insert(name, shim.clone());
+        Ok(shim)
+    }
+}
+
+/// Evaluates to a function that keeps the values it is called with alive for as long
+/// as the function is, with no way to read them back. They are stored by index in an
+/// object without a prototype, so no getter or setter of a script runs.
+const KEEP_ALIVE: &str = r#"(() => {
+    const kept = { __proto__: null };
+    let next = 0;
+    return (value) => {
+        kept[next++] = value;
+    };
+})()"#;
+
+/// Defines the object on the global object under a new, unregistered symbol as a
+/// non-enumerable, non-configurable property, so scripts cannot look it up by name,
+/// replace or remove it.
+///
+/// Scripts can still find it with `Object.getOwnPropertySymbols(globalThis)`, so only
+/// objects that reveal nothing are hidden this way: the holders of the context data
+/// and of [`on_finalize`], which have no properties, and the function of
+/// [`KEEP_ALIVE`].
+fn hide(ctx: &JSContext, description: &str, object: &JSObject) -> JSResult<()> {
+    let key = JSValue::symbol(ctx, description);
+    let descriptor = PropertyDescriptorBuilder::new()
+        .writable(false)
+        .enumerable(false)
+        .configurable(false)
+        .build();
+    ctx.global_object().set(&key, object, descriptor)
+}
@@ -0,0 +1,1046 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    JSArray, JSConstructor, JSContext, JSError, JSFunction, JSObject, JSResult, JSValue,
+};
+
+/// Converts a Rust value into a JavaScript value.
+pub trait IntoJSValue {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue;
+
+    /// Converts the value, returning the error of a conversion that can fail instead
+    /// of panicking, such as an `i64` outside the safe range under
+    /// [`Int64Policy::Strict`]. By default it calls [`IntoJSValue::into_js_value`].
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue>
+    where
+        Self: Sized,
+    {
+        Ok(self.into_js_value(ctx))
+    }
+}
+
+/// Converts a JavaScript value into a Rust value.
+///
+/// Conversions are strict: a value of the wrong type is rejected with a `TypeError`
+/// instead of being coerced, e.g. `"42"` does not convert to `f64`.
+pub trait FromJSValue: Sized {
+    fn from_js_value(value: &JSValue) -> JSResult<Self>;
+}
+
+/// A Rust enum exposed to JavaScript, implemented by `#[derive(JSEnum)]` for enums
+/// whose variants have no fields.
+///
+/// The derive also implements [`IntoJSValue`], converting a variant to its numeric
+/// value, and [`FromJSValue`], accepting either the name or the numeric value.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{convert::JSEnum, JSContext, JSEnum};
+///
+/// #[derive(JSEnum)]
+/// enum Level {
+///     Debug = 10,
+///     Info = 20,
+/// }
+///
+/// let ctx = JSContext::new();
+/// let level = Level::js_object(&ctx).unwrap();
+/// assert_eq!(level.get_property("Info").unwrap().as_number().unwrap(), 20.0);
+/// ```
+pub trait JSEnum: Sized {
+    /// The name of the enum, used in error messages.
+    const NAME: &'static str;
+    /// The names and numeric values of the variants, in declaration order.
+    const VARIANTS: &'static [(&'static str, i64)];
+
+    /// Creates a frozen object mapping the name of each variant to its value, and
+    /// each value back to the name, like a TypeScript numeric enum.
+    ///
+    /// # Errors
+    /// If the object cannot be created.
+    fn js_object(ctx: &JSContext) -> JSResult<JSObject> {
+        let object = JSObject::new(ctx);
+        object.define_constants(
+            Self::VARIANTS.iter().map(|(name, value)| (*name, *value)),
+        )?;
+        let reverse: Vec<(String, &str)> = Self::VARIANTS
+            .iter()
+            .map(|(name, value)| (value.to_string(), *name))
+            .collect();
+        object.define_constants(
+            reverse.iter().map(|(value, name)| (value.as_str(), *name)),
+        )?;
+
+        let constructor: JSObject = ctx.global_constructor("Object")?.into();
+        let freeze = constructor.get_property("freeze")?.as_object()?;
+        freeze.call(Some(&constructor), &[object.clone().into()])?;
+        Ok(object)
+    }
+
+    /// Returns the index in [`JSEnum::VARIANTS`] of the variant named by the value,
+    /// or with its numeric value.
+    ///
+    /// # Errors
+    /// A `TypeError` listing the variants if the value names none of them.
+    fn variant_index(value: &JSValue) -> JSResult<usize> {
+        let index = if value.is_string() {
+            let name = value.as_string()?.to_string();
+            Self::VARIANTS
+                .iter()
+                .position(|(variant, _)| *variant == name)
+        } else if value.is_number() {
+            let number = value.as_number()?;
+            Self::VARIANTS
+                .iter()
+                .position(|(_, variant)| *variant as f64 == number)
+        } else {
+            None
+        };
+
+        index.ok_or_else(|| {
+            let variants: Vec<String> = Self::VARIANTS
+                .iter()
+                .map(|(name, value)| format!("{} ({})", name, value))
+                .collect();
+            let expected = format!("one of {}: {}", Self::NAME, variants.join(", "));
+            if value.is_string() || value.is_number() {
+                invalid_variant(value, &expected)
+            } else {
+                type_error(value, &expected)
+            }
+        })
+    }
+}
+
+/// Converts a Rust value into a list of JavaScript arguments.
+/// Implemented for tuples of up to eight [`IntoJSValue`] values and for slices of `JSValue`.
+///
+/// Each value is converted with [`IntoJSValue::try_into_js_value`], so a conversion
+/// that fails, such as an `i64` outside the safe range under [`Int64Policy::Strict`],
+/// returns its error.
+pub trait IntoJSArgs {
+    fn into_js_args(self, ctx: &JSContext) -> JSResult<Vec<JSValue>>;
+}
+
+/// Creates a `TypeError` reporting that `value` is not of the `expected` type.
+pub(crate) fn type_error(value: &JSValue, expected: &str) -> JSError {
+    let ctx = JSContext::from(value.ctx);
+    let message = format!("Expected {}, got {:?}", expected, value.get_type());
+    match JSError::new_typ(&ctx, message) {
+        Ok(error) => error,
+        Err(error) => error,
+    }
+}
+
+/// Creates a `TypeError` reporting that `value`, a string or a number, is not one of
+/// the `expected` values.
+fn invalid_variant(value: &JSValue, expected: &str) -> JSError {
+    let ctx = JSContext::from(value.ctx);
+    let got = value.as_json_string(0).map(|got| got.to_string());
+    let message = format!("Expected {}, got {}", expected, got.unwrap_or_default());
+    match JSError::new_typ(&ctx, message) {
+        Ok(error) => error,
+        Err(error) => error,
+    }
+}
+
+impl IntoJSValue for JSValue {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self
+    }
+}
+
+impl IntoJSValue for &JSValue {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self.clone()
+    }
+}
+
+impl IntoJSValue for JSObject {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self.into()
+    }
+}
+
+impl IntoJSValue for &JSObject {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self.clone().into()
+    }
+}
+
+impl IntoJSValue for JSFunction {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self.into()
+    }
+}
+
+impl IntoJSValue for JSConstructor {
+    fn into_js_value(self, _ctx: &JSContext) -> JSValue {
+        self.into()
+    }
+}
+
+impl IntoJSValue for () {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        JSValue::undefined(ctx)
+    }
+}
+
+impl IntoJSValue for bool {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        JSValue::boolean(ctx, self)
+    }
+}
+
+impl IntoJSValue for &str {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        JSValue::string(ctx, self)
+    }
+}
+
+impl IntoJSValue for String {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        JSValue::string(ctx, self)
+    }
+}
+
+impl IntoJSValue for &String {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        JSValue::string(ctx, self.as_str())
+    }
+}
+
+impl<T: IntoJSValue> IntoJSValue for Option<T> {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        match self {
+            Some(value) => value.into_js_value(ctx),
+            None => JSValue::null(ctx),
+        }
+    }
+
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+        match self {
+            Some(value) => value.try_into_js_value(ctx),
+            None => Ok(JSValue::null(ctx)),
+        }
+    }
+}
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoJSValue for $ty {
+                fn into_js_value(self, ctx: &JSContext) -> JSValue {
+                    JSValue::number(ctx, self as f64)
+                }
+            }
+
+            impl FromJSValue for $ty {
+                fn from_js_value(value: &JSValue) -> JSResult<Self> {
+                    if !value.is_number() {
+                        return Err(type_error(value, "a number"));
+                    }
+
+                    Ok(value.as_number()? as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f64, f32);
+
+/// Integers convert only from numbers they represent exactly: a fractional number
+/// or `NaN` is rejected with a `TypeError`, a number out of the range of the type
+/// with a `RangeError`.
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoJSValue for $ty {
+                fn into_js_value(self, ctx: &JSContext) -> JSValue {
+                    JSValue::number(ctx, self as f64)
+                }
+            }
+
+            impl FromJSValue for $ty {
+                fn from_js_value(value: &JSValue) -> JSResult<Self> {
+                    if !value.is_number() {
+                        return Err(type_error(value, "a number"));
+                    }
+
+                    let number = value.as_number()?;
+                    if number.trunc() != number {
+                        return Err(type_error(value, "an integer"));
+                    }
+                    if number < <$ty>::MIN as f64 || number > <$ty>::MAX as f64 {
+                        let ctx = JSContext::from(value.ctx);
+                        let message = format!(
+                            "{} is out of the range of {}",
+                            number,
+                            stringify!($ty)
+                        );
+                        return Err(crate::builtins::named_error(&ctx, "RangeError", &message));
+                    }
+
+                    Ok(number as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, u8, u16, u32, isize, usize);
+
+/// How `i64` and `u64` values cross the boundary, set per context with
+/// [`JSContext::set_int64_policy`].
+///
+/// JavaScript numbers represent integers exactly only up to `2^53 - 1`, see
+/// [`crate::value::MAX_SAFE_INTEGER`]; larger 64-bit integers either lose precision
+/// as numbers or have to be `BigInt`s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Int64Policy {
+    /// Integers become numbers, rounding those outside the safe range; numbers are
+    /// truncated and saturated when converted back. The default.
+    #[default]
+    Lossy,
+    /// Integers always become `BigInt`s; numbers and `BigInt`s are accepted back
+    /// as long as they are exact.
+    BigInt,
+    /// Integers in the safe range become numbers, and integers outside of it, or
+    /// numbers and `BigInt`s that do not convert exactly, are rejected with a
+    /// `RangeError`.
+    Strict,
+}
+
+impl JSContext {
+    /// Sets how `i64` and `u64` values are converted by [`IntoJSValue`] and
+    /// [`FromJSValue`] in this context.
+    ///
+    /// The `BigInt` constructor is looked up when a policy creating `BigInt`s is set,
+    /// so scripts that replace the global afterwards do not affect conversions.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{convert::{Int64Policy, IntoJSValue}, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_int64_policy(Int64Policy::BigInt);
+    /// let value = i64::MAX.into_js_value(&ctx);
+    /// assert_eq!(value.typeof_str(), "bigint");
+    /// ```
+    pub fn set_int64_policy(&self, policy: Int64Policy) {
+        if policy != Int64Policy::Lossy {
+            // Caches the constructor; if a script already removed it, the conversions
+            // that need it fail instead.
+            let _ = self.global_constructor("BigInt");
+        }
+        self.set_extension(policy);
+    }
+
+    /// Returns the policy of [`JSContext::set_int64_policy`].
+    pub fn int64_policy(&self) -> Int64Policy {
+        self.get_extension::<Int64Policy>()
+            .map(|policy| *policy)
+            .unwrap_or_default()
+    }
+
+    /// Converts a Rust value into a JavaScript value of this context, e.g.
+    /// `ctx.value("name")` instead of `JSValue::string(&ctx, "name")`.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let object = ctx.evaluate_script("({})", None).unwrap().as_object().unwrap();
+    /// object.set_property("count", &ctx.value(3).unwrap(), Default::default()).unwrap();
+    /// object.set_property("label", &ctx.value("total").unwrap(), Default::default()).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// The error of a conversion that fails, see [`IntoJSValue::try_into_js_value`].
+    pub fn value(&self, value: impl IntoJSValue) -> JSResult<JSValue> {
+        value.try_into_js_value(self)
+    }
+}
+
+impl<T: IntoJSValue> From<(&JSContext, T)> for JSValue {
+    fn from((ctx, value): (&JSContext, T)) -> Self {
+        value.into_js_value(ctx)
+    }
+}
+
+/// Creates a `BigInt` with the decimal digits of an integer, with the constructor
+/// cached by [`JSContext::set_int64_policy`].
+fn big_int(ctx: &JSContext, digits: String) -> JSResult<JSValue> {
+    let big_int: JSObject = ctx.global_constructor("BigInt")?.into();
+    let value = JSValue::string(ctx, digits);
+    big_int.call(None, std::slice::from_ref(&value))
+}
+
+macro_rules! impl_int64 {
+    ($($ty:ty),*) => {
+        $(
+            /// # Panics
+            /// If the conversion fails, see [`IntoJSValue::try_into_js_value`].
+            impl IntoJSValue for $ty {
+                fn into_js_value(self, ctx: &JSContext) -> JSValue {
+                    match self.try_into_js_value(ctx) {
+                        Ok(value) => value,
+                        Err(error) => panic!(
+                            "Failed to convert {} to JavaScript: {}",
+                            self,
+                            error.message().map(|message| message.to_string()).unwrap_or_default()
+                        ),
+                    }
+                }
+
+                fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+                    let safe = (self as i128).unsigned_abs()
+                        <= crate::value::MAX_SAFE_INTEGER as u128;
+                    match ctx.int64_policy() {
+                        Int64Policy::Lossy => Ok(JSValue::number(ctx, self as f64)),
+                        Int64Policy::Strict if safe => Ok(JSValue::number(ctx, self as f64)),
+                        Int64Policy::Strict => {
+                            let message = format!(
+                                "{} is outside the safe integer range of numbers",
+                                self
+                            );
+                            Err(crate::builtins::named_error(ctx, "RangeError", &message))
+                        }
+                        Int64Policy::BigInt => big_int(ctx, self.to_string()),
+                    }
+                }
+            }
+
+            impl FromJSValue for $ty {
+                fn from_js_value(value: &JSValue) -> JSResult<Self> {
+                    let ctx = JSContext::from(value.ctx);
+                    let policy = ctx.int64_policy();
+                    let range_error = |got: &str| {
+                        let message = format!(
+                            "{} cannot be converted to {} exactly",
+                            got,
+                            stringify!($ty)
+                        );
+                        crate::builtins::named_error(&ctx, "RangeError", &message)
+                    };
+
+                    if value.typeof_str() == "bigint" {
+                        let digits = value.as_string()?.to_string();
+                        return digits
+                            .parse::<$ty>()
+                            .map_err(|_| range_error(&format!("{}n", digits)));
+                    }
+                    if !value.is_number() {
+                        return Err(type_error(value, "a number or a BigInt"));
+                    }
+
+                    let number = value.as_number()?;
+                    if policy == Int64Policy::Lossy {
+                        return Ok(number as $ty);
+                    }
+
+                    let exact = number.trunc() == number
+                        && number >= <$ty>::MIN as f64
+                        && number < <$ty>::MAX as f64
+                        && (policy == Int64Policy::BigInt || value.is_safe_integer());
+                    if !exact {
+                        return Err(range_error(&number.to_string()));
+                    }
+
+                    Ok(number as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_int64!(i64, u64);
+
+impl FromJSValue for JSValue {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJSValue for JSObject {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        if !value.is_object() {
+            return Err(type_error(value, "an object"));
+        }
+
+        value.as_object()
+    }
+}
+
+impl FromJSValue for JSFunction {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        let object = JSObject::from_js_value(value)?;
+        if !object.is_function() {
+            return Err(type_error(value, "a function"));
+        }
+
+        Ok(JSFunction::from(object))
+    }
+}
+
+impl FromJSValue for JSConstructor {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        let object = JSObject::from_js_value(value)?;
+        if !object.is_contructor() {
+            return Err(type_error(value, "a constructor"));
+        }
+
+        Ok(JSConstructor { object })
+    }
+}
+
+impl FromJSValue for () {
+    fn from_js_value(_value: &JSValue) -> JSResult<Self> {
+        Ok(())
+    }
+}
+
+impl FromJSValue for bool {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        if !value.is_boolean() {
+            return Err(type_error(value, "a boolean"));
+        }
+
+        Ok(value.as_boolean())
+    }
+}
+
+impl FromJSValue for String {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        if !value.is_string() {
+            return Err(type_error(value, "a string"));
+        }
+
+        Ok(value.as_string()?.to_string())
+    }
+}
+
+impl<T: FromJSValue> FromJSValue for Option<T> {
+    fn from_js_value(value: &JSValue) -> JSResult<Self> {
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+
+        T::from_js_value(value).map(Some)
+    }
+}
+
+/// A JavaScript value deeply converted to Rust data, in the shape of JSON.
+/// Produced by [`JSValue::to_plain`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlainValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<PlainValue>),
+    Object(BTreeMap<String, PlainValue>),
+}
+
+impl JSValue {
+    /// Converts the value into Rust data, following arrays and objects up to
+    /// `max_depth` levels deep.
+    ///
+    /// Objects are converted from their enumerable properties, like `JSON.stringify`
+    /// does; functions and symbols become `PlainValue::Undefined`.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{convert::PlainValue, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("({ list: [1, 'a'] })", None).unwrap();
+    /// let PlainValue::Object(object) = value.to_plain(8).unwrap() else {
+    ///     panic!("expected an object");
+    /// };
+    /// assert_eq!(
+    ///     object["list"],
+    ///     PlainValue::Array(vec![PlainValue::Number(1.0), PlainValue::String("a".into())])
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    /// A `RangeError` if the value is nested deeper than `max_depth`, e.g. because it
+    /// is cyclic, or an exception thrown while reading a property.
+    pub fn to_plain(&self, max_depth: usize) -> JSResult<PlainValue> {
+        self.to_plain_at(0, max_depth)
+    }
+
+    fn to_plain_at(&self, depth: usize, max_depth: usize) -> JSResult<PlainValue> {
+        if self.is_undefined() {
+            return Ok(PlainValue::Undefined);
+        }
+        if self.is_null() {
+            return Ok(PlainValue::Null);
+        }
+        if self.is_boolean() {
+            return Ok(PlainValue::Bool(self.as_boolean()));
+        }
+        if self.is_number() {
+            return Ok(PlainValue::Number(self.as_number()?));
+        }
+        if self.is_string() {
+            return Ok(PlainValue::String(self.as_string()?.to_string()));
+        }
+        if !self.is_object() || self.as_object()?.is_function() {
+            return Ok(PlainValue::Undefined);
+        }
+
+        if depth >= max_depth {
+            let ctx = JSContext::from(self.ctx);
+            let message =
+                JSValue::string(&ctx, format!("Maximum depth of {} exceeded", max_depth));
+            let error = ctx
+                .global_constructor("RangeError")?
+                .call_constructor(&[message])?;
+            return Err(JSError::from(error));
+        }
+
+        let object = self.as_object()?;
+        if self.is_array() {
+            let length = object.get_property("length")?.as_number()? as u32;
+            let items = (0..length)
+                .map(|index| {
+                    object
+                        .get_property_at_index(index)?
+                        .to_plain_at(depth + 1, max_depth)
+                })
+                .collect::<JSResult<_>>()?;
+            return Ok(PlainValue::Array(items));
+        }
+
+        let properties = object
+            .get_property_names()
+            .map(|name| {
+                let value = object
+                    .get_property(name.to_string().as_str())?
+                    .to_plain_at(depth + 1, max_depth)?;
+                Ok((name.to_string(), value))
+            })
+            .collect::<JSResult<_>>()?;
+        Ok(PlainValue::Object(properties))
+    }
+}
+
+/// # Panics
+/// If an array or object cannot be created, see [`IntoJSValue::try_into_js_value`].
+impl IntoJSValue for PlainValue {
+    fn into_js_value(self, ctx: &JSContext) -> JSValue {
+        match self.try_into_js_value(ctx) {
+            Ok(value) => value,
+            Err(error) => panic!(
+                "Failed to convert a plain value to JavaScript: {}",
+                error
+                    .message()
+                    .map(|message| message.to_string())
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    fn try_into_js_value(self, ctx: &JSContext) -> JSResult<JSValue> {
+        match self {
+            PlainValue::Undefined => Ok(JSValue::undefined(ctx)),
+            PlainValue::Null => Ok(JSValue::null(ctx)),
+            PlainValue::Bool(value) => Ok(JSValue::boolean(ctx, value)),
+            PlainValue::Number(value) => Ok(JSValue::number(ctx, value)),
+            PlainValue::String(value) => Ok(JSValue::string(ctx, value)),
+            PlainValue::Array(items) => {
+                let items = items
+                    .into_iter()
+                    .map(|item| item.try_into_js_value(ctx))
+                    .collect::<JSResult<Vec<_>>>()?;
+                Ok(JSArray::new_array(ctx, &items)?.into())
+            }
+            PlainValue::Object(properties) => {
+                let object = JSObject::new(ctx);
+                for (name, value) in properties {
+                    let value = value.try_into_js_value(ctx)?;
+                    object.set_property(name.as_str(), &value, Default::default())?;
+                }
+
+                Ok(object.into())
+            }
+        }
+    }
+}
+
+impl IntoJSArgs for &[JSValue] {
+    fn into_js_args(self, _ctx: &JSContext) -> JSResult<Vec<JSValue>> {
+        Ok(self.to_vec())
+    }
+}
+
+impl IntoJSArgs for Vec<JSValue> {
+    fn into_js_args(self, _ctx: &JSContext) -> JSResult<Vec<JSValue>> {
+        Ok(self)
+    }
+}
+
+impl IntoJSArgs for () {
+    fn into_js_args(self, _ctx: &JSContext) -> JSResult<Vec<JSValue>> {
+        Ok(Vec::new())
+    }
+}
+
+macro_rules! impl_args {
+    ($($name:ident),+) => {
+        impl<$($name: IntoJSValue),+> IntoJSArgs for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_js_args(self, ctx: &JSContext) -> JSResult<Vec<JSValue>> {
+                let ($($name,)+) = self;
+                Ok(vec![$($name.try_into_js_value(ctx)?),+])
+            }
+        }
+    };
+}
+
+impl_args!(A);
+impl_args!(A, B);
+impl_args!(A, B, C);
+impl_args!(A, B, C, D);
+impl_args!(A, B, C, D, E);
+impl_args!(A, B, C, D, E, F);
+impl_args!(A, B, C, D, E, F, G);
+impl_args!(A, B, C, D, E, F, G, H);
+
+/// Builds a `JSResult<Vec<JSValue>>` argument list from Rust values of mixed types,
+/// converting each one with [`IntoJSValue::try_into_js_value`], for
+/// [`JSObject::call`] and similar functions. The first conversion that fails, such
+/// as an `i64` outside the safe range under [`Int64Policy::Strict`], is the error.
+///
+/// # Examples
+/// ```
+/// use rust_jsc::{js_args, JSContext};
+///
+/// let ctx = JSContext::new();
+/// let log = ctx.evaluate_script("(...items) => items.length", None).unwrap();
+/// let options = ctx.evaluate_script("({ verbose: true })", None).unwrap();
+/// let count = log
+///     .as_object()
+///     .unwrap()
+///     .call(None, &js_args![ctx; 1, "two", true, &options].unwrap())
+///     .unwrap();
+/// assert_eq!(count.as_number().unwrap(), 4.0);
+/// ```
+#[macro_export]
+macro_rules! js_args {
+    ($ctx:expr $(;)?) => {
+        $crate::JSResult::<::std::vec::Vec<$crate::JSValue>>::Ok(::std::vec::Vec::new())
+    };
+    ($ctx:expr; $($argument:expr),+ $(,)?) => {
+        [$($crate::convert::IntoJSValue::try_into_js_value($argument, &$ctx)),+]
+            .into_iter()
+            .collect::<$crate::JSResult<::std::vec::Vec<$crate::JSValue>>>()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as rust_jsc};
+    use rust_jsc_macros::JSEnum;
+
+    #[derive(Debug, PartialEq, JSEnum)]
+    enum Level {
+        Debug = 10,
+        Info = 20,
+        Error = 40,
+    }
+
+    #[test]
+    fn test_context_value() {
+        let ctx = JSContext::new();
+        let value = |value: JSResult<JSValue>| value.unwrap();
+        assert_eq!(
+            value(ctx.value("name")).as_string().unwrap().to_string(),
+            "name"
+        );
+        assert_eq!(value(ctx.value(2.5)).as_number().unwrap(), 2.5);
+        assert_eq!(value(ctx.value(-3i32)).as_number().unwrap(), -3.0);
+        assert!(value(ctx.value(true)).as_boolean());
+        assert!(value(ctx.value(None::<&str>)).is_null());
+        let value = JSValue::from((&ctx, "pair"));
+        assert_eq!(value.as_string().unwrap().to_string(), "pair");
+
+        let pad = ctx
+            .evaluate_script("(s, n) => s.padStart(n, '0')", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let padded: String = pad.call_with(None, ("7", 3)).unwrap();
+        assert_eq!(padded, "007");
+        let error = pad.call_with::<_, f64>(None, ("7", 3)).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        let date = ctx
+            .global_object()
+            .get_property("Date")
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let epoch = date.call_as_constructor_with((0,)).unwrap();
+        let time: f64 = epoch
+            .get_property("getTime")
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .call_with(Some(&epoch), ())
+            .unwrap();
+        assert_eq!(time, 0.0);
+    }
+
+    #[test]
+    fn test_js_args() {
+        let ctx = JSContext::new();
+        let describe = ctx
+            .evaluate_script(
+                "(...items) => items.map((item) => typeof item).join()",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let options = JSObject::new(&ctx);
+
+        let result = describe
+            .call(
+                None,
+                &rust_jsc::js_args![ctx; 1, "two", true, &options, None::<f64>].unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "number,string,boolean,object,object"
+        );
+        let arguments = rust_jsc::js_args![ctx; 2.5, String::from("x"),].unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert!(rust_jsc::js_args![ctx].unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_int64_policy() {
+        let ctx = JSContext::new();
+        let large = (1i64 << 53) + 1;
+        assert_eq!(ctx.int64_policy(), Int64Policy::Lossy);
+        let value = large.into_js_value(&ctx);
+        assert!(value.is_number());
+        assert_eq!(i64::from_js_value(&value).unwrap(), large - 1);
+
+        ctx.set_int64_policy(Int64Policy::BigInt);
+        ctx.evaluate_script("globalThis.BigInt = () => 0;", None)
+            .unwrap();
+        let value = large.into_js_value(&ctx);
+        assert_eq!(value.typeof_str(), "bigint");
+        assert_eq!(i64::from_js_value(&value).unwrap(), large);
+        assert_eq!(7u64.into_js_value(&ctx).typeof_str(), "bigint");
+        let value = u64::MAX.into_js_value(&ctx);
+        assert_eq!(u64::from_js_value(&value).unwrap(), u64::MAX);
+        assert!(u64::from_js_value(&(-1i64).into_js_value(&ctx)).is_err());
+        assert_eq!(i64::from_js_value(&JSValue::number(&ctx, 3.0)).unwrap(), 3);
+        assert!(i64::from_js_value(&JSValue::number(&ctx, 1.5)).is_err());
+
+        ctx.set_int64_policy(Int64Policy::Strict);
+        assert!(7i64.into_js_value(&ctx).is_number());
+        let error = large.try_into_js_value(&ctx).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        let identity = ctx
+            .evaluate_script("(value) => value", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let errors = [
+            ctx.value(large).unwrap_err(),
+            ctx.value(Some(large)).unwrap_err(),
+            rust_jsc::js_args![ctx; 1, large].unwrap_err(),
+            (1, large).into_js_args(&ctx).unwrap_err(),
+            identity.call_with::<_, f64>(None, (large,)).unwrap_err(),
+            identity.call_as_constructor_with((large,)).unwrap_err(),
+        ];
+        for error in errors {
+            assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        }
+        let value = ctx.evaluate_script("BigInt(2) ** 60n", None).unwrap();
+        assert_eq!(i64::from_js_value(&value).unwrap(), 1 << 60);
+        let value = ctx.evaluate_script("2 ** 60", None).unwrap();
+        let error = i64::from_js_value(&value).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        let value = JSValue::string(&ctx, "1");
+        let error = i64::from_js_value(&value).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_js_enum() {
+        let ctx = JSContext::new();
+        assert_eq!(
+            Level::VARIANTS,
+            &[("Debug", 10), ("Info", 20), ("Error", 40)]
+        );
+        assert_eq!(Level::Info.into_js_value(&ctx).as_number().unwrap(), 20.0);
+
+        let object = Level::js_object(&ctx).unwrap();
+        ctx.global_object()
+            .set_property("Level", &object, Default::default())
+            .unwrap();
+        let result = ctx
+            .evaluate_script("Level.Error + ':' + Level[Level.Info]", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "40:Info");
+        let result = ctx.evaluate_script("Object.isFrozen(Level)", None).unwrap();
+        assert!(result.as_boolean());
+
+        let from = |script: &str| {
+            Level::from_js_value(&ctx.evaluate_script(script, None).unwrap())
+        };
+        assert_eq!(from("'Debug'").unwrap(), Level::Debug);
+        assert_eq!(from("40").unwrap(), Level::Error);
+
+        let error = from("'Warning'").unwrap_err();
+        assert_eq!(
+            error.message().unwrap().to_string(),
+            "Expected one of Level: Debug (10), Info (20), Error (40), got \"Warning\""
+        );
+        assert!(from("30").is_err());
+        assert_eq!(
+            from("true").unwrap_err().name().unwrap().to_string(),
+            "TypeError"
+        );
+    }
+
+    #[test]
+    fn test_into_js_value() {
+        let ctx = JSContext::new();
+        assert_eq!(42.0.into_js_value(&ctx).as_number().unwrap(), 42.0);
+        assert_eq!(7u32.into_js_value(&ctx).as_number().unwrap(), 7.0);
+        assert!(true.into_js_value(&ctx).as_boolean());
+        assert_eq!(
+            "hello".into_js_value(&ctx).as_string().unwrap().to_string(),
+            "hello"
+        );
+        assert!(None::<f64>.into_js_value(&ctx).is_null());
+        assert!(().into_js_value(&ctx).is_undefined());
+    }
+
+    #[test]
+    fn test_from_js_value() {
+        let ctx = JSContext::new();
+        let value = JSValue::number(&ctx, 42.0);
+        assert_eq!(f64::from_js_value(&value).unwrap(), 42.0);
+        assert_eq!(i32::from_js_value(&value).unwrap(), 42);
+        assert!(String::from_js_value(&value).is_err());
+        assert!(bool::from_js_value(&value).is_err());
+
+        let value = JSValue::string(&ctx, "hello");
+        assert_eq!(String::from_js_value(&value).unwrap(), "hello");
+        assert!(f64::from_js_value(&value).is_err());
+
+        let value = JSValue::undefined(&ctx);
+        assert_eq!(Option::<f64>::from_js_value(&value).unwrap(), None);
+    }
+
+    #[test]
+    fn test_from_js_value_integers() {
+        let ctx = JSContext::new();
+        let value = JSValue::number(&ctx, 255.0);
+        assert_eq!(u8::from_js_value(&value).unwrap(), 255);
+
+        for (number, name) in [(1.5, "TypeError"), (f64::NAN, "TypeError")] {
+            let error = i32::from_js_value(&JSValue::number(&ctx, number)).unwrap_err();
+            assert_eq!(error.name().unwrap().to_string(), name);
+        }
+
+        let error = u8::from_js_value(&JSValue::number(&ctx, 300.0)).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        let error = u32::from_js_value(&JSValue::number(&ctx, -1.0)).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+    }
+
+    #[test]
+    fn test_type_error() {
+        let ctx = JSContext::new();
+        let value = JSValue::string(&ctx, "42");
+        let error = f64::from_js_value(&value).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_to_plain() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script(
+                "({ name: 'a', tags: ['x', null], nested: { ok: true }, f() {} })",
+                None,
+            )
+            .unwrap();
+
+        let plain = value.to_plain(4).unwrap();
+        let expected = PlainValue::Object(BTreeMap::from([
+            ("name".to_string(), PlainValue::String("a".to_string())),
+            (
+                "tags".to_string(),
+                PlainValue::Array(vec![
+                    PlainValue::String("x".to_string()),
+                    PlainValue::Null,
+                ]),
+            ),
+            (
+                "nested".to_string(),
+                PlainValue::Object(BTreeMap::from([(
+                    "ok".to_string(),
+                    PlainValue::Bool(true),
+                )])),
+            ),
+            ("f".to_string(), PlainValue::Undefined),
+        ]));
+        assert_eq!(plain, expected);
+
+        let copy = plain.clone().try_into_js_value(&ctx).unwrap();
+        assert_eq!(copy.to_plain(4).unwrap(), plain);
+
+        ctx.evaluate_script(
+            "Object.defineProperty(Object.prototype, 'boom', { set() { throw new URIError('boom'); } })",
+            None,
+        )
+        .unwrap();
+        let plain = PlainValue::Array(vec![PlainValue::Object(BTreeMap::from([(
+            "boom".to_string(),
+            PlainValue::Null,
+        )]))]);
+        let error = plain.try_into_js_value(&ctx).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "URIError");
+    }
+
+    #[test]
+    fn test_to_plain_depth() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script("const cycle = {}; cycle.self = cycle; cycle", None)
+            .unwrap();
+        let error = value.to_plain(16).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+
+        let value = ctx.evaluate_script("[[1]]", None).unwrap();
+        assert!(value.to_plain(1).is_err());
+        assert!(value.to_plain(2).is_ok());
+    }
+
+    #[test]
+    fn test_into_js_args() {
+        let ctx = JSContext::new();
+        let args = (1.0, "two", true).into_js_args(&ctx).unwrap();
+        assert_eq!(args.len(), 3);
+        assert!(args[0].is_number());
+        assert!(args[1].is_string());
+        assert!(args[2].is_boolean());
+        assert!(().into_js_args(&ctx).unwrap().is_empty());
+    }
+}
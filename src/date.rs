@@ -1,6 +1,8 @@
 use rust_jsc_sys::{JSObjectMakeDate, JSValueRef};
 
-use crate::{JSContext, JSDate, JSError, JSObject, JSResult, JSValue};
+use crate::{
+    buffer::SmallBuffer, JSContext, JSDate, JSError, JSObject, JSResult, JSValue,
+};
 
 impl JSDate {
     pub fn new(object: JSObject) -> Self {
@@ -9,15 +11,14 @@ impl JSDate {
 
     pub fn new_date(ctx: JSContext, args: &[JSValue]) -> JSResult<Self> {
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
 
         let result = unsafe {
             JSObjectMakeDate(ctx.inner, args.len(), args.as_ptr(), &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self::new(JSObject::from_ref(result, ctx.inner)))
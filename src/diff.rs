@@ -0,0 +1,226 @@
+//! Structural diffs between JavaScript values, e.g. to find what changed in the
+//! state of a script between two evaluations.
+
+use crate::{
+    context_data::ContextData, pointer::JsonPointer, JSContext, JSResult, JSValue,
+};
+
+/// Compares two values and returns `[tokens, kind, old, new]` for each difference.
+/// Plain objects and arrays are compared member by member, dates by their time, and
+/// other objects by identity.
+const DIFF: &str = r#"(function (a, b) {
+    const isContainer = (v) => {
+        if (Array.isArray(v)) {
+            return true;
+        }
+        if (typeof v !== "object" || v === null) {
+            return false;
+        }
+        const prototype = Object.getPrototypeOf(v);
+        return prototype === Object.prototype || prototype === null;
+    };
+    const same = (a, b) => {
+        if (Object.is(a, b)) {
+            return true;
+        }
+        return a instanceof Date && b instanceof Date && Object.is(a.getTime(), b.getTime());
+    };
+    const keys = (v) => (Array.isArray(v) ? v.map((_, index) => String(index)) : Object.keys(v));
+    const has = (v, key) => Object.prototype.hasOwnProperty.call(v, key);
+
+    const entries = [];
+    const visited = new Map();
+    const walk = (a, b, path) => {
+        if (same(a, b)) {
+            return;
+        }
+        if (!isContainer(a) || !isContainer(b) || Array.isArray(a) !== Array.isArray(b)) {
+            entries.push([path, "changed", a, b]);
+            return;
+        }
+        let seen = visited.get(a);
+        if (seen === undefined) {
+            seen = new Set();
+            visited.set(a, seen);
+        }
+        if (seen.has(b)) {
+            return;
+        }
+        seen.add(b);
+
+        for (const key of keys(a)) {
+            if (has(b, key)) {
+                walk(a[key], b[key], [...path, key]);
+            } else {
+                entries.push([[...path, key], "removed", a[key], undefined]);
+            }
+        }
+        for (const key of keys(b)) {
+            if (!has(a, key)) {
+                entries.push([[...path, key], "added", undefined, b[key]]);
+            }
+        }
+    };
+    walk(a, b, []);
+    return entries;
+})"#;
+
+/// How a value differs between the two sides of a [`diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The value only exists on the new side.
+    Added,
+    /// The value only exists on the old side.
+    Removed,
+    /// The value exists on both sides but differs.
+    Changed,
+}
+
+/// A difference found by [`diff`].
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    /// Where the values differ, as a JSON Pointer from the compared values.
+    pub path: JsonPointer,
+    /// How the values differ.
+    pub kind: DiffKind,
+    /// The old value, `None` when it was added.
+    pub old: Option<JSValue>,
+    /// The new value, `None` when it was removed.
+    pub new: Option<JSValue>,
+}
+
+/// Computes the structural differences between two values, such as the state of a
+/// script before and after an evaluation.
+///
+/// Plain objects and arrays are compared property by property, and an entry is
+/// reported for each property added, removed or changed. Primitives are compared
+/// with `Object.is`, dates by their time, and other objects, such as functions or
+/// maps, by identity.
+///
+/// # Arguments
+/// - `old`: The old value.
+/// - `new`: The new value.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{diff, diff::DiffKind, JSContext};
+///
+/// let ctx = JSContext::new();
+/// let before = ctx.evaluate_script("({ count: 1, tags: ['a'] })", None).unwrap();
+/// let after = ctx.evaluate_script("({ count: 2, tags: ['a', 'b'] })", None).unwrap();
+///
+/// let entries = diff(&before, &after).unwrap();
+/// assert_eq!(entries[0].path.to_string(), "/count");
+/// assert_eq!(entries[1].kind, DiffKind::Added);
+/// ```
+///
+/// # Errors
+/// If an exception is thrown while reading the values, e.g. by a getter.
+pub fn diff(old: &JSValue, new: &JSValue) -> JSResult<Vec<DiffEntry>> {
+    let ctx = JSContext::from(old.ctx);
+    let compare = ContextData::shim(&ctx, "diff", DIFF)?;
+    let entries = compare
+        .call(None, &[old.clone(), new.clone()])?
+        .as_object()?;
+    let length = entries.get_property("length")?.as_number()? as u32;
+
+    let mut result = Vec::with_capacity(length as usize);
+    for index in 0..length {
+        let entry = entries.get_property_at_index(index)?.as_object()?;
+        let tokens = entry.get_property_at_index(0)?.as_object()?;
+        let mut path = JsonPointer::root();
+        for token in 0..tokens.get_property("length")?.as_number()? as u32 {
+            path.push(
+                tokens
+                    .get_property_at_index(token)?
+                    .as_string()?
+                    .to_string(),
+            );
+        }
+
+        let kind = match entry
+            .get_property_at_index(1)?
+            .as_string()?
+            .to_string()
+            .as_str()
+        {
+            "added" => DiffKind::Added,
+            "removed" => DiffKind::Removed,
+            _ => DiffKind::Changed,
+        };
+        let old = entry.get_property_at_index(2)?;
+        let new = entry.get_property_at_index(3)?;
+        result.push(DiffEntry {
+            path,
+            kind,
+            old: (kind != DiffKind::Added).then_some(old),
+            new: (kind != DiffKind::Removed).then_some(new),
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let ctx = JSContext::new();
+        let before = ctx
+            .evaluate_script(
+                "globalThis.log = () => {};
+                 ({ count: 1, tags: ['a', 'b'], user: { name: 'Ada', id: 7 }, log, at: new Date(0) })",
+                None,
+            )
+            .unwrap();
+        let after = ctx
+            .evaluate_script(
+                "({ count: 2, tags: ['a'], user: { name: 'Ada', role: 'admin' }, log, at: new Date(0), extra: null })",
+                None,
+            )
+            .unwrap();
+
+        let entries = diff(&before, &after).unwrap();
+        let summary: Vec<(String, DiffKind)> = entries
+            .iter()
+            .map(|entry| (entry.path.to_string(), entry.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("/count".to_string(), DiffKind::Changed),
+                ("/tags/1".to_string(), DiffKind::Removed),
+                ("/user/id".to_string(), DiffKind::Removed),
+                ("/user/role".to_string(), DiffKind::Added),
+                ("/extra".to_string(), DiffKind::Added),
+            ]
+        );
+        assert_eq!(entries[0].old.as_ref().unwrap().as_number().unwrap(), 1.0);
+        assert_eq!(entries[0].new.as_ref().unwrap().as_number().unwrap(), 2.0);
+        assert!(entries[1].new.is_none());
+        assert!(entries[4].old.is_none());
+        assert!(entries[4].new.as_ref().unwrap().is_null());
+
+        assert!(diff(&before, &before).unwrap().is_empty());
+        let replaced = diff(&before, &JSValue::number(&ctx, 1.0)).unwrap();
+        assert_eq!(replaced.len(), 1);
+        assert!(replaced[0].path.is_root());
+
+        let cyclic = ctx
+            .evaluate_script(
+                "const a = { n: 1 }; a.self = a; const b = { n: 2 }; b.self = b; [a, b]",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let entries = diff(
+            &cyclic.get_property_at_index(0).unwrap(),
+            &cyclic.get_property_at_index(1).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path.to_string(), "/n");
+    }
+}
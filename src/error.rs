@@ -1,8 +1,11 @@
 use std::ops::Deref;
 
-use rust_jsc_sys::{JSObjectMakeError, JSObjectMakeTypeError, JSValueRef};
+use rust_jsc_sys::{JSContextRef, JSObjectMakeError, JSObjectMakeTypeError, JSValueRef};
 
-use crate::{JSContext, JSError, JSObject, JSResult, JSString, JSValue};
+use crate::{
+    buffer::SmallBuffer, context::ExceptionHandlers, JSArray, JSContext, JSError,
+    JSObject, JSResult, JSString, JSValue,
+};
 
 impl JSError {
     /// Creates a new `JSError` object.
@@ -28,15 +31,14 @@ impl JSError {
     /// A new `JSError` object.
     pub fn new(ctx: &JSContext, args: &[JSValue]) -> JSResult<Self> {
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
 
         let result = unsafe {
             JSObjectMakeError(ctx.inner, args.len(), args.as_ptr(), &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self::from(JSObject::from_ref(result, ctx.inner)))
@@ -72,8 +74,7 @@ impl JSError {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self::from(JSObject::from_ref(result, ctx.inner)))
@@ -84,6 +85,70 @@ impl JSError {
         Self::new(ctx, &args)
     }
 
+    /// Creates a new `AggregateError` holding the errors.
+    /// This is the same as `new AggregateError(errors, message)`, the error `Promise.any`
+    /// rejects with when every promise is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The JavaScript context.
+    /// * `errors` - The errors to collect.
+    /// * `message` - The message of the aggregate error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_jsc::{JSContext, JSError};
+    ///
+    /// let ctx = JSContext::new();
+    /// let errors = vec![
+    ///     JSError::with_message(&ctx, "first").unwrap(),
+    ///     JSError::new_typ(&ctx, "second").unwrap(),
+    /// ];
+    /// let error = JSError::aggregate(&ctx, errors, "2 tasks failed").unwrap();
+    /// assert_eq!(error.name().unwrap().to_string(), "AggregateError");
+    /// assert_eq!(error.errors().unwrap().len(), 2);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A new `JSError` of type `AggregateError`.
+    pub fn aggregate(
+        ctx: &JSContext,
+        errors: impl IntoIterator<Item = JSError>,
+        message: impl Into<JSString>,
+    ) -> JSResult<Self> {
+        let errors: Vec<JSValue> = errors.into_iter().map(JSValue::from).collect();
+        let errors = JSArray::new_array(ctx, &errors)?;
+        let message = JSValue::string(ctx, message);
+
+        let error = ctx
+            .global_constructor("AggregateError")?
+            .call_constructor(&[errors.into(), message])?;
+        Ok(Self::from(error))
+    }
+
+    /// Returns the errors of an `AggregateError`, or an empty list for other errors.
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading the errors, or one of them is
+    /// `undefined` or `null`.
+    pub fn errors(&self) -> JSResult<Vec<JSError>> {
+        let errors = self.object.get_property("errors")?;
+        if !errors.is_array() {
+            return Ok(Vec::new());
+        }
+
+        let errors = errors.as_object()?;
+        let length = errors.get_property("length")?.as_number()? as u32;
+        (0..length)
+            .map(|index| {
+                let error = errors.get_property_at_index(index)?;
+                Ok(Self::from(error.as_object()?))
+            })
+            .collect()
+    }
+
     pub fn name(&self) -> JSResult<JSString> {
         self.object.get_property("name")?.as_string()
     }
@@ -107,6 +172,49 @@ impl JSError {
     pub fn set_stack(&self, stack: &JSValue) -> JSResult<()> {
         self.object.set_property("stack", stack, Default::default())
     }
+
+    /// Creates the error for a thrown value or a rejection reason. Objects are used as
+    /// they are; other values, such as `undefined` or a string, are wrapped in an
+    /// `Error` whose message is the value as a string and whose `cause` is the value.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::{JSContext, JSError, JSValue};
+    ///
+    /// let ctx = JSContext::new();
+    /// let error = JSError::from_thrown(JSValue::null(&ctx));
+    /// assert_eq!(error.message().unwrap().to_string(), "null");
+    /// assert!(error.cause().unwrap().is_null());
+    /// ```
+    pub fn from_thrown(value: JSValue) -> Self {
+        if value.is_object() {
+            return Self::from(value);
+        }
+
+        let ctx = JSContext::from(value.ctx);
+        let message = match value.as_string() {
+            Ok(message) => message.to_string(),
+            Err(_) => value.typeof_str().to_string(),
+        };
+        let error = match Self::with_message(&ctx, message) {
+            Ok(error) => error,
+            Err(error) => return error,
+        };
+        match error.set_cause(&value) {
+            Ok(()) => error,
+            Err(error) => error,
+        }
+    }
+
+    /// Creates the error for an exception thrown by the engine through the C API,
+    /// and reports it to the hook of [`JSContext::set_exception_hook`]. Exceptions
+    /// that are not objects, such as `throw null`, are wrapped as by
+    /// [`JSError::from_thrown`].
+    pub(crate) fn from_exception(exception: JSValueRef, ctx: JSContextRef) -> Self {
+        let error = Self::from_thrown(JSValue::new(exception, ctx));
+        ExceptionHandlers::report(&JSContext::from(ctx), &error);
+        error
+    }
 }
 
 impl std::fmt::Display for JSError {
@@ -194,4 +302,39 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().as_boolean(), true);
     }
+
+    #[test]
+    fn test_aggregate_error() {
+        let ctx = JSContext::new();
+        let errors = vec![
+            JSError::with_message(&ctx, "first").unwrap(),
+            JSError::new_typ(&ctx, "second").unwrap(),
+        ];
+        let error = JSError::aggregate(&ctx, errors, "2 tasks failed").unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "AggregateError");
+        assert_eq!(error.message().unwrap().to_string(), "2 tasks failed");
+
+        let errors = error.errors().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].name().unwrap().to_string(), "TypeError");
+        assert_eq!(errors[1].message().unwrap().to_string(), "second");
+
+        let error = JSError::with_message(&ctx, "single").unwrap();
+        assert!(error.errors().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_error_from_promise_any() {
+        let ctx = JSContext::new();
+        ctx.evaluate_script(
+            "Promise.any([Promise.reject(new Error('a'))]).catch((e) => globalThis.caught = e)",
+            None,
+        )
+        .unwrap();
+
+        let caught = ctx.evaluate_script("caught", None).unwrap();
+        let errors = JSError::from(caught).errors().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message().unwrap().to_string(), "a");
+    }
 }
@@ -1,11 +1,84 @@
 use std::ops::Deref;
 
 use rust_jsc_sys::{
-    JSObjectCallAsConstructorCallback, JSObjectCallAsFunctionCallback,
-    JSObjectMakeConstructor, JSObjectMakeFunctionWithCallback,
+    JSContextRef, JSObjectCallAsConstructorCallback, JSObjectCallAsFunctionCallback,
+    JSObjectGetPrivate, JSObjectMake, JSObjectMakeConstructor,
+    JSObjectMakeFunctionWithCallback, JSObjectRef, JSValueMakeUndefined, JSValueRef,
 };
 
-use crate::{JSClass, JSContext, JSFunction, JSObject, JSResult, JSString, JSValue};
+use crate::{
+    buffer::SmallBuffer,
+    class::StaticClass,
+    convert::{FromJSValue, IntoJSArgs},
+    recursion::RecursionGuard,
+    trace::{CallDirection, CallSpan},
+    JSClass, JSConstructor, JSContext, JSFunction, JSObject, JSResult, JSString, JSValue,
+};
+
+/// The signature of the Rust closures backing functions created with
+/// [`JSFunction::closure`]. The closure receives the context, the `this`
+/// object and the arguments of the call.
+pub(crate) type ClosureCallback =
+    dyn Fn(&JSContext, &JSObject, &[JSValue]) -> JSResult<JSValue>;
+
+static CLOSURE_CLASS: StaticClass = StaticClass::new();
+
+fn closure_class() -> &'static JSClass {
+    CLOSURE_CLASS.get_or_init(|| {
+        JSClass::builder("Function")
+            .call_as_function(Some(closure_call))
+            .set_finalize(Some(closure_finalize))
+            .build()
+            .expect("Failed to create closure class")
+    })
+}
+
+unsafe extern "C" fn closure_call(
+    ctx: JSContextRef,
+    function: JSObjectRef,
+    this_object: JSObjectRef,
+    argument_count: usize,
+    arguments: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let callback = JSObjectGetPrivate(function) as *const Box<ClosureCallback>;
+    if callback.is_null() {
+        return JSValueMakeUndefined(ctx);
+    }
+
+    let context = JSContext::from(ctx);
+    let this = if this_object.is_null() {
+        context.global_object()
+    } else {
+        JSObject::from_ref(this_object, ctx)
+    };
+    let arguments = SmallBuffer::arguments(ctx, arguments, argument_count);
+    let _span = CallSpan::enter_ref(
+        &context,
+        CallDirection::JsToNative,
+        function,
+        argument_count,
+    );
+
+    match context
+        .check_budget()
+        .and_then(|_| RecursionGuard::enter(&context))
+        .and_then(|_recursion| (*callback)(&context, &this, &arguments))
+    {
+        Ok(value) => value.inner,
+        Err(error) => {
+            *exception = error.into();
+            std::ptr::null()
+        }
+    }
+}
+
+unsafe extern "C" fn closure_finalize(object: JSObjectRef) {
+    let callback = JSObjectGetPrivate(object) as *mut Box<ClosureCallback>;
+    if !callback.is_null() {
+        drop(Box::from_raw(callback));
+    }
+}
 
 impl JSFunction {
     pub(crate) fn new(object: JSObject) -> Self {
@@ -59,6 +132,42 @@ impl JSFunction {
         self.object.call(this, arguments)
     }
 
+    /// Calls the function converting the arguments and the result with the
+    /// [`convert`](crate::convert) traits.
+    ///
+    /// # Arguments
+    /// - `this`: The `this` object to use when calling the function.
+    /// - `arguments`: A tuple of values convertible to JavaScript values.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// use rust_jsc::{JSContext, JSFunction};
+    ///
+    /// let ctx = JSContext::new();
+    /// let function = ctx.evaluate_script("(n, s) => n > s.length", None).unwrap();
+    /// let function = JSFunction::from(function.as_object().unwrap());
+    /// let result = function.call_with::<_, bool>(None, (42.0, "x")).unwrap();
+    /// assert!(result);
+    /// ```
+    ///
+    /// # Errors
+    /// If an argument cannot be converted, an exception is thrown while calling the
+    /// function, or the result cannot be converted to `R`. A `JSError` will be
+    /// returned.
+    ///
+    /// # Returns
+    /// The converted result of calling the function.
+    pub fn call_with<A, R>(&self, this: Option<&JSObject>, arguments: A) -> JSResult<R>
+    where
+        A: IntoJSArgs,
+        R: FromJSValue,
+    {
+        let ctx = JSContext::from(self.object.value.ctx);
+        let arguments = arguments.into_js_args(&ctx)?;
+        let result = self.call(this, &arguments)?;
+        R::from_js_value(&result)
+    }
+
     /// Calls the function as a constructor with the specified arguments.
     /// This is equivalent to `new function(...arguments)` in JavaScript.
     /// If `arguments` is empty, no arguments will be passed to the constructor.
@@ -86,6 +195,59 @@ impl JSFunction {
         self.object.call_as_constructor(arguments)
     }
 
+    /// Creates a new bound function with the specified `this` object and leading
+    /// arguments. This is equivalent to `function.bind(this, ...arguments)` in JavaScript.
+    /// If `this` is `None`, `undefined` will be bound as `this`.
+    ///
+    /// # Arguments
+    /// - `this`: The `this` object to bind.
+    /// - `arguments`: The arguments to prepend to the arguments of every call.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// use rust_jsc::{JSContext, JSValue};
+    ///
+    /// let ctx = JSContext::new();
+    /// let add = ctx.evaluate_script("(a, b) => a + b", None).unwrap();
+    /// let add = JSFunction::from(add.as_object().unwrap());
+    /// let add_one = add.bind(None, &[JSValue::number(&ctx, 1.0)]).unwrap();
+    /// let result = add_one.call(None, &[JSValue::number(&ctx, 2.0)]).unwrap();
+    /// assert_eq!(result.as_number().unwrap(), 3.0);
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while binding the function.
+    /// A `JSError` will be returned.
+    ///
+    /// # Returns
+    /// The bound function.
+    pub fn bind(
+        &self,
+        this: Option<&JSObject>,
+        arguments: &[JSValue],
+    ) -> JSResult<JSFunction> {
+        let ctx = JSContext::from(self.object.value.ctx);
+        let bind = ctx
+            .global_object()
+            .get_property("Function")?
+            .as_object()?
+            .get_property("prototype")?
+            .as_object()?
+            .get_property("bind")?
+            .as_object()?;
+
+        let this = match this {
+            Some(this) => this.value.clone(),
+            None => JSValue::undefined(&ctx),
+        };
+        let mut bind_arguments = Vec::with_capacity(arguments.len() + 1);
+        bind_arguments.push(this);
+        bind_arguments.extend_from_slice(arguments);
+
+        let bound = bind.call(Some(&self.object), &bind_arguments)?;
+        Ok(Self::new(bound.as_object()?))
+    }
+
     /// Returns `true` if the function is a constructor.
     ///
     /// # Returns
@@ -145,47 +307,136 @@ impl JSFunction {
         Self::new(object)
     }
 
-    /// Creates a new function with the specified name and callback.
+    /// Creates a new constructor of objects of the class, which calls the callback
+    /// when invoked with `new`.
     ///
     /// # Arguments
-    /// - `name`: The name of the function.
-    /// - `callback`: The callback to call when the function is called.
+    /// - `js_class`: The class of the objects the constructor creates.
+    /// - `callback`: The callback to call when the constructor is invoked.
     ///
     /// # Example
     /// ```rust,ignore
-    /// use rust_jsc::{JSContext, JSFunction, JSObject, JSValue};
+    /// use rust_jsc::{constructor, JSClass, JSContext, JSFunction, JSObject, JSResult, JSValue};
     ///
-    /// #[callback]
-    /// fn person(
-    ///    ctx: JSContext,
-    ///   _constructor: JSObject,
-    ///   _this: JSObject,
-    ///  arguments: &[JSValue],
-    /// ) -> JSResult<JSValue> {
-    ///     _constructor.set_property(&"name".into(), &arguments.get(0).unwrap(), Default::default());
-    ///     Ok(_constructor)
+    /// #[constructor]
+    /// fn person(ctx: JSContext, _constructor: JSObject, arguments: &[JSValue]) -> JSResult<JSValue> {
+    ///     let object = JSObject::new(&ctx);
+    ///     object.set_property("name", &arguments[0], Default::default())?;
+    ///     Ok(object.into())
     /// }
+    ///
     /// let ctx = JSContext::new();
-    /// let function = JSFunction::contructor(&ctx, Some("log"), Some(log_error));
-    /// let result = function.call(None, &[JSValue::string(&ctx, "Hello, World!")]);
-    /// assert!(result.is_err());
+    /// let class = JSClass::builder("Person").build().unwrap();
+    /// let constructor = JSFunction::constructor(&ctx, &class, Some(person));
+    /// let person = constructor.construct(&[JSValue::string(&ctx, "Ada")]).unwrap();
     /// ```
     ///
     /// # Returns
-    /// A new function with the specified name and callback.
-    pub fn contructor(
+    /// A new constructor with the specified class and callback.
+    pub fn constructor(
         ctx: &JSContext,
         js_class: &JSClass,
         callback: JSObjectCallAsConstructorCallback,
-    ) -> Self {
+    ) -> JSConstructor {
+        js_class.pin_counter();
         let result =
             unsafe { JSObjectMakeConstructor(ctx.inner, js_class.inner, callback) };
 
+        JSConstructor {
+            object: JSObject::from_ref(result, ctx.inner),
+        }
+    }
+
+    /// Creates a new constructor, see [`JSFunction::constructor`].
+    #[deprecated(
+        note = "renamed to `JSFunction::constructor`, which returns a `JSConstructor`"
+    )]
+    pub fn contructor(
+        ctx: &JSContext,
+        js_class: &JSClass,
+        callback: JSObjectCallAsConstructorCallback,
+    ) -> Self {
+        Self::new(Self::constructor(ctx, js_class, callback).object)
+    }
+
+    /// Creates a new function backed by a Rust closure.
+    /// The closure is dropped when the function is garbage collected.
+    ///
+    /// # Arguments
+    /// - `ctx`: The JavaScript context to create the function in.
+    /// - `callback`: The closure to call when the function is called.
+    ///
+    /// # Returns
+    /// A new function that calls the closure.
+    pub(crate) fn closure<F>(ctx: &JSContext, callback: F) -> Self
+    where
+        F: Fn(&JSContext, &JSObject, &[JSValue]) -> JSResult<JSValue> + 'static,
+    {
+        let callback: Box<Box<ClosureCallback>> = Box::new(Box::new(callback));
+        let result = unsafe {
+            JSObjectMake(
+                ctx.inner,
+                closure_class().inner,
+                Box::into_raw(callback) as *mut std::ffi::c_void,
+            )
+        };
+
         let object = JSObject::from_ref(result, ctx.inner);
         Self::new(object)
     }
 }
 
+impl JSConstructor {
+    /// Calls the constructor with the specified arguments.
+    /// This is equivalent to `new constructor(...arguments)` in JavaScript.
+    ///
+    /// # Arguments
+    /// - `arguments`: The arguments to pass to the constructor.
+    ///
+    /// # Errors
+    /// If an exception is thrown by the constructor.
+    ///
+    /// # Returns
+    /// The object created by the constructor.
+    pub fn construct(&self, arguments: &[JSValue]) -> JSResult<JSObject> {
+        self.object.call_as_constructor(arguments)
+    }
+
+    /// Calls the constructor with arguments converted from Rust values, see
+    /// [`JSConstructor::construct`].
+    ///
+    /// # Arguments
+    /// - `arguments`: A tuple of values convertible to JavaScript values.
+    ///
+    /// # Errors
+    /// If an argument cannot be converted or an exception is thrown by the
+    /// constructor.
+    pub fn construct_with(&self, arguments: impl IntoJSArgs) -> JSResult<JSObject> {
+        let ctx = JSContext::from(self.object.value.ctx);
+        self.construct(&arguments.into_js_args(&ctx)?)
+    }
+}
+
+impl Deref for JSConstructor {
+    type Target = JSValue;
+
+    fn deref(&self) -> &JSValue {
+        &self.object.value
+    }
+}
+
+impl From<JSConstructor> for JSObject {
+    fn from(constructor: JSConstructor) -> Self {
+        constructor.object
+    }
+}
+
+impl From<JSConstructor> for JSValue {
+    fn from(constructor: JSConstructor) -> Self {
+        constructor.object.into()
+    }
+}
+
 impl Deref for JSFunction {
     type Target = JSValue;
 
@@ -219,10 +470,44 @@ mod tests {
     use rust_jsc_sys::{JSContextRef, JSObjectRef, JSValueRef};
 
     use crate::{
-        JSClass, JSContext, JSError, JSFunction, JSObject, JSResult, JSValue,
-        PropertyDescriptorBuilder,
+        convert::FromJSValue, JSClass, JSConstructor, JSContext, JSError, JSFunction,
+        JSObject, JSResult, JSValue, PropertyDescriptorBuilder,
     };
 
+    #[test]
+    fn test_call_with() {
+        let ctx = JSContext::new();
+        let function = ctx.evaluate_script("(n, s) => n > s.length", None).unwrap();
+        let function = JSFunction::from(function.as_object().unwrap());
+
+        let result = function
+            .call_with::<(f64, &str), bool>(None, (42.0, "x"))
+            .unwrap();
+        assert!(result);
+
+        let result = function.call_with::<_, String>(None, (42.0, "x"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bind() {
+        let ctx = JSContext::new();
+        let function = ctx
+            .evaluate_script("(function (a, b) { return this.base + a + b; })", None)
+            .unwrap();
+        let function = JSFunction::from(function.as_object().unwrap());
+
+        let this = JSObject::new(&ctx);
+        this.set_property("base", &JSValue::number(&ctx, 10.0), Default::default())
+            .unwrap();
+        let bound = function
+            .bind(Some(&this), &[JSValue::number(&ctx, 1.0)])
+            .unwrap();
+
+        let result = bound.call(None, &[JSValue::number(&ctx, 2.0)]).unwrap();
+        assert_eq!(result.as_number().unwrap(), 13.0);
+    }
+
     #[test]
     fn test_callback() {
         #[callback]
@@ -323,7 +608,7 @@ mod tests {
             birth_date: "1990-01-01".into(),
         };
 
-        let state = Box::new(state);
+        let state = std::sync::Arc::new(state);
         ctx.set_shared_data(state);
 
         unsafe extern "C" fn callback(
@@ -392,9 +677,9 @@ mod tests {
             .enumerable(true)
             .build();
         let class = JSClass::builder("Person").build().unwrap();
-        let function = JSFunction::contructor(&ctx, &class, Some(new_object));
+        let constructor = JSFunction::constructor(&ctx, &class, Some(new_object));
         global_object
-            .set_property("Person", &function.into(), attributes)
+            .set_property("Person", &constructor.clone().into(), attributes)
             .unwrap();
 
         let result = ctx
@@ -415,5 +700,20 @@ mod tests {
         let age = person.get_property("age").unwrap();
         assert!(age.is_number());
         assert_eq!(age.as_number().unwrap(), 30.0);
+
+        let person = constructor.construct_with(("Ada", 36)).unwrap();
+        let name = person.get_property("name").unwrap();
+        assert_eq!(name.as_string().unwrap(), "Ada");
+        let value = ctx.evaluate_script("Person", None).unwrap();
+        let person = JSConstructor::from_js_value(&value)
+            .unwrap()
+            .construct(&[JSValue::string(&ctx, "Grace"), JSValue::number(&ctx, 45.0)])
+            .unwrap();
+        let age = person.get_property("age").unwrap();
+        assert_eq!(age.as_number().unwrap(), 45.0);
+
+        let function = ctx.evaluate_script("(() => {})", None).unwrap();
+        let error = JSConstructor::from_js_value(&function).err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
     }
 }
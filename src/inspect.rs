@@ -0,0 +1,718 @@
+//! Previews of JavaScript values shaped like the `RemoteObject` of the Chrome
+//! DevTools Protocol, for building custom devtools UIs without an inspector.
+
+use crate::{
+    context_data::ContextData, JSArray, JSContext, JSObject, JSResult, JSValue,
+    JSValueKind,
+};
+
+/// The maximum number of properties or entries in the preview of an object.
+pub const PREVIEW_LIMIT: usize = 100;
+
+/// Evaluates to a function returning the name of the constructor of an object: the
+/// `name` of the `constructor` of its prototype, read from own data properties so no
+/// getter runs, `""` when there is none and `null` when the object has no prototype.
+/// Exceptions, such as those of a proxy trap, are caught so they are not reported.
+const CLASS_NAME: &str = r#"(() => {
+    const { getPrototypeOf, getOwnPropertyDescriptor } = Object;
+    return (object) => {
+        try {
+            const prototype = getPrototypeOf(object);
+            if (prototype === null) {
+                return null;
+            }
+            const constructor = getOwnPropertyDescriptor(prototype, "constructor");
+            if (constructor === undefined || typeof constructor.value !== "function") {
+                return "";
+            }
+            const name = getOwnPropertyDescriptor(constructor.value, "name");
+            return name !== undefined && typeof name.value === "string" ? name.value : "";
+        } catch {
+            return "";
+        }
+    };
+})()"#;
+
+/// A preview of a JavaScript value, built by [`JSContext::inspect_value`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteObject {
+    /// The `typeof` of the value, such as `"object"` or `"string"`.
+    pub kind: &'static str,
+    /// The kind of object, such as `"array"`, `"map"` or `"null"`.
+    pub subtype: Option<&'static str>,
+    /// The name of the constructor of an object, such as `"Object"` or `"Map"`.
+    pub class_name: Option<String>,
+    /// A short description of the value, such as `"Array(3)"` or `"42"`.
+    pub description: String,
+    /// The own enumerable properties of an object.
+    pub properties: Vec<PropertyPreview>,
+    /// The entries of a `Map` or `Set`.
+    pub entries: Vec<EntryPreview>,
+    /// True if the object has more properties or entries than the preview holds.
+    pub overflow: bool,
+}
+
+/// A property in the preview of an object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyPreview {
+    pub name: String,
+    pub value: RemoteObject,
+}
+
+/// An entry in the preview of a `Map` or `Set`. Entries of sets have no key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryPreview {
+    pub key: Option<RemoteObject>,
+    pub value: RemoteObject,
+}
+
+impl JSContext {
+    /// Builds a preview of a value, with the properties of objects and the entries of
+    /// maps and sets down to the given depth. Properties are read with their getters.
+    ///
+    /// # Arguments
+    /// - `value`: The value to inspect.
+    /// - `depth`: The number of levels of properties to preview; 0 only describes the
+    ///   value itself.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("new Map([['a', [1, 2]]])", None).unwrap();
+    /// let preview = ctx.inspect_value(&value, 2).unwrap();
+    /// assert_eq!(preview.description, "Map(1)");
+    /// assert_eq!(preview.entries[0].value.description, "Array(2)");
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading the value, e.g. by a getter.
+    pub fn inspect_value(&self, value: &JSValue, depth: usize) -> JSResult<RemoteObject> {
+        let kind = value.match_type();
+        let subtype = match &kind {
+            JSValueKind::Null => Some("null"),
+            JSValueKind::Array => Some("array"),
+            JSValueKind::Date => Some("date"),
+            JSValueKind::RegExp => Some("regexp"),
+            JSValueKind::Error => Some("error"),
+            JSValueKind::Promise => Some("promise"),
+            JSValueKind::Map => Some("map"),
+            JSValueKind::Set => Some("set"),
+            JSValueKind::ArrayBuffer => Some("arraybuffer"),
+            JSValueKind::TypedArray(_) => Some("typedarray"),
+            _ => None,
+        };
+
+        let mut remote = RemoteObject {
+            kind: value.typeof_str(),
+            subtype,
+            ..Default::default()
+        };
+        if !value.is_object() {
+            remote.description = match kind {
+                JSValueKind::BigInt => format!("{}n", self.describe(value)?),
+                _ => self.describe(value)?,
+            };
+            return Ok(remote);
+        }
+
+        let object = value.as_object()?;
+        let class_name = self
+            .class_name(&object)?
+            .unwrap_or_else(|| "Object".to_string());
+        remote.description = match kind {
+            JSValueKind::Array | JSValueKind::TypedArray(_) => {
+                let length = object.get_property("length")?.as_number()?;
+                format!("{}({})", class_name, length)
+            }
+            JSValueKind::Map | JSValueKind::Set => {
+                let size = object.get_property("size")?.as_number()?;
+                format!("{}({})", class_name, size)
+            }
+            JSValueKind::Function
+            | JSValueKind::Date
+            | JSValueKind::RegExp
+            | JSValueKind::Error => self.describe(value)?,
+            _ => class_name.clone(),
+        };
+        remote.class_name = Some(class_name);
+
+        if depth == 0 {
+            return Ok(remote);
+        }
+
+        if matches!(kind, JSValueKind::Map | JSValueKind::Set) {
+            let entries = self.entries(value)?;
+            let length = entries.length()? as u32;
+            remote.overflow = length as usize > PREVIEW_LIMIT;
+            for index in 0..length.min(PREVIEW_LIMIT as u32) {
+                let entry = entries.get(index)?;
+                let entry = match kind {
+                    JSValueKind::Map => {
+                        let pair = JSArray::new(entry.as_object()?);
+                        EntryPreview {
+                            key: Some(self.inspect_value(&pair.get(0)?, depth - 1)?),
+                            value: self.inspect_value(&pair.get(1)?, depth - 1)?,
+                        }
+                    }
+                    _ => EntryPreview {
+                        key: None,
+                        value: self.inspect_value(&entry, depth - 1)?,
+                    },
+                };
+                remote.entries.push(entry);
+            }
+        }
+
+        for name in object.get_property_names() {
+            if remote.properties.len() == PREVIEW_LIMIT {
+                remote.overflow = true;
+                break;
+            }
+
+            let name = name.to_string();
+            let property = object.get_property(name.as_str())?;
+            remote.properties.push(PropertyPreview {
+                value: self.inspect_value(&property, depth - 1)?,
+                name,
+            });
+        }
+
+        Ok(remote)
+    }
+
+    /// Returns the name of the constructor of the object, `"Object"` when it has
+    /// none, or `None` for objects without a prototype.
+    fn class_name(&self, object: &JSObject) -> JSResult<Option<String>> {
+        let class_name = ContextData::shim(self, "className", CLASS_NAME)?;
+        let name = class_name.call(None, &[object.clone().into()])?;
+        if name.is_null() {
+            return Ok(None);
+        }
+
+        let name = name.as_string()?.to_string();
+        match name.is_empty() {
+            true => Ok(Some("Object".to_string())),
+            false => Ok(Some(name)),
+        }
+    }
+
+    /// Returns `String(value)`, which unlike a conversion to a string does not throw
+    /// for symbols.
+    fn describe(&self, value: &JSValue) -> JSResult<String> {
+        let string = self.global_constructor("String")?;
+        Ok(string
+            .call(None, std::slice::from_ref(value))?
+            .as_string()?
+            .to_string())
+    }
+
+    /// Returns `Array.from(value)`, the entries of a map or the values of a set.
+    fn entries(&self, value: &JSValue) -> JSResult<JSArray> {
+        let array: JSObject = self.global_constructor("Array")?.into();
+        let from = array.get_property("from")?.as_object()?;
+        let entries = from.call(Some(&array), std::slice::from_ref(value))?;
+        Ok(JSArray::new(entries.as_object()?))
+    }
+}
+
+/// The options of [`inspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InspectOptions {
+    /// The number of levels of nested objects to expand; objects nested deeper are
+    /// printed as `[Object]` or `[Array]`.
+    pub depth: usize,
+    /// Whether to color the output with ANSI escape codes, for terminals.
+    pub colors: bool,
+    /// The maximum number of items printed for arrays, maps and sets.
+    pub max_items: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            colors: false,
+            max_items: PREVIEW_LIMIT,
+        }
+    }
+}
+
+/// The width above which objects are printed on several lines, as in Node.js.
+const LINE_WIDTH: usize = 72;
+
+/// Formats a value for humans, like `util.inspect` of Node.js or `console.dir`:
+/// strings are quoted, objects show their properties, maps and sets their entries,
+/// and long objects are broken over several lines.
+///
+/// # Arguments
+/// - `value`: The value to format.
+/// - `options`: The depth, colors and item limit.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{inspect, inspect::InspectOptions, JSContext};
+///
+/// let ctx = JSContext::new();
+/// let value = ctx.evaluate_script("({ a: [1, 'two'], b: new Map([[1, true]]) })", None).unwrap();
+/// let text = inspect(&value, InspectOptions::default()).unwrap();
+/// assert_eq!(text, "{ a: [ 1, 'two' ], b: Map(1) { 1 => true } }");
+/// ```
+///
+/// # Errors
+/// If an exception is thrown while reading the value, e.g. by a getter.
+pub fn inspect(value: &JSValue, options: InspectOptions) -> JSResult<String> {
+    let ctx = JSContext::from(value.ctx);
+    let mut printer = Printer {
+        ctx: &ctx,
+        options,
+        parents: Vec::new(),
+    };
+    printer.format(value, 0)
+}
+
+/// The state of [`inspect`]: the objects being printed, to detect cycles.
+struct Printer<'a> {
+    ctx: &'a JSContext,
+    options: InspectOptions,
+    parents: Vec<JSValue>,
+}
+
+impl Printer<'_> {
+    fn format(&mut self, value: &JSValue, level: usize) -> JSResult<String> {
+        let kind = value.match_type();
+        let text = match kind {
+            JSValueKind::Undefined => self.color("undefined", 90),
+            JSValueKind::Null => self.color("null", 1),
+            JSValueKind::Boolean | JSValueKind::Number => {
+                let number = value.as_number();
+                let text = match number {
+                    Ok(number) if number == 0.0 && number.is_sign_negative() => {
+                        "-0".to_string()
+                    }
+                    _ => self.ctx.describe(value)?,
+                };
+                self.color(&text, 33)
+            }
+            JSValueKind::BigInt => {
+                let text = format!("{}n", self.ctx.describe(value)?);
+                self.color(&text, 33)
+            }
+            JSValueKind::String => {
+                let text = quote(&value.as_string()?.to_string());
+                self.color(&text, 32)
+            }
+            JSValueKind::Symbol => {
+                let text = self.ctx.describe(value)?;
+                self.color(&text, 32)
+            }
+            _ => return self.format_object(value, kind, level),
+        };
+        Ok(text)
+    }
+
+    fn format_object(
+        &mut self,
+        value: &JSValue,
+        kind: JSValueKind,
+        level: usize,
+    ) -> JSResult<String> {
+        if self.parents.iter().any(|parent| parent == value) {
+            return Ok(self.color("[Circular]", 36));
+        }
+
+        let object = value.as_object()?;
+        let class_name = self.ctx.class_name(&object)?;
+        let base = match kind {
+            JSValueKind::Function => {
+                let name = object.get_property("name")?.as_string()?.to_string();
+                let text = match (self.ctx.describe(value)?.starts_with("class"), name) {
+                    (true, name) if name.is_empty() => "[class (anonymous)]".to_string(),
+                    (true, name) => format!("[class {}]", name),
+                    (false, name) if name.is_empty() => {
+                        "[Function (anonymous)]".to_string()
+                    }
+                    (false, name) => format!("[Function: {}]", name),
+                };
+                Some(self.color(&text, 36))
+            }
+            JSValueKind::Date => {
+                let text = match object.get_property("toISOString")?.as_object() {
+                    Ok(to_iso) if value.as_number().is_ok_and(f64::is_finite) => {
+                        to_iso.call(Some(&object), &[])?.as_string()?.to_string()
+                    }
+                    _ => "Invalid Date".to_string(),
+                };
+                Some(self.color(&text, 35))
+            }
+            JSValueKind::RegExp => Some(self.color(&self.ctx.describe(value)?, 31)),
+            JSValueKind::Error => {
+                let stack = object.get_property("stack")?;
+                let summary = self.ctx.describe(value)?;
+                match stack.is_string() {
+                    true if !stack.as_string()?.to_string().is_empty() => Some(format!(
+                        "{}\n{}",
+                        summary,
+                        indent_lines(&stack.as_string()?.to_string(), "    ")
+                    )),
+                    _ => Some(format!("[{}]", summary)),
+                }
+            }
+            _ => None,
+        };
+
+        let names: Vec<String> = object
+            .get_property_names()
+            .map(|name| name.to_string())
+            .collect();
+        let is_list = matches!(kind, JSValueKind::Array | JSValueKind::TypedArray(_));
+        let is_collection = matches!(kind, JSValueKind::Map | JSValueKind::Set);
+        let has_contents = !names.is_empty() || is_collection;
+
+        let prefix = match &kind {
+            JSValueKind::Array if class_name.as_deref() == Some("Array") => String::new(),
+            JSValueKind::Array
+            | JSValueKind::TypedArray(_)
+            | JSValueKind::Map
+            | JSValueKind::Set => {
+                let size = match is_collection {
+                    true => object.get_property("size")?.as_number()?,
+                    false => object.get_property("length")?.as_number()?,
+                };
+                format!("{}({}) ", class_name.as_deref().unwrap_or("Object"), size)
+            }
+            _ if base.is_some() => String::new(),
+            _ => match class_name.as_deref() {
+                Some("Object") => String::new(),
+                Some(name) => format!("{} ", name),
+                None => "[Object: null prototype] ".to_string(),
+            },
+        };
+
+        if !has_contents {
+            return Ok(match (base, is_list) {
+                (Some(base), _) => base,
+                (None, true) => format!("{}[]", prefix),
+                (None, false) => format!("{}{{}}", prefix),
+            });
+        }
+
+        if level > self.options.depth {
+            let text = match (&base, is_list) {
+                (Some(base), _) => return Ok(base.clone()),
+                (None, true) => "[Array]".to_string(),
+                (None, false) => {
+                    format!("[{}]", class_name.as_deref().unwrap_or("Object"))
+                }
+            };
+            return Ok(self.color(&text, 36));
+        }
+
+        self.parents.push(value.clone());
+        let items = self.items(value, &object, &kind, &names, level);
+        self.parents.pop();
+        let items = items?;
+
+        let (open, close) = match is_list {
+            true => ("[", "]"),
+            false => ("{", "}"),
+        };
+        let head = match base {
+            Some(base) => format!("{} ", base),
+            None => prefix,
+        };
+        let single = format!("{}{} {} {}", head, open, items.join(", "), close);
+        if visible_length(&single) + level * 2 <= LINE_WIDTH && !single.contains('\n') {
+            return Ok(single);
+        }
+
+        let indentation = "  ".repeat(level + 1);
+        let lines: Vec<String> = items
+            .iter()
+            .map(|item| format!("{}{}", indentation, item))
+            .collect();
+        Ok(format!(
+            "{}{}\n{}\n{}{}",
+            head,
+            open,
+            lines.join(",\n"),
+            "  ".repeat(level),
+            close
+        ))
+    }
+
+    /// Formats the entries and properties of an object.
+    fn items(
+        &mut self,
+        value: &JSValue,
+        object: &JSObject,
+        kind: &JSValueKind,
+        names: &[String],
+        level: usize,
+    ) -> JSResult<Vec<String>> {
+        let limit = self.options.max_items;
+        let mut items = Vec::new();
+        let mut hidden = 0;
+
+        if matches!(kind, JSValueKind::Map | JSValueKind::Set) {
+            let entries = self.ctx.entries(value)?;
+            let length = entries.length()? as usize;
+            for index in 0..length.min(limit) {
+                let entry = entries.get(index as u32)?;
+                let item = match kind {
+                    JSValueKind::Map => {
+                        let pair = JSArray::new(entry.as_object()?);
+                        let key = self.format(&pair.get(0)?, level + 1)?;
+                        let value = self.format(&pair.get(1)?, level + 1)?;
+                        format!("{} => {}", key, value)
+                    }
+                    _ => self.format(&entry, level + 1)?,
+                };
+                items.push(item);
+            }
+            hidden += length.saturating_sub(limit);
+        }
+
+        let is_list = matches!(kind, JSValueKind::Array | JSValueKind::TypedArray(_));
+        let mut shown = 0;
+        for name in names {
+            let is_index = is_list && name.parse::<u32>().is_ok();
+            if is_index && shown == limit {
+                hidden += 1;
+                continue;
+            }
+
+            let value = self.format(&object.get_property(name.as_str())?, level + 1)?;
+            match is_index {
+                true => {
+                    shown += 1;
+                    items.push(value);
+                }
+                false => items.push(format!("{}: {}", property_key(name), value)),
+            }
+        }
+
+        if hidden > 0 {
+            let noun = if hidden == 1 { "item" } else { "items" };
+            items.push(format!("... {} more {}", hidden, noun));
+        }
+        Ok(items)
+    }
+
+    fn color(&self, text: &str, code: u8) -> String {
+        match self.options.colors {
+            true => format!("\u{1b}[{}m{}\u{1b}[0m", code, text),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// Quotes a string with single quotes, escaping quotes, backslashes and newlines.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for character in text.chars() {
+        match character {
+            '\'' => quoted.push_str("\\'"),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Formats a property name as an object key, quoting it unless it is an identifier.
+fn property_key(name: &str) -> String {
+    let mut characters = name.chars();
+    let identifier = characters
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && characters.all(|character| {
+            character.is_alphanumeric() || character == '_' || character == '$'
+        });
+    match identifier {
+        true => name.to_string(),
+        false => quote(name),
+    }
+}
+
+/// Indents every line of the text.
+fn indent_lines(text: &str, indentation: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", indentation, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the length of the text without ANSI escape codes.
+fn visible_length(text: &str) -> usize {
+    let mut length = 0;
+    let mut escape = false;
+    for character in text.chars() {
+        match (escape, character) {
+            (false, '\u{1b}') => escape = true,
+            (true, 'm') => escape = false,
+            (true, _) => {}
+            (false, _) => length += 1,
+        }
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(ctx: &JSContext, script: &str, options: InspectOptions) -> String {
+        let value = ctx.evaluate_script(script, None).unwrap();
+        inspect(&value, options).unwrap()
+    }
+
+    #[test]
+    fn test_inspect() {
+        let ctx = JSContext::new();
+        let options = InspectOptions::default();
+        let cases = [
+            ("undefined", "undefined"),
+            ("-0", "-0"),
+            ("10n", "10n"),
+            ("'it\\'s'", "'it\\'s'"),
+            ("Symbol('id')", "Symbol(id)"),
+            ("[]", "[]"),
+            ("({})", "{}"),
+            ("Object.create(null)", "[Object: null prototype] {}"),
+            ("(function add() {})", "[Function: add]"),
+            ("(class Point {})", "[class Point]"),
+            ("new Date(0)", "1970-01-01T00:00:00.000Z"),
+            ("/a+/g", "/a+/g"),
+            (
+                "({ a: [1, 'two'], 'b-c': null })",
+                "{ a: [ 1, 'two' ], 'b-c': null }",
+            ),
+            ("new Map([[1, true]])", "Map(1) { 1 => true }"),
+            ("new Set(['a'])", "Set(1) { 'a' }"),
+            (
+                "new (class Point { constructor() { this.x = 1 } })",
+                "Point { x: 1 }",
+            ),
+            (
+                "({ a: { b: { c: { d: 1 } } } })",
+                "{ a: { b: { c: [Object] } } }",
+            ),
+            (
+                "const o = { name: 'o' }; o.self = o; o",
+                "{ name: 'o', self: [Circular] }",
+            ),
+        ];
+        for (script, expected) in cases {
+            assert_eq!(print(&ctx, script, options), expected, "{}", script);
+        }
+
+        let options = InspectOptions {
+            max_items: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            print(&ctx, "[1, 2, 3, 4]", options),
+            "[ 1, 2, ... 2 more items ]"
+        );
+
+        let long = print(
+            &ctx,
+            "({ first: 'a'.repeat(40), second: 'b'.repeat(40) })",
+            InspectOptions::default(),
+        );
+        assert_eq!(
+            long,
+            format!(
+                "{{\n  first: '{}',\n  second: '{}'\n}}",
+                "a".repeat(40),
+                "b".repeat(40)
+            )
+        );
+
+        let options = InspectOptions {
+            colors: true,
+            ..Default::default()
+        };
+        assert_eq!(print(&ctx, "[1]", options), "[ \u{1b}[33m1\u{1b}[0m ]");
+    }
+
+    #[test]
+    fn test_inspect_value() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script(
+                "({ name: 'jsc', list: [1, 2], tags: new Set(['a']), \
+                   scores: new Map([['x', 1]]), none: null, id: Symbol('id') })",
+                None,
+            )
+            .unwrap();
+
+        let shallow = ctx.inspect_value(&value, 0).unwrap();
+        assert_eq!(shallow.kind, "object");
+        assert_eq!(shallow.description, "Object");
+        assert!(shallow.properties.is_empty());
+
+        let preview = ctx.inspect_value(&value, 2).unwrap();
+        let property = |name: &str| {
+            preview
+                .properties
+                .iter()
+                .find(|property| property.name == name)
+                .map(|property| property.value.clone())
+                .unwrap()
+        };
+        assert_eq!(property("name").description, "jsc");
+        assert_eq!(property("list").subtype, Some("array"));
+        assert_eq!(property("list").description, "Array(2)");
+        assert_eq!(property("list").properties.len(), 2);
+        assert_eq!(property("tags").description, "Set(1)");
+        assert_eq!(property("tags").entries[0].value.description, "a");
+        let scores = property("scores");
+        assert_eq!(scores.entries[0].key.as_ref().unwrap().description, "x");
+        assert_eq!(scores.entries[0].value.description, "1");
+        assert_eq!(property("none").subtype, Some("null"));
+        assert_eq!(property("id").description, "Symbol(id)");
+    }
+
+    #[test]
+    fn test_class_name_runs_no_script() {
+        let ctx = JSContext::new();
+        let reported = std::rc::Rc::new(std::cell::Cell::new(0));
+        let count = reported.clone();
+        ctx.set_exception_hook(move |_error, _ctx| count.set(count.get() + 1));
+
+        let value = ctx
+            .evaluate_script(
+                "globalThis.reads = 0; \
+                 [Object.create(null), \
+                  new Proxy({}, { get() { reads++; throw new Error('trap'); } }), \
+                  new (class Point { static get name() { reads++; return 'x'; } })]",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let describe = |index: &str| {
+            let object = value.get_property(index).unwrap();
+            ctx.inspect_value(&object, 0).unwrap().description
+        };
+        assert_eq!(describe("0"), "Object");
+        assert_eq!(describe("1"), "Object");
+        assert_eq!(describe("2"), "Object");
+
+        let reads = ctx.evaluate_script("reads", None).unwrap();
+        assert_eq!(reads.as_number().unwrap(), 0.0);
+        assert_eq!(reported.get(), 0);
+        assert_eq!(
+            print(&ctx, "Object.create(null)", InspectOptions::default()),
+            "[Object: null prototype] {}"
+        );
+    }
+}
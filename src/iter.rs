@@ -0,0 +1,320 @@
+//! Iteration of JavaScript iterables and array-like objects from Rust, so host
+//! functions can accept arrays, `arguments` objects, sets or generators alike.
+
+use crate::{
+    builtins::named_error, context_data::ContextData, JSContext, JSObject, JSResult,
+    JSValue,
+};
+
+/// Returns `true` if `value[Symbol[name]]` is a function, without calling it. The
+/// symbols are read once, when the shim is evaluated.
+const HAS_ITERATOR: &str = r#"(() => {
+    const symbols = { __proto__: null, iterator: Symbol.iterator, asyncIterator: Symbol.asyncIterator };
+    return (value, name) =>
+        value !== null && value !== undefined && typeof value[symbols[name]] === "function";
+})()"#;
+
+/// Returns `value[Symbol.iterator]()` and the `next` method of the iterator, or
+/// `null` if the value is not iterable. The method is read only once.
+const GET_ITERATOR: &str = r#"(() => {
+    const symbol = Symbol.iterator;
+    return (value) => {
+        const method = value === null || value === undefined ? undefined : value[symbol];
+        if (typeof method !== "function") {
+            return null;
+        }
+        const iterator = method.call(value);
+        if ((typeof iterator !== "object" && typeof iterator !== "function") || iterator === null) {
+            throw new TypeError("The iterator is not an object");
+        }
+        return [iterator, iterator.next];
+    };
+})()"#;
+
+enum Source {
+    Iterator { iterator: JSObject, next: JSObject },
+    ArrayLike { object: JSObject, length: u32 },
+}
+
+/// An iterator over the values of a JavaScript iterable or array-like object,
+/// created with [`JSValue::try_iter`].
+///
+/// Each item is the next value, or the exception thrown while getting it, after
+/// which the iteration ends. Dropping the iterator before the end closes the
+/// JavaScript iterator, calling its `return` method like a `break` in a `for...of`
+/// loop.
+pub struct JSValueIter {
+    source: Source,
+    index: u32,
+    done: bool,
+}
+
+impl Iterator for JSValueIter {
+    type Item = JSResult<JSValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = match &self.source {
+            Source::Iterator { iterator, next } => step(iterator, next).transpose(),
+            Source::ArrayLike { object, length } => {
+                (self.index < *length).then(|| object.get_property_at_index(self.index))
+            }
+        };
+        self.index += 1;
+        if !matches!(item, Some(Ok(_))) {
+            self.done = true;
+        }
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.source {
+            _ if self.done => (0, Some(0)),
+            Source::ArrayLike { length, .. } => {
+                let remaining = length.saturating_sub(self.index) as usize;
+                (remaining, Some(remaining))
+            }
+            Source::Iterator { .. } => (0, None),
+        }
+    }
+}
+
+impl Drop for JSValueIter {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        if let Source::Iterator { iterator, .. } = &self.source {
+            let close = iterator
+                .get_property("return")
+                .and_then(|close| close.as_object())
+                .ok()
+                .filter(JSObject::is_function);
+            if let Some(close) = close {
+                let _ = close.call(Some(iterator), &[]);
+            }
+        }
+    }
+}
+
+/// Calls `next` on the iterator and returns the value, or `None` when it is done.
+fn step(iterator: &JSObject, next: &JSObject) -> JSResult<Option<JSValue>> {
+    let result = next.call(Some(iterator), &[])?;
+    if !result.is_object() {
+        let ctx = JSContext::from(iterator.value.ctx);
+        return Err(named_error(
+            &ctx,
+            "TypeError",
+            "The iterator result is not an object",
+        ));
+    }
+
+    let result = result.as_object()?;
+    if result.get_property("done")?.as_boolean() {
+        return Ok(None);
+    }
+    result.get_property("value").map(Some)
+}
+
+impl JSValue {
+    /// Checks if the value is iterable, i.e. it has a `Symbol.iterator` method, like
+    /// arrays, strings, maps, sets and generators. The method is not called.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("new Set([1, 2])", None).unwrap();
+    /// assert!(value.is_iterable());
+    /// ```
+    ///
+    /// # Returns
+    /// A boolean value, `false` if reading the method throws.
+    pub fn is_iterable(&self) -> bool {
+        self.has_iterator("iterator")
+    }
+
+    /// Checks if the value is async iterable, i.e. it has a `Symbol.asyncIterator`
+    /// method, like async generators. The method is not called.
+    ///
+    /// # Returns
+    /// A boolean value, `false` if reading the method throws.
+    pub fn is_async_iterable(&self) -> bool {
+        self.has_iterator("asyncIterator")
+    }
+
+    fn has_iterator(&self, name: &str) -> bool {
+        let ctx = JSContext::from(self.ctx);
+        ContextData::shim(&ctx, "hasIterator", HAS_ITERATOR)
+            .and_then(|check| {
+                check.call(None, &[self.clone(), JSValue::string(&ctx, name)])
+            })
+            .is_ok_and(|result| result.as_boolean())
+    }
+
+    /// Iterates the value from Rust, whether it is iterable, see
+    /// [`JSValue::is_iterable`], or array-like, see [`JSValue::is_array_like`].
+    /// Iterables are iterated with their iterator, like `for...of`, and other
+    /// array-like objects by index, from `0` to their `length` at the start.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let values = ctx.evaluate_script("(function* () { yield 1; yield 2; })()", None).unwrap();
+    /// let sum: f64 = values
+    ///     .try_iter()
+    ///     .unwrap()
+    ///     .map(|value| value.and_then(|value| value.as_number()))
+    ///     .sum::<JSResult<f64>>()
+    ///     .unwrap();
+    /// assert_eq!(sum, 3.0);
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the value is neither iterable nor array-like, or the
+    /// exception thrown while getting its iterator. Async iterables cannot be
+    /// iterated synchronously.
+    pub fn try_iter(&self) -> JSResult<JSValueIter> {
+        let ctx = JSContext::from(self.ctx);
+        let get_iterator = ContextData::shim(&ctx, "getIterator", GET_ITERATOR)?;
+        let pair = get_iterator.call(None, std::slice::from_ref(self))?;
+        let source = if !pair.is_null() {
+            let pair = pair.as_object()?;
+            Source::Iterator {
+                iterator: pair.get_property_at_index(0)?.as_object()?,
+                next: pair.get_property_at_index(1)?.as_object()?,
+            }
+        } else if self.is_array_like() {
+            let object = self.as_object()?;
+            let length = object.get_property("length")?.as_number()?;
+            Source::ArrayLike {
+                object,
+                length: length.min(u32::MAX as f64) as u32,
+            }
+        } else {
+            let message = format!(
+                "Expected an iterable or array-like value, got {}",
+                self.typeof_str()
+            );
+            return Err(named_error(&ctx, "TypeError", &message));
+        };
+
+        Ok(JSValueIter {
+            source,
+            index: 0,
+            done: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterable_detection() {
+        let ctx = JSContext::new();
+        let check = |script: &str| {
+            let value = ctx.evaluate_script(script, None).unwrap();
+            (
+                value.is_array_like(),
+                value.is_iterable(),
+                value.is_async_iterable(),
+                value.is_typed_array(),
+            )
+        };
+
+        assert_eq!(check("[1, 2]"), (true, true, false, false));
+        assert_eq!(check("'abc'"), (false, true, false, false));
+        assert_eq!(
+            check("(function () { return arguments; })(1)"),
+            (true, true, false, false)
+        );
+        assert_eq!(
+            check("({ length: 1, 0: 'a' })"),
+            (true, false, false, false)
+        );
+        assert_eq!(check("({ length: -1 })"), (false, false, false, false));
+        assert_eq!(check("new Uint8Array(2)"), (true, true, false, true));
+        assert_eq!(check("new ArrayBuffer(2)"), (false, false, false, false));
+        assert_eq!(check("new Map()"), (false, true, false, false));
+        assert_eq!(
+            check("(async function* () {})()"),
+            (false, false, true, false)
+        );
+        assert_eq!(check("(function (a, b) {})"), (false, false, false, false));
+        assert_eq!(check("null"), (false, false, false, false));
+    }
+
+    #[test]
+    fn test_try_iter() {
+        let ctx = JSContext::new();
+        let collect = |script: &str| -> JSResult<Vec<String>> {
+            ctx.evaluate_script(script, None)?
+                .try_iter()?
+                .map(|value| Ok(value?.as_string()?.to_string()))
+                .collect()
+        };
+
+        assert_eq!(collect("['a', 'b']").unwrap(), ["a", "b"]);
+        assert_eq!(collect("'hé'").unwrap(), ["h", "é"]);
+        assert_eq!(collect("new Set(['x', 'y'])").unwrap(), ["x", "y"]);
+        assert_eq!(
+            collect("({ length: 2, 0: 'p', 1: 'q' })").unwrap(),
+            ["p", "q"]
+        );
+        assert_eq!(
+            collect("(function* () { yield 'g'; yield 'h'; })()").unwrap(),
+            ["g", "h"]
+        );
+
+        let error =
+            collect("(function* () { yield 'a'; throw new RangeError('stop'); })()")
+                .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        let error = collect("42").unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        let generator = ctx
+            .evaluate_script(
+                "globalThis.closed = false;
+                 (function* () { try { yield 1; yield 2; } finally { closed = true; } })()",
+                None,
+            )
+            .unwrap();
+        let mut values = generator.try_iter().unwrap();
+        assert_eq!(values.next().unwrap().unwrap().as_number().unwrap(), 1.0);
+        drop(values);
+        let closed = ctx.evaluate_script("closed", None).unwrap();
+        assert!(closed.as_boolean());
+    }
+    #[test]
+    fn test_try_iter_reads_iterator_once() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script(
+                "globalThis.reads = 0;
+                 ({ get [Symbol.iterator]() { reads++; return [].values.bind(['a']); } })",
+                None,
+            )
+            .unwrap();
+        let values: Vec<JSValue> =
+            value.try_iter().unwrap().collect::<JSResult<_>>().unwrap();
+        assert_eq!(values.len(), 1);
+        let reads = ctx.evaluate_script("reads", None).unwrap();
+        assert_eq!(reads.as_number().unwrap(), 1.0);
+
+        // The shims keep the symbols they were evaluated with.
+        assert!(value.is_iterable());
+        ctx.evaluate_script("globalThis.Symbol = undefined", None)
+            .unwrap();
+        assert!(value.is_iterable());
+        assert_eq!(value.try_iter().unwrap().count(), 1);
+    }
+}
@@ -20,19 +20,46 @@ use rust_jsc_sys::{
     JSTypedArrayType_kJSTypedArrayTypeUint8ClampedArray, JSValueRef,
 };
 
+pub mod args;
 pub mod array;
+mod audit;
+pub mod batch;
+pub mod budget;
+pub mod buffer;
+pub mod builtins;
+pub mod bytes;
+pub mod cancellation;
 pub mod class;
 pub mod context;
+mod context_data;
+pub mod convert;
 pub mod date;
+pub mod diff;
 pub mod error;
 pub mod function;
+pub mod inspect;
+pub mod iter;
+pub mod loader;
+pub mod locale;
+pub mod memory;
 pub mod object;
+pub mod patch;
+pub mod pointer;
+pub mod pool;
 pub mod promise;
+pub mod recursion;
 pub mod reg_exp;
+pub mod script;
 pub mod string;
+pub mod surface;
+pub mod template;
+pub mod testing;
+pub mod trace;
 pub mod typed_array;
 pub mod value;
 
+pub use diff::diff;
+pub use inspect::inspect;
 pub use rust_jsc_macros::*;
 
 #[doc(hidden)]
@@ -58,6 +85,8 @@ pub struct JSClass {
     // pub(crate) ctx: JSContextRef,
     pub(crate) inner: JSClassRef,
     pub(crate) name: String,
+    /// The slot of the live object counter of the class, if it has one.
+    pub(crate) counter: Option<usize>,
 }
 
 /// A JavaScript object.
@@ -73,6 +102,13 @@ pub struct JSFunction {
     pub(crate) object: JSObject,
 }
 
+/// A JavaScript constructor object, created with [`JSFunction::constructor`]. It
+/// can only be called with `new`, see [`JSConstructor::construct`].
+#[derive(Clone)]
+pub struct JSConstructor {
+    pub(crate) object: JSObject,
+}
+
 /// A JavaScript date object.
 pub struct JSDate {
     pub(crate) object: JSObject,
@@ -113,11 +149,32 @@ pub struct JSPromiseResolvingFunctions {
     reject: JSObject,
 }
 
+/// The kind of a promise rejection event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromiseRejectionKind {
+    /// A promise was rejected and no handler was attached to it.
+    Unhandled,
+    /// A handler was attached to a promise that was previously reported as unhandled.
+    Handled,
+}
+
+/// A promise rejection event, passed to the handler registered with
+/// [`JSContext::on_promise_rejection`].
+#[derive(Debug, Clone)]
+pub struct PromiseRejectionEvent {
+    pub kind: PromiseRejectionKind,
+    pub promise: JSObject,
+    pub reason: JSValue,
+}
+
 /// A JavaScript value.
 #[derive(Debug, Clone)]
 pub struct JSValue {
     pub(crate) inner: JSValueRef,
     pub(crate) ctx: JSContextRef,
+    /// The context generation of the value, checked with the `value-audit` feature.
+    #[cfg(feature = "value-audit")]
+    pub(crate) tag: audit::Tag,
 }
 
 /// A JavaScript class attribute.
@@ -168,6 +225,43 @@ impl JSValueType {
     }
 }
 
+/// A JavaScript value kind, distinguishing the built-in object types.
+/// Returned by [`JSValue::match_type`].
+#[derive(Debug, PartialEq)]
+pub enum JSValueKind {
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    BigInt,
+    String,
+    Symbol,
+    Function,
+    Array,
+    Date,
+    RegExp,
+    Error,
+    Promise,
+    Map,
+    Set,
+    ArrayBuffer,
+    TypedArray(JSTypedArrayType),
+    /// Any other object.
+    Object,
+}
+
+/// The preferred type of a conversion to a primitive value, passed to
+/// `Symbol.toPrimitive`, see [`JSValue::to_primitive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveHint {
+    /// No preference, as for `+` and `==`; `valueOf` is tried first.
+    Default,
+    /// A number is preferred, as for arithmetic; `valueOf` is tried first.
+    Number,
+    /// A string is preferred, as for template literals; `toString` is tried first.
+    String,
+}
+
 /// A JavaScript typed array type.
 #[derive(Debug, PartialEq)]
 pub enum JSTypedArrayType {
@@ -257,11 +351,15 @@ pub struct JSString {
     pub(crate) inner: JSStringRef,
 }
 
-/// A JavaScript string reference.
-/// This struct is used to retain a reference to a JavaScript string.
-/// It won't release the string when it goes out of scope.
-/// To release the string, use the `release` method.
-pub struct JSStringRetain(JSStringRef);
+/// A JavaScript string reference owned by the holder.
+/// It won't release the string when it goes out of scope: hand the reference over to
+/// the engine with `leak_to_engine`, e.g. as the result of a module loader callback,
+/// or drop it with `release`.
+pub struct JSRetainedString(JSStringRef);
+
+/// The former name of [`JSRetainedString`].
+#[deprecated(note = "renamed to `JSRetainedString`")]
+pub type JSStringRetain = JSRetainedString;
 
 pub type JSResult<T> = Result<T, JSError>;
 
@@ -304,8 +402,16 @@ impl Default for PropertyDescriptor {
 }
 
 // A builder for constructing a set of JavaScript property attributes
+///
+/// With a value or accessor functions, [`PropertyDescriptorBuilder::try_build`] checks
+/// the combination and returns a [`PropertyDefinition`] for
+/// [`JSObject::define_property`].
 pub struct PropertyDescriptorBuilder {
     attributes: JSPropertyAttributes,
+    writable: Option<bool>,
+    value: Option<JSValue>,
+    getter: Option<JSObject>,
+    setter: Option<JSObject>,
 }
 
 impl PropertyDescriptorBuilder {
@@ -313,10 +419,15 @@ impl PropertyDescriptorBuilder {
     pub fn new() -> Self {
         Self {
             attributes: kJSPropertyAttributeNone,
+            writable: None,
+            value: None,
+            getter: None,
+            setter: None,
         }
     }
 
-    pub fn writable(self, value: bool) -> Self {
+    pub fn writable(mut self, value: bool) -> Self {
+        self.writable = Some(value);
         self.set_attribute(kJSPropertyAttributeReadOnly, value)
     }
 
@@ -328,6 +439,26 @@ impl PropertyDescriptorBuilder {
         self.set_attribute(kJSPropertyAttributeDontDelete, value)
     }
 
+    /// Sets the value of a data property.
+    pub fn value(mut self, value: &JSValue) -> Self {
+        self.value = Some(value.clone());
+        self
+    }
+
+    /// Sets the function called when the property is read, making it an accessor
+    /// property.
+    pub fn getter(mut self, getter: &JSObject) -> Self {
+        self.getter = Some(getter.clone());
+        self
+    }
+
+    /// Sets the function called when the property is written, making it an accessor
+    /// property.
+    pub fn setter(mut self, setter: &JSObject) -> Self {
+        self.setter = Some(setter.clone());
+        self
+    }
+
     // disable specific attributes could be implemented
     fn set_attribute(mut self, attribute: JSPropertyAttributes, value: bool) -> Self {
         if value {
@@ -344,8 +475,124 @@ impl PropertyDescriptorBuilder {
             attributes: self.attributes,
         }
     }
+
+    /// Builds a complete property definition, with its value or accessor functions,
+    /// for [`JSObject::define_property`].
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = JSValue::number(&ctx, 1.0);
+    /// let getter = ctx.evaluate_script("() => 2", None).unwrap().as_object().unwrap();
+    ///
+    /// let error = PropertyDescriptorBuilder::new()
+    ///     .value(&value)
+    ///     .getter(&getter)
+    ///     .try_build()
+    ///     .unwrap_err();
+    /// assert_eq!(error, DescriptorError::ValueWithAccessor);
+    /// ```
+    ///
+    /// # Errors
+    /// A [`DescriptorError`] if the property has both a value or `writable`, and
+    /// accessor functions, or if an accessor is not a function.
+    pub fn try_build(self) -> Result<PropertyDefinition, DescriptorError> {
+        let descriptor = PropertyDescriptor {
+            attributes: self.attributes,
+        };
+        if self.getter.is_none() && self.setter.is_none() {
+            return Ok(PropertyDefinition {
+                descriptor,
+                kind: PropertyKind::Data(self.value),
+            });
+        }
+
+        if self.value.is_some() {
+            return Err(DescriptorError::ValueWithAccessor);
+        }
+        if self.writable.is_some() {
+            return Err(DescriptorError::WritableWithAccessor);
+        }
+        let callable = |function: &Option<JSObject>| {
+            function
+                .as_ref()
+                .is_none_or(|function| function.is_function())
+        };
+        if !callable(&self.getter) || !callable(&self.setter) {
+            return Err(DescriptorError::AccessorNotCallable);
+        }
+
+        Ok(PropertyDefinition {
+            descriptor,
+            kind: PropertyKind::Accessor {
+                getter: self.getter,
+                setter: self.setter,
+            },
+        })
+    }
+}
+
+/// A property built by [`PropertyDescriptorBuilder::try_build`]: its attributes and
+/// either its value or its accessor functions.
+#[derive(Debug)]
+pub struct PropertyDefinition {
+    descriptor: PropertyDescriptor,
+    kind: PropertyKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum PropertyKind {
+    Data(Option<JSValue>),
+    Accessor {
+        getter: Option<JSObject>,
+        setter: Option<JSObject>,
+    },
+}
+
+impl PropertyDefinition {
+    /// Returns the attributes of the property.
+    pub fn descriptor(&self) -> PropertyDescriptor {
+        self.descriptor
+    }
+
+    /// Returns `true` if the property has a getter or a setter.
+    pub fn is_accessor(&self) -> bool {
+        matches!(self.kind, PropertyKind::Accessor { .. })
+    }
 }
 
+/// Why [`PropertyDescriptorBuilder::try_build`] rejected a property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorError {
+    /// The property has both a value and a getter or a setter.
+    ValueWithAccessor,
+    /// The property has both `writable` and a getter or a setter.
+    WritableWithAccessor,
+    /// The getter or the setter is not a function.
+    AccessorNotCallable,
+}
+
+impl std::fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::ValueWithAccessor => {
+                "Invalid property descriptor. Cannot both specify accessors and a value"
+            }
+            Self::WritableWithAccessor => {
+                "Invalid property descriptor. Cannot both specify accessors and writable"
+            }
+            Self::AccessorNotCallable => {
+                "Invalid property descriptor. Getters and setters must be functions"
+            }
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for DescriptorError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -388,4 +635,50 @@ mod tests {
         assert_eq!(descriptor.is_enumerable(), true);
         assert_eq!(descriptor.is_configurable(), true);
     }
+
+    #[test]
+    fn test_property_descriptor_builder_try_build() {
+        let ctx = JSContext::new();
+        let value = JSValue::number(&ctx, 1.0);
+        let getter = ctx
+            .evaluate_script("() => 2", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let definition = PropertyDescriptorBuilder::new()
+            .value(&value)
+            .writable(false)
+            .try_build()
+            .unwrap();
+        assert!(!definition.is_accessor());
+        assert!(!definition.descriptor().is_writable());
+
+        let definition = PropertyDescriptorBuilder::new()
+            .getter(&getter)
+            .enumerable(false)
+            .try_build()
+            .unwrap();
+        assert!(definition.is_accessor());
+        assert!(!definition.descriptor().is_enumerable());
+
+        let error = PropertyDescriptorBuilder::new()
+            .value(&value)
+            .getter(&getter)
+            .try_build()
+            .unwrap_err();
+        assert_eq!(error, DescriptorError::ValueWithAccessor);
+        let error = PropertyDescriptorBuilder::new()
+            .setter(&getter)
+            .writable(true)
+            .try_build()
+            .unwrap_err();
+        assert_eq!(error, DescriptorError::WritableWithAccessor);
+        let error = PropertyDescriptorBuilder::new()
+            .getter(&JSObject::new(&ctx))
+            .try_build()
+            .unwrap_err();
+        assert_eq!(error, DescriptorError::AccessorNotCallable);
+        assert!(error.to_string().starts_with("Invalid property descriptor"));
+    }
 }
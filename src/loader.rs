@@ -0,0 +1,2206 @@
+//! A module loader that reads modules from the filesystem, optionally confined to
+//! a root directory and an allowlist, and from host handlers for URL schemes.
+//!
+//! The engine calls the loader through the C callbacks of [`JSAPIModuleLoader`],
+//! which cannot throw: a specifier that is rejected while resolving gets a key whose
+//! source throws the error when the module is evaluated.
+//!
+//! Files are read through a [`ModuleFs`], so modules can also be served from memory
+//! or an archive; see [`JSContext::set_module_fs`].
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    ops::Deref,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use rust_jsc_sys::{
+    JSAPIModuleLoader, JSContextRef, JSObjectRef, JSStringCreateWithCharacters,
+    JSStringRef, JSValueMakeUndefined, JSValueRef,
+};
+
+use crate::{
+    context::CustomModuleLoader, JSContext, JSError, JSObject, JSResult,
+    JSRetainedString, JSString, JSValue,
+};
+
+/// The prefix of the keys of specifiers that failed to resolve.
+const FAILURE_PREFIX: &str = "rust-jsc-loader-error:";
+
+/// The prefix of the keys of specifiers that a `#[module_resolve]` function failed to
+/// resolve, followed by the name and message of the error.
+const RESOLVE_ERROR_PREFIX: &str = "rust-jsc-resolve-error:";
+
+type SchemeHandler = dyn Fn(&JSContext, &str) -> JSResult<String>;
+
+/// The filesystem the module loader reads modules from.
+///
+/// Paths passed to the methods are absolute.
+pub trait ModuleFs: 'static {
+    /// Reads the source of the module at the path.
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Returns true if a file or directory exists at the path.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Returns true if a directory exists at the path.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Returns the canonical form of the path, with `.`, `..` and any links resolved.
+    /// The loader checks the canonical path against the module root.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// The filesystem of the operating system, the default [`ModuleFs`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFs;
+
+impl ModuleFs for OsFs {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+}
+
+/// A [`ModuleFs`] that serves modules from memory, for tests and bundlers.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{loader::MemoryFs, JSContext};
+///
+/// let fs = MemoryFs::new()
+///     .file("/app/main.js", "import './util.js';")
+///     .file("/app/util.js", "globalThis.ready = true;");
+///
+/// let ctx = JSContext::new();
+/// ctx.set_module_fs(fs);
+/// ctx.evaluate_module("/app/main.js").unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    files: HashMap<PathBuf, String>,
+}
+
+impl MemoryFs {
+    /// Creates an empty filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, replacing any file at the same path.
+    ///
+    /// # Arguments
+    /// - `path`: The absolute path of the file.
+    /// - `source`: The contents of the file.
+    pub fn file(mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> Self {
+        self.insert(path, source);
+        self
+    }
+
+    /// Adds a file, replacing any file at the same path.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) {
+        self.files.insert(normalize(&path.into()), source.into());
+    }
+}
+
+impl ModuleFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        self.files
+            .get(&normalize(path))
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.files.keys().any(|file| file.starts_with(&path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.files
+            .keys()
+            .any(|file| *file != path && file.starts_with(&path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if !self.exists(path) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+
+        Ok(normalize(path))
+    }
+}
+
+/// Removes the `.` and `..` components of the path without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// The configuration of the loader of a context, stored as a context extension.
+#[derive(Default)]
+pub(crate) struct LoaderState {
+    root: RefCell<Option<PathBuf>>,
+    allowed: RefCell<Vec<PathBuf>>,
+    /// The errors of the failed specifiers, by key: the error name and message.
+    failures: RefCell<HashMap<String, (String, String)>>,
+    next_failure: Cell<u64>,
+    /// The handlers of [`JSContext::register_module_scheme`], by scheme without the colon.
+    schemes: RefCell<HashMap<String, Rc<SchemeHandler>>>,
+    /// The filesystem of [`JSContext::set_module_fs`], or `None` for [`OsFs`].
+    fs: RefCell<Option<Rc<dyn ModuleFs>>>,
+    /// The key of the module that first imported each module, by key.
+    importers: RefCell<HashMap<String, String>>,
+    /// The static imports of each module the loader resolved or fetched, by key.
+    imports: RefCell<HashMap<String, ModuleImports>>,
+    /// The data files of [`JSContext::set_data_modules`].
+    data_modules: RefCell<DataModules>,
+    /// The modules the crate provides, such as [`crate::testing::TEST_MODULE`], by
+    /// key: their source.
+    builtins: RefCell<HashMap<String, String>>,
+    /// The last specifier that failed to resolve or module that failed to load.
+    failure: RefCell<Option<ModuleFailure>>,
+}
+
+/// The imports of a module, recorded by the loader to tell which import cycle a
+/// `ReferenceError` comes from without reading the module again.
+#[derive(Default)]
+struct ModuleImports {
+    /// The key each specifier resolved to, as the engine resolves them.
+    resolved: Vec<(String, String)>,
+    /// The names each static import binds, by specifier, scanned from the source.
+    bindings: Vec<(String, Vec<String>)>,
+}
+
+/// A failure recorded by the loader, to tell which module an error comes from.
+struct ModuleFailure {
+    phase: ModulePhase,
+    module: String,
+    referrer: Option<String>,
+    message: String,
+}
+
+impl LoaderState {
+    /// Returns the loader state of the context, installing the loader on first use.
+    pub(crate) fn get(ctx: &JSContext) -> Rc<LoaderState> {
+        if let Some(state) = ctx.get_extension::<LoaderState>() {
+            return state;
+        }
+
+        ctx.set_extension(LoaderState::default());
+        ctx.install_module_loader(JSAPIModuleLoader {
+            disableBuiltinFileSystemLoader: true,
+            moduleLoaderResolve: Some(loader_resolve),
+            moduleLoaderEvaluate: Some(loader_evaluate),
+            moduleLoaderFetch: Some(loader_fetch),
+            moduleLoaderCreateImportMetaProperties: Some(loader_import_meta),
+        });
+        ctx.remove_extension::<CustomModuleLoader>();
+        ctx.get_extension::<LoaderState>()
+            .expect("Loader state must be set")
+    }
+
+    /// Serves the source under the bare key, ahead of virtual modules.
+    pub(crate) fn register_builtin(&self, key: &str, source: &str) {
+        self.builtins
+            .borrow_mut()
+            .insert(key.to_string(), source.to_string());
+    }
+
+    fn is_allowed(&self, path: &Path) -> bool {
+        let root = self.root.borrow();
+        if root.is_none() && self.allowed.borrow().is_empty() {
+            return true;
+        }
+
+        root.iter()
+            .chain(self.allowed.borrow().iter())
+            .any(|directory| path.starts_with(directory))
+    }
+
+    fn fail(
+        &self,
+        specifier: &str,
+        referrer: Option<&str>,
+        name: impl Into<String>,
+        message: String,
+    ) -> String {
+        self.failure.replace(Some(ModuleFailure {
+            phase: ModulePhase::Resolve,
+            module: specifier.to_string(),
+            referrer: referrer.map(str::to_string),
+            message: message.clone(),
+        }));
+
+        let id = self.next_failure.get();
+        self.next_failure.set(id + 1);
+
+        let key = format!("{}{}", FAILURE_PREFIX, id);
+        self.failures
+            .borrow_mut()
+            .insert(key.clone(), (name.into(), message));
+        key
+    }
+
+    /// Removes the error of a failed specifier, once it is thrown.
+    fn take_failure(&self, key: &str) -> Option<(String, String)> {
+        self.failures.borrow_mut().remove(key)
+    }
+
+    /// Records that the specifier of the referrer resolved to the key.
+    fn record_import(&self, referrer: &str, specifier: &str, key: &str) {
+        let referrer = referrer.strip_prefix("file://").unwrap_or(referrer);
+        if referrer.is_empty() || key.starts_with(FAILURE_PREFIX) {
+            return;
+        }
+
+        let mut imports = self.imports.borrow_mut();
+        let resolved = &mut imports.entry(referrer.to_string()).or_default().resolved;
+        if !resolved.iter().any(|(known, _)| known == specifier) {
+            resolved.push((specifier.to_string(), key.to_string()));
+        }
+    }
+
+    /// Returns the import cycle a `ReferenceError` thrown by the module comes from:
+    /// a cycle through the module in which a module uses a binding of the next one
+    /// before it is initialized. Other reference errors, such as a misspelled
+    /// variable, have no cycle.
+    fn import_cycle(&self, module: &str, error: &JSError) -> Option<ModuleGraphError> {
+        if !error.name().is_ok_and(|name| name == "ReferenceError") {
+            return None;
+        }
+        let message = error.message().ok()?.to_string();
+        if !message.contains("before initialization")
+            && !message.contains("uninitialized")
+        {
+            return None;
+        }
+        // The binding is quoted when the engine names it.
+        let binding = message.split('\'').nth(1);
+
+        let imports = self.imports.borrow();
+        let graph = ModuleGraph {
+            entry: module.to_string(),
+            modules: imports
+                .iter()
+                .map(|(key, imports)| {
+                    let dependencies =
+                        imports.resolved.iter().map(|(_, key)| key.clone());
+                    (key.clone(), dependencies.collect())
+                })
+                .collect(),
+        };
+        // Whether the importer binds the name from the imported module.
+        let binds = |importer: &str, imported: &str, name: &str| {
+            imports.get(importer).is_some_and(|imports| {
+                imports.bindings.iter().any(|(specifier, names)| {
+                    names.iter().any(|bound| bound == name)
+                        && imports
+                            .resolved
+                            .iter()
+                            .any(|(known, key)| known == specifier && key == imported)
+                })
+            })
+        };
+
+        let cycle = graph.cycles().into_iter().find(|cycle| match binding {
+            Some(name) => cycle.windows(2).any(|edge| binds(&edge[0], &edge[1], name)),
+            None => cycle.iter().any(|key| key == module),
+        })?;
+        Some(ModuleGraphError { cycle })
+    }
+
+    fn fs(&self) -> Rc<dyn ModuleFs> {
+        match &*self.fs.borrow() {
+            Some(fs) => fs.clone(),
+            None => Rc::new(OsFs),
+        }
+    }
+
+    /// Returns the handler of the scheme of the specifier, if one is registered.
+    fn scheme_handler(&self, specifier: &str) -> Option<Rc<SchemeHandler>> {
+        let (scheme, _) = specifier.split_once(':')?;
+        self.schemes.borrow().get(scheme).cloned()
+    }
+
+    /// Resolves a specifier to the key of the module.
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> String {
+        let specifier = specifier.strip_prefix("file://").unwrap_or(specifier);
+        // Classic scripts refer to imports by their source URL, which may be a file
+        // URL, or empty when the script was evaluated without one.
+        let referrer = referrer
+            .map(|referrer| referrer.strip_prefix("file://").unwrap_or(referrer))
+            .filter(|referrer| !referrer.is_empty());
+        let relative = specifier.starts_with("./") || specifier.starts_with("../");
+        if self.scheme_handler(specifier).is_some() {
+            return specifier.to_string();
+        }
+        if let Some(referrer) = referrer {
+            if relative && self.scheme_handler(referrer).is_some() {
+                return join_url(referrer, specifier);
+            }
+        }
+
+        if !relative && !Path::new(specifier).is_absolute() {
+            // Bare specifiers are left to virtual modules.
+            return specifier.to_string();
+        }
+
+        let base = match referrer.map(Path::new) {
+            Some(referrer) if relative && referrer.is_absolute() => {
+                referrer.parent().map(Path::to_path_buf)
+            }
+            _ => self.root.borrow().clone(),
+        };
+        let path = match base {
+            Some(base) => base.join(specifier),
+            None => PathBuf::from(specifier),
+        };
+
+        // Extensions may be left out of specifiers.
+        let fs = self.fs();
+        let path = match path.extension() {
+            None if !fs.exists(&path) => path.with_extension("js"),
+            _ => path,
+        };
+
+        // Canonical paths have no `..` or symbolic links left to escape the root with.
+        let path = match fs.canonicalize(&path) {
+            Ok(path) => path,
+            Err(error) => {
+                let message = match referrer {
+                    Some(referrer) => format!(
+                        "Cannot find module '{}' imported from '{}': {}",
+                        specifier, referrer, error
+                    ),
+                    None => format!("Cannot find module '{}': {}", specifier, error),
+                };
+                return self.fail(specifier, referrer, "TypeError", message);
+            }
+        };
+
+        if !self.is_allowed(&path) {
+            let message = format!(
+                "Access to module '{}' is denied: it is outside the module root",
+                path.display()
+            );
+            return self.fail(specifier, referrer, "SecurityError", message);
+        }
+
+        path.to_string_lossy().into_owned()
+    }
+
+    /// Returns the source of the module with the key, checked against the type the
+    /// import attributes ask for.
+    fn fetch(&self, ctx: &JSContext, key: &str, attributes: &ImportAttributes) -> String {
+        match self.load_typed(ctx, key, attributes) {
+            Ok(source) => {
+                if attributes
+                    .module_type()
+                    .is_none_or(|module_type| module_type == "javascript")
+                {
+                    let bindings = import_bindings(&source);
+                    self.imports
+                        .borrow_mut()
+                        .entry(key.to_string())
+                        .or_default()
+                        .bindings = bindings;
+                }
+                source
+            }
+            Err((name, message)) => {
+                if !key.starts_with(FAILURE_PREFIX) {
+                    self.failure.replace(Some(ModuleFailure {
+                        phase: ModulePhase::Fetch,
+                        module: key.to_string(),
+                        referrer: None,
+                        message: message.clone(),
+                    }));
+                }
+                throw_source(&name, &message)
+            }
+        }
+    }
+
+    /// Reads the source of the module with the key and checks it against the type of
+    /// the import attributes.
+    ///
+    /// JSON modules are returned as their JSON text, which the engine parses into the
+    /// default export when the import has `type: "json"`.
+    fn load_typed(
+        &self,
+        ctx: &JSContext,
+        key: &str,
+        attributes: &ImportAttributes,
+    ) -> Result<String, (String, String)> {
+        match attributes.module_type() {
+            None => {
+                let source = self.load(ctx, key)?;
+                self.data_modules.borrow().wrap(ctx, key, source)
+            }
+            Some("javascript") => self.load(ctx, key),
+            Some("json") => {
+                let source = self.load(ctx, key)?;
+                check_json(ctx, key, &source)?;
+                Ok(source)
+            }
+            Some(module_type) => Err((
+                "TypeError".to_string(),
+                format!("Unsupported module type \"{}\" for '{}'", module_type, key),
+            )),
+        }
+    }
+
+    /// Reads the source of the module with the key, or returns the name and message
+    /// of the error to throw.
+    fn load(&self, ctx: &JSContext, key: &str) -> Result<String, (String, String)> {
+        if let Some(failure) = self.take_failure(key) {
+            return Err(failure);
+        }
+
+        if let Some(handler) = self.scheme_handler(key) {
+            return handler(ctx, key).map_err(|error| {
+                let name = error.name().map(|name| name.to_string());
+                let message = error.message().map(|message| message.to_string());
+                (
+                    name.unwrap_or_else(|_| "Error".to_string()),
+                    message.unwrap_or_default(),
+                )
+            });
+        }
+
+        if let Some(source) = self.builtins.borrow().get(key) {
+            return Ok(source.clone());
+        }
+
+        if !Path::new(key).is_absolute() {
+            let message = format!("Cannot find module '{}'", key);
+            return Err(("TypeError".to_string(), message));
+        }
+
+        self.fs().read(Path::new(key)).map_err(|error| {
+            let message = format!("Cannot read module '{}': {}", key, error);
+            ("Error".to_string(), message)
+        })
+    }
+
+    /// Returns true if the key names a module the loader reads, rather than a
+    /// virtual module.
+    fn is_loadable(&self, key: &str) -> bool {
+        Path::new(key).is_absolute()
+            || key.starts_with(FAILURE_PREFIX)
+            || self.scheme_handler(key).is_some()
+            || self.builtins.borrow().contains_key(key)
+    }
+}
+
+/// Returns the specifiers of the static imports and re-exports of a module.
+///
+/// This is a scanner rather than a parser: it skips comments and strings, and takes
+/// the string that follows an `import` keyword or the `from` of an import or export.
+pub(crate) fn static_imports(source: &str) -> Vec<String> {
+    import_bindings(source)
+        .into_iter()
+        .map(|(specifier, _)| specifier)
+        .collect()
+}
+
+/// Returns the specifiers of the static imports and re-exports of a module, each
+/// with the words of its statement, which include the names it binds.
+fn import_bindings(source: &str) -> Vec<(String, Vec<String>)> {
+    let characters: Vec<char> = source.chars().collect();
+    let mut imports = Vec::new();
+    // The words of the pending statement.
+    let mut names = Vec::new();
+    let mut index = 0;
+    // Set after `import` or `export`, until the statement's specifier is found.
+    let mut pending = false;
+    // Set right after `import` or a pending `from`: the next string is a specifier.
+    let mut expect_specifier = false;
+    let mut previous = ' ';
+
+    while index < characters.len() {
+        let character = characters[index];
+        let next = characters.get(index + 1).copied();
+        match character {
+            '/' if next == Some('/') => {
+                while index < characters.len() && characters[index] != '\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            '/' if next == Some('*') => {
+                index += 2;
+                while index + 1 < characters.len()
+                    && !(characters[index] == '*' && characters[index + 1] == '/')
+                {
+                    index += 1;
+                }
+                index += 2;
+                continue;
+            }
+            '"' | '\'' | '`' => {
+                let start = index + 1;
+                index += 1;
+                while index < characters.len() && characters[index] != character {
+                    if characters[index] == '\\' {
+                        index += 1;
+                    }
+                    index += 1;
+                }
+
+                if expect_specifier && character != '`' {
+                    let end = index.min(characters.len());
+                    let specifier = characters[start..end].iter().collect();
+                    imports.push((specifier, std::mem::take(&mut names)));
+                    pending = false;
+                }
+                expect_specifier = false;
+                index += 1;
+                previous = character;
+                continue;
+            }
+            character
+                if character.is_alphabetic() || character == '_' || character == '$' =>
+            {
+                let start = index;
+                while index < characters.len()
+                    && (characters[index].is_alphanumeric()
+                        || characters[index] == '_'
+                        || characters[index] == '$')
+                {
+                    index += 1;
+                }
+
+                let word: String = characters[start..index].iter().collect();
+                let member = previous == '.';
+                expect_specifier = false;
+                match word.as_str() {
+                    "import" | "export" if !member => {
+                        pending = true;
+                        expect_specifier = word == "import";
+                        names.clear();
+                    }
+                    "from" if pending => expect_specifier = true,
+                    _ if pending => names.push(word.clone()),
+                    _ => {}
+                }
+                previous = 'a';
+                continue;
+            }
+            ';' => {
+                pending = false;
+                expect_specifier = false;
+            }
+            character if character.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            _ => expect_specifier = false,
+        }
+
+        previous = character;
+        index += 1;
+    }
+
+    imports
+}
+
+/// Resolves a relative specifier against the URL of the importing module.
+fn join_url(base: &str, specifier: &str) -> String {
+    let (prefix, path) = match base.find("://") {
+        Some(index) => {
+            let authority_end = base[index + 3..]
+                .find('/')
+                .map_or(base.len(), |end| index + 3 + end);
+            base.split_at(authority_end)
+        }
+        None => match base.split_once(':') {
+            Some((scheme, path)) => (&base[..scheme.len() + 1], path),
+            None => ("", base),
+        },
+    };
+
+    let mut segments: Vec<&str> = path.split('/').collect();
+    // The last segment is the file name of the importing module.
+    segments.pop();
+    for segment in specifier.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                if segments.len() > 1
+                    || segments.first().is_some_and(|first| !first.is_empty())
+                {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("{}{}", prefix, segments.join("/"))
+}
+
+/// Quotes the text as a JavaScript string literal.
+pub(crate) fn string_literal(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            '\u{2028}' | '\u{2029}' => {
+                literal.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character if character.is_control() => {
+                literal.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => literal.push(character),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// The errors that modules throw as instances of their own constructor.
+const NATIVE_ERRORS: &[&str] = &[
+    "Error",
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+];
+
+/// Returns the source of a module that throws an error with the name and message.
+/// Native error names construct that error, so that `instanceof TypeError` holds;
+/// other names are set on an `Error`.
+fn throw_source(name: &str, message: &str) -> String {
+    if NATIVE_ERRORS.contains(&name) {
+        return format!("throw new {}({});", name, string_literal(message));
+    }
+
+    format!(
+        "throw Object.assign(new Error({}), {{ name: {} }});",
+        string_literal(message),
+        string_literal(name)
+    )
+}
+
+/// Creates a string for the engine, which takes ownership of the reference.
+/// Unlike the conversions from `&str`, the text may contain NUL characters.
+fn owned_string(text: &str) -> JSStringRef {
+    let characters: Vec<u16> = text.encode_utf16().collect();
+    unsafe { JSStringCreateWithCharacters(characters.as_ptr(), characters.len()) }
+}
+
+fn key_string(ctx: JSContextRef, value: JSValueRef) -> Option<String> {
+    let value = JSValue::new(value, ctx);
+    if !value.is_string() {
+        return None;
+    }
+
+    value.as_string().ok().map(|key| key.to_string())
+}
+
+/// A type that `#[module_resolve]` and `#[module_fetch]` functions can return: the
+/// key or the source of the module, or an error.
+///
+/// A resolve error is thrown when the module is evaluated, as long as the fetch
+/// function is also defined with `#[module_fetch]`. A fetch error is thrown by the
+/// module instead of its source.
+///
+/// `None`, or an [`io::Error`] of kind [`io::ErrorKind::NotFound`], means that the
+/// module does not exist, and throws `TypeError: Cannot find module '...'` with the
+/// module that imported it.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{module_fetch, JSContext, JSValue};
+///
+/// #[module_fetch]
+/// fn fetch(
+///     _ctx: JSContext,
+///     key: JSValue,
+///     _attributes: JSValue,
+///     _script_fetcher: JSValue,
+/// ) -> std::io::Result<String> {
+///     std::fs::read_to_string(key.as_string().unwrap().to_string())
+/// }
+/// ```
+pub trait ModuleLoaderOutput {
+    /// Returns the key or the source, or why the module could not be loaded.
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError>;
+}
+
+/// Why a `#[module_resolve]` or `#[module_fetch]` function could not load a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleLoaderError {
+    /// The module does not exist. Thrown as a `TypeError` naming the module and the
+    /// module that imported it.
+    NotFound,
+    /// An error thrown with its name and message.
+    Thrown {
+        /// The name of the error, such as `"TypeError"`.
+        name: String,
+        /// The message of the error.
+        message: String,
+    },
+}
+
+impl ModuleLoaderOutput for JSRetainedString {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        let text = self.to_string();
+        self.release();
+        Ok(text)
+    }
+}
+
+impl ModuleLoaderOutput for &str {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        Ok(self.to_string())
+    }
+}
+
+impl ModuleLoaderOutput for String {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        Ok(self)
+    }
+}
+
+impl ModuleLoaderOutput for &Path {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        Ok(self.to_string_lossy().into_owned())
+    }
+}
+
+impl ModuleLoaderOutput for PathBuf {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        self.as_path().into_loader_output()
+    }
+}
+
+impl<T: ModuleLoaderOutput> ModuleLoaderOutput for Option<T> {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        self.ok_or(ModuleLoaderError::NotFound)?
+            .into_loader_output()
+    }
+}
+
+impl<T: ModuleLoaderOutput> ModuleLoaderOutput for Result<T, ModuleLoaderError> {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        self?.into_loader_output()
+    }
+}
+
+impl<T: ModuleLoaderOutput> ModuleLoaderOutput for Result<T, JSError> {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        self.map_err(|error| {
+            let name = error.name().map(|name| name.to_string());
+            let message = error.message().map(|message| message.to_string());
+            ModuleLoaderError::Thrown {
+                name: name.unwrap_or_else(|_| "Error".to_string()),
+                message: message.unwrap_or_default(),
+            }
+        })?
+        .into_loader_output()
+    }
+}
+
+impl<T: ModuleLoaderOutput> ModuleLoaderOutput for io::Result<T> {
+    fn into_loader_output(self) -> Result<String, ModuleLoaderError> {
+        self.map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => ModuleLoaderError::NotFound,
+            _ => ModuleLoaderError::Thrown {
+                name: "Error".to_string(),
+                message: error.to_string(),
+            },
+        })?
+        .into_loader_output()
+    }
+}
+
+/// The modules that imported the keys resolved by a `#[module_resolve]` function,
+/// stored as a context extension, to name them when a module cannot be found.
+#[derive(Default)]
+struct ModuleImporters(RefCell<HashMap<String, String>>);
+
+/// Returns the message of the `TypeError` thrown for a module that does not exist.
+fn not_found_message(module: &str, importer: Option<&str>) -> String {
+    match importer {
+        Some(importer) => format!(
+            "Cannot find module '{}' imported from '{}'",
+            module, importer
+        ),
+        None => format!("Cannot find module '{}'", module),
+    }
+}
+
+/// Converts the result of a `#[module_resolve]` function for the engine.
+#[doc(hidden)]
+pub fn resolve_output(
+    specifier: &JSValue,
+    referrer: &JSValue,
+    output: impl ModuleLoaderOutput,
+) -> JSStringRef {
+    let ctx = JSContext::from(specifier.ctx);
+    let referrer = key_string(specifier.ctx, referrer.inner)
+        .map(|referrer| match referrer.strip_prefix("file://") {
+            Some(path) => path.to_string(),
+            None => referrer,
+        })
+        .filter(|referrer| !referrer.is_empty());
+
+    let (name, message) = match output.into_loader_output() {
+        Ok(key) => {
+            if let Some(referrer) = referrer {
+                if ctx.get_extension::<ModuleImporters>().is_none() {
+                    ctx.set_extension(ModuleImporters::default());
+                }
+                if let Some(importers) = ctx.get_extension::<ModuleImporters>() {
+                    importers
+                        .0
+                        .borrow_mut()
+                        .entry(key.clone())
+                        .or_insert(referrer);
+                }
+            }
+            return owned_string(&key);
+        }
+        Err(ModuleLoaderError::NotFound) => {
+            let specifier =
+                key_string(specifier.ctx, specifier.inner).unwrap_or_default();
+            let message = not_found_message(&specifier, referrer.as_deref());
+            ("TypeError".to_string(), message)
+        }
+        Err(ModuleLoaderError::Thrown { name, message }) => (name, message),
+    };
+    owned_string(&format!("{}{}:{}", RESOLVE_ERROR_PREFIX, name, message))
+}
+
+/// Converts the result of a `#[module_fetch]` function for the engine.
+#[doc(hidden)]
+pub fn fetch_output(key: &JSValue, output: impl ModuleLoaderOutput) -> JSStringRef {
+    match output.into_loader_output() {
+        Ok(source) => owned_string(&source),
+        Err(ModuleLoaderError::NotFound) => {
+            let ctx = JSContext::from(key.ctx);
+            let key = key_string(key.ctx, key.inner).unwrap_or_default();
+            let importer = ctx
+                .get_extension::<ModuleImporters>()
+                .and_then(|importers| importers.0.borrow().get(&key).cloned());
+            let message = not_found_message(&key, importer.as_deref());
+            owned_string(&throw_source("TypeError", &message))
+        }
+        Err(ModuleLoaderError::Thrown { name, message }) => {
+            owned_string(&throw_source(&name, &message))
+        }
+    }
+}
+
+/// Returns the source that throws the error of a key that a `#[module_resolve]`
+/// function failed to resolve.
+#[doc(hidden)]
+pub fn resolve_error_source(key: &JSValue) -> Option<JSStringRef> {
+    let key = key.as_string().ok()?.to_string();
+    let (name, message) = key.strip_prefix(RESOLVE_ERROR_PREFIX)?.split_once(':')?;
+    Some(owned_string(&throw_source(name, message)))
+}
+
+unsafe extern "C" fn loader_resolve(
+    ctx: JSContextRef,
+    key: JSValueRef,
+    referrer: JSValueRef,
+    _script_fetcher: JSValueRef,
+) -> JSStringRef {
+    let context = JSContext::from(ctx);
+    let specifier = key_string(ctx, key).unwrap_or_default();
+    let referrer = key_string(ctx, referrer);
+    let resolved = match context.get_extension::<LoaderState>() {
+        Some(state) => {
+            let resolved = state.resolve(&specifier, referrer.as_deref());
+            if let Some(referrer) = &referrer {
+                state.record_import(referrer, &specifier, &resolved);
+            }
+            if let Some(referrer) = referrer {
+                state
+                    .importers
+                    .borrow_mut()
+                    .entry(resolved.clone())
+                    .or_insert(referrer);
+            }
+            resolved
+        }
+        None => specifier,
+    };
+
+    owned_string(&resolved)
+}
+
+unsafe extern "C" fn loader_fetch(
+    ctx: JSContextRef,
+    key: JSValueRef,
+    attributes: JSValueRef,
+    _script_fetcher: JSValueRef,
+) -> JSStringRef {
+    let context = JSContext::from(ctx);
+    let key = key_string(ctx, key).unwrap_or_default();
+    let attributes = ImportAttributes::from(JSValue::new(attributes, ctx));
+    let source = match context.get_extension::<LoaderState>() {
+        Some(state) => state.fetch(&context, &key, &attributes),
+        None => throw_source("TypeError", &format!("Cannot find module '{}'", key)),
+    };
+
+    owned_string(&source)
+}
+
+unsafe extern "C" fn loader_evaluate(ctx: JSContextRef, _key: JSValueRef) -> JSValueRef {
+    JSValueMakeUndefined(ctx)
+}
+
+unsafe extern "C" fn loader_import_meta(
+    ctx: JSContextRef,
+    key: JSValueRef,
+    _script_fetcher: JSValueRef,
+) -> JSObjectRef {
+    let context = JSContext::from(ctx);
+    let meta = JSObject::new(&context);
+    if let Some(key) = key_string(ctx, key) {
+        let path = Path::new(&key);
+        if !path.is_absolute() {
+            let url = JSValue::string(&context, key.as_str());
+            let _ = meta.set_property("url", &url, Default::default());
+            return meta.into();
+        }
+
+        let url = JSValue::string(&context, format!("file://{}", key));
+        let filename = JSValue::string(&context, key.as_str());
+        let _ = meta.set_property("url", &url, Default::default());
+        let _ = meta.set_property("filename", &filename, Default::default());
+        if let Some(directory) = path.parent() {
+            let dirname = JSValue::string(&context, directory.to_string_lossy().as_ref());
+            let _ = meta.set_property("dirname", &dirname, Default::default());
+        }
+    }
+
+    meta.into()
+}
+
+/// The data files the module loader turns into modules whose default export is
+/// their content, so they can be imported without import attributes or a custom
+/// fetch callback. Set them with [`JSContext::set_data_modules`].
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{loader::DataModules, JSContext};
+///
+/// let ctx = JSContext::new();
+/// ctx.set_data_modules(DataModules::new().json(true).text("css").text("txt"));
+/// // import config from "./config.json";
+/// // import styles from "./theme.css";
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataModules {
+    json: bool,
+    text: Vec<String>,
+}
+
+impl DataModules {
+    /// Creates a configuration with every data file type turned off.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether `.json` files are parsed into the default export.
+    ///
+    /// # Arguments
+    /// - `enabled`: True to turn JSON modules on.
+    pub fn json(mut self, enabled: bool) -> Self {
+        self.json = enabled;
+        self
+    }
+
+    /// Adds an extension whose files are imported as a string default export.
+    ///
+    /// # Arguments
+    /// - `extension`: The extension, with or without the leading dot, such as `"css"`.
+    pub fn text(mut self, extension: &str) -> Self {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        self.text.push(extension.to_string());
+        self
+    }
+
+    /// Returns the source of the module for the file with the key, or the file
+    /// unchanged if it is not a data file.
+    fn wrap(
+        &self,
+        ctx: &JSContext,
+        key: &str,
+        source: String,
+    ) -> Result<String, (String, String)> {
+        let extension = match Path::new(key).extension() {
+            Some(extension) => extension.to_string_lossy(),
+            None => return Ok(source),
+        };
+
+        if self.json && extension == "json" {
+            check_json(ctx, key, &source)?;
+            return Ok(format!(
+                "export default JSON.parse({});",
+                string_literal(&source)
+            ));
+        }
+
+        if self.text.iter().any(|text| *text == extension) {
+            return Ok(format!("export default {};", string_literal(&source)));
+        }
+
+        Ok(source)
+    }
+}
+
+/// Returns the SyntaxError to throw if the source of the module is not valid JSON.
+fn check_json(ctx: &JSContext, key: &str, source: &str) -> Result<(), (String, String)> {
+    let characters: Vec<u16> = source.encode_utf16().collect();
+    let value = JSValue::from_json(ctx, JSString::from_utf16(&characters));
+    if value.inner.is_null() {
+        let message = format!("Module '{}' is not valid JSON", key);
+        return Err(("SyntaxError".to_string(), message));
+    }
+
+    Ok(())
+}
+
+/// The import attributes of a module request, such as the `{ type: "json" }` of
+/// `import data from "./data.json" with { type: "json" }`.
+///
+/// The engine passes the attributes to the fetch callback of the module loader as a
+/// raw value: `undefined` when the import has none, or an object of strings.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{loader::ImportAttributes, module_fetch, JSContext, JSRetainedString, JSValue};
+///
+/// #[module_fetch]
+/// fn fetch(
+///     _ctx: JSContext,
+///     key: JSValue,
+///     attributes: JSValue,
+///     _script_fetcher: JSValue,
+/// ) -> JSRetainedString {
+///     let mut path = std::path::PathBuf::from(key.as_string().unwrap().to_string());
+///     if ImportAttributes::from(attributes).module_type() == Some("json") {
+///         path.set_extension("json");
+///     }
+///     JSRetainedString::from(std::fs::read_to_string(path).unwrap())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportAttributes {
+    attributes: BTreeMap<String, String>,
+}
+
+impl ImportAttributes {
+    /// Reads the attributes from the value passed by the engine.
+    ///
+    /// Values that are not objects give no attributes, and properties whose value is
+    /// not a string are skipped.
+    pub fn from_value(value: &JSValue) -> Self {
+        if value.inner.is_null() || !value.is_object() {
+            return Self::default();
+        }
+
+        let properties = value
+            .as_object()
+            .and_then(|object| object.to_btree_map())
+            .unwrap_or_default();
+        let attributes = properties
+            .into_iter()
+            .filter(|(_, value)| value.is_string())
+            .filter_map(|(name, value)| Some((name, value.as_string().ok()?.to_string())))
+            .collect();
+
+        Self { attributes }
+    }
+
+    /// Returns the value of an attribute.
+    ///
+    /// # Arguments
+    /// - `key`: The name of the attribute.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
+    /// Returns the `type` attribute, such as `"json"`.
+    pub fn module_type(&self) -> Option<&str> {
+        self.get("type")
+    }
+
+    /// Returns true if the import has no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Returns an iterator over the attributes, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+impl From<JSValue> for ImportAttributes {
+    fn from(value: JSValue) -> Self {
+        Self::from_value(&value)
+    }
+}
+
+/// The script fetcher argument of the module loader callbacks.
+///
+/// The engine passes the fetcher of the script that started the load: `undefined`
+/// for modules loaded through the C API, such as [`JSContext::evaluate_module`], and
+/// possibly an engine object for dynamic `import()` calls. The object carries no
+/// information the API can read; use [`JSContext::module_referrer_chain`] to find
+/// out why a module was loaded.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{loader::ScriptFetcher, module_fetch, JSContext, JSRetainedString, JSValue};
+///
+/// #[module_fetch]
+/// fn fetch(
+///     _ctx: JSContext,
+///     key: JSValue,
+///     _attributes: JSValue,
+///     script_fetcher: JSValue,
+/// ) -> JSRetainedString {
+///     let dynamic = !ScriptFetcher::from(script_fetcher).is_empty();
+///     JSRetainedString::from(std::fs::read_to_string(key.as_string().unwrap().to_string()).unwrap())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptFetcher {
+    value: JSValue,
+}
+
+impl ScriptFetcher {
+    /// Returns the raw value passed by the engine.
+    pub fn value(&self) -> &JSValue {
+        &self.value
+    }
+
+    /// Returns true if no fetcher was passed, which is the case for modules whose
+    /// load was started through the C API.
+    pub fn is_empty(&self) -> bool {
+        self.value.is_undefined() || self.value.is_null()
+    }
+
+    /// Returns the fetcher object, if any.
+    pub fn as_object(&self) -> Option<JSObject> {
+        if !self.value.is_object() {
+            return None;
+        }
+
+        self.value.as_object().ok()
+    }
+}
+
+impl From<JSValue> for ScriptFetcher {
+    fn from(value: JSValue) -> Self {
+        Self { value }
+    }
+}
+
+/// The step of loading a module graph that failed, see [`ModuleError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModulePhase {
+    /// A specifier could not be resolved to a module, e.g. the file is missing or
+    /// outside the module root.
+    Resolve,
+    /// The source of a module could not be read.
+    Fetch,
+    /// The source of a module is not valid JavaScript.
+    Parse,
+    /// An import does not match the exports of the imported module.
+    Link,
+    /// A module threw while running.
+    Evaluate,
+}
+
+impl fmt::Display for ModulePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phase = match self {
+            ModulePhase::Resolve => "resolve",
+            ModulePhase::Fetch => "fetch",
+            ModulePhase::Parse => "parse",
+            ModulePhase::Link => "link",
+            ModulePhase::Evaluate => "evaluate",
+        };
+        f.write_str(phase)
+    }
+}
+
+/// The error of [`JSContext::evaluate_module`]: the exception, together with the
+/// module it comes from, the chain of modules that imported it and the phase that
+/// failed.
+///
+/// It dereferences to the [`JSError`] and converts into it, so it can be returned
+/// with `?` from functions returning a [`JSResult`].
+///
+/// The module and the chain are tracked by the loader of
+/// [`JSContext::set_module_root`]; with another loader, the module is the entry
+/// unless the engine records the source URL of the error.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{loader::ModulePhase, JSContext};
+///
+/// let ctx = JSContext::new();
+/// ctx.set_module_root("/srv/app").unwrap();
+/// let error = ctx.evaluate_module("./main.js").unwrap_err();
+/// if error.phase() == ModulePhase::Resolve {
+///     eprintln!("{} (imported from {:?})", error.module(), error.referrers());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ModuleError {
+    error: JSError,
+    phase: ModulePhase,
+    module: String,
+    referrers: Vec<String>,
+    cycle: Option<ModuleGraphError>,
+}
+
+impl ModuleError {
+    /// Returns the exception.
+    pub fn error(&self) -> &JSError {
+        &self.error
+    }
+
+    /// Returns the exception, dropping the module context.
+    pub fn into_error(self) -> JSError {
+        self.error
+    }
+
+    /// Returns the phase that failed.
+    pub fn phase(&self) -> ModulePhase {
+        self.phase
+    }
+
+    /// Returns the key of the module that failed, or the specifier when it could
+    /// not be resolved.
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns the modules that led to the failing one: its importer, the importer
+    /// of that module, and so on up to the entry module.
+    pub fn referrers(&self) -> &[String] {
+        &self.referrers
+    }
+
+    /// Returns the import cycle that caused a `ReferenceError`: a cycle through the
+    /// failing module in which a module uses a binding it imports before the
+    /// imported module initialized it.
+    pub fn cycle(&self) -> Option<&ModuleGraphError> {
+        self.cycle.as_ref()
+    }
+}
+
+impl Deref for ModuleError {
+    type Target = JSError;
+
+    fn deref(&self) -> &JSError {
+        &self.error
+    }
+}
+
+impl From<ModuleError> for JSError {
+    fn from(error: ModuleError) -> Self {
+        error.error
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error in module '{}'", self.phase, self.module)?;
+        if !self.referrers.is_empty() {
+            write!(f, " (imported from {})", self.referrers.join(" ← "))?;
+        }
+        let name = self.error.name().map(|name| name.to_string());
+        let message = self.error.message().map(|message| message.to_string());
+        write!(
+            f,
+            ": {}: {}",
+            name.unwrap_or_else(|_| "Error".to_string()),
+            message.unwrap_or_default()
+        )?;
+        if let Some(cycle) = &self.cycle {
+            write!(f, " ({})", cycle)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ModuleError {}
+
+/// The static import graph of a module and its dependencies, built by
+/// [`JSContext::module_graph`].
+#[derive(Debug, Clone, Default)]
+pub struct ModuleGraph {
+    entry: String,
+    modules: BTreeMap<String, Vec<String>>,
+}
+
+/// An import cycle found in a [`ModuleGraph`].
+///
+/// Cycles are allowed by the module specification, but a module of the cycle that
+/// uses a binding of another before it is initialized throws a `ReferenceError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleGraphError {
+    /// The keys of the modules of the cycle, starting and ending with the same module.
+    pub cycle: Vec<String>,
+}
+
+impl std::fmt::Display for ModuleGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Circular import: {}", self.cycle.join(" → "))
+    }
+}
+
+impl std::error::Error for ModuleGraphError {}
+
+impl ModuleGraph {
+    /// Returns the key of the entry module.
+    pub fn entry(&self) -> &str {
+        &self.entry
+    }
+
+    /// Returns the keys of all the modules of the graph, in order.
+    pub fn modules(&self) -> impl Iterator<Item = &str> {
+        self.modules.keys().map(String::as_str)
+    }
+
+    /// Returns the keys of the modules the module imports, in source order.
+    pub fn dependencies(&self, key: &str) -> Option<&[String]> {
+        self.modules.get(key).map(Vec::as_slice)
+    }
+
+    /// Returns every import cycle, each starting and ending with the same module.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = Vec::new();
+        let mut finished = HashSet::new();
+        let mut stack = Vec::new();
+        self.find_cycles(&self.entry, &mut stack, &mut finished, &mut cycles);
+        cycles
+    }
+
+    /// Checks that the graph has no import cycle.
+    ///
+    /// # Errors
+    /// The first cycle found, walking the imports from the entry module.
+    pub fn check(&self) -> Result<(), ModuleGraphError> {
+        match self.cycles().into_iter().next() {
+            Some(cycle) => Err(ModuleGraphError { cycle }),
+            None => Ok(()),
+        }
+    }
+
+    fn find_cycles<'a>(
+        &'a self,
+        key: &'a str,
+        stack: &mut Vec<&'a str>,
+        finished: &mut HashSet<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        if let Some(position) = stack.iter().position(|module| *module == key) {
+            let mut cycle: Vec<String> = stack[position..]
+                .iter()
+                .map(|module| module.to_string())
+                .collect();
+            cycle.push(key.to_string());
+            cycles.push(cycle);
+            return;
+        }
+        if !finished.insert(key) {
+            return;
+        }
+
+        stack.push(key);
+        for dependency in self.dependencies(key).unwrap_or_default() {
+            self.find_cycles(dependency, stack, finished, cycles);
+        }
+        stack.pop();
+    }
+}
+
+impl JSContext {
+    /// Confines the filesystem module loader to a directory. Module specifiers are
+    /// resolved against the importing module, or against the root for the entry module,
+    /// and modules that resolve outside the root or the directories added with
+    /// [`JSContext::allow_module_path`] throw a `SecurityError`. Symbolic links are
+    /// followed before the check, so they cannot be used to escape the root.
+    ///
+    /// This replaces the module loader set with [`JSContext::set_module_loader`].
+    ///
+    /// # Arguments
+    /// - `root`: The directory to load modules from.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_module_root("/srv/app").unwrap();
+    /// ctx.evaluate_module("main.js").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the directory does not exist or is not a directory.
+    pub fn set_module_root(&self, root: impl AsRef<Path>) -> JSResult<()> {
+        let root = self.canonical_directory(root.as_ref())?;
+        LoaderState::get(self).root.replace(Some(root));
+        Ok(())
+    }
+
+    /// Allows the filesystem module loader to load modules from a directory outside
+    /// the module root, such as a shared library directory.
+    /// See [`JSContext::set_module_root`].
+    ///
+    /// # Arguments
+    /// - `path`: The directory to allow.
+    ///
+    /// # Errors
+    /// A `TypeError` if the directory does not exist or is not a directory.
+    pub fn allow_module_path(&self, path: impl AsRef<Path>) -> JSResult<()> {
+        let path = self.canonical_directory(path.as_ref())?;
+        LoaderState::get(self).allowed.borrow_mut().push(path);
+        Ok(())
+    }
+
+    /// Registers a handler that returns the source of the modules whose specifiers
+    /// start with the scheme, such as `https:`, `data:` or `npm:`. Relative imports
+    /// from those modules are resolved against their URL. Paths and `file:` URLs keep
+    /// going through the filesystem loader.
+    ///
+    /// This replaces the module loader set with [`JSContext::set_module_loader`].
+    ///
+    /// # Arguments
+    /// - `scheme`: The scheme, with or without the trailing colon.
+    /// - `handler`: A closure that receives the module URL and returns its source.
+    ///   An error it returns is thrown when the module is evaluated.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.register_module_scheme("npm", |_ctx, url| {
+    ///     Ok(std::fs::read_to_string(format!("/srv/packages/{}.js", &url[4..])).unwrap())
+    /// });
+    /// ```
+    pub fn register_module_scheme<F>(&self, scheme: &str, handler: F)
+    where
+        F: Fn(&JSContext, &str) -> JSResult<String> + 'static,
+    {
+        let scheme = scheme.strip_suffix(':').unwrap_or(scheme).to_string();
+        LoaderState::get(self)
+            .schemes
+            .borrow_mut()
+            .insert(scheme, Rc::new(handler));
+    }
+
+    /// Sets the filesystem the module loader reads modules from, in place of the
+    /// filesystem of the operating system. Set it before [`JSContext::set_module_root`]
+    /// and [`JSContext::allow_module_path`], which look the directories up in it.
+    ///
+    /// This replaces the module loader set with [`JSContext::set_module_loader`].
+    ///
+    /// # Arguments
+    /// - `fs`: The filesystem, such as a [`MemoryFs`].
+    pub fn set_module_fs(&self, fs: impl ModuleFs) {
+        LoaderState::get(self).fs.replace(Some(Rc::new(fs)));
+    }
+
+    /// Sets the data files the module loader imports as modules whose default export
+    /// is their content: `.json` files parsed to a value, and text files as a string.
+    /// Both are off by default.
+    ///
+    /// This replaces the module loader set with [`JSContext::set_module_loader`].
+    ///
+    /// # Arguments
+    /// - `modules`: The data file types to turn on.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{loader::DataModules, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_data_modules(DataModules::new().json(true).text("txt"));
+    /// ctx.evaluate_module("/app/main.js").unwrap();
+    /// ```
+    pub fn set_data_modules(&self, modules: DataModules) {
+        LoaderState::get(self).data_modules.replace(modules);
+    }
+
+    /// Builds the static import graph of a module without evaluating anything, using
+    /// the resolution rules of the module loader of [`JSContext::set_module_root`].
+    /// Virtual modules are leaves of the graph.
+    ///
+    /// # Arguments
+    /// - `entry`: The specifier of the entry module.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_module_root("/srv/app").unwrap();
+    /// if let Err(error) = ctx.module_graph("./main.js").unwrap().check() {
+    ///     eprintln!("{}", error);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// The error a module would throw when loaded, for example a `SecurityError`
+    /// or a missing file.
+    pub fn module_graph(&self, entry: &str) -> JSResult<ModuleGraph> {
+        let state = self.get_extension::<LoaderState>().unwrap_or_default();
+        let entry = state.resolve(entry, None);
+
+        let mut graph = ModuleGraph {
+            entry: entry.clone(),
+            modules: BTreeMap::new(),
+        };
+        let mut queue = vec![entry];
+        while let Some(key) = queue.pop() {
+            if graph.modules.contains_key(&key) {
+                continue;
+            }
+            if !state.is_loadable(&key) {
+                graph.modules.insert(key, Vec::new());
+                continue;
+            }
+
+            let source = state.load(self, &key).map_err(|(name, message)| {
+                crate::builtins::named_error(self, &name, &message)
+            })?;
+            let mut dependencies = Vec::new();
+            for specifier in static_imports(&source) {
+                let dependency = state.resolve(&specifier, Some(&key));
+                // Unresolvable imports fail the graph rather than become modules.
+                if let Some((name, message)) = state.take_failure(&dependency) {
+                    return Err(crate::builtins::named_error(self, &name, &message));
+                }
+                dependencies.push(dependency);
+            }
+            queue.extend(dependencies.iter().rev().cloned());
+            graph.modules.insert(key, dependencies);
+        }
+
+        Ok(graph)
+    }
+
+    /// Returns the chain of modules that led the loader of [`JSContext::set_module_root`]
+    /// to load a module: the module itself, the module that first imported it, and so
+    /// on up to the entry module.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the module, such as the `import.meta.filename` of a file.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_module_root("/srv/app").unwrap();
+    /// ctx.evaluate_module("./main.js").unwrap();
+    /// let chain = ctx.module_referrer_chain("/srv/app/lib/util.js");
+    /// assert_eq!(chain.last().unwrap(), "/srv/app/main.js");
+    /// ```
+    pub fn module_referrer_chain(&self, key: &str) -> Vec<String> {
+        let mut chain = vec![key.to_string()];
+        let state = match self.get_extension::<LoaderState>() {
+            Some(state) => state,
+            None => return chain,
+        };
+
+        let importers = state.importers.borrow();
+        while let Some(importer) =
+            importers.get(chain.last().expect("chain is not empty"))
+        {
+            if chain.contains(importer) {
+                break;
+            }
+            chain.push(importer.clone());
+        }
+
+        chain
+    }
+
+    /// Forgets the failure recorded by the loader, before evaluating a module.
+    pub(crate) fn clear_module_failure(&self) {
+        if let Some(state) = self.get_extension::<LoaderState>() {
+            state.failure.take();
+        }
+    }
+
+    /// Wraps an error of [`JSContext::evaluate_module`] with the module it comes
+    /// from, using the failure recorded by the loader when it matches the error.
+    pub(crate) fn module_error(&self, entry: &str, error: JSError) -> ModuleError {
+        let message = error
+            .message()
+            .map(|message| message.to_string())
+            .unwrap_or_default();
+        let failure = self
+            .get_extension::<LoaderState>()
+            .and_then(|state| state.failure.take())
+            .filter(|failure| failure.message == message);
+
+        let (phase, module, referrer) = match failure {
+            Some(failure) => (failure.phase, failure.module, failure.referrer),
+            None => {
+                let syntax_error = error.name().is_ok_and(|name| name == "SyntaxError");
+                // The engine reports unresolvable imports as syntax errors too.
+                let phase = if !syntax_error {
+                    ModulePhase::Evaluate
+                } else if message.starts_with("Importing binding name") {
+                    ModulePhase::Link
+                } else {
+                    ModulePhase::Parse
+                };
+                let source_url = error
+                    .object
+                    .get_property("sourceURL")
+                    .ok()
+                    .filter(|url| url.is_string())
+                    .and_then(|url| url.as_string().ok())
+                    .map(|url| url.to_string());
+                let module = match source_url {
+                    Some(url) => url.strip_prefix("file://").unwrap_or(&url).to_string(),
+                    None => entry.to_string(),
+                };
+                (phase, module, None)
+            }
+        };
+
+        let referrers = match referrer {
+            Some(referrer) => self.module_referrer_chain(&referrer),
+            None => self.module_referrer_chain(&module).split_off(1),
+        };
+        let cycle = match phase {
+            ModulePhase::Evaluate => self
+                .get_extension::<LoaderState>()
+                .and_then(|state| state.import_cycle(&module, &error)),
+            _ => None,
+        };
+        ModuleError {
+            error,
+            phase,
+            module,
+            referrers,
+            cycle,
+        }
+    }
+
+    fn canonical_directory(&self, path: &Path) -> JSResult<PathBuf> {
+        let fs = LoaderState::get(self).fs();
+        match fs.canonicalize(path) {
+            Ok(path) if fs.is_dir(&path) => Ok(path),
+            Ok(path) => {
+                let message = format!("'{}' is not a directory", path.display());
+                Err(JSError::new_typ(self, message)?)
+            }
+            Err(error) => {
+                let message = format!("Cannot open '{}': {}", path.display(), error);
+                Err(JSError::new_typ(self, message)?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for the test, removing what a previous run left.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "rust_jsc_loader_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_string_literal() {
+        assert_eq!(string_literal("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(string_literal("\n\u{0}\u{2028}"), r#""\n\u0000\u2028""#);
+    }
+
+    #[test]
+    fn test_join_url() {
+        assert_eq!(
+            join_url("https://example.com/lib/a.js", "./b.js"),
+            "https://example.com/lib/b.js"
+        );
+        assert_eq!(
+            join_url("https://example.com/lib/a.js", "../../c.js"),
+            "https://example.com/c.js"
+        );
+        assert_eq!(
+            join_url("npm:left-pad/index.js", "./util.js"),
+            "npm:left-pad/util.js"
+        );
+    }
+
+    #[test]
+    fn test_module_scheme() {
+        let ctx = JSContext::new();
+        ctx.register_module_scheme("mem:", |ctx, url| match url {
+            "mem:/app/main.js" => Ok(
+                "import { value } from './value.js'; globalThis.value = value;"
+                    .to_string(),
+            ),
+            "mem:/app/value.js" => {
+                Ok("export const value = import.meta.url;".to_string())
+            }
+            _ => Err(JSError::new_typ(ctx, format!("No module at {}", url))?),
+        });
+
+        ctx.evaluate_module("mem:/app/main.js").unwrap();
+        let value = ctx.evaluate_script("value", None).unwrap();
+        assert_eq!(value.as_string().unwrap().to_string(), "mem:/app/value.js");
+
+        let error = ctx.evaluate_module("mem:/missing.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_static_imports() {
+        let source = r#"
+            // import "./commented.js";
+            import a, { b } from "./a.js";
+            import './side-effect.js';
+            export { c } from '../c.js';
+            export * as d from "./d.js";
+            export const from = "./not-an-import.js";
+            const text = "import './in-string.js'";
+            import.meta.url;
+            const lazy = import("./dynamic.js");
+            /* export * from "./block-comment.js"; */
+        "#;
+        assert_eq!(
+            static_imports(source),
+            vec!["./a.js", "./side-effect.js", "../c.js", "./d.js"]
+        );
+        assert_eq!(
+            import_bindings(source)[0],
+            ("./a.js".to_string(), vec!["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_module_graph() {
+        let fs = MemoryFs::new()
+            .file("/app/main.js", "import './a.js'; import '@virtual';")
+            .file(
+                "/app/a.js",
+                "import { b } from './b.js'; export const a = 1;",
+            )
+            .file(
+                "/app/b.js",
+                "import { a } from './a.js'; export const b = a;",
+            );
+
+        let ctx = JSContext::new();
+        ctx.set_module_fs(fs);
+        let graph = ctx.module_graph("/app/main.js").unwrap();
+        assert_eq!(graph.entry(), "/app/main.js");
+        assert_eq!(
+            graph.dependencies("/app/main.js").unwrap(),
+            &["/app/a.js".to_string(), "@virtual".to_string()]
+        );
+        assert_eq!(graph.modules().count(), 4);
+
+        let error = graph.check().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Circular import: /app/a.js → /app/b.js → /app/a.js"
+        );
+
+        let error = ctx.evaluate_module("/app/a.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "ReferenceError");
+        assert!(!error.message().unwrap().to_string().contains("→"));
+        let cycle = &error.cycle().unwrap().cycle;
+        assert_eq!(cycle.len(), 3);
+        assert_eq!(cycle.first(), cycle.last());
+        assert!(cycle.contains(&"/app/a.js".to_string()));
+        assert!(cycle.contains(&"/app/b.js".to_string()));
+        assert!(error.to_string().contains("Circular import"));
+    }
+
+    #[test]
+    fn test_module_error_cycle_needs_binding() {
+        let fs = MemoryFs::new()
+            .file(
+                "/app/a.js",
+                "import { b } from './b.js'; export const a = 1;",
+            )
+            .file(
+                "/app/b.js",
+                "import { a } from './a.js'; export const b = 2; misspelled;",
+            );
+
+        let ctx = JSContext::new();
+        ctx.set_module_fs(fs);
+        let error = ctx.evaluate_module("/app/a.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "ReferenceError");
+        assert!(error.cycle().is_none());
+    }
+
+    #[test]
+    fn test_module_referrer_chain() {
+        let fs = MemoryFs::new()
+            .file("/app/main.js", "import './lib/a.js';")
+            .file("/app/lib/a.js", "import './b.js';")
+            .file("/app/lib/b.js", "globalThis.fetcher = typeof import.meta;");
+
+        let ctx = JSContext::new();
+        ctx.set_module_fs(fs);
+        ctx.evaluate_module("/app/main.js").unwrap();
+        assert_eq!(
+            ctx.module_referrer_chain("/app/lib/b.js"),
+            vec!["/app/lib/b.js", "/app/lib/a.js", "/app/main.js"]
+        );
+        assert_eq!(
+            ctx.module_referrer_chain("/app/main.js"),
+            vec!["/app/main.js"]
+        );
+
+        let fetcher = ScriptFetcher::from(JSValue::undefined(&ctx));
+        assert!(fetcher.is_empty());
+        assert!(fetcher.as_object().is_none());
+    }
+
+    #[test]
+    fn test_module_error() {
+        let fs = MemoryFs::new()
+            .file("/app/main.js", "import './lib.js';")
+            .file("/app/lib.js", "import './missing.js';")
+            .file("/app/throws.js", "import './thrower.js';")
+            .file("/app/thrower.js", "throw new TypeError('bad plugin');")
+            .file("/app/broken.js", "export const = 1;");
+        let ctx = JSContext::new();
+        ctx.set_module_fs(fs);
+        ctx.set_module_root("/app").unwrap();
+
+        let error = ctx.evaluate_module("/app/main.js").unwrap_err();
+        assert_eq!(error.phase(), ModulePhase::Resolve);
+        assert_eq!(error.module(), "./missing.js");
+        assert_eq!(
+            error.referrers(),
+            &["/app/lib.js".to_string(), "/app/main.js".to_string()]
+        );
+        assert!(error.to_string().starts_with(
+            "resolve error in module './missing.js' (imported from /app/lib.js ← /app/main.js)"
+        ));
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        let error = ctx.evaluate_module("/app/throws.js").unwrap_err();
+        assert_eq!(error.phase(), ModulePhase::Evaluate);
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        let error = ctx.evaluate_module("/app/broken.js").unwrap_err();
+        assert_eq!(error.phase(), ModulePhase::Parse);
+
+        ctx.register_module_scheme("mem", |ctx, url| {
+            Err(JSError::new_typ(ctx, format!("No module at {}", url))?)
+        });
+        let error = ctx.evaluate_module("mem:/gone.js").unwrap_err();
+        assert_eq!(error.phase(), ModulePhase::Fetch);
+        assert_eq!(error.module(), "mem:/gone.js");
+        let error: JSError = error.into();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_memory_fs() {
+        let fs = MemoryFs::new()
+            .file(
+                "/app/main.js",
+                "import { name } from './lib/name'; globalThis.name = name;",
+            )
+            .file("/app/lib/name.js", "export const name = 'memory';")
+            .file("/secret.js", "globalThis.secret = true;");
+        assert!(fs.exists(Path::new("/app/lib")));
+        assert_eq!(
+            fs.canonicalize(Path::new("/app/lib/../main.js")).unwrap(),
+            PathBuf::from("/app/main.js")
+        );
+
+        let ctx = JSContext::new();
+        ctx.set_module_fs(fs);
+        ctx.set_module_root("/app").unwrap();
+        ctx.evaluate_module("./main.js").unwrap();
+        let name = ctx.evaluate_script("name", None).unwrap();
+        assert_eq!(name.as_string().unwrap().to_string(), "memory");
+
+        let error = ctx.evaluate_module("/secret.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "SecurityError");
+    }
+
+    #[test]
+    fn test_import_attributes() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script("({ type: 'json', integrity: 1 })", None)
+            .unwrap();
+        let attributes = ImportAttributes::from(value);
+        assert_eq!(attributes.module_type(), Some("json"));
+        assert_eq!(attributes.get("integrity"), None);
+        assert_eq!(attributes.iter().count(), 1);
+        assert!(ImportAttributes::from(JSValue::undefined(&ctx)).is_empty());
+
+        ctx.set_module_fs(
+            MemoryFs::new()
+                .file("/data.json", r#"{ "name": "data" }"#)
+                .file("/broken.json", "{ name: }"),
+        );
+        let state = LoaderState::get(&ctx);
+        let source = state.load_typed(&ctx, "/data.json", &attributes).unwrap();
+        assert_eq!(source, r#"{ "name": "data" }"#);
+
+        let (name, _) = state
+            .load_typed(&ctx, "/broken.json", &attributes)
+            .unwrap_err();
+        assert_eq!(name, "SyntaxError");
+
+        let css = ctx.evaluate_script("({ type: 'css' })", None).unwrap();
+        let (name, message) = state
+            .load_typed(&ctx, "/data.json", &ImportAttributes::from(css))
+            .unwrap_err();
+        assert_eq!(name, "TypeError");
+        assert!(message.contains("\"css\""));
+    }
+
+    #[test]
+    fn test_import_json_module() {
+        let ctx = JSContext::new();
+        ctx.set_module_fs(
+            MemoryFs::new()
+                .file(
+                    "/app/main.js",
+                    "import data from './data.json' with { type: 'json' }; \
+                     globalThis.result = data.name;",
+                )
+                .file(
+                    "/app/untyped.js",
+                    "import data from './data.json'; globalThis.untyped = data;",
+                )
+                .file(
+                    "/app/css.js",
+                    "import data from './data.json' with { type: 'css' }; \
+                     globalThis.css = data;",
+                )
+                .file("/app/data.json", r#"{ "name": "data" }"#),
+        );
+        ctx.set_module_root("/app").unwrap();
+
+        ctx.evaluate_module("./main.js").unwrap();
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "data");
+
+        // Without the attribute, the file is a JavaScript module, and JSON is not
+        // valid JavaScript.
+        let error = ctx.evaluate_module("./untyped.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "SyntaxError");
+        let untyped = ctx.evaluate_script("typeof untyped", None).unwrap();
+        assert_eq!(untyped.as_string().unwrap().to_string(), "undefined");
+
+        let error = ctx.evaluate_module("./css.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert!(error.message().unwrap().to_string().contains("\"css\""));
+        let css = ctx.evaluate_script("typeof css", None).unwrap();
+        assert_eq!(css.as_string().unwrap().to_string(), "undefined");
+    }
+
+    #[test]
+    fn test_resolve_script_referrers() {
+        let ctx = JSContext::new();
+        ctx.set_module_fs(
+            MemoryFs::new()
+                .file("/app/lib.js", "export const lib = 1;")
+                .file("/lib.js", "export const lib = 2;"),
+        );
+        let state = LoaderState::get(&ctx);
+        assert_eq!(
+            state.resolve("./lib.js", Some("file:///app/main.js")),
+            "/app/lib.js"
+        );
+        assert_eq!(
+            state.resolve("./lib.js", Some("/app/main.js")),
+            "/app/lib.js"
+        );
+        // A script evaluated without a source URL passes an empty referrer, which
+        // falls back to the module root.
+        ctx.set_module_root("/app").unwrap();
+        assert_eq!(state.resolve("./lib.js", Some("")), "/app/lib.js");
+    }
+
+    #[test]
+    fn test_throw_source() {
+        let ctx = JSContext::new();
+        for (name, check) in [
+            ("TypeError", "error instanceof TypeError"),
+            ("SyntaxError", "error instanceof SyntaxError"),
+            ("ModuleError", "error instanceof Error"),
+        ] {
+            let script = format!(
+                "(() => {{ try {{ {} }} catch (error) {{ return {} && error.name; }} }})()",
+                throw_source(name, "it's \"broken\""),
+                check
+            );
+            let result = ctx.evaluate_script(&script, None).unwrap();
+            assert_eq!(result.as_string().unwrap().to_string(), name);
+        }
+
+        ctx.set_module_fs(MemoryFs::new().file("/app/main.js", "import './missing.js';"));
+        ctx.evaluate_script(
+            "import('/app/main.js').catch((error) => { globalThis.native = error instanceof TypeError; })",
+            None,
+        )
+        .unwrap();
+        assert!(ctx.evaluate_script("native", None).unwrap().as_boolean());
+    }
+
+    #[test]
+    fn test_dynamic_import_from_script() {
+        let ctx = JSContext::new();
+        ctx.set_module_fs(
+            MemoryFs::new()
+                .file("/app/lib.js", "export const lib = 1;")
+                .file("/lib.js", "export const lib = 2;"),
+        );
+
+        ctx.evaluate_script_with_source_url(
+            "import('./lib.js').then((module) => { globalThis.lib = String(module.lib); }, \
+             (error) => { globalThis.lib = String(error); })",
+            "file:///app/main.js",
+            Some(1),
+        )
+        .unwrap();
+        // The import settles once the script has returned and its jobs have run.
+        let lib = ctx.evaluate_script("lib", None).unwrap();
+        assert_eq!(lib.as_string().unwrap().to_string(), "1");
+    }
+
+    #[test]
+    fn test_data_modules() {
+        let ctx = JSContext::new();
+        ctx.set_module_fs(
+            MemoryFs::new()
+                .file(
+                    "/app/main.js",
+                    "import data from './data.json'; import note from './note.txt'; \
+                     globalThis.result = data.items.length + ':' + note;",
+                )
+                .file("/app/data.json", r#"{ "items": [1, 2, 3] }"#)
+                .file("/app/note.txt", "it's \"quoted\"\n")
+                .file("/app/broken.json", "{ items: }"),
+        );
+        ctx.set_module_root("/app").unwrap();
+        ctx.set_data_modules(DataModules::new().json(true).text(".txt"));
+
+        ctx.evaluate_module("./main.js").unwrap();
+        let result = ctx.evaluate_script("result", None).unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "3:it's \"quoted\"\n"
+        );
+
+        let error = ctx.evaluate_module("./broken.json").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "SyntaxError");
+    }
+
+    #[test]
+    fn test_module_root() {
+        let directory = test_directory("root");
+        let root = directory.join("app");
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(
+            root.join("main.js"),
+            "import { name } from './lib/name.js'; globalThis.loaded = name;",
+        )
+        .unwrap();
+        std::fs::write(root.join("lib/name.js"), "export const name = 'app';").unwrap();
+        std::fs::write(
+            root.join("escape.js"),
+            "import '../secret.js'; globalThis.escaped = true;",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("shared.js"),
+            "import '../secret.js'; globalThis.shared = true;",
+        )
+        .unwrap();
+        std::fs::write(directory.join("secret.js"), "globalThis.secret = true;").unwrap();
+
+        let ctx = JSContext::new();
+        ctx.set_module_root(&root).unwrap();
+        ctx.evaluate_module("./main.js").unwrap();
+        let loaded = ctx.evaluate_script("loaded", None).unwrap();
+        assert_eq!(loaded.as_string().unwrap().to_string(), "app");
+
+        let error = ctx.evaluate_module("./escape.js").err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "SecurityError");
+        let secret = ctx.evaluate_script("typeof secret", None).unwrap();
+        assert_eq!(secret.as_string().unwrap().to_string(), "undefined");
+
+        ctx.allow_module_path(&directory).unwrap();
+        ctx.evaluate_module("./shared.js").unwrap();
+        let secret = ctx.evaluate_script("secret", None).unwrap();
+        assert!(secret.as_boolean());
+
+        assert!(ctx.set_module_root(directory.join("missing")).is_err());
+        let error = ctx.set_module_root(root.join("main.js")).unwrap_err();
+        assert!(error
+            .message()
+            .unwrap()
+            .to_string()
+            .ends_with("is not a directory"));
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
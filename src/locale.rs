@@ -0,0 +1,213 @@
+//! Context-level defaults for the locale and time zone of `Intl` and the
+//! `toLocaleString` family, for output that does not depend on the host.
+
+use crate::{JSContext, JSResult, JSValue};
+
+/// Wraps the `Intl` constructors and the locale-sensitive methods so that a missing
+/// locale or time zone argument is replaced by the configured default. Installing it
+/// again only updates the defaults.
+const LOCALE_DEFAULTS: &str = r#"(function (locale, timeZone) {
+    const key = Symbol.for("rust_jsc.localeDefaults");
+    if (locale !== undefined) {
+        locale = Intl.getCanonicalLocales(locale)[0];
+    }
+    if (timeZone !== undefined) {
+        timeZone = new Intl.DateTimeFormat("en-US", { timeZone }).resolvedOptions().timeZone;
+    }
+    if (typeof Intl[key] === "function") {
+        return Intl[key](locale, timeZone);
+    }
+
+    const defaults = { locale: undefined, timeZone: undefined };
+    const apply = Reflect.apply;
+    const construct = Reflect.construct;
+
+    // Fills in the locale at `index` and, for dates, the time zone of the options after it.
+    function withDefaults(args, index, dates) {
+        args = Array.prototype.slice.call(args);
+        if (args[index] === undefined && defaults.locale !== undefined) {
+            args[index] = defaults.locale;
+        }
+        const options = args[index + 1];
+        if (dates && defaults.timeZone !== undefined
+            && (options === undefined || (typeof options === "object" && options !== null
+                && options.timeZone === undefined))) {
+            args[index + 1] = Object.assign({}, options, { timeZone: defaults.timeZone });
+        }
+        return args;
+    }
+
+    function wrapConstructor(name, dates) {
+        const original = Intl[name];
+        if (typeof original !== "function") {
+            return;
+        }
+        Object.defineProperty(Intl, name, {
+            value: new Proxy(original, {
+                apply: (target, self, args) => apply(target, self, withDefaults(args, 0, dates)),
+                construct: (target, args, newTarget) =>
+                    construct(target, withDefaults(args, 0, dates), newTarget),
+            }),
+            writable: true,
+            configurable: true,
+        });
+    }
+
+    function wrapMethod(prototype, name, index, dates) {
+        const original = prototype[name];
+        if (typeof original !== "function") {
+            return;
+        }
+        Object.defineProperty(prototype, name, {
+            value: new Proxy(original, {
+                apply: (target, self, args) => apply(target, self, withDefaults(args, index, dates)),
+            }),
+            writable: true,
+            configurable: true,
+        });
+    }
+
+    wrapConstructor("DateTimeFormat", true);
+    for (const name of ["Collator", "DisplayNames", "ListFormat", "NumberFormat",
+        "PluralRules", "RelativeTimeFormat", "Segmenter"]) {
+        wrapConstructor(name, false);
+    }
+    wrapMethod(Date.prototype, "toLocaleString", 0, true);
+    wrapMethod(Date.prototype, "toLocaleDateString", 0, true);
+    wrapMethod(Date.prototype, "toLocaleTimeString", 0, true);
+    wrapMethod(Number.prototype, "toLocaleString", 0, false);
+    wrapMethod(BigInt.prototype, "toLocaleString", 0, false);
+    wrapMethod(String.prototype, "localeCompare", 1, false);
+    wrapMethod(String.prototype, "toLocaleLowerCase", 0, false);
+    wrapMethod(String.prototype, "toLocaleUpperCase", 0, false);
+
+    Object.defineProperty(Intl, key, {
+        value: (locale, timeZone) => {
+            if (locale !== undefined) {
+                defaults.locale = locale;
+            }
+            if (timeZone !== undefined) {
+                defaults.timeZone = timeZone;
+            }
+        },
+    });
+    return Intl[key](locale, timeZone);
+})"#;
+
+impl JSContext {
+    /// Sets the locale used by `Intl` and the `toLocaleString` family when a script
+    /// passes none, instead of the locale of the host.
+    ///
+    /// # Arguments
+    /// - `locale`: A BCP 47 language tag, such as `"en-US"`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_default_locale("de-DE").unwrap();
+    /// let result = ctx.evaluate_script("(1234.5).toLocaleString()", None).unwrap();
+    /// assert_eq!(result.as_string().unwrap(), "1.234,5");
+    /// ```
+    ///
+    /// # Errors
+    /// A `RangeError` if the locale is not a valid language tag.
+    pub fn set_default_locale(&self, locale: &str) -> JSResult<()> {
+        self.install_locale_defaults(JSValue::string(self, locale), self.undefined())
+    }
+
+    /// Sets the time zone used by `Intl.DateTimeFormat` and the `toLocaleString`
+    /// family of `Date` when a script passes none, instead of the time zone of the
+    /// host.
+    ///
+    /// The time zone of `Date` methods that take no locale, such as `getHours` or
+    /// `toString`, is the one of the process and is not affected; it can only be
+    /// changed for the whole process, e.g. with the `TZ` environment variable.
+    ///
+    /// # Arguments
+    /// - `time_zone`: An IANA time zone name, such as `"UTC"` or `"Europe/Paris"`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_default_time_zone("UTC").unwrap();
+    /// let result = ctx
+    ///     .evaluate_script("new Date(0).toLocaleTimeString('en-GB')", None)
+    ///     .unwrap();
+    /// assert_eq!(result.as_string().unwrap(), "00:00:00");
+    /// ```
+    ///
+    /// # Errors
+    /// A `RangeError` if the time zone is not supported.
+    pub fn set_default_time_zone(&self, time_zone: &str) -> JSResult<()> {
+        self.install_locale_defaults(self.undefined(), JSValue::string(self, time_zone))
+    }
+
+    fn install_locale_defaults(
+        &self,
+        locale: JSValue,
+        time_zone: JSValue,
+    ) -> JSResult<()> {
+        let install = self.evaluate_script(LOCALE_DEFAULTS, None)?.as_object()?;
+        install.call(None, &[locale, time_zone])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_time_zone() {
+        let ctx = JSContext::new();
+        ctx.set_default_time_zone("Asia/Tokyo").unwrap();
+        ctx.set_default_time_zone("utc").unwrap();
+
+        let result = ctx
+            .evaluate_script("new Intl.DateTimeFormat().resolvedOptions().timeZone", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "UTC");
+        let result = ctx
+            .evaluate_script("new Date(0).toLocaleTimeString('en-GB')", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "00:00:00");
+        let result = ctx
+            .evaluate_script(
+                "new Date(0).toLocaleTimeString('en-GB', { timeZone: 'Asia/Tokyo' })",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "09:00:00");
+
+        let result = ctx.evaluate_script(
+            "new Intl.DateTimeFormat() instanceof Intl.DateTimeFormat",
+            None,
+        );
+        assert!(result.unwrap().as_boolean());
+        assert!(ctx.set_default_time_zone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_default_locale() {
+        let ctx = JSContext::new();
+        ctx.set_default_locale("de-DE").unwrap();
+
+        let result = ctx
+            .evaluate_script("(1234.5).toLocaleString()", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "1.234,5");
+        let result = ctx
+            .evaluate_script("new Intl.NumberFormat().resolvedOptions().locale", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "de-DE");
+        let result = ctx
+            .evaluate_script("(1234.5).toLocaleString('en-US')", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "1,234.5");
+        assert!(ctx.set_default_locale("not a locale").is_err());
+    }
+}
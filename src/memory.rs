@@ -0,0 +1,164 @@
+//! Memory pressure notifications between the host and the contexts it embeds.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{JSContext, JSObject, JSResult};
+
+/// The level of a memory pressure notification, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemoryPressure {
+    /// Memory is no longer scarce.
+    Normal,
+    /// Memory is getting scarce; caches should be trimmed.
+    Warning,
+    /// Memory is critically low; everything that can be released should be.
+    Critical,
+}
+
+/// The heap statistics of a virtual machine, shared by the contexts of its group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapStatistics {
+    /// The size of the live objects on the heap, in bytes.
+    pub heap_size: usize,
+    /// The size of the memory reserved for the heap, in bytes.
+    pub heap_capacity: usize,
+    /// The size of the memory owned by objects outside of the heap, in bytes.
+    pub extra_memory_size: usize,
+    /// The number of objects on the heap.
+    pub object_count: usize,
+    /// The number of objects protected from garbage collection.
+    pub protected_object_count: usize,
+    /// The number of global objects, one per context.
+    pub global_object_count: usize,
+    /// The number of protected global objects, one per context with a handle.
+    pub protected_global_object_count: usize,
+}
+
+impl HeapStatistics {
+    /// Reads the statistics from the object of [`JSContext::get_memory_usage`].
+    fn from_usage(usage: &JSObject) -> JSResult<Self> {
+        let read = |name: &str| -> JSResult<usize> {
+            Ok(usage.get_property(name)?.as_number()? as usize)
+        };
+
+        Ok(Self {
+            heap_size: read("heapSize")?,
+            heap_capacity: read("heapCapacity")?,
+            extra_memory_size: read("extraMemorySize")?,
+            object_count: read("objectCount")?,
+            protected_object_count: read("protectedObjectCount")?,
+            global_object_count: read("globalObjectCount")?,
+            protected_global_object_count: read("protectedGlobalObjectCount")?,
+        })
+    }
+}
+
+type PressureHandler = dyn Fn(&JSContext, MemoryPressure);
+
+/// The handlers of [`JSContext::on_memory_pressure`], stored as a context extension.
+#[derive(Default)]
+struct PressureHandlers {
+    handlers: RefCell<Vec<Rc<PressureHandler>>>,
+}
+
+impl JSContext {
+    /// Returns the heap statistics of the virtual machine of the context, which the
+    /// context shares with the other contexts of its group.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let statistics = ctx.heap_statistics().unwrap();
+    /// assert!(statistics.heap_size <= statistics.heap_capacity);
+    /// ```
+    ///
+    /// # Errors
+    /// If the statistics cannot be read.
+    pub fn heap_statistics(&self) -> JSResult<HeapStatistics> {
+        HeapStatistics::from_usage(&self.get_memory_usage())
+    }
+
+    /// Adds a closure that is called by [`JSContext::notify_memory_pressure`], so
+    /// host caches tied to the context can be trimmed when memory is scarce.
+    ///
+    /// # Arguments
+    /// - `handler`: A closure called with the context and the pressure level.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{memory::MemoryPressure, JSContext};
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.on_memory_pressure(|_ctx, level| {
+    ///     if level >= MemoryPressure::Warning {
+    ///         println!("Trimming caches");
+    ///     }
+    /// });
+    /// ctx.notify_memory_pressure(MemoryPressure::Critical);
+    /// ```
+    pub fn on_memory_pressure<F>(&self, handler: F)
+    where
+        F: Fn(&JSContext, MemoryPressure) + 'static,
+    {
+        let handlers = match self.get_extension::<PressureHandlers>() {
+            Some(handlers) => handlers,
+            None => {
+                self.set_extension(PressureHandlers::default());
+                self.get_extension::<PressureHandlers>()
+                    .expect("Pressure handlers must be set")
+            }
+        };
+        handlers.handlers.borrow_mut().push(Rc::new(handler));
+    }
+
+    /// Notifies the context of memory pressure, e.g. from a signal of the operating
+    /// system. The closures of [`JSContext::on_memory_pressure`] are called in the
+    /// order they were added, then, above [`MemoryPressure::Normal`], the engine is
+    /// asked to collect garbage so the memory they released can be reclaimed.
+    ///
+    /// # Arguments
+    /// - `level`: The pressure level.
+    pub fn notify_memory_pressure(&self, level: MemoryPressure) {
+        if let Some(handlers) = self.get_extension::<PressureHandlers>() {
+            // Handlers may add handlers, so they are called from a copy of the list.
+            let handlers = handlers.handlers.borrow().clone();
+            for handler in handlers {
+                handler(self, level);
+            }
+        }
+
+        if level > MemoryPressure::Normal {
+            self.garbage_collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_memory_pressure() {
+        let ctx = JSContext::new();
+        ctx.notify_memory_pressure(MemoryPressure::Warning);
+
+        let levels = Rc::new(RefCell::new(Vec::new()));
+        let log = levels.clone();
+        ctx.on_memory_pressure(move |_ctx, level| log.borrow_mut().push(level));
+        let calls = Rc::new(Cell::new(0));
+        let count = calls.clone();
+        ctx.on_memory_pressure(move |_ctx, _level| count.set(count.get() + 1));
+
+        ctx.notify_memory_pressure(MemoryPressure::Critical);
+        ctx.notify_memory_pressure(MemoryPressure::Normal);
+        assert_eq!(
+            *levels.borrow(),
+            vec![MemoryPressure::Critical, MemoryPressure::Normal]
+        );
+        assert_eq!(calls.get(), 2);
+    }
+}
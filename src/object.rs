@@ -1,21 +1,105 @@
-use std::ops::Deref;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    ops::Deref,
+    rc::Rc,
+};
 
 use rust_jsc_sys::{
     JSContextRef, JSObjectCallAsConstructor, JSObjectCallAsFunction,
-    JSObjectCopyPropertyNames, JSObjectDeleteProperty, JSObjectDeletePropertyForKey,
-    JSObjectGetPrivate, JSObjectGetProperty, JSObjectGetPropertyAtIndex,
-    JSObjectGetPropertyForKey, JSObjectGetPrototype, JSObjectHasProperty,
-    JSObjectHasPropertyForKey, JSObjectIsConstructor, JSObjectIsFunction, JSObjectMake,
-    JSObjectRef, JSObjectSetAsyncIterator, JSObjectSetIterator, JSObjectSetPrivate,
-    JSObjectSetProperty, JSObjectSetPropertyAtIndex, JSObjectSetPropertyForKey,
-    JSObjectSetPrototype, JSPropertyNameArrayGetCount, JSPropertyNameArrayGetNameAtIndex,
-    JSPropertyNameArrayRef, JSPropertyNameArrayRelease, JSStringRetain, JSValueRef,
+    JSObjectCallAsFunctionCallback, JSObjectCopyPropertyNames, JSObjectDeleteProperty,
+    JSObjectDeletePropertyForKey, JSObjectGetPrivate, JSObjectGetProperty,
+    JSObjectGetPropertyAtIndex, JSObjectGetPropertyForKey, JSObjectGetPrototype,
+    JSObjectHasProperty, JSObjectHasPropertyForKey, JSObjectIsConstructor,
+    JSObjectIsFunction, JSObjectMake, JSObjectRef, JSObjectSetAsyncIterator,
+    JSObjectSetIterator, JSObjectSetPrivate, JSObjectSetProperty,
+    JSObjectSetPropertyAtIndex, JSObjectSetPropertyForKey, JSObjectSetPrototype,
+    JSPropertyNameArrayGetCount, JSPropertyNameArrayGetNameAtIndex,
+    JSPropertyNameArrayRef, JSPropertyNameArrayRelease, JSStringRetain,
+    JSValueIsObjectOfClass, JSValueRef,
 };
 
 use crate::{
-    JSContext, JSError, JSObject, JSResult, JSString, JSValue, PrivateData, PropertyDescriptor
+    audit,
+    buffer::SmallBuffer,
+    class::debug_assert_not_finalizing,
+    context::debug_assert_context_thread,
+    context_data::ContextData,
+    convert::{FromJSValue, IntoJSArgs, IntoJSValue},
+    trace::{CallDirection, CallSpan},
+    JSClass, JSContext, JSError, JSFunction, JSObject, JSPromise, JSResult, JSString,
+    JSValue, PrivateData, PropertyDefinition, PropertyDescriptor,
+    PropertyDescriptorBuilder, PropertyKind,
 };
 
+/// Lists the own properties of an object that hold functions, without calling getters,
+/// see [`JSObject::own_functions`].
+const OWN_FUNCTIONS: &str = r#"(function (object) {
+    const functions = [];
+    for (const name of Object.getOwnPropertyNames(object)) {
+        const descriptor = Object.getOwnPropertyDescriptor(object, name);
+        if (descriptor !== undefined && typeof descriptor.value === "function") {
+            functions.push([name, descriptor.value]);
+        }
+    }
+    return functions;
+})"#;
+
+/// A function held by an own property of an object, see [`JSObject::own_functions`].
+#[derive(Debug, Clone)]
+pub struct OwnFunction {
+    /// The name of the property.
+    pub name: String,
+    /// The number of parameters the function declares, its `length`.
+    pub arity: u32,
+    /// The function.
+    pub function: JSObject,
+}
+
+/// Replaces a property with an accessor pair that keeps its value in a closure and
+/// reports each assignment, see [`JSObject::watch_property`].
+const WATCH_PROPERTY: &str = r#"(function (object, name, notify) {
+    const descriptor = Object.getOwnPropertyDescriptor(object, name);
+    if (descriptor !== undefined && !descriptor.configurable) {
+        throw new TypeError(`Cannot watch property '${name}': it is not configurable`);
+    }
+    if (descriptor !== undefined && "value" in descriptor && !descriptor.writable) {
+        throw new TypeError(`Cannot watch property '${name}': it is read-only`);
+    }
+
+    let get;
+    let set;
+    if (descriptor !== undefined && !("value" in descriptor)) {
+        const getter = descriptor.get;
+        const setter = descriptor.set;
+        get = function () {
+            return getter === undefined ? undefined : getter.call(this);
+        };
+        set = function (value) {
+            const old = get.call(this);
+            if (setter !== undefined) {
+                setter.call(this, value);
+            }
+            notify(old, get.call(this));
+        };
+    } else {
+        let current = descriptor === undefined ? undefined : descriptor.value;
+        get = () => current;
+        set = (value) => {
+            const old = current;
+            current = value;
+            notify(old, value);
+        };
+    }
+
+    Object.defineProperty(object, name, {
+        get,
+        set,
+        enumerable: descriptor === undefined ? true : descriptor.enumerable,
+        configurable: true,
+    });
+})"#;
+
 pub struct JSPropertyNameIter {
     inner: JSPropertyNameArrayRef,
     index: usize,
@@ -49,6 +133,60 @@ impl Drop for JSPropertyNameIter {
     }
 }
 
+/// Creates a JavaScript iterator object whose `next` and `return` methods drive a
+/// Rust iterator. With `asynchronous`, the methods return promises.
+fn rust_iterator<I>(
+    ctx: &JSContext,
+    iterator: I,
+    asynchronous: bool,
+) -> JSResult<JSObject>
+where
+    I: Iterator + 'static,
+    I::Item: IntoJSValue,
+{
+    let iterator = Rc::new(RefCell::new(Some(iterator)));
+    let result = move |ctx: &JSContext, value: JSValue, done: bool| {
+        let result = JSObject::new(ctx);
+        result.set_property("value", &value, Default::default())?;
+        let done = JSValue::boolean(ctx, done);
+        result.set_property("done", &done, Default::default())?;
+        if !asynchronous {
+            return Ok(result.into());
+        }
+
+        let (promise, resolver) = JSPromise::new_pending(ctx)?;
+        resolver.resolve(None, &[result.into()])?;
+        Ok(promise.into())
+    };
+
+    let object = JSObject::new(ctx);
+    let state = iterator.clone();
+    let next = JSFunction::closure(ctx, move |ctx, _this, _arguments| {
+        let mut state = state.borrow_mut();
+        match state.as_mut().and_then(Iterator::next) {
+            Some(item) => result(ctx, item.try_into_js_value(ctx)?, false),
+            None => {
+                state.take();
+                result(ctx, JSValue::undefined(ctx), true)
+            }
+        }
+    });
+    object.set_property("next", &next.into(), Default::default())?;
+
+    let state = iterator;
+    let done = JSFunction::closure(ctx, move |ctx, _this, arguments| {
+        state.borrow_mut().take();
+        let value = arguments
+            .first()
+            .cloned()
+            .unwrap_or_else(|| JSValue::undefined(ctx));
+        result(ctx, value, true)
+    });
+    object.set_property("return", &done.into(), Default::default())?;
+
+    Ok(object)
+}
+
 impl JSObject {
     /// Creates a new `JSObject` object.
     ///
@@ -61,6 +199,35 @@ impl JSObject {
         Self { inner, value }
     }
 
+    /// Creates a new empty object with the specified prototype.
+    /// This function is the same as performing "Object.create(prototype)" from JavaScript,
+    /// e.g. to build prototype chains for host objects without defining a class.
+    ///
+    /// # Arguments
+    /// * `prototype` - The prototype of the object, or `None` for an object without
+    ///   a prototype, like `Object.create(null)`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let animal = JSObject::new(&ctx);
+    /// let dog = JSObject::create(&ctx, Some(&animal));
+    /// assert_eq!(dog.get_prototype(), animal.into());
+    /// ```
+    pub fn create(ctx: &JSContext, prototype: Option<&JSObject>) -> Self {
+        let object = Self::new(ctx);
+        let prototype = match prototype {
+            Some(prototype) => prototype.value.inner,
+            None => JSValue::null(ctx).inner,
+        };
+        unsafe {
+            JSObjectSetPrototype(ctx.inner, object.inner, prototype);
+        }
+        object
+    }
+
     pub fn from_ref(inner: JSObjectRef, ctx: JSContextRef) -> Self {
         let value = JSValue::new(inner, ctx);
         Self { inner, value }
@@ -111,8 +278,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(())
@@ -161,13 +327,79 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(())
     }
 
+    /// Makes the object iterable with iterators created from Rust iterators.
+    /// Each `object[Symbol.iterator]()` call creates a new Rust iterator with the
+    /// factory, and the `next` and `return` methods of the JavaScript iterator are
+    /// generated from it. The Rust iterator is dropped once it is exhausted or the
+    /// loop is left early.
+    ///
+    /// # Arguments
+    /// * `factory` - A closure that creates a Rust iterator.
+    /// * `descriptor` - The property descriptor to set on the object.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let object = JSObject::new(&ctx);
+    /// object
+    ///     .set_iterator_fn(|_ctx| (1..=3).map(f64::from), PropertyDescriptor::default())
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if the operation fails.
+    pub fn set_iterator_fn<F, I>(
+        &self,
+        factory: F,
+        descriptor: PropertyDescriptor,
+    ) -> JSResult<()>
+    where
+        F: Fn(&JSContext) -> I + 'static,
+        I: Iterator + 'static,
+        I::Item: IntoJSValue,
+    {
+        let ctx = JSContext::from(self.ctx);
+        let function = JSFunction::closure(&ctx, move |ctx, _this, _arguments| {
+            Ok(rust_iterator(ctx, factory(ctx), false)?.into())
+        });
+        self.set_iterator(&function.into(), descriptor)
+    }
+
+    /// Makes the object async iterable with iterators created from Rust iterators,
+    /// like [`JSObject::set_iterator_fn`] except that `next` and `return` return
+    /// promises resolved with the iterator results, for `for await` loops.
+    ///
+    /// # Arguments
+    /// * `factory` - A closure that creates a Rust iterator.
+    /// * `descriptor` - The property descriptor to set on the object.
+    ///
+    /// # Errors
+    /// Returns a `JSError` if the operation fails.
+    pub fn set_async_iterator_fn<F, I>(
+        &self,
+        factory: F,
+        descriptor: PropertyDescriptor,
+    ) -> JSResult<()>
+    where
+        F: Fn(&JSContext) -> I + 'static,
+        I: Iterator + 'static,
+        I::Item: IntoJSValue,
+    {
+        let ctx = JSContext::from(self.ctx);
+        let function = JSFunction::closure(&ctx, move |ctx, _this, _arguments| {
+            Ok(rust_iterator(ctx, factory(ctx), true)?.into())
+        });
+        self.set_async_iterator(&function.into(), descriptor)
+    }
+
     /// Tests whether an object has a given property.
     /// Returns true if the object has the property, otherwise false.
     /// This function is the same as performing "property in object" from JavaScript.
@@ -215,6 +447,7 @@ impl JSObject {
     /// # Returns
     /// Returns the value of the property if it exists, otherwise returns undefined.
     pub fn get_property(&self, name: impl Into<JSString>) -> JSResult<JSValue> {
+        audit::check_value(&self.value, "JSObject::get_property");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let value = unsafe {
             JSObjectGetProperty(
@@ -226,8 +459,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(JSValue::new(value, self.value.ctx))
@@ -262,8 +494,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(JSValue::new(result, self.value.ctx))
@@ -298,6 +529,7 @@ impl JSObject {
         value: &JSValue,
         descriptor: PropertyDescriptor,
     ) -> JSResult<()> {
+        audit::check_value(&self.value, "JSObject::set");
         let mut exception: JSValueRef = std::ptr::null_mut();
         unsafe {
             JSObjectSetPropertyForKey(
@@ -311,8 +543,7 @@ impl JSObject {
         }
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(())
@@ -342,14 +573,14 @@ impl JSObject {
     /// ```
     ///
     pub fn get(&self, key: &JSValue) -> JSResult<JSValue> {
+        audit::check_value(&self.value, "JSObject::get");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let result = unsafe {
             JSObjectGetPropertyForKey(self.ctx, self.inner, key.inner, &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(JSValue::new(result, self.ctx))
@@ -387,8 +618,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(result)
@@ -429,8 +659,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(result)
@@ -461,6 +690,7 @@ impl JSObject {
         value: &JSValue,
         descriptor: PropertyDescriptor,
     ) -> JSResult<()> {
+        audit::check_value(&self.value, "JSObject::set_property");
         let mut exception: JSValueRef = std::ptr::null_mut();
         unsafe {
             JSObjectSetProperty(
@@ -511,13 +741,320 @@ impl JSObject {
         }
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(())
     }
 
+    /// Defines an accessor property on the object, backed by native callbacks.
+    /// This function is the same as performing
+    /// \"Object.defineProperty(object, name, { get, set })\" from JavaScript.
+    /// The `#[getter]` and `#[setter]` macros generate suitable callbacks.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the property.
+    /// * `getter` - The callback called when the property is read.
+    /// * `setter` - The callback called when the property is written, if any.
+    /// * `descriptor` - The property descriptor; only enumerable and configurable apply.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// #[getter]
+    /// fn size(ctx: JSContext, this: JSObject) -> JSResult<JSValue> {
+    ///     this.get_property("_size")
+    /// }
+    ///
+    /// #[setter]
+    /// fn set_size(ctx: JSContext, this: JSObject, value: JSValue) -> JSResult<()> {
+    ///     this.set_property("_size", &value, PropertyDescriptor::default())
+    /// }
+    ///
+    /// let ctx = JSContext::new();
+    /// let object = JSObject::new(&ctx);
+    /// object
+    ///     .define_accessor("size", Some(size), Some(set_size), PropertyDescriptor::default())
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if the property cannot be defined.
+    pub fn define_accessor(
+        &self,
+        name: impl Into<JSString>,
+        getter: JSObjectCallAsFunctionCallback,
+        setter: JSObjectCallAsFunctionCallback,
+        descriptor: PropertyDescriptor,
+    ) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+        let name: JSString = name.into();
+
+        let getter = getter
+            .is_some()
+            .then(|| JSFunction::callback(&ctx, Some(name.clone()), getter).into());
+        let setter = setter
+            .is_some()
+            .then(|| JSFunction::callback(&ctx, Some(name.clone()), setter).into());
+        self.define_accessor_functions(name, getter, setter, descriptor)
+    }
+
+    /// Defines an accessor property with the given getter and setter functions, see
+    /// [`JSObject::define_accessor`].
+    pub(crate) fn define_accessor_functions(
+        &self,
+        name: JSString,
+        getter: Option<JSObject>,
+        setter: Option<JSObject>,
+        descriptor: PropertyDescriptor,
+    ) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+
+        let accessor = JSObject::new(&ctx);
+        if let Some(getter) = getter {
+            accessor.set_property("get", &getter, Default::default())?;
+        }
+        if let Some(setter) = setter {
+            accessor.set_property("set", &setter, Default::default())?;
+        }
+        self.define_with_descriptor(name, accessor, descriptor)
+    }
+
+    /// Defines a data or accessor property built with
+    /// [`PropertyDescriptorBuilder::try_build`].
+    /// This function is the same as performing
+    /// \"Object.defineProperty(object, name, descriptor)\" from JavaScript, with every
+    /// attribute set explicitly.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the property.
+    /// * `definition` - The attributes and the value or accessor functions.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let object = JSObject::new(&ctx);
+    /// let getter = ctx.evaluate_script("() => 42", None).unwrap().as_object().unwrap();
+    ///
+    /// let definition = PropertyDescriptorBuilder::new()
+    ///     .getter(&getter)
+    ///     .enumerable(false)
+    ///     .try_build()
+    ///     .unwrap();
+    /// object.define_property("answer", definition).unwrap();
+    /// assert_eq!(object.get_property("answer").unwrap().as_number().unwrap(), 42.0);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if the property cannot be defined, e.g. a `TypeError` if
+    /// it exists and is not configurable.
+    pub fn define_property(
+        &self,
+        name: impl Into<JSString>,
+        definition: PropertyDefinition,
+    ) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+        let PropertyDefinition { descriptor, kind } = definition;
+        let (getter, setter) = match kind {
+            PropertyKind::Accessor { getter, setter } => (getter, setter),
+            PropertyKind::Data(value) => {
+                let data = JSObject::new(&ctx);
+                if let Some(value) = value {
+                    data.set_property("value", &value, Default::default())?;
+                }
+                let writable = JSValue::boolean(&ctx, descriptor.is_writable());
+                data.set_property("writable", &writable, Default::default())?;
+                return self.define_with_descriptor(name.into(), data, descriptor);
+            }
+        };
+        self.define_accessor_functions(name.into(), getter, setter, descriptor)
+    }
+
+    /// Calls `Object.defineProperty` with `fields` completed by the enumerable and
+    /// configurable attributes of `descriptor`.
+    fn define_with_descriptor(
+        &self,
+        name: JSString,
+        fields: JSObject,
+        descriptor: PropertyDescriptor,
+    ) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+        let enumerable = JSValue::boolean(&ctx, descriptor.is_enumerable());
+        fields.set_property("enumerable", &enumerable, Default::default())?;
+        let configurable = JSValue::boolean(&ctx, descriptor.is_configurable());
+        fields.set_property("configurable", &configurable, Default::default())?;
+
+        let object = ctx.global_object().get_property("Object")?.as_object()?;
+        let define_property = object.get_property("defineProperty")?.as_object()?;
+        define_property.call(
+            Some(&object),
+            &[
+                self.value.clone(),
+                JSValue::string(&ctx, name),
+                fields.into(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Defines read-only, non-configurable, enumerable constants on the object in
+    /// one call, such as the members of an enum-like host API.
+    ///
+    /// # Arguments
+    /// * `constants` - The names and values of the constants.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let level = JSObject::new(&ctx);
+    /// level
+    ///     .define_constants([("DEBUG", 10), ("INFO", 20), ("ERROR", 40)])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if one of the constants cannot be defined; the constants
+    /// before it stay defined.
+    pub fn define_constants<'a, V>(
+        &self,
+        constants: impl IntoIterator<Item = (&'a str, V)>,
+    ) -> JSResult<()>
+    where
+        V: IntoJSValue,
+    {
+        let ctx = JSContext::from(self.value.ctx);
+        let descriptor = PropertyDescriptorBuilder::new()
+            .writable(false)
+            .configurable(false)
+            .build();
+        for (name, value) in constants {
+            self.set_property(name, &value.try_into_js_value(&ctx)?, descriptor)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calls `on_change` with the old and the new value whenever a script assigns
+    /// the property, so host code can react to mutations without polling.
+    ///
+    /// The property is replaced by an accessor pair that keeps its current value and
+    /// enumerability, and calls the previous getter and setter if it already was an
+    /// accessor. Watching a property twice calls both watchers. The callback runs
+    /// after the value is stored; an error it returns is thrown to the script.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the property, which need not exist yet.
+    /// * `on_change` - The callback, called with the context, the old value and the
+    ///   new value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let global = ctx.global_object();
+    /// global
+    ///     .watch_property("config", |_ctx, old, new| {
+    ///         println!("config changed from {:?} to {:?}", old, new);
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ctx.evaluate_script("config = { debug: true }", None).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the property is not configurable or is read-only.
+    pub fn watch_property<F>(
+        &self,
+        name: impl Into<JSString>,
+        on_change: F,
+    ) -> JSResult<()>
+    where
+        F: Fn(&JSContext, &JSValue, &JSValue) -> JSResult<()> + 'static,
+    {
+        let ctx = JSContext::from(self.value.ctx);
+        let notify = JSFunction::closure(&ctx, move |ctx, _this, arguments| {
+            let undefined = ctx.undefined();
+            let old = arguments.first().unwrap_or(&undefined);
+            let new = arguments.get(1).unwrap_or(&undefined);
+            on_change(ctx, old, new)?;
+            Ok(ctx.undefined())
+        });
+
+        let watch = ContextData::shim(&ctx, "watchProperty", WATCH_PROPERTY)?;
+        watch.call(
+            None,
+            &[
+                self.value.clone(),
+                JSValue::string(&ctx, name),
+                notify.into(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the own properties of the object that hold functions, with their
+    /// arities, e.g. to discover the lifecycle hooks a script exports. Non-enumerable
+    /// properties are included, accessors are skipped without calling their getters,
+    /// and symbol keys are ignored.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let plugin = ctx
+    ///     .evaluate_script("({ name: 'logger', onStart(config) {}, onStop() {} })", None)
+    ///     .unwrap()
+    ///     .as_object()
+    ///     .unwrap();
+    /// for hook in plugin.own_functions().unwrap() {
+    ///     println!("{} takes {} arguments", hook.name, hook.arity);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while listing the properties, e.g. by a proxy.
+    pub fn own_functions(&self) -> JSResult<Vec<OwnFunction>> {
+        let ctx = JSContext::from(self.value.ctx);
+        let list = ContextData::shim(&ctx, "ownFunctions", OWN_FUNCTIONS)?;
+        let entries = list
+            .call(None, std::slice::from_ref(&self.value))?
+            .as_object()?;
+        let length = entries.get_property("length")?.as_number()? as u32;
+
+        let mut functions = Vec::with_capacity(length as usize);
+        for index in 0..length {
+            let entry = entries.get_property_at_index(index)?.as_object()?;
+            let function = entry.get_property_at_index(1)?.as_object()?;
+            functions.push(OwnFunction {
+                name: entry.get_property_at_index(0)?.as_string()?.to_string(),
+                arity: function.get_property("length")?.as_number()? as u32,
+                function,
+            });
+        }
+        Ok(functions)
+    }
+
+    /// Returns the names of the own properties of the object that hold functions,
+    /// see [`JSObject::own_functions`].
+    ///
+    /// # Errors
+    /// If an exception is thrown while listing the properties, e.g. by a proxy.
+    pub fn own_function_names(&self) -> JSResult<Vec<String>> {
+        Ok(self
+            .own_functions()?
+            .into_iter()
+            .map(|function| function.name)
+            .collect())
+    }
+
     /// Deletes a property from an object where the key is a JSString
     /// Returns true if the delete operation succeeds, otherwise false
     /// (for example, if the property is not configurable).\n
@@ -554,8 +1091,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(result)
@@ -593,6 +1129,89 @@ impl JSObject {
         }
     }
 
+    /// Copies the enumerable properties of the object into a `HashMap`.
+    /// Values are not converted, see [`JSValue::to_plain`] for a deep conversion.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let object = ctx.evaluate_script("({ a: 1, b: 'two' })", None).unwrap();
+    /// let map = object.as_object().unwrap().to_hash_map().unwrap();
+    /// assert_eq!(map["a"].as_number().unwrap(), 1.0);
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading a property, e.g. by a getter.
+    /// A `JSError` will be returned.
+    pub fn to_hash_map(&self) -> JSResult<HashMap<String, JSValue>> {
+        self.get_property_names()
+            .map(|name| Ok((name.to_string(), self.get_property(name)?)))
+            .collect()
+    }
+
+    /// Copies the enumerable properties of the object into a `BTreeMap`,
+    /// sorted by name.
+    ///
+    /// # Errors
+    /// If an exception is thrown while reading a property, e.g. by a getter.
+    /// A `JSError` will be returned.
+    pub fn to_btree_map(&self) -> JSResult<BTreeMap<String, JSValue>> {
+        self.get_property_names()
+            .map(|name| Ok((name.to_string(), self.get_property(name)?)))
+            .collect()
+    }
+
+    /// Creates a plain object with a property for each entry of the map.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let mut map = HashMap::new();
+    /// map.insert("answer".to_string(), JSValue::number(&ctx, 42.0));
+    /// let object = JSObject::from_hash_map(&ctx, &map).unwrap();
+    /// assert_eq!(object.get_property("answer").unwrap().as_number().unwrap(), 42.0);
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while setting a property.
+    /// A `JSError` will be returned.
+    pub fn from_hash_map(
+        ctx: &JSContext,
+        map: &HashMap<String, JSValue>,
+    ) -> JSResult<Self> {
+        Self::from_entries(ctx, map)
+    }
+
+    /// Creates a plain object with a property for each entry of the map,
+    /// defined in the order of the map.
+    ///
+    /// # Errors
+    /// If an exception is thrown while setting a property.
+    /// A `JSError` will be returned.
+    pub fn from_btree_map(
+        ctx: &JSContext,
+        map: &BTreeMap<String, JSValue>,
+    ) -> JSResult<Self> {
+        Self::from_entries(ctx, map)
+    }
+
+    fn from_entries<'a>(
+        ctx: &JSContext,
+        entries: impl IntoIterator<Item = (&'a String, &'a JSValue)>,
+    ) -> JSResult<Self> {
+        let object = Self::new(ctx);
+        for (name, value) in entries {
+            object.set_property(name.as_str(), value, Default::default())?;
+        }
+
+        Ok(object)
+    }
+
     /// Gets an object's prototype.
     /// This function is the same as performing "Object.getPrototypeOf(object)" from JavaScript.
     ///
@@ -700,6 +1319,77 @@ impl JSObject {
         Some(data_ptr)
     }
 
+    /// Returns `true` if the object was created from the given class, or from a
+    /// class that inherits from it.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let class = JSClass::builder("Test").build().unwrap();
+    /// let object = class.object::<i32>(&ctx, Some(Box::new(42)));
+    /// assert!(object.is_instance_of_class(&class));
+    /// assert!(!JSObject::new(&ctx).is_instance_of_class(&class));
+    /// ```
+    pub fn is_instance_of_class(&self, class: &JSClass) -> bool {
+        unsafe { JSValueIsObjectOfClass(self.value.ctx, self.value.inner, class.inner) }
+    }
+
+    /// Returns a reference to the private data of the object, after checking that
+    /// the object was created from `class`.
+    ///
+    /// # Safety
+    /// The private data of every instance of `class`, including instances of classes
+    /// that inherit from it, must be a `Box<T>` of this `T`. Classes keep no record
+    /// of the type, so only the class is checked; any other type reinterprets the
+    /// data.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let class = JSClass::builder("Counter").build().unwrap();
+    /// let other = JSClass::builder("Other").build().unwrap();
+    /// let object = class.object::<i32>(&ctx, Some(Box::new(42)));
+    ///
+    /// // Safety: every `Counter` object holds an `i32`.
+    /// assert_eq!(unsafe { object.downcast_ref::<i32>(&class) }, Some(&42));
+    /// assert_eq!(unsafe { object.downcast_ref::<i32>(&other) }, None);
+    /// ```
+    ///
+    /// # Returns
+    /// The private data, or `None` if the object is not an instance of the class
+    /// or has no private data.
+    pub unsafe fn downcast_ref<T>(&self, class: &JSClass) -> Option<&T> {
+        if !self.is_instance_of_class(class) {
+            return None;
+        }
+
+        let data_ptr = JSObjectGetPrivate(self.inner) as *const T;
+        data_ptr.as_ref()
+    }
+
+    /// Returns a mutable reference to the private data of the object, after checking
+    /// that the object was created from `class`.
+    ///
+    /// # Safety
+    /// As for [`JSObject::downcast_ref`], and other handles to the same object must
+    /// not access the private data while the reference is alive.
+    ///
+    /// # Returns
+    /// The private data, or `None` if the object is not an instance of the class
+    /// or has no private data.
+    pub unsafe fn downcast_mut<T>(&mut self, class: &JSClass) -> Option<&mut T> {
+        if !self.is_instance_of_class(class) {
+            return None;
+        }
+
+        let data_ptr = JSObjectGetPrivate(self.inner) as *mut T;
+        data_ptr.as_mut()
+    }
+
     /// Tests whether an object is a constructor.
     ///
     /// # Example
@@ -756,8 +1446,17 @@ impl JSObject {
     /// # Errors
     /// Returns a `JSError` if the operation fails.
     pub fn call_as_constructor(&self, args: &[JSValue]) -> JSResult<Self> {
+        audit::check_value(&self.value, "JSObject::call_as_constructor");
+        debug_assert_not_finalizing("JSObject::call_as_constructor");
+        debug_assert_context_thread(self.ctx, "JSObject::call_as_constructor");
+        let _span = CallSpan::enter(
+            &JSContext::from(self.ctx),
+            CallDirection::NativeToJs,
+            self,
+            args.len(),
+        );
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
         let result = unsafe {
             JSObjectCallAsConstructor(
                 self.value.ctx,
@@ -769,8 +1468,7 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(JSObject::from_ref(result, self.value.ctx))
@@ -797,8 +1495,17 @@ impl JSObject {
     /// # Errors
     /// Returns a `JSError` if the operation fails.
     pub fn call(&self, this: Option<&JSObject>, args: &[JSValue]) -> JSResult<JSValue> {
+        audit::check_value(&self.value, "JSObject::call");
+        debug_assert_not_finalizing("JSObject::call");
+        debug_assert_context_thread(self.ctx, "JSObject::call");
+        let _span = CallSpan::enter(
+            &JSContext::from(self.ctx),
+            CallDirection::NativeToJs,
+            self,
+            args.len(),
+        );
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
         let this_object = this.map_or(std::ptr::null_mut(), |this| this.inner);
         let result = unsafe {
             JSObjectCallAsFunction(
@@ -812,12 +1519,55 @@ impl JSObject {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.value.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.value.ctx));
         }
 
         Ok(JSValue::new(result, self.value.ctx))
     }
+
+    /// Calls an object as a function with arguments converted from Rust values, and
+    /// converts the result, see [`JSObject::call`].
+    ///
+    /// # Arguments
+    /// * `this` - The object to use as `this` when calling the function.
+    /// * `arguments` - A tuple of values convertible to JavaScript values.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let pad = ctx.evaluate_script("(s, n) => s.padStart(n)", None).unwrap();
+    /// let padded: String = pad.as_object().unwrap().call_with(None, ("7", 3)).unwrap();
+    /// assert_eq!(padded, "  7");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `JSError` if an argument cannot be converted, the call throws, or
+    /// the result cannot be converted to `R`.
+    pub fn call_with<A, R>(&self, this: Option<&JSObject>, arguments: A) -> JSResult<R>
+    where
+        A: IntoJSArgs,
+        R: FromJSValue,
+    {
+        let ctx = JSContext::from(self.value.ctx);
+        let arguments = arguments.into_js_args(&ctx)?;
+        R::from_js_value(&self.call(this, &arguments)?)
+    }
+
+    /// Calls an object as a constructor with arguments converted from Rust values,
+    /// see [`JSObject::call_as_constructor`].
+    ///
+    /// # Arguments
+    /// * `arguments` - A tuple of values convertible to JavaScript values.
+    ///
+    /// # Errors
+    /// Returns a `JSError` if an argument cannot be converted or the constructor
+    /// throws.
+    pub fn call_as_constructor_with(&self, arguments: impl IntoJSArgs) -> JSResult<Self> {
+        let ctx = JSContext::from(self.value.ctx);
+        self.call_as_constructor(&arguments.into_js_args(&ctx)?)
+    }
 }
 
 impl std::fmt::Debug for JSObject {
@@ -848,11 +1598,277 @@ impl From<JSObject> for JSObjectRef {
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, rc::Rc};
 
     use crate::{self as rust_jsc, JSString};
-    use rust_jsc_macros::callback;
+    use rust_jsc_macros::{callback, getter, setter};
+
+    use crate::{
+        JSClass, JSContext, JSError, JSFunction, JSObject, JSResult, JSValue,
+        PropertyDescriptor, PropertyDescriptorBuilder,
+    };
+
+    #[test]
+    fn test_hash_map() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script("({ a: 1, b: 'two', nested: { c: true } })", None)
+            .unwrap();
+        let object = value.as_object().unwrap();
+
+        let map = object.to_hash_map().unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["b"].as_string().unwrap().to_string(), "two");
+        assert!(map["nested"].is_object());
+
+        let names: Vec<_> = object.to_btree_map().unwrap().into_keys().collect();
+        assert_eq!(names, ["a", "b", "nested"]);
+
+        let copy = JSObject::from_hash_map(&ctx, &map).unwrap();
+        assert_eq!(copy.get_property("a").unwrap().as_number().unwrap(), 1.0);
+        assert!(copy.get_property("nested").unwrap() == map["nested"]);
+    }
+
+    #[test]
+    fn test_downcast() {
+        let ctx = JSContext::new();
+        let class = JSClass::builder("Counter").build().unwrap();
+        let other = JSClass::builder("Other").build().unwrap();
+
+        let mut object = class.object::<u32>(&ctx, Some(Box::new(1)));
+        assert!(object.is_instance_of_class(&class));
+        assert!(!object.is_instance_of_class(&other));
+        unsafe {
+            assert!(object.downcast_ref::<u32>(&other).is_none());
+
+            *object.downcast_mut::<u32>(&class).unwrap() += 1;
+            assert_eq!(object.downcast_ref::<u32>(&class), Some(&2));
+
+            let mut plain = JSObject::new(&ctx);
+            assert!(plain.downcast_mut::<u32>(&class).is_none());
+        }
+    }
+
+    #[test]
+    fn test_define_accessor() {
+        #[getter]
+        fn size(_ctx: JSContext, this: JSObject) -> JSResult<JSValue> {
+            this.get_property("_size")
+        }
+
+        #[setter]
+        fn set_size(ctx: JSContext, this: JSObject, value: JSValue) -> JSResult<()> {
+            let size = value.as_number()? * 2.0;
+            this.set_property("_size", &JSValue::number(&ctx, size), Default::default())
+        }
+
+        let ctx = JSContext::new();
+        let object = JSObject::new(&ctx);
+        object
+            .define_accessor(
+                "size",
+                Some(size),
+                Some(set_size),
+                PropertyDescriptor::default(),
+            )
+            .unwrap();
+        ctx.global_object()
+            .set_property("object", &object, Default::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script("object.size = 21; object.size", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 42.0);
+
+        let result = ctx
+            .evaluate_script(
+                "typeof Object.getOwnPropertyDescriptor(object, 'size').get",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "function");
+    }
+
+    #[test]
+    fn test_watch_property() {
+        let ctx = JSContext::new();
+        let global = ctx.global_object();
+        global
+            .set_property("config", &JSValue::number(&ctx, 1.0), Default::default())
+            .unwrap();
+
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let seen = changes.clone();
+        global
+            .watch_property("config", move |_ctx, old, new| {
+                seen.borrow_mut()
+                    .push((old.as_number().unwrap(), new.as_number().unwrap()));
+                Ok(())
+            })
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                "config = 2; config += 3; config + '|' + Object.keys(globalThis).includes('config')",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "5|true");
+        assert_eq!(changes.borrow().as_slice(), &[(1.0, 2.0), (2.0, 5.0)]);
+
+        global
+            .watch_property("config", |ctx, _old, new| {
+                if new.as_number()? < 0.0 {
+                    return Err(JSError::new_typ(ctx, "config must be positive")?);
+                }
+                Ok(())
+            })
+            .unwrap();
+        let result = ctx
+            .evaluate_script("try { config = -1 } catch (e) { e.message }", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "config must be positive");
+        assert_eq!(changes.borrow().last(), Some(&(5.0, -1.0)));
+
+        let frozen = JSObject::new(&ctx);
+        frozen.define_constants([("fixed", 1)]).unwrap();
+        assert!(frozen
+            .watch_property("fixed", |_ctx, _old, _new| Ok(()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_own_functions() {
+        let ctx = JSContext::new();
+        let plugin = ctx
+            .evaluate_script(
+                "const plugin = { name: 'logger', onStart(config, logger) {}, onStop() {} };
+                 Object.defineProperty(plugin, 'hidden', { value: (a) => a });
+                 Object.defineProperty(plugin, 'lazy', { get() { throw new Error('called'); } });
+                 plugin",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let functions = plugin.own_functions().unwrap();
+        let arities: Vec<(&str, u32)> = functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.arity))
+            .collect();
+        assert_eq!(arities, [("onStart", 2), ("onStop", 0), ("hidden", 1)]);
+        assert!(functions[0].function.is_function());
+
+        assert_eq!(
+            plugin.own_function_names().unwrap(),
+            ["onStart", "onStop", "hidden"]
+        );
+        assert!(JSObject::new(&ctx).own_function_names().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_define_constants() {
+        let ctx = JSContext::new();
+        let level = JSObject::new(&ctx);
+        level
+            .define_constants([("DEBUG", 10), ("INFO", 20), ("ERROR", 40)])
+            .unwrap();
+        level.define_constants([("DEFAULT", "INFO")]).unwrap();
+        ctx.global_object()
+            .set_property("Level", &level, Default::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                "Level.DEBUG = 0; delete Level.INFO; Level.DEBUG + Level.INFO",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 30.0);
+        let result = ctx
+            .evaluate_script("Object.keys(Level).join()", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "DEBUG,INFO,ERROR,DEFAULT");
+        let result = ctx
+            .evaluate_script(
+                "'use strict'; try { Level.ERROR = 1 } catch (e) { e.name }",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "TypeError");
+    }
+
+    #[test]
+    fn test_define_accessor_read_only() {
+        #[getter]
+        fn answer(ctx: JSContext, _this: JSObject) -> JSResult<JSValue> {
+            Ok(JSValue::number(&ctx, 42.0))
+        }
+
+        let ctx = JSContext::new();
+        let object = JSObject::new(&ctx);
+        object
+            .define_accessor("answer", Some(answer), None, PropertyDescriptor::default())
+            .unwrap();
+
+        assert_eq!(
+            object.get_property("answer").unwrap().as_number().unwrap(),
+            42.0
+        );
+    }
+
+    #[test]
+    fn test_define_property() {
+        let ctx = JSContext::new();
+        let object = JSObject::new(&ctx);
+        let value = JSValue::number(&ctx, 1.0);
+        let definition = PropertyDescriptorBuilder::new()
+            .value(&value)
+            .writable(false)
+            .enumerable(false)
+            .try_build()
+            .unwrap();
+        object.define_property("fixed", definition).unwrap();
+
+        let getter = ctx
+            .evaluate_script("(function () { return this.fixed + 1; })", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let definition = PropertyDescriptorBuilder::new()
+            .getter(&getter)
+            .configurable(false)
+            .try_build()
+            .unwrap();
+        object.define_property("next", definition).unwrap();
 
-    use crate::{JSContext, JSFunction, JSObject, JSResult, JSValue, PropertyDescriptor};
+        ctx.global_object()
+            .set_property("object", &object.clone().into(), Default::default())
+            .unwrap();
+        let result = ctx
+            .evaluate_script(
+                "const fixed = Object.getOwnPropertyDescriptor(object, 'fixed');
+                 const next = Object.getOwnPropertyDescriptor(object, 'next');
+                 [fixed.writable, fixed.enumerable, fixed.configurable,
+                  typeof next.get, next.set, next.enumerable, next.configurable,
+                  object.next].join()",
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "false,false,true,function,,true,false,2"
+        );
+
+        let definition = PropertyDescriptorBuilder::new()
+            .value(&value)
+            .try_build()
+            .unwrap();
+        let error = object.define_property("next", definition).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
 
     #[test]
     fn test_object() {
@@ -1106,6 +2122,31 @@ mod tests {
         assert_eq!(object.get_prototype(), prototype.into());
     }
 
+    #[test]
+    fn test_object_create() {
+        let ctx = JSContext::new();
+        let animal = ctx
+            .evaluate_script(
+                "({ speak() { return this.name + ' makes a sound'; } })",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let dog = JSObject::create(&ctx, Some(&animal));
+        dog.set_property("name", &JSValue::string(&ctx, "Rex"), Default::default())
+            .unwrap();
+        assert_eq!(dog.get_prototype(), animal.clone().into());
+
+        let speak = dog.get_property("speak").unwrap().as_object().unwrap();
+        let sound = speak.call(Some(&dog), &[]).unwrap();
+        assert_eq!(sound.as_string().unwrap(), "Rex makes a sound");
+
+        let bare = JSObject::create(&ctx, None);
+        assert!(bare.get_prototype().is_null());
+        assert!(!bare.has_property("toString"));
+    }
+
     // #[test]
     // fn test_object_set_private_data() {
     //     let ctx = JSContext::new();
@@ -1293,4 +2334,51 @@ mod tests {
 
         assert_eq!(result.is_ok(), true);
     }
+
+    #[test]
+    fn test_iterator_fn() {
+        let ctx = JSContext::new();
+        let object = JSObject::new(&ctx);
+        object
+            .set_iterator_fn(
+                |_ctx| ["a", "b", "c"].into_iter(),
+                PropertyDescriptor::default(),
+            )
+            .unwrap();
+        ctx.global_object()
+            .set_property("letters", &object, PropertyDescriptor::default())
+            .unwrap();
+
+        let result = ctx
+            .evaluate_script(
+                "let early = []; for (const l of letters) { early.push(l); break; } \
+                 [...letters].join('') + early.join('')",
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "abca");
+
+        let numbers = JSObject::new(&ctx);
+        numbers
+            .set_async_iterator_fn(
+                |_ctx| (1..=3).map(f64::from),
+                PropertyDescriptor::default(),
+            )
+            .unwrap();
+        ctx.global_object()
+            .set_property("numbers", &numbers, PropertyDescriptor::default())
+            .unwrap();
+
+        let promise = ctx
+            .evaluate_script(
+                "(async () => { let sum = 0; for await (const n of numbers) sum += n; \
+                 return sum; })()",
+                None,
+            )
+            .unwrap();
+        let sum = ctx
+            .run_until_settled(&promise, std::time::Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(sum.as_number().unwrap(), 6.0);
+    }
 }
@@ -0,0 +1,455 @@
+//! RFC 6902 JSON Patch and RFC 7386 JSON Merge Patch, applied in place to live
+//! objects, e.g. to synchronize state pushed by the host with scripts.
+
+use crate::{
+    context_data::ContextData, pointer::pointer_helpers, JSContext, JSObject, JSResult,
+    JSValue,
+};
+
+/// Applies a JSON Patch to an object. On failure, the operations already applied are
+/// undone and a `TypeError` naming the failing operation is thrown. Members are
+/// defined as own data properties, so a `/__proto__` path never replaces a prototype.
+/// It is called with the helpers of [`pointer_helpers`] first.
+const JSON_PATCH: &str = r#"(function ({ isObject, arrayIndex }, document, patch) {
+    const hasOwn = (object, key) => Object.prototype.hasOwnProperty.call(object, key);
+    const isPlain = (v) => {
+        if (typeof v !== "object" || v === null) {
+            return false;
+        }
+        const prototype = Object.getPrototypeOf(v);
+        return prototype === Object.prototype || prototype === null;
+    };
+    const clone = (v) => {
+        if (Array.isArray(v)) {
+            return v.map(clone);
+        }
+        if (isPlain(v)) {
+            return Object.fromEntries(Object.keys(v).map((key) => [key, clone(v[key])]));
+        }
+        return v;
+    };
+    const equal = (a, b) => {
+        if (a === b) {
+            return true;
+        }
+        if (!isObject(a) || !isObject(b) || Array.isArray(a) !== Array.isArray(b)) {
+            return false;
+        }
+        if (Array.isArray(a)) {
+            return a.length === b.length && a.every((value, index) => equal(value, b[index]));
+        }
+        const keys = Object.keys(a);
+        return keys.length === Object.keys(b).length
+            && keys.every((key) => hasOwn(b, key) && equal(a[key], b[key]));
+    };
+
+    if (!Array.isArray(patch)) {
+        throw new TypeError("A JSON patch must be an array of operations");
+    }
+
+    const undo = [];
+    const setProperty = (parent, key, value) => {
+        const old = Object.getOwnPropertyDescriptor(parent, key);
+        Object.defineProperty(parent, key, { value, writable: true, enumerable: true, configurable: true });
+        undo.push(() => {
+            if (old !== undefined) {
+                Object.defineProperty(parent, key, old);
+            } else {
+                delete parent[key];
+            }
+        });
+    };
+    const deleteProperty = (parent, key) => {
+        const old = Object.getOwnPropertyDescriptor(parent, key);
+        delete parent[key];
+        undo.push(() => {
+            Object.defineProperty(parent, key, old);
+        });
+        return old.value;
+    };
+    const splice = (array, index, remove, ...insert) => {
+        const removed = array.splice(index, remove, ...insert);
+        undo.push(() => array.splice(index, insert.length, ...removed));
+        return removed[0];
+    };
+
+    const apply = (operation, fail) => {
+        const parse = (member) => {
+            const pointer = operation[member];
+            if (typeof pointer !== "string" || (pointer !== "" && pointer[0] !== "/")) {
+                fail(`'${member}' is not a JSON pointer`);
+            }
+            if (pointer === "") {
+                return [];
+            }
+            return pointer.slice(1).split("/").map((token) => {
+                if (/~([^01]|$)/.test(token)) {
+                    fail(`'${pointer}' is not a valid JSON pointer`);
+                }
+                return token.replace(/~1/g, "/").replace(/~0/g, "~");
+            });
+        };
+        const get = (tokens) => {
+            let current = [document];
+            for (const token of tokens) {
+                const parent = current[0];
+                if (!isObject(parent)) {
+                    return undefined;
+                }
+                if (Array.isArray(parent)) {
+                    const index = arrayIndex(parent, token, false);
+                    if (index === -1) {
+                        return undefined;
+                    }
+                    current = [parent[index]];
+                } else if (hasOwn(parent, token)) {
+                    current = [parent[token]];
+                } else {
+                    return undefined;
+                }
+            }
+            return current;
+        };
+        const locate = (tokens, member) => {
+            if (tokens.length === 0) {
+                fail(`'${operation[member]}' is the root of the document`);
+            }
+            const parent = get(tokens.slice(0, -1));
+            if (parent === undefined || !isObject(parent[0])) {
+                fail(`the parent of '${operation[member]}' does not exist`);
+            }
+            return [parent[0], tokens[tokens.length - 1]];
+        };
+        const existing = (tokens, member) => {
+            const [parent, key] = locate(tokens, member);
+            const exists = Array.isArray(parent)
+                ? arrayIndex(parent, key, false) !== -1
+                : hasOwn(parent, key);
+            if (!exists) {
+                fail(`'${operation[member]}' does not exist`);
+            }
+            return Array.isArray(parent) ? [parent, Number(key)] : [parent, key];
+        };
+        const add = (tokens, value) => {
+            const [parent, key] = locate(tokens, "path");
+            if (!Array.isArray(parent)) {
+                setProperty(parent, key, value);
+                return;
+            }
+            const index = arrayIndex(parent, key, true);
+            if (index === -1) {
+                fail(`'${key}' is not an index of the array at '${operation.path}'`);
+            }
+            splice(parent, index, 0, value);
+        };
+        const remove = (tokens, member) => {
+            const [parent, key] = existing(tokens, member);
+            return Array.isArray(parent) ? splice(parent, key, 1) : deleteProperty(parent, key);
+        };
+        const value = () => {
+            if (!hasOwn(operation, "value")) {
+                fail("it has no 'value'");
+            }
+            return clone(operation.value);
+        };
+
+        switch (operation.op) {
+            case "add":
+                add(parse("path"), value());
+                break;
+            case "remove":
+                remove(parse("path"), "path");
+                break;
+            case "replace": {
+                const [parent, key] = existing(parse("path"), "path");
+                if (Array.isArray(parent)) {
+                    splice(parent, key, 1, value());
+                } else {
+                    setProperty(parent, key, value());
+                }
+                break;
+            }
+            case "move": {
+                const from = parse("from");
+                const path = parse("path");
+                if (operation.from === operation.path) {
+                    existing(from, "from");
+                    break;
+                }
+                if (operation.path.startsWith(operation.from + "/")) {
+                    fail(`'${operation.path}' is inside '${operation.from}'`);
+                }
+                add(path, remove(from, "from"));
+                break;
+            }
+            case "copy": {
+                const source = get(parse("from"));
+                if (source === undefined) {
+                    fail(`'${operation.from}' does not exist`);
+                }
+                add(parse("path"), clone(source[0]));
+                break;
+            }
+            case "test": {
+                const target = get(parse("path"));
+                if (target === undefined) {
+                    fail(`'${operation.path}' does not exist`);
+                }
+                if (!equal(target[0], value())) {
+                    fail(`the value at '${operation.path}' does not match`);
+                }
+                break;
+            }
+            default:
+                fail(`'${operation.op}' is not an operation`);
+        }
+    };
+
+    try {
+        patch.forEach((operation, index) => {
+            const fail = (message) => {
+                const op = isObject(operation) ? operation.op : operation;
+                throw new TypeError(`JSON patch operation ${index} (${op}) failed: ${message}`);
+            };
+            if (!isObject(operation)) {
+                fail("it is not an object");
+            }
+            apply(operation, fail);
+        });
+    } catch (error) {
+        while (undo.length > 0) {
+            undo.pop()();
+        }
+        throw error;
+    }
+})"#;
+
+/// Applies a JSON Merge Patch to an object, defining members as own data properties.
+/// It is called with the helpers of [`pointer_helpers`] first.
+const MERGE_PATCH: &str = r#"(function ({ define }, document, patch) {
+    const isMergeable = (v) => typeof v === "object" && v !== null && !Array.isArray(v);
+    const clone = (v) => {
+        if (Array.isArray(v)) {
+            return v.map(clone);
+        }
+        if (isMergeable(v)) {
+            return merge({}, v);
+        }
+        return v;
+    };
+    const merge = (target, patch) => {
+        for (const key of Object.keys(patch)) {
+            const value = patch[key];
+            if (value === null) {
+                delete target[key];
+            } else if (isMergeable(value)) {
+                if (!isMergeable(target[key]) || !Object.prototype.hasOwnProperty.call(target, key)) {
+                    define(target, key, {});
+                }
+                merge(target[key], value);
+            } else {
+                define(target, key, clone(value));
+            }
+        }
+        return target;
+    };
+
+    if (!isMergeable(patch)) {
+        throw new TypeError("A JSON merge patch applied to an object must be an object");
+    }
+    merge(document, patch);
+})"#;
+
+impl JSObject {
+    /// Applies an RFC 6902 JSON Patch to the object in place. The patch is applied
+    /// atomically: if an operation fails, the operations before it are undone.
+    /// Values from the patch are copied, so the object does not share them with it.
+    ///
+    /// # Arguments
+    /// - `patch`: The patch, an array of operations such as
+    ///   `{ op: "replace", path: "/count", value: 2 }`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let state = ctx.evaluate_script("globalThis.state = { items: [] }", None).unwrap();
+    /// let patch = JSValue::from_json(
+    ///     &ctx,
+    ///     r#"[{ "op": "add", "path": "/items/-", "value": "milk" }]"#,
+    /// );
+    /// state.as_object().unwrap().apply_json_patch(&patch).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the patch is not an array, an operation is malformed, refers
+    /// to a value that does not exist, replaces the object itself, or is a `test`
+    /// that does not match.
+    pub fn apply_json_patch(&self, patch: &JSValue) -> JSResult<()> {
+        self.apply_patch("jsonPatch", JSON_PATCH, patch)
+    }
+
+    /// Applies an RFC 7386 JSON Merge Patch to the object in place: `null` members
+    /// delete properties, object members are merged recursively, and other members
+    /// replace properties.
+    ///
+    /// # Arguments
+    /// - `patch`: The patch, an object such as `{ title: "Hello", draft: null }`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let state = ctx.evaluate_script("globalThis.state = { user: { name: 'Ada' } }", None).unwrap();
+    /// let patch = JSValue::from_json(&ctx, r#"{ "user": { "email": "ada@example.com" } }"#);
+    /// state.as_object().unwrap().apply_merge_patch(&patch).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if the patch is not an object, since a merge patch that is not
+    /// an object would replace the object itself.
+    pub fn apply_merge_patch(&self, patch: &JSValue) -> JSResult<()> {
+        self.apply_patch("mergePatch", MERGE_PATCH, patch)
+    }
+
+    fn apply_patch(
+        &self,
+        name: &'static str,
+        shim: &str,
+        patch: &JSValue,
+    ) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+        let helpers = pointer_helpers(&ctx)?;
+        let apply = ContextData::shim(&ctx, name, shim)?;
+        apply.call(None, &[helpers.into(), self.value.clone(), patch.clone()])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(value: &JSObject) -> String {
+        JSValue::from(value.clone())
+            .as_json_string(0)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let ctx = JSContext::new();
+        let state = ctx
+            .evaluate_script(
+                "({ items: ['milk'], user: { name: 'Ada' }, count: 1 })",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let patch = JSValue::from_json(
+            &ctx,
+            r#"[
+                { "op": "test", "path": "/count", "value": 1 },
+                { "op": "add", "path": "/items/0", "value": "eggs" },
+                { "op": "add", "path": "/items/-", "value": "tea" },
+                { "op": "replace", "path": "/count", "value": 3 },
+                { "op": "copy", "from": "/user", "path": "/owner" },
+                { "op": "move", "from": "/user/name", "path": "/name" },
+                { "op": "remove", "path": "/items/1" },
+                { "op": "test", "path": "/owner", "value": { "name": "Ada" } }
+            ]"#,
+        );
+        state.apply_json_patch(&patch).unwrap();
+        assert_eq!(
+            json(&state),
+            r#"{"items":["eggs","tea"],"user":{},"count":3,"owner":{"name":"Ada"},"name":"Ada"}"#
+        );
+    }
+
+    #[test]
+    fn test_json_patch_is_atomic() {
+        let ctx = JSContext::new();
+        let state = ctx
+            .evaluate_script("({ items: ['milk'], count: 1 })", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let before = json(&state);
+
+        for patch in [
+            r#"[{ "op": "add", "path": "/items/-", "value": 1 }, { "op": "test", "path": "/count", "value": 2 }]"#,
+            r#"[{ "op": "remove", "path": "/count" }, { "op": "remove", "path": "/missing" }]"#,
+            r#"[{ "op": "replace", "path": "/items/0", "value": 1 }, { "op": "add", "path": "/items/5", "value": 1 }]"#,
+            r#"[{ "op": "move", "from": "/items", "path": "/items/0" }]"#,
+            r#"[{ "op": "add", "path": "", "value": {} }]"#,
+            r#"[{ "op": "add", "path": "/a" }]"#,
+            r#"[{ "op": "frobnicate", "path": "/a" }]"#,
+            r#"{ "op": "add", "path": "/a", "value": 1 }"#,
+        ] {
+            let error = state
+                .apply_json_patch(&JSValue::from_json(&ctx, patch))
+                .unwrap_err();
+            assert_eq!(error.name().unwrap().to_string(), "TypeError", "{}", patch);
+            assert_eq!(json(&state), before, "{}", patch);
+        }
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let ctx = JSContext::new();
+        let state = ctx
+            .evaluate_script(
+                "({ title: 'Goodbye', author: { given: 'John', family: 'Doe' }, tags: ['a'] })",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let patch = JSValue::from_json(
+            &ctx,
+            r#"{ "title": "Hello", "author": { "family": null }, "tags": ["b"], "phone": "555" }"#,
+        );
+        state.apply_merge_patch(&patch).unwrap();
+        assert_eq!(
+            json(&state),
+            r#"{"title":"Hello","author":{"given":"John"},"tags":["b"],"phone":"555"}"#
+        );
+
+        let error = state
+            .apply_merge_patch(&JSValue::from_json(&ctx, "[1]"))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_patches_define_own_properties() {
+        let ctx = JSContext::new();
+        let state = ctx
+            .evaluate_script("({})", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let patch = JSValue::from_json(
+            &ctx,
+            r#"[{ "op": "add", "path": "/__proto__", "value": { "polluted": true } }]"#,
+        );
+        state.apply_json_patch(&patch).unwrap();
+        let patch = JSValue::from_json(&ctx, r#"{ "__proto__": { "merged": true } }"#);
+        state.apply_merge_patch(&patch).unwrap();
+
+        assert_eq!(
+            json(&state),
+            r#"{"__proto__":{"polluted":true,"merged":true}}"#
+        );
+        let prototype = state.get_prototype().as_object().unwrap();
+        assert!(!prototype.has_property("polluted"));
+        assert!(!prototype.has_property("merged"));
+    }
+}
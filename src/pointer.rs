@@ -0,0 +1,395 @@
+//! RFC 6901 JSON Pointers, such as `/servers/0/host`, to read and write values
+//! nested in objects and arrays.
+
+use crate::{
+    builtins::named_error, context_data::ContextData, JSArray, JSContext, JSObject,
+    JSResult, JSValue,
+};
+
+/// Evaluates to the helpers shared by JSON Pointers and JSON Patches, see
+/// [`pointer_helpers`]. Its `evaluate` reads or assigns the value a pointer refers
+/// to: reading returns `[value]`, or `undefined` when the value does not exist.
+/// Assigning throws when the parent does not exist or the last token is not an index
+/// of the parent array. Assignments define an own data property, so `/__proto__`
+/// and inherited setters are not invoked.
+const POINTER: &str = r#"(() => {
+    const isObject = (v) => (typeof v === "object" && v !== null) || typeof v === "function";
+    const define = (target, key, value) =>
+        Object.defineProperty(target, key, { value, writable: true, enumerable: true, configurable: true });
+    const arrayIndex = (array, token, append) => {
+        if (append && token === "-") {
+            return array.length;
+        }
+        if (!/^(0|[1-9][0-9]*)$/.test(token)) {
+            return -1;
+        }
+        const index = Number(token);
+        return index < array.length || (append && index === array.length) ? index : -1;
+    };
+    const child = (parent, token) => {
+        if (!isObject(parent)) {
+            return undefined;
+        }
+        if (Array.isArray(parent)) {
+            const index = arrayIndex(parent, token, false);
+            return index === -1 ? undefined : [parent[index]];
+        }
+        return Object.prototype.hasOwnProperty.call(parent, token) ? [parent[token]] : undefined;
+    };
+    const evaluate = (root, tokens, pointer, assign, value) => {
+        let current = [root];
+        const depth = assign ? tokens.length - 1 : tokens.length;
+        for (let i = 0; i < depth && current !== undefined; i++) {
+            current = child(current[0], tokens[i]);
+        }
+        if (!assign) {
+            return current;
+        }
+
+        const parent = current === undefined ? undefined : current[0];
+        if (!isObject(parent)) {
+            throw new TypeError(`Cannot set '${pointer}': its parent does not exist or is not an object`);
+        }
+        const token = tokens[depth];
+        if (Array.isArray(parent)) {
+            const index = arrayIndex(parent, token, true);
+            if (index === -1) {
+                throw new RangeError(`Cannot set '${pointer}': '${token}' is not an index of the array`);
+            }
+            define(parent, index, value);
+        } else {
+            define(parent, token, value);
+        }
+    };
+
+    return { __proto__: null, isObject, define, arrayIndex, evaluate };
+})()"#;
+
+/// A parsed RFC 6901 JSON Pointer: a list of reference tokens, where `""` is the
+/// whole document and `/a~1b/0` is the tokens `a/b` and `0`.
+///
+/// # Examples
+/// ```
+/// use rust_jsc::pointer::JsonPointer;
+///
+/// let pointer = JsonPointer::parse("/a~1b/0").unwrap();
+/// assert_eq!(pointer.tokens(), ["a/b", "0"]);
+/// assert_eq!(pointer.to_string(), "/a~1b/0");
+/// assert!(JsonPointer::parse("a/b").is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+impl JsonPointer {
+    /// Returns the pointer to the whole document, `""`.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parses a pointer.
+    ///
+    /// # Arguments
+    /// - `pointer`: The pointer, empty or starting with `/`.
+    ///
+    /// # Returns
+    /// `None` if the pointer does not start with `/` or has a `~` not followed by
+    /// `0` or `1`.
+    pub fn parse(pointer: &str) -> Option<Self> {
+        if pointer.is_empty() {
+            return Some(Self::root());
+        }
+
+        let tokens = pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape)
+            .collect::<Option<_>>()?;
+        Some(Self { tokens })
+    }
+
+    /// Returns the unescaped reference tokens.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Returns `true` for the pointer to the whole document.
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the pointer to the parent and the last token, or `None` for the root.
+    pub fn split_last(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        Some((
+            Self {
+                tokens: parent.to_vec(),
+            },
+            last,
+        ))
+    }
+
+    /// Appends a token.
+    ///
+    /// # Arguments
+    /// - `token`: The unescaped token, such as a property name or an index.
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// Returns the tokens as a JavaScript array of strings.
+    pub(crate) fn to_array(&self, ctx: &JSContext) -> JSResult<JSArray> {
+        let tokens: Vec<JSValue> = self
+            .tokens
+            .iter()
+            .map(|token| JSValue::string(ctx, token.as_str()))
+            .collect();
+        JSArray::new_array(ctx, &tokens)
+    }
+
+    /// Parses a pointer, or returns a `SyntaxError` to throw back to scripts.
+    pub(crate) fn parse_or_throw(ctx: &JSContext, pointer: &str) -> JSResult<Self> {
+        Self::parse(pointer).ok_or_else(|| {
+            named_error(
+                ctx,
+                "SyntaxError",
+                &format!("Invalid JSON pointer '{}'", pointer),
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+fn unescape(token: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(char) = chars.next() {
+        if char != '~' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => unescaped.push('~'),
+            Some('1') => unescaped.push('/'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+impl JSObject {
+    /// Returns the value a JSON Pointer refers to, starting from the object.
+    /// Only own properties are followed, and array indexes must be in bounds.
+    ///
+    /// # Arguments
+    /// - `pointer`: The pointer, such as `"/servers/0/host"`. `""` is the object.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let config = ctx
+    ///     .evaluate_script("({ servers: [{ host: 'localhost' }] })", None)
+    ///     .unwrap()
+    ///     .as_object()
+    ///     .unwrap();
+    /// let host = config.pointer("/servers/0/host").unwrap().unwrap();
+    /// assert_eq!(host.as_string().unwrap(), "localhost");
+    /// assert!(config.pointer("/servers/1").unwrap().is_none());
+    /// ```
+    ///
+    /// # Returns
+    /// `None` if the value does not exist.
+    ///
+    /// # Errors
+    /// A `SyntaxError` if the pointer is not valid, or the exception thrown by a
+    /// getter along the way.
+    pub fn pointer(&self, pointer: &str) -> JSResult<Option<JSValue>> {
+        let ctx = JSContext::from(self.value.ctx);
+        let parsed = JsonPointer::parse_or_throw(&ctx, pointer)?;
+        let result = evaluate_pointer(&ctx, self, &parsed, pointer, None)?;
+        if result.is_undefined() {
+            return Ok(None);
+        }
+        Ok(Some(result.as_object()?.get_property_at_index(0)?))
+    }
+
+    /// Assigns the value a JSON Pointer refers to, starting from the object. The
+    /// parent must exist; on arrays the last token can be an index up to the length
+    /// of the array, or `-` to append.
+    ///
+    /// # Arguments
+    /// - `pointer`: The pointer, such as `"/servers/0/port"`.
+    /// - `value`: The value to assign.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let config = ctx
+    ///     .evaluate_script("({ servers: [] })", None)
+    ///     .unwrap()
+    ///     .as_object()
+    ///     .unwrap();
+    /// let server = ctx.evaluate_script("({ host: 'localhost' })", None).unwrap();
+    /// config.set_pointer("/servers/-", &server).unwrap();
+    /// config.set_pointer("/servers/0/port", &JSValue::number(&ctx, 8080.0)).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// - A `SyntaxError` if the pointer is not valid.
+    /// - A `TypeError` if the pointer is `""` or its parent does not exist.
+    /// - A `RangeError` if the last token is not an index of the parent array.
+    pub fn set_pointer(&self, pointer: &str, value: &JSValue) -> JSResult<()> {
+        let ctx = JSContext::from(self.value.ctx);
+        let parsed = JsonPointer::parse_or_throw(&ctx, pointer)?;
+        if parsed.is_root() {
+            return Err(named_error(
+                &ctx,
+                "TypeError",
+                "Cannot set the root of the document",
+            ));
+        }
+        evaluate_pointer(&ctx, self, &parsed, pointer, Some(value))?;
+        Ok(())
+    }
+}
+
+/// Returns the helpers of the context shared by JSON Pointers and JSON Patches:
+/// `isObject`, `define`, which defines an own data property, `arrayIndex`, which
+/// resolves a token to an index of an array, and `evaluate`.
+pub(crate) fn pointer_helpers(ctx: &JSContext) -> JSResult<JSObject> {
+    ContextData::shim(ctx, "pointer", POINTER)
+}
+
+fn evaluate_pointer(
+    ctx: &JSContext,
+    root: &JSObject,
+    parsed: &JsonPointer,
+    pointer: &str,
+    value: Option<&JSValue>,
+) -> JSResult<JSValue> {
+    let evaluate = pointer_helpers(ctx)?
+        .get_property("evaluate")?
+        .as_object()?;
+    evaluate.call(
+        None,
+        &[
+            root.clone().into(),
+            parsed.to_array(ctx)?.into(),
+            JSValue::string(ctx, pointer),
+            JSValue::boolean(ctx, value.is_some()),
+            value.cloned().unwrap_or_else(|| ctx.undefined()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_pointer() {
+        let pointer = JsonPointer::parse("/a~1b/~0c/0").unwrap();
+        assert_eq!(pointer.tokens(), ["a/b", "~c", "0"]);
+        assert_eq!(pointer.to_string(), "/a~1b/~0c/0");
+        assert_eq!(JsonPointer::parse("/").unwrap().tokens(), [""]);
+        assert!(JsonPointer::parse("").unwrap().is_root());
+        assert!(JsonPointer::parse("a").is_none());
+        assert!(JsonPointer::parse("/a~2").is_none());
+
+        let (parent, last) = pointer.split_last().unwrap();
+        assert_eq!(parent.to_string(), "/a~1b/~0c");
+        assert_eq!(last, "0");
+    }
+
+    #[test]
+    fn test_object_pointer() {
+        let ctx = JSContext::new();
+        let config = ctx
+            .evaluate_script(
+                "({ servers: [{ host: 'localhost' }], 'a/b': { '~': 1 }, empty: null })",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let host = config.pointer("/servers/0/host").unwrap().unwrap();
+        assert_eq!(host.as_string().unwrap().to_string(), "localhost");
+        let escaped = config.pointer("/a~1b/~0").unwrap().unwrap();
+        assert_eq!(escaped.as_number().unwrap(), 1.0);
+        assert!(config.pointer("/empty").unwrap().unwrap().is_null());
+        assert!(config.pointer("").unwrap().unwrap().is_object());
+        for missing in [
+            "/servers/1",
+            "/servers/01",
+            "/servers/-",
+            "/toString",
+            "/empty/a",
+        ] {
+            assert!(config.pointer(missing).unwrap().is_none(), "{}", missing);
+        }
+        let error = config.pointer("servers").unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "SyntaxError");
+
+        let port = JSValue::number(&ctx, 8080.0);
+        config.set_pointer("/servers/0/port", &port).unwrap();
+        config.set_pointer("/servers/-", &port).unwrap();
+        config.set_pointer("/servers/2", &port).unwrap();
+        let json = config.pointer("/servers").unwrap().unwrap();
+        assert_eq!(
+            json.as_json_string(0).unwrap().to_string(),
+            r#"[{"host":"localhost","port":8080},8080,8080]"#
+        );
+
+        let error = config.set_pointer("/servers/5", &port).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+        let error = config.set_pointer("/missing/port", &port).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        let error = config.set_pointer("", &port).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_set_pointer_defines_own_property() {
+        let ctx = JSContext::new();
+        let target = ctx
+            .evaluate_script(
+                "({ nested: Object.create({ set port(v) { globalThis.leaked = v; } }) })",
+                None,
+            )
+            .unwrap()
+            .as_object()
+            .unwrap();
+        let value = ctx.evaluate_script("({ polluted: true })", None).unwrap();
+
+        target.set_pointer("/__proto__", &value).unwrap();
+        target.set_pointer("/nested/port", &value).unwrap();
+        let prototype_changed = ctx
+            .evaluate_script(
+                "({}).polluted === true || globalThis.leaked !== undefined",
+                None,
+            )
+            .unwrap();
+        assert!(!prototype_changed.as_boolean());
+        assert!(target.pointer("/__proto__").unwrap().unwrap().is_object());
+        assert!(target.pointer("/nested/port").unwrap().unwrap().is_object());
+        assert!(!target
+            .get_prototype()
+            .as_object()
+            .unwrap()
+            .has_property("polluted"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{JSContext, JSContextGroup, JSResult};
+
+type ContextSetup = Box<dyn Fn(&JSContext) -> JSResult<()>>;
+
+/// A pool of contexts that share one context group (and so one virtual machine).
+///
+/// Released contexts get a fresh global object with [`JSContext::reset_global_state`]
+/// before they are handed out again, so request-per-context workloads do not pay the
+/// full virtual machine startup cost on every request.
+pub struct ContextPool {
+    group: JSContextGroup,
+    available: Vec<JSContext>,
+    max_idle: usize,
+    setup: Option<ContextSetup>,
+}
+
+impl ContextPool {
+    /// Creates a new pool that keeps at most `max_idle` released contexts around.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::pool::ContextPool;
+    ///
+    /// let mut pool = ContextPool::new(4);
+    /// let ctx = pool.acquire().unwrap();
+    /// ctx.evaluate_script("globalThis.request = 1", None).unwrap();
+    /// pool.release(ctx).unwrap();
+    /// ```
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            group: JSContextGroup::new(),
+            available: Vec::new(),
+            max_idle,
+            setup: None,
+        }
+    }
+
+    /// Creates a new pool that runs `setup` on every fresh global object, for
+    /// example to install globals or a module loader.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::{pool::ContextPool, JSValue};
+    ///
+    /// let mut pool = ContextPool::with_setup(4, |ctx| {
+    ///     let value = JSValue::string(ctx, "1.0.0");
+    ///     ctx.global_object().set_property("version", &value, Default::default())
+    /// });
+    /// ```
+    pub fn with_setup<F>(max_idle: usize, setup: F) -> Self
+    where
+        F: Fn(&JSContext) -> JSResult<()> + 'static,
+    {
+        Self {
+            setup: Some(Box::new(setup)),
+            ..Self::new(max_idle)
+        }
+    }
+
+    /// Takes a context from the pool, creating a new one in the pool group
+    /// if no released context is available.
+    ///
+    /// # Errors
+    /// If the setup function fails on a new context.
+    pub fn acquire(&mut self) -> JSResult<JSContext> {
+        if let Some(ctx) = self.available.pop() {
+            return Ok(ctx);
+        }
+
+        let ctx = self.group.new_context();
+        self.run_setup(&ctx)?;
+        Ok(ctx)
+    }
+
+    /// Returns a context to the pool. Its global state is reset so the next
+    /// user cannot observe anything left behind. If the pool is full the
+    /// context is dropped instead.
+    ///
+    /// # Errors
+    /// If the setup function fails on the fresh global object.
+    /// The context is dropped in that case.
+    pub fn release(&mut self, mut ctx: JSContext) -> JSResult<()> {
+        if self.available.len() >= self.max_idle {
+            return Ok(());
+        }
+
+        ctx.reset_global_state();
+        self.run_setup(&ctx)?;
+        self.available.push(ctx);
+        Ok(())
+    }
+
+    /// Returns the context group shared by all the contexts of the pool.
+    pub fn group(&self) -> &JSContextGroup {
+        &self.group
+    }
+
+    /// Returns the number of released contexts ready to be acquired.
+    pub fn idle(&self) -> usize {
+        self.available.len()
+    }
+
+    fn run_setup(&self, ctx: &JSContext) -> JSResult<()> {
+        match &self.setup {
+            Some(setup) => setup(ctx),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ContextPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextPool")
+            .field("idle", &self.available.len())
+            .field("max_idle", &self.max_idle)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JSValue;
+
+    #[test]
+    fn test_acquire_release() {
+        let mut pool = ContextPool::new(2);
+        let ctx = pool.acquire().unwrap();
+        assert_eq!(pool.idle(), 0);
+
+        ctx.evaluate_script("globalThis.leak = 1", None).unwrap();
+        pool.release(ctx).unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        let ctx = pool.acquire().unwrap();
+        assert_eq!(pool.idle(), 0);
+        let result = ctx.evaluate_script("typeof leak", None).unwrap();
+        assert_eq!(result.as_string().unwrap().to_string(), "undefined");
+    }
+
+    #[test]
+    fn test_release_over_capacity() {
+        let mut pool = ContextPool::new(1);
+        let first = pool.acquire().unwrap();
+        let second = pool.acquire().unwrap();
+        pool.release(first).unwrap();
+        pool.release(second).unwrap();
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn test_setup_runs_on_reset() {
+        let mut pool = ContextPool::with_setup(1, |ctx| {
+            let value = JSValue::number(ctx, 42.0);
+            ctx.global_object()
+                .set_property("answer", &value, Default::default())
+        });
+
+        let ctx = pool.acquire().unwrap();
+        ctx.evaluate_script("answer = 0", None).unwrap();
+        pool.release(ctx).unwrap();
+
+        let ctx = pool.acquire().unwrap();
+        let result = ctx.evaluate_script("answer", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 42.0);
+    }
+}
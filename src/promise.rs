@@ -1,10 +1,22 @@
-use std::ops::Deref;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    ops::Deref,
+    panic::AssertUnwindSafe,
+    rc::{Rc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant, SystemTime},
+};
 
-use rust_jsc_sys::{JSObjectMakeDeferredPromise, JSValueRef};
+use rust_jsc_sys::{JSContextRef, JSObjectMakeDeferredPromise, JSValueRef};
 
 use crate::{
-    JSContext, JSError, JSObject, JSPromise, JSPromiseResolvingFunctions, JSResult,
-    JSValue,
+    builtins::named_error, convert::IntoJSValue, JSContext, JSError, JSFunction,
+    JSObject, JSPromise, JSPromiseResolvingFunctions, JSResult, JSValue,
+    PromiseRejectionKind,
 };
 
 impl JSPromiseResolvingFunctions {
@@ -41,8 +53,7 @@ impl JSPromise {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         let resolver = JSPromiseResolvingFunctions { resolve, reject };
@@ -116,6 +127,655 @@ impl From<JSPromise> for JSValue {
 
 unsafe impl Send for JSPromise {}
 
+/// The settlement a [`JSContext::run_until_settled_with`] call waits for. The value
+/// is only recorded, and protected, while the call is waiting.
+#[derive(Default)]
+struct Settlement {
+    result: RefCell<Option<Result<JSValue, JSValue>>>,
+    done: Cell<bool>,
+}
+
+/// Ends the wait of a [`JSContext::run_until_settled_with`] call when it returns, and
+/// releases a settlement that arrived but was not handed back, e.g. during `pump`.
+struct SettlementGuard(Rc<Settlement>);
+
+impl Drop for SettlementGuard {
+    fn drop(&mut self) {
+        self.0.done.set(true);
+        if let Some(Ok(value) | Err(value)) = self.0.result.borrow_mut().take() {
+            value.unprotect();
+        }
+    }
+}
+
+/// A promise rejection recorded by a [`RejectionTracker`].
+#[derive(Debug, Clone)]
+pub struct UnhandledRejection {
+    /// The order in which the tracker saw the rejection, starting at 1.
+    pub id: u64,
+    pub promise: JSObject,
+    pub reason: JSValue,
+    /// The `stack` of the reason, if it is an error.
+    pub stack: Option<String>,
+    pub timestamp: SystemTime,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    next_id: u64,
+    rejections: Vec<UnhandledRejection>,
+}
+
+impl TrackerState {
+    fn remove(&mut self, promise: &JSObject) {
+        if let Some(index) = self
+            .rejections
+            .iter()
+            .position(|rejection| rejection.promise.value == promise.value)
+        {
+            let rejection = self.rejections.remove(index);
+            rejection.promise.unprotect();
+            rejection.reason.unprotect();
+        }
+    }
+}
+
+impl Drop for TrackerState {
+    fn drop(&mut self) {
+        for rejection in self.rejections.drain(..) {
+            rejection.promise.unprotect();
+            rejection.reason.unprotect();
+        }
+    }
+}
+
+/// Records the promise rejections of a context that are still unhandled, so they can
+/// be reported in one go, e.g. at the end of a request.
+///
+/// A rejection is removed from the tracker when a handler is attached to the
+/// promise later on. Tracking stops when the tracker is dropped.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::JSContext;
+///
+/// let ctx = JSContext::new();
+/// let tracker = ctx.track_rejections().unwrap();
+/// ctx.evaluate_script("Promise.reject(new Error('lost'))", None).unwrap();
+///
+/// for rejection in tracker.drain() {
+///     eprintln!("#{} {:?}", rejection.id, rejection.stack);
+/// }
+/// ```
+pub struct RejectionTracker {
+    state: Rc<RefCell<TrackerState>>,
+}
+
+impl RejectionTracker {
+    /// Returns the number of unhandled rejections recorded.
+    pub fn len(&self) -> usize {
+        self.state.borrow().rejections.len()
+    }
+
+    /// Returns `true` if no unhandled rejections are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes and returns the unhandled rejections recorded so far, oldest first.
+    pub fn drain(&self) -> Vec<UnhandledRejection> {
+        let rejections = std::mem::take(&mut self.state.borrow_mut().rejections);
+        for rejection in &rejections {
+            rejection.promise.unprotect();
+            rejection.reason.unprotect();
+        }
+
+        rejections
+    }
+}
+
+impl std::fmt::Debug for RejectionTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RejectionTracker")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl JSContext {
+    /// Starts recording the unhandled promise rejections of the context.
+    ///
+    /// The tracker is registered with [`JSContext::on_promise_rejection`], so it
+    /// replaces a handler registered there, and is replaced by the next one.
+    ///
+    /// # Errors
+    /// If the rejection tracker cannot be installed in the context.
+    pub fn track_rejections(&self) -> JSResult<RejectionTracker> {
+        let state = Rc::new(RefCell::new(TrackerState::default()));
+        // The handler only holds a weak reference, so that dropping the tracker
+        // releases the recorded values outside of garbage collection.
+        let weak: Weak<RefCell<TrackerState>> = Rc::downgrade(&state);
+
+        self.on_promise_rejection(move |_ctx, event| {
+            let Some(state) = weak.upgrade() else {
+                return;
+            };
+
+            let mut state = state.borrow_mut();
+            match event.kind {
+                PromiseRejectionKind::Handled => state.remove(&event.promise),
+                PromiseRejectionKind::Unhandled => {
+                    let stack = Some(&event.reason)
+                        .filter(|reason| reason.is_object())
+                        .and_then(|reason| reason.as_object().ok())
+                        .and_then(|reason| reason.get_property("stack").ok())
+                        .filter(|stack| stack.is_string())
+                        .and_then(|stack| stack.as_string().ok())
+                        .map(|stack| stack.to_string());
+
+                    event.promise.protect();
+                    event.reason.protect();
+                    state.next_id += 1;
+                    let id = state.next_id;
+                    state.rejections.push(UnhandledRejection {
+                        id,
+                        promise: event.promise,
+                        reason: event.reason,
+                        stack,
+                        timestamp: SystemTime::now(),
+                    });
+                }
+            }
+        })?;
+
+        Ok(RejectionTracker { state })
+    }
+
+    /// Blocks until the promise settles, draining microtasks in between.
+    ///
+    /// The crate has no event loop of its own, so only microtasks can make progress;
+    /// use [`JSContext::run_until_settled_with`] to also pump the embedder's event loop.
+    ///
+    /// # Arguments
+    /// - `promise`: The promise to wait for. Other values are treated as resolved.
+    /// - `budget`: The longest time to wait.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let promise = ctx
+    ///     .evaluate_script("(async () => 40 + 2)()", None)
+    ///     .unwrap();
+    /// let value = ctx.run_until_settled(&promise, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(value.as_number().unwrap(), 42.0);
+    /// ```
+    ///
+    /// # Errors
+    /// The rejection reason if the promise is rejected, or an `Error` if it is still
+    /// pending once the microtasks are drained.
+    pub fn run_until_settled(
+        &self,
+        promise: &JSValue,
+        budget: Duration,
+    ) -> JSResult<JSValue> {
+        self.run_until_settled_with(promise, budget, |_ctx| Ok(false))
+    }
+
+    /// Blocks until the promise settles, draining microtasks and calling `pump` to run
+    /// the embedder's event loop, e.g. due timers and completed I/O, in between.
+    ///
+    /// `pump` returns `true` while the event loop may still settle the promise, and
+    /// `false` once it has no work left, which ends the wait early.
+    ///
+    /// Microtasks are only drained when the context is not running a script, so
+    /// calling this from inside a native callback waits for the whole budget.
+    ///
+    /// Between polls the thread sleeps, starting at 50 microseconds and doubling up
+    /// to a millisecond, so waiting on a slow event loop does not spin a core.
+    ///
+    /// # Errors
+    /// The rejection reason if the promise is rejected, wrapped as by
+    /// [`JSError::from_thrown`] if it is not an object, the error returned by `pump`,
+    /// or an `Error` if the promise is still pending when the event loop is idle.
+    /// A `TimeoutError` if the budget is exhausted.
+    pub fn run_until_settled_with<F>(
+        &self,
+        promise: &JSValue,
+        budget: Duration,
+        mut pump: F,
+    ) -> JSResult<JSValue>
+    where
+        F: FnMut(&JSContext) -> JSResult<bool>,
+    {
+        let deadline = Instant::now() + budget;
+        let mut backoff = MIN_POLL_INTERVAL;
+        let settlement = Rc::new(Settlement::default());
+        let guard = SettlementGuard(settlement.clone());
+
+        let on_settled = |fulfilled: bool| {
+            let settlement = settlement.clone();
+            JSFunction::closure(self, move |ctx, _this, arguments| {
+                if settlement.done.get() {
+                    // The call stopped waiting; nothing would release the value.
+                    return Ok(JSValue::undefined(ctx));
+                }
+
+                let value = arguments
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| JSValue::undefined(ctx));
+                // Kept alive until it is handed back to the caller, or the guard
+                // releases it.
+                value.protect();
+                *settlement.result.borrow_mut() =
+                    Some(if fulfilled { Ok(value) } else { Err(value) });
+                Ok(JSValue::undefined(ctx))
+            })
+        };
+
+        let constructor: JSObject = self.global_constructor("Promise")?.into();
+        let promise = constructor
+            .get_property("resolve")?
+            .as_object()?
+            .call(Some(&constructor), std::slice::from_ref(promise))?
+            .as_object()?;
+        promise.get_property("then")?.as_object()?.call(
+            Some(&promise),
+            &[on_settled(true).into(), on_settled(false).into()],
+        )?;
+
+        loop {
+            // Leaving the API call above, and this one, drains the microtask queue.
+            self.evaluate_script("undefined", None)?;
+
+            let result = guard.0.result.borrow_mut().take();
+            if let Some(result) = result {
+                return match result {
+                    Ok(value) => {
+                        value.unprotect();
+                        Ok(value)
+                    }
+                    Err(reason) => {
+                        reason.unprotect();
+                        Err(JSError::from_thrown(reason))
+                    }
+                };
+            }
+
+            if Instant::now() >= deadline {
+                return Err(named_error(
+                    self,
+                    "TimeoutError",
+                    "The promise did not settle within the budget",
+                ));
+            }
+
+            if !pump(self)? {
+                return Err(JSError::with_message(
+                    self,
+                    "The promise is still pending and there is no work left to run",
+                )?);
+            }
+
+            std::thread::sleep(
+                backoff.min(deadline.saturating_duration_since(Instant::now())),
+            );
+            backoff = (backoff * 2).min(MAX_POLL_INTERVAL);
+        }
+    }
+}
+
+/// The first sleep between two polls of [`JSContext::run_until_settled_with`].
+const MIN_POLL_INTERVAL: Duration = Duration::from_micros(50);
+/// The longest sleep between two polls of [`JSContext::run_until_settled_with`].
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Produces the value a [`DeferredHandle`] settles its promise with, on the context
+/// thread: `Ok` resolves the promise and `Err` rejects it.
+type Resolution = Box<dyn FnOnce(&JSContext) -> JSResult<JSValue> + Send>;
+
+/// The settlements sent by [`DeferredHandle`]s, by deferred id. `None` is sent by a
+/// handle dropped without settling.
+type ResolutionQueue = Arc<Mutex<VecDeque<(u64, Option<Resolution>)>>>;
+
+struct DeferredState {
+    resolver: JSPromiseResolvingFunctions,
+    settled: Arc<AtomicBool>,
+}
+
+impl DeferredState {
+    fn settle(&self, result: Result<JSValue, JSValue>) -> JSResult<()> {
+        if self.settled.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        match result {
+            Ok(value) => self.resolver.resolve(None, &[value])?,
+            Err(reason) => self.resolver.reject(None, &[reason])?,
+        };
+        Ok(())
+    }
+}
+
+impl Drop for DeferredState {
+    fn drop(&mut self) {
+        self.resolver.resolve.unprotect();
+        self.resolver.reject.unprotect();
+    }
+}
+
+/// The deferreds of a context with handles in flight, stored as a context extension.
+#[derive(Default)]
+struct DeferredQueue {
+    queue: ResolutionQueue,
+    pending: RefCell<HashMap<u64, Rc<DeferredState>>>,
+    next_id: Cell<u64>,
+}
+
+/// The resolving functions of a pending promise, created with [`JSPromise::deferred`].
+///
+/// The promise can be settled once: [`Deferred::resolve`] and [`Deferred::reject`]
+/// consume the deferred. To settle it from another thread, send a
+/// [`DeferredHandle`] there.
+pub struct Deferred {
+    ctx: JSContext,
+    state: Rc<DeferredState>,
+}
+
+impl Deferred {
+    /// Resolves the promise with the value.
+    ///
+    /// # Errors
+    /// If calling the resolve function throws.
+    pub fn resolve(self, value: &JSValue) -> JSResult<()> {
+        self.state.settle(Ok(value.clone()))
+    }
+
+    /// Rejects the promise with the reason.
+    ///
+    /// # Errors
+    /// If calling the reject function throws.
+    pub fn reject(self, reason: &JSValue) -> JSResult<()> {
+        self.state.settle(Err(reason.clone()))
+    }
+
+    /// Returns true once the promise has been settled, e.g. through a handle.
+    pub fn has_settled(&self) -> bool {
+        self.state.settled.load(Ordering::SeqCst)
+    }
+
+    /// Returns a handle that settles the promise from any thread. The settlement
+    /// takes effect on the context thread, the next time
+    /// [`JSContext::run_deferred`] is called.
+    pub fn handle(&self) -> DeferredHandle {
+        let deferreds = match self.ctx.get_extension::<DeferredQueue>() {
+            Some(deferreds) => deferreds,
+            None => {
+                self.ctx.set_extension(DeferredQueue::default());
+                self.ctx
+                    .get_extension::<DeferredQueue>()
+                    .expect("Deferred queue must be set")
+            }
+        };
+
+        let id = deferreds.next_id.get();
+        deferreds.next_id.set(id + 1);
+        deferreds
+            .pending
+            .borrow_mut()
+            .insert(id, self.state.clone());
+
+        DeferredHandle {
+            id,
+            queue: deferreds.queue.clone(),
+            settled: self.state.settled.clone(),
+            sent: false,
+        }
+    }
+}
+
+impl std::fmt::Debug for Deferred {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Deferred")
+            .field("settled", &self.has_settled())
+            .finish()
+    }
+}
+
+/// A `Send` handle that settles the promise of a [`Deferred`] from another thread.
+///
+/// JavaScript values cannot cross threads, so the handle carries a closure that
+/// creates the value on the context thread, when [`JSContext::run_deferred`] runs.
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{JSContext, JSPromise, JSValue};
+///
+/// let ctx = JSContext::new();
+/// let (promise, deferred) = JSPromise::deferred(&ctx).unwrap();
+/// let handle = deferred.handle();
+/// std::thread::spawn(move || {
+///     let answer = 6.0 * 7.0;
+///     handle.settle(move |ctx| Ok(JSValue::number(ctx, answer)));
+/// })
+/// .join()
+/// .unwrap();
+///
+/// ctx.run_deferred().unwrap();
+/// ```
+pub struct DeferredHandle {
+    id: u64,
+    queue: ResolutionQueue,
+    settled: Arc<AtomicBool>,
+    sent: bool,
+}
+
+impl DeferredHandle {
+    /// Settles the promise with the result of the closure, run on the context
+    /// thread: `Ok` resolves the promise and `Err` rejects it.
+    ///
+    /// # Arguments
+    /// - `resolution`: A closure that creates the value or the error.
+    pub fn settle<F>(mut self, resolution: F)
+    where
+        F: FnOnce(&JSContext) -> JSResult<JSValue> + Send + 'static,
+    {
+        self.send(Some(Box::new(resolution)));
+    }
+
+    /// Returns true once the promise has been settled on the context thread.
+    pub fn has_settled(&self) -> bool {
+        self.settled.load(Ordering::SeqCst)
+    }
+
+    fn send(&mut self, resolution: Option<Resolution>) {
+        self.sent = true;
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.push_back((self.id, resolution));
+        }
+    }
+}
+
+impl Drop for DeferredHandle {
+    fn drop(&mut self) {
+        if !self.sent {
+            // Lets the context release the deferred; its promise stays pending.
+            self.send(None);
+        }
+    }
+}
+
+impl std::fmt::Debug for DeferredHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeferredHandle")
+            .field("id", &self.id)
+            .field("settled", &self.has_settled())
+            .finish()
+    }
+}
+
+type BlockingJob = Box<dyn FnOnce() + Send>;
+
+/// The threads that run the work of [`JSContext::spawn_blocking`], one per core,
+/// started on first use.
+static BLOCKING_POOL: OnceLock<Mutex<mpsc::Sender<BlockingJob>>> = OnceLock::new();
+
+pub(crate) fn run_blocking(job: BlockingJob) {
+    let sender = BLOCKING_POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<BlockingJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let threads = std::thread::available_parallelism().map_or(4, usize::from);
+        for index in 0..threads {
+            let receiver = receiver.clone();
+            let _ = std::thread::Builder::new()
+                .name(format!("rust-jsc-blocking-{}", index))
+                .spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                });
+        }
+        Mutex::new(sender)
+    });
+
+    if let Ok(sender) = sender.lock() {
+        let _ = sender.send(job);
+    }
+}
+
+impl JSPromise {
+    /// Creates a pending promise and the [`Deferred`] that settles it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::{JSContext, JSPromise, JSValue};
+    ///
+    /// let ctx = JSContext::new();
+    /// let (promise, deferred) = JSPromise::deferred(&ctx).unwrap();
+    /// deferred.resolve(&JSValue::number(&ctx, 42.0)).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the promise cannot be created.
+    pub fn deferred(ctx: &JSContext) -> JSResult<(Self, Deferred)> {
+        let (promise, resolver) = Self::new_pending(ctx)?;
+        // The functions are kept on the Rust heap until the deferred is released.
+        resolver.resolve.protect();
+        resolver.reject.protect();
+
+        let state = Rc::new(DeferredState {
+            resolver,
+            settled: Arc::new(AtomicBool::new(false)),
+        });
+        let ctx = JSContext::from(ctx.inner as JSContextRef);
+        Ok((promise, Deferred { ctx, state }))
+    }
+}
+
+impl JSContext {
+    /// Runs blocking Rust work, such as file I/O or a long computation, on a pool
+    /// of worker threads and returns a promise of its result.
+    ///
+    /// The promise is settled through a [`DeferredHandle`], so it settles on the
+    /// context thread the next time [`JSContext::run_deferred`] is called after the
+    /// work is done.
+    ///
+    /// # Arguments
+    /// - `work`: The closure to run. `Ok` resolves the promise with the value, and
+    ///   `Err` rejects it with an `Error` whose message is the error; a panic rejects
+    ///   it too.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let promise = ctx
+    ///     .spawn_blocking(|| std::fs::read_to_string("/etc/hostname").map_err(|e| e.to_string()))
+    ///     .unwrap();
+    /// let hostname = ctx
+    ///     .run_until_settled_with(&promise, Duration::from_secs(1), |ctx| {
+    ///         ctx.run_deferred()?;
+    ///         Ok(true)
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If the promise cannot be created.
+    pub fn spawn_blocking<F, T, E>(&self, work: F) -> JSResult<JSPromise>
+    where
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+        T: IntoJSValue + Send + 'static,
+        E: std::fmt::Display,
+    {
+        let (promise, deferred) = JSPromise::deferred(self)?;
+        let handle = deferred.handle();
+
+        run_blocking(Box::new(move || {
+            let result = match std::panic::catch_unwind(AssertUnwindSafe(work)) {
+                Ok(result) => result.map_err(|error| error.to_string()),
+                Err(_) => Err("The blocking work panicked".to_string()),
+            };
+            handle.settle(move |ctx| match result {
+                Ok(value) => value.try_into_js_value(ctx),
+                Err(message) => Err(JSError::with_message(ctx, message)?),
+            });
+        }));
+
+        Ok(promise)
+    }
+
+    /// Returns `true` while [`DeferredHandle`]s of the context are in flight, i.e.
+    /// [`JSContext::run_deferred`] may still settle a promise.
+    pub(crate) fn has_pending_deferreds(&self) -> bool {
+        self.get_extension::<DeferredQueue>()
+            .is_some_and(|deferreds| !deferreds.pending.borrow().is_empty())
+    }
+
+    /// Settles the promises of the [`DeferredHandle`]s that were settled since the
+    /// last call, in the order they were sent. Call it from the event loop of the
+    /// context thread.
+    ///
+    /// # Errors
+    /// If calling a resolving function throws; the remaining settlements stay queued.
+    ///
+    /// # Returns
+    /// The number of promises settled.
+    pub fn run_deferred(&self) -> JSResult<usize> {
+        let Some(deferreds) = self.get_extension::<DeferredQueue>() else {
+            return Ok(0);
+        };
+
+        let mut settled = 0;
+        loop {
+            let next = match deferreds.queue.lock() {
+                Ok(mut queue) => queue.pop_front(),
+                Err(_) => None,
+            };
+            let Some(next) = next else {
+                return Ok(settled);
+            };
+
+            let (id, resolution) = next;
+            let state = deferreds.pending.borrow_mut().remove(&id);
+            if let (Some(state), Some(resolution)) = (state, resolution) {
+                state.settle(resolution(self).map_err(JSValue::from))?;
+                settled += 1;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{self as rust_jsc, JSFunction, JSString};
@@ -150,6 +810,158 @@ mod tests {
         assert_eq!(result.is_undefined(), true);
     }
 
+    #[test]
+    fn test_run_until_settled() {
+        let ctx = JSContext::new();
+        let promise = ctx
+            .evaluate_script("(async () => (await Promise.resolve(40)) + 2)()", None)
+            .unwrap();
+        let value = ctx
+            .run_until_settled(&promise, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(value.as_number().unwrap(), 42.0);
+
+        let promise = ctx
+            .evaluate_script("Promise.reject(new TypeError('nope'))", None)
+            .unwrap();
+        let error = ctx
+            .run_until_settled(&promise, Duration::from_secs(1))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+
+        for script in [
+            "Promise.reject()",
+            "Promise.reject(null)",
+            "Promise.reject(7)",
+        ] {
+            let promise = ctx.evaluate_script(script, None).unwrap();
+            let error = ctx
+                .run_until_settled(&promise, Duration::from_secs(1))
+                .unwrap_err();
+            assert_eq!(error.name().unwrap().to_string(), "Error");
+            assert!(!error.cause().unwrap().is_object());
+        }
+
+        let (pending, _resolver) = JSPromise::new_pending(&ctx).unwrap();
+        let error = ctx
+            .run_until_settled(&pending, Duration::from_secs(1))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "Error");
+    }
+
+    #[test]
+    fn test_run_until_settled_with_pump() {
+        let ctx = JSContext::new();
+        let (promise, resolver) = JSPromise::new_pending(&ctx).unwrap();
+
+        let mut ticks = 0;
+        let value = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(1), |ctx| {
+                ticks += 1;
+                if ticks == 3 {
+                    resolver.resolve(None, &[JSValue::number(ctx, 7.0)])?;
+                }
+                Ok(true)
+            })
+            .unwrap();
+        assert_eq!(value.as_number().unwrap(), 7.0);
+        assert_eq!(ticks, 3);
+
+        let (pending, resolver) = JSPromise::new_pending(&ctx).unwrap();
+        let error = ctx
+            .run_until_settled_with(&pending, Duration::from_millis(10), |_ctx| Ok(true))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TimeoutError");
+
+        // A settlement after the wait ended is left to the next wait.
+        resolver
+            .resolve(None, &[JSValue::number(&ctx, 8.0)])
+            .unwrap();
+        let value = ctx
+            .run_until_settled(&pending, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(value.as_number().unwrap(), 8.0);
+
+        // A settlement that arrives while the pump fails is released with the wait.
+        let (pending, resolver) = JSPromise::new_pending(&ctx).unwrap();
+        let error = ctx
+            .run_until_settled_with(&pending, Duration::from_secs(1), |ctx| {
+                resolver.resolve(None, &[JSValue::number(ctx, 9.0)])?;
+                ctx.evaluate_script("undefined", None)?;
+                Err(JSError::with_message(ctx, "pump failed")?)
+            })
+            .unwrap_err();
+        assert_eq!(error.message().unwrap().to_string(), "pump failed");
+    }
+
+    #[test]
+    fn test_spawn_blocking() {
+        fn pump(ctx: &JSContext) -> JSResult<bool> {
+            ctx.run_deferred()?;
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(true)
+        }
+
+        let ctx = JSContext::new();
+        let promise = ctx.spawn_blocking(|| Ok::<_, String>(6.0 * 7.0)).unwrap();
+        let value = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(5), pump)
+            .unwrap();
+        assert_eq!(value.as_number().unwrap(), 42.0);
+
+        let promise = ctx.spawn_blocking(|| Err::<f64, _>("disk full")).unwrap();
+        let error = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(5), pump)
+            .unwrap_err();
+        assert_eq!(error.message().unwrap().to_string(), "disk full");
+
+        let promise = ctx
+            .spawn_blocking(|| -> Result<f64, String> { panic!("boom") })
+            .unwrap();
+        let error = ctx
+            .run_until_settled_with(&promise, Duration::from_secs(5), pump)
+            .unwrap_err();
+        assert_eq!(
+            error.message().unwrap().to_string(),
+            "The blocking work panicked"
+        );
+    }
+
+    #[test]
+    fn test_track_rejections() {
+        let ctx = JSContext::new();
+        let tracker = ctx.track_rejections().unwrap();
+
+        ctx.evaluate_script(
+            r#"
+            globalThis.late = Promise.reject(new Error("late"));
+            Promise.reject(new TypeError("lost"));
+            Promise.reject("plain");
+        "#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(tracker.len(), 3);
+
+        ctx.evaluate_script("late.catch(() => {})", None).unwrap();
+        let rejections = tracker.drain();
+        assert!(tracker.is_empty());
+        assert_eq!(rejections.len(), 2);
+
+        assert_eq!(rejections[0].id, 2);
+        let reason = JSError::from(rejections[0].reason.clone());
+        assert_eq!(reason.message().unwrap().to_string(), "lost");
+        assert!(rejections[0].stack.is_some());
+
+        assert_eq!(rejections[1].id, 3);
+        assert_eq!(
+            rejections[1].reason.as_string().unwrap().to_string(),
+            "plain"
+        );
+        assert!(rejections[1].stack.is_none());
+        assert!(rejections[0].timestamp <= rejections[1].timestamp);
+    }
+
     #[test]
     fn test_resolve_function() {
         #[callback]
@@ -176,4 +988,52 @@ mod tests {
 
         assert_eq!(result.unwrap().is_object(), true);
     }
+
+    #[test]
+    fn test_deferred() {
+        let ctx = JSContext::new();
+        let (promise, deferred) = JSPromise::deferred(&ctx).unwrap();
+        assert!(!deferred.has_settled());
+        deferred.resolve(&JSValue::number(&ctx, 42.0)).unwrap();
+        let value = ctx
+            .run_until_settled(&promise, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(value.as_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_deferred_handle() {
+        let ctx = JSContext::new();
+        let (resolved, deferred) = JSPromise::deferred(&ctx).unwrap();
+        let handle = deferred.handle();
+        let (rejected, failing) = JSPromise::deferred(&ctx).unwrap();
+        let failing_handle = failing.handle();
+        let (_pending, dropped) = JSPromise::deferred(&ctx).unwrap();
+        let dropped_handle = dropped.handle();
+
+        std::thread::spawn(move || {
+            handle.settle(|ctx| Ok(JSValue::string(ctx, "from thread")));
+            failing_handle.settle(|ctx| Err(JSError::with_message(ctx, "failed")?));
+            drop(dropped_handle);
+        })
+        .join()
+        .unwrap();
+
+        assert!(!deferred.has_settled());
+        assert_eq!(ctx.run_deferred().unwrap(), 2);
+        assert!(deferred.has_settled());
+        assert!(failing.has_settled());
+        assert!(!dropped.has_settled());
+
+        let value = ctx
+            .run_until_settled(&resolved, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(value.as_string().unwrap().to_string(), "from thread");
+        let error = ctx
+            .run_until_settled(&rejected, Duration::from_secs(1))
+            .err()
+            .unwrap();
+        assert_eq!(error.message().unwrap().to_string(), "failed");
+        assert_eq!(ctx.run_deferred().unwrap(), 0);
+    }
 }
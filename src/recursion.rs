@@ -0,0 +1,195 @@
+//! A per-context limit on how deeply native functions can be reentered, so recursion
+//! between scripts and the host fails with a catchable `RangeError` at a predictable
+//! depth instead of exhausting the stack of the host thread.
+
+use std::{cell::Cell, rc::Rc};
+
+use crate::{builtins::named_error, JSContext, JSResult};
+
+/// The limit and the current depth of a context, stored as a context extension.
+#[derive(Default)]
+struct RecursionLimit {
+    limit: Cell<Option<usize>>,
+    depth: Cell<usize>,
+}
+
+/// Counts a native call while it runs, see [`JSContext::set_recursion_limit`].
+#[doc(hidden)]
+pub struct RecursionGuard {
+    entered: Option<Rc<RecursionLimit>>,
+}
+
+impl RecursionGuard {
+    /// Enters a native call.
+    ///
+    /// # Errors
+    /// A `RangeError` if the call would exceed the recursion limit of the context.
+    pub fn enter(ctx: &JSContext) -> JSResult<Self> {
+        let Some(state) = ctx.get_extension::<RecursionLimit>() else {
+            return Ok(Self { entered: None });
+        };
+
+        let depth = state.depth.get() + 1;
+        if let Some(limit) = state.limit.get().filter(|limit| depth > *limit) {
+            let message = format!("Maximum native call depth of {} exceeded", limit);
+            return Err(named_error(ctx, "RangeError", &message));
+        }
+        state.depth.set(depth);
+        Ok(Self {
+            entered: Some(state),
+        })
+    }
+}
+
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        if let Some(state) = self.entered.take() {
+            state.depth.set(state.depth.get() - 1);
+        }
+    }
+}
+
+impl JSContext {
+    /// Limits how many native functions, defined with `#[callback]`,
+    /// `#[constructor]` or as closures, can be running at once in the context. A
+    /// native call beyond the limit throws a `RangeError` that scripts can catch,
+    /// before any of its code runs.
+    ///
+    /// The engine already throws a `RangeError` for scripts that recurse on their
+    /// own, but each call from a script into the host and back uses the stack of the
+    /// host thread as well, which the engine does not account for. The C API does
+    /// not expose the stack size of the engine, so this limit is counted in native
+    /// calls rather than bytes.
+    ///
+    /// # Arguments
+    /// - `limit`: The maximum depth, or `None` to remove the limit.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_recursion_limit(Some(256));
+    /// ```
+    pub fn set_recursion_limit(&self, limit: Option<usize>) {
+        match self.get_extension::<RecursionLimit>() {
+            Some(state) => state.limit.set(limit),
+            None if limit.is_some() => {
+                self.set_extension(RecursionLimit {
+                    limit: Cell::new(limit),
+                    depth: Cell::new(0),
+                });
+            }
+            None => {}
+        }
+    }
+
+    /// Returns the limit set by [`JSContext::set_recursion_limit`].
+    pub fn recursion_limit(&self) -> Option<usize> {
+        self.get_extension::<RecursionLimit>()?.limit.get()
+    }
+
+    /// Returns how many native functions are running in the context, counted once a
+    /// limit has been set with [`JSContext::set_recursion_limit`].
+    pub fn recursion_depth(&self) -> usize {
+        self.get_extension::<RecursionLimit>()
+            .map_or(0, |state| state.depth.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JSFunction, JSObject};
+
+    #[test]
+    fn test_recursion_limit() {
+        let ctx = JSContext::new();
+        assert!(ctx.recursion_limit().is_none());
+
+        let native: JSObject = JSFunction::closure(&ctx, |ctx, _, arguments| {
+            let step = ctx.global_object().get_property("step")?.as_object()?;
+            step.call(None, arguments)
+        })
+        .into();
+        ctx.global_object()
+            .set_property("native", &native.into(), Default::default())
+            .unwrap();
+        ctx.evaluate_script(
+            "globalThis.step = (n) => (n === 0 ? 0 : native(n - 1) + 1);",
+            None,
+        )
+        .unwrap();
+
+        ctx.set_recursion_limit(Some(10));
+        assert_eq!(ctx.recursion_limit(), Some(10));
+        let result = ctx.evaluate_script("step(10)", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 10.0);
+
+        let result = ctx
+            .evaluate_script(
+                "try { step(50); 'no error' } catch (error) { error.name + ': ' + error.message }",
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "RangeError: Maximum native call depth of 10 exceeded"
+        );
+        assert_eq!(ctx.recursion_depth(), 0);
+
+        ctx.set_recursion_limit(None);
+        let result = ctx.evaluate_script("step(50)", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 50.0);
+    }
+
+    #[test]
+    fn test_recursion_limit_accessors() {
+        use crate::{self as rust_jsc, JSResult, JSValue, PropertyDescriptor};
+        use rust_jsc_macros::{getter, setter};
+
+        #[getter]
+        fn depth(ctx: JSContext, this: JSObject) -> JSResult<JSValue> {
+            let next = ctx.global_object().get_property("next")?.as_object()?;
+            next.call(None, &[this.into()])
+        }
+
+        #[setter]
+        fn store(ctx: JSContext, this: JSObject, value: JSValue) -> JSResult<()> {
+            let next = ctx.global_object().get_property("store")?.as_object()?;
+            next.call(None, &[this.into(), value])?;
+            Ok(())
+        }
+
+        let ctx = JSContext::new();
+        let object = JSObject::new(&ctx);
+        object
+            .define_accessor(
+                "depth",
+                Some(depth),
+                Some(store),
+                PropertyDescriptor::default(),
+            )
+            .unwrap();
+        ctx.global_object()
+            .set_property("object", &object.into(), Default::default())
+            .unwrap();
+        ctx.evaluate_script(
+            "globalThis.next = (object) => object.depth; \
+             globalThis.store = (object, value) => { object.depth = value; };",
+            None,
+        )
+        .unwrap();
+
+        ctx.set_recursion_limit(Some(10));
+        for script in ["object.depth", "object.depth = 1"] {
+            let script = format!(
+                "try {{ {}; 'no error' }} catch (error) {{ error.name }}",
+                script
+            );
+            let result = ctx.evaluate_script(&script, None).unwrap();
+            assert_eq!(result.as_string().unwrap().to_string(), "RangeError");
+        }
+        assert_eq!(ctx.recursion_depth(), 0);
+    }
+}
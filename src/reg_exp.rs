@@ -1,6 +1,8 @@
 use rust_jsc_sys::{JSObjectMakeRegExp, JSValueRef};
 
-use crate::{JSContext, JSError, JSObject, JSRegExp, JSResult, JSValue};
+use crate::{
+    buffer::SmallBuffer, JSContext, JSError, JSObject, JSRegExp, JSResult, JSValue,
+};
 
 impl JSRegExp {
     pub fn new(object: JSObject) -> Self {
@@ -31,15 +33,14 @@ impl JSRegExp {
     /// The new `JSRegExp` object.
     pub fn new_regexp(ctx: &JSContext, args: &[JSValue]) -> JSResult<Self> {
         let mut exception: JSValueRef = std::ptr::null_mut();
-        let args: Vec<JSValueRef> = args.iter().map(|arg| arg.inner).collect();
+        let args = SmallBuffer::refs(args);
 
         let result = unsafe {
             JSObjectMakeRegExp(ctx.inner, args.len(), args.as_ptr(), &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self::new(JSObject::from_ref(result, ctx.inner)))
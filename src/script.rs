@@ -0,0 +1,469 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{builtins::named_error, JSContext, JSError, JSResult, JSValue};
+
+/// The most syntax errors [`JSContext::compile_script`] reports for a script.
+pub const MAX_DIAGNOSTICS: usize = 32;
+
+/// A script embedded in the binary with [`include_js!`](crate::include_js).
+///
+/// # Example
+/// ```ignore
+/// use rust_jsc::{include_js, JSContext};
+///
+/// const PRELUDE: rust_jsc::script::EmbeddedScript = include_js!("js/prelude.js");
+///
+/// let ctx = JSContext::new();
+/// PRELUDE.evaluate(&ctx).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedScript {
+    source: &'static str,
+    path: &'static str,
+    module: bool,
+}
+
+impl EmbeddedScript {
+    /// Creates an embedded script. Prefer [`include_js!`](crate::include_js), which
+    /// reads the source from a file at compile time.
+    ///
+    /// # Arguments
+    /// - `source`: The source of the script.
+    /// - `path`: The path the script was read from, used as its source URL.
+    /// - `module`: Whether the source is a module.
+    pub const fn new(source: &'static str, path: &'static str, module: bool) -> Self {
+        Self {
+            source,
+            path,
+            module,
+        }
+    }
+
+    /// Returns the source of the script.
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Returns the path the script was read from.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns true if the script is a module.
+    pub fn is_module(&self) -> bool {
+        self.module
+    }
+
+    /// Checks the full syntax of the script at run time, without running it.
+    /// [`include_js!`](crate::include_js) only checks the tokens of scripts and
+    /// modules when it compiles. Modules are not checked here, as the engine only
+    /// checks classic scripts; their syntax errors are reported by
+    /// [`EmbeddedScript::evaluate`].
+    ///
+    /// # Errors
+    /// A `SyntaxError` if the script does not parse.
+    pub fn check_syntax(&self, ctx: &JSContext) -> JSResult<()> {
+        if !self.module {
+            ctx.check_syntax(self.source, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the script in the context. Modules are loaded under their path, so
+    /// other modules can import them with that key.
+    ///
+    /// # Errors
+    /// If the script throws or does not parse.
+    ///
+    /// # Returns
+    /// The completion value of a classic script, or `undefined` for a module.
+    pub fn evaluate(&self, ctx: &JSContext) -> JSResult<JSValue> {
+        if self.module {
+            ctx.evaluate_module_from_source(self.source, self.path, None)?;
+            return Ok(ctx.undefined());
+        }
+
+        ctx.evaluate_script_with_source_url(self.source, self.path, Some(1))
+    }
+}
+
+/// A syntax error found by [`JSContext::compile_script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxDiagnostic {
+    /// The message of the `SyntaxError`, such as `Unexpected token ';'`.
+    pub message: String,
+    /// The line of the error, starting at 1.
+    pub line: u32,
+    /// The column of the error, when the engine reports it.
+    pub column: Option<u32>,
+}
+
+impl fmt::Display for SyntaxDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}: {}", self.line, column, self.message),
+            None => write!(f, "{}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// A classic script whose syntax was checked by [`JSContext::compile_script`].
+///
+/// The C API of the engine does not expose compiled code, so the script keeps its
+/// checked source and is parsed again when it is evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledScript {
+    source: String,
+}
+
+impl CompiledScript {
+    /// Returns the source of the script.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the script in the context.
+    ///
+    /// # Errors
+    /// If the script throws.
+    ///
+    /// # Returns
+    /// The completion value of the script.
+    pub fn evaluate(&self, ctx: &JSContext) -> JSResult<JSValue> {
+        ctx.evaluate_script(&self.source, Some(1))
+    }
+}
+
+impl JSContext {
+    /// Checks the syntax of a classic script and returns either the checked script
+    /// or all the syntax errors found in it, rather than only the first one, for
+    /// editors and linters.
+    ///
+    /// The engine stops at the first error, so the others are found by blanking the
+    /// line of each error and checking the script again. An error that leaves a
+    /// block open may be followed by a spurious error at the end of the script. At
+    /// most [`MAX_DIAGNOSTICS`] errors are reported.
+    ///
+    /// # Arguments
+    /// - `source`: The source of the script.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// let diagnostics = ctx.compile_script("let a = ;\nlet b = );").unwrap_err();
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert_eq!(diagnostics[1].line, 2);
+    ///
+    /// let script = ctx.compile_script("1 + 1").unwrap();
+    /// assert_eq!(script.evaluate(&ctx).unwrap().as_number().unwrap(), 2.0);
+    /// ```
+    ///
+    /// # Errors
+    /// The syntax errors of the script, in the order of their lines.
+    pub fn compile_script(
+        &self,
+        source: &str,
+    ) -> Result<CompiledScript, Vec<SyntaxDiagnostic>> {
+        let mut lines: Vec<String> = source.split('\n').map(str::to_string).collect();
+        let mut diagnostics: Vec<SyntaxDiagnostic> = Vec::new();
+
+        while diagnostics.len() < MAX_DIAGNOSTICS {
+            let error = match self.check_syntax(&lines.join("\n"), 1) {
+                Ok(_) => break,
+                Err(error) => error,
+            };
+
+            let diagnostic = diagnostic(&error);
+            let index = diagnostic.line as usize;
+            let repeated = diagnostics.iter().any(|seen| seen.line == diagnostic.line);
+            if repeated {
+                break;
+            }
+            diagnostics.push(diagnostic);
+
+            // Blanked rather than removed, so the other errors keep their positions.
+            match lines.get_mut(index.wrapping_sub(1)) {
+                Some(line) if !line.trim().is_empty() => {
+                    *line = " ".repeat(line.chars().count());
+                }
+                _ => break,
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+            return Err(diagnostics);
+        }
+
+        Ok(CompiledScript {
+            source: source.to_string(),
+        })
+    }
+}
+
+/// Where an error thrown by a file of [`JSContext::evaluate_file`] was raised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLocation {
+    /// The canonical path of the file.
+    pub path: PathBuf,
+    /// The line, starting at 1.
+    pub line: u32,
+    /// The column, starting at 1, when the engine records it.
+    pub column: Option<u32>,
+    /// The text of the line, as it was when the file was evaluated.
+    pub source_line: String,
+}
+
+impl fmt::Display for ScriptLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sources of the files evaluated in a context, by source URL.
+#[derive(Default)]
+struct ScriptFiles(RefCell<HashMap<String, (PathBuf, Rc<str>)>>);
+
+impl JSContext {
+    /// Reads a classic script from a file and evaluates it with the canonical path
+    /// of the file as its source URL, so errors and stacks name the file. The source
+    /// is kept for error reporting, see [`JSContext::locate_error`].
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// if let Err(error) = ctx.evaluate_file("scripts/setup.js") {
+    ///     match ctx.locate_error(&error) {
+    ///         Some(location) => eprintln!("{}: {}\n    {}", location, error, location.source_line),
+    ///         None => eprintln!("{}", error),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// An `Error` if the file cannot be read, or the error thrown by the script.
+    ///
+    /// # Returns
+    /// The completion value of the script.
+    pub fn evaluate_file(&self, path: impl AsRef<Path>) -> JSResult<JSValue> {
+        let path = path.as_ref();
+        let read = |path: &Path| {
+            let path = path.canonicalize()?;
+            let source = std::fs::read_to_string(&path)?;
+            Ok::<_, std::io::Error>((path, source))
+        };
+        let (path, source) = read(path).map_err(|error| {
+            let message = format!("Cannot read script '{}': {}", path.display(), error);
+            named_error(self, "Error", &message)
+        })?;
+
+        let source_url = path.to_string_lossy().into_owned();
+        let source: Rc<str> = source.into();
+        let files = match self.get_extension::<ScriptFiles>() {
+            Some(files) => files,
+            None => {
+                self.set_extension(ScriptFiles::default());
+                self.get_extension::<ScriptFiles>()
+                    .expect("the script files extension was just set")
+            }
+        };
+        files
+            .0
+            .borrow_mut()
+            .insert(source_url.clone(), (path, source.clone()));
+
+        self.evaluate_script_with_source_url(&source, &source_url, Some(1))
+    }
+
+    /// Returns the canonical paths of the files evaluated with
+    /// [`JSContext::evaluate_file`].
+    pub fn script_files(&self) -> Vec<PathBuf> {
+        let Some(files) = self.get_extension::<ScriptFiles>() else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = files
+            .0
+            .borrow()
+            .values()
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Returns the source of a file evaluated with [`JSContext::evaluate_file`], as
+    /// it was when it was evaluated.
+    ///
+    /// # Arguments
+    /// - `source_url`: The source URL of the file, its canonical path.
+    pub fn script_source(&self, source_url: &str) -> Option<Rc<str>> {
+        let files = self.get_extension::<ScriptFiles>()?;
+        let files = files.0.borrow();
+        files.get(source_url).map(|(_, source)| source.clone())
+    }
+
+    /// Returns where an error was thrown, if it was thrown by a file evaluated with
+    /// [`JSContext::evaluate_file`].
+    ///
+    /// # Arguments
+    /// - `error`: The error, which records the source URL, line and column of the
+    ///   code that threw it.
+    pub fn locate_error(&self, error: &JSError) -> Option<ScriptLocation> {
+        let source_url = error.as_object().ok()?.get_property("sourceURL").ok()?;
+        if !source_url.is_string() {
+            return None;
+        }
+        let source_url = source_url.as_string().ok()?.to_string();
+
+        let files = self.get_extension::<ScriptFiles>()?;
+        let files = files.0.borrow();
+        let (path, source) = files.get(&source_url)?;
+        let SyntaxDiagnostic { line, column, .. } = diagnostic(error);
+        let source_line = source.lines().nth(line.checked_sub(1)? as usize)?;
+        Some(ScriptLocation {
+            path: path.clone(),
+            line,
+            column,
+            source_line: source_line.to_string(),
+        })
+    }
+}
+
+/// Reads the message and the position the engine records on a `SyntaxError`.
+fn diagnostic(error: &JSError) -> SyntaxDiagnostic {
+    let position = |name: &str| {
+        let value = error.as_object().ok()?.get_property(name).ok()?;
+        let value = value.as_number().ok().filter(|_| value.is_number())?;
+        (value >= 1.0).then_some(value as u32)
+    };
+
+    SyntaxDiagnostic {
+        message: error
+            .message()
+            .map(|message| message.to_string())
+            .unwrap_or_default(),
+        line: position("line").unwrap_or(0),
+        column: position("column"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as rust_jsc, include_js};
+
+    const SCRIPT: EmbeddedScript = include_js!("tests/modules/script2.js");
+    const MODULE: EmbeddedScript = include_js!("tests/modules/embedded.mjs");
+
+    #[test]
+    fn test_include_js() {
+        assert_eq!(SCRIPT.path(), "tests/modules/script2.js");
+        assert!(!SCRIPT.is_module());
+        assert!(SCRIPT.source().contains("Array.prototype.test"));
+
+        let ctx = JSContext::new();
+        SCRIPT.check_syntax(&ctx).unwrap();
+        SCRIPT.evaluate(&ctx).unwrap();
+        let result = ctx
+            .evaluate_script("typeof Array.prototype.test", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "function");
+    }
+
+    #[test]
+    fn test_include_js_module() {
+        assert_eq!(MODULE.path(), "tests/modules/embedded.mjs");
+        assert!(MODULE.is_module());
+
+        let ctx = JSContext::new();
+        MODULE.check_syntax(&ctx).unwrap();
+        MODULE.evaluate(&ctx).unwrap();
+        let result = ctx
+            .evaluate_script("globalThis.embeddedAnswer", None)
+            .unwrap();
+        assert_eq!(result.as_string().unwrap(), "42");
+    }
+
+    #[test]
+    fn test_compile_script() {
+        let ctx = JSContext::new();
+        let diagnostics = ctx
+            .compile_script("let a = ;\nlet b = 1;\nlet c = );\n")
+            .unwrap_err();
+        let lines: Vec<u32> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.line)
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert!(!diagnostics[0].message.is_empty());
+        assert!(diagnostics[0].to_string().starts_with("1:"));
+
+        let script = ctx.compile_script("let x = 20;\nx + 22").unwrap();
+        assert_eq!(script.source(), "let x = 20;\nx + 22");
+        assert_eq!(script.evaluate(&ctx).unwrap().as_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_evaluate_file() {
+        let dir =
+            std::env::temp_dir().join(format!("rust_jsc_script_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("setup.js");
+        std::fs::write(
+            &file,
+            "globalThis.ready = true;\nthrow new TypeError('boom');\n",
+        )
+        .unwrap();
+
+        let ctx = JSContext::new();
+        let error = ctx
+            .evaluate_file(
+                dir.join("../")
+                    .join(dir.file_name().unwrap())
+                    .join("setup.js"),
+            )
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert!(ctx.evaluate_script("ready", None).unwrap().as_boolean());
+
+        let path = file.canonicalize().unwrap();
+        assert_eq!(ctx.script_files(), vec![path.clone()]);
+        let source = ctx.script_source(path.to_str().unwrap()).unwrap();
+        assert!(source.starts_with("globalThis.ready"));
+
+        let location = ctx.locate_error(&error).unwrap();
+        assert_eq!(location.path, path);
+        assert_eq!(location.line, 2);
+        assert_eq!(location.source_line, "throw new TypeError('boom');");
+        assert!(location
+            .to_string()
+            .starts_with(&format!("{}:2", path.display())));
+
+        let error = ctx.evaluate_file(dir.join("missing.js")).unwrap_err();
+        assert!(error
+            .message()
+            .unwrap()
+            .to_string()
+            .starts_with("Cannot read script"));
+        assert!(ctx.locate_error(&error).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,14 +4,15 @@ use std::{
 };
 
 use rust_jsc_sys::{
-    JSStringCreateWithUTF8CString, JSStringGetLength, JSStringGetMaximumUTF8CStringSize,
+    JSStringCreateWithCharacters, JSStringCreateWithUTF8CString,
+    JSStringGetCharactersPtr, JSStringGetLength, JSStringGetMaximumUTF8CStringSize,
     JSStringGetUTF8CString, JSStringIsEqual, JSStringIsEqualToUTF8CString, JSStringRef,
     JSStringRelease,
 };
 
-use crate::{JSString, JSStringRetain};
+use crate::{JSRetainedString, JSString};
 
-impl JSStringRetain {
+impl JSRetainedString {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -20,57 +21,75 @@ impl JSStringRetain {
         unsafe { JSStringGetLength(self.0) }
     }
 
-    pub fn release(&self) {
+    /// Gives the reference to the engine, which releases it once done with it, such
+    /// as when the string is returned from a module loader callback.
+    pub fn leak_to_engine(self) -> JSStringRef {
+        self.0
+    }
+
+    /// Releases the reference. The string is freed once no one else retains it.
+    pub fn release(self) {
         unsafe {
             JSStringRelease(self.0);
         }
     }
 }
 
-impl From<&str> for JSStringRetain {
+impl From<&str> for JSRetainedString {
     fn from(s: &str) -> Self {
-        let c =
-            CString::new(s.as_bytes()).expect("&str to JSStringRetain conversion failed");
+        let c = CString::new(s.as_bytes())
+            .expect("&str to JSRetainedString conversion failed");
         Self(unsafe { JSStringCreateWithUTF8CString(c.as_ptr()) })
     }
 }
 
-impl From<String> for JSStringRetain {
+impl From<String> for JSRetainedString {
     fn from(s: String) -> Self {
         let c = CString::new(s.as_bytes())
-            .expect("String to JSStringRetain conversion failed");
+            .expect("String to JSRetainedString conversion failed");
         Self(unsafe { JSStringCreateWithUTF8CString(c.as_ptr()) })
     }
 }
 
-impl From<JSStringRef> for JSStringRetain {
+impl From<JSStringRef> for JSRetainedString {
     fn from(inner: JSStringRef) -> Self {
         Self(inner)
     }
 }
 
-impl From<JSStringRetain> for JSStringRef {
-    fn from(s: JSStringRetain) -> Self {
-        s.0
+impl From<JSRetainedString> for JSStringRef {
+    fn from(s: JSRetainedString) -> Self {
+        s.leak_to_engine()
     }
 }
 
-impl std::fmt::Display for JSStringRetain {
+impl std::fmt::Display for JSRetainedString {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let max_len = unsafe { JSStringGetMaximumUTF8CStringSize(self.0) };
-        let mut buffer = vec![0u8; max_len];
-        let new_size = unsafe {
-            JSStringGetUTF8CString(self.0, buffer.as_mut_ptr() as *mut i8, max_len)
-        };
-        unsafe {
-            buffer.set_len(new_size - 1);
-        };
-        let s = String::from_utf8(buffer).map_err(|_| std::fmt::Error)?;
-        write!(fmt, "{}", s)
+        fmt.write_str(&utf8_string(self.0))
+    }
+}
+
+/// Converts the string to UTF-8, allocating exactly the converted length instead of
+/// the maximum UTF-8 size. Unpaired surrogates are replaced with U+FFFD.
+fn utf8_string(string: JSStringRef) -> String {
+    let len = unsafe { JSStringGetLength(string) };
+    if len == 0 {
+        return String::new();
     }
+
+    let characters =
+        unsafe { std::slice::from_raw_parts(JSStringGetCharactersPtr(string), len) };
+    let decode = || {
+        char::decode_utf16(characters.iter().copied())
+            .map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+    };
+
+    let mut result = String::with_capacity(decode().map(char::len_utf8).sum());
+    result.extend(decode());
+    result
 }
 
-impl Clone for JSStringRetain {
+impl Clone for JSRetainedString {
     fn clone(&self) -> Self {
         self.to_string().into()
     }
@@ -90,6 +109,50 @@ impl JSString {
     pub fn len(&self) -> usize {
         unsafe { JSStringGetLength(self.inner) }
     }
+
+    /// Creates a new `JSString` from UTF-16 code units.
+    /// Unlike the conversions from `&str`, the code units may contain NUL characters
+    /// and unpaired surrogates.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::JSString;
+    ///
+    /// let s = JSString::from_utf16(&[0x68, 0x00, 0x69]);
+    /// assert_eq!(s.len(), 3);
+    /// ```
+    pub fn from_utf16(characters: &[u16]) -> Self {
+        Self {
+            inner: unsafe {
+                JSStringCreateWithCharacters(characters.as_ptr(), characters.len())
+            },
+        }
+    }
+
+    /// Converts the string to a Rust `String`, consuming the handle.
+    /// Unpaired surrogates are replaced with U+FFFD.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jsc::JSString;
+    ///
+    /// let s = JSString::from("héllo");
+    /// assert_eq!(s.into_string(), "héllo");
+    /// ```
+    pub fn into_string(self) -> String {
+        utf8_string(self.inner)
+    }
+
+    /// Returns the UTF-16 code units of the string.
+    pub fn to_utf16(&self) -> Vec<u16> {
+        let len = self.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        let characters = unsafe { JSStringGetCharactersPtr(self.inner) };
+        unsafe { std::slice::from_raw_parts(characters, len) }.to_vec()
+    }
 }
 
 impl PartialEq for JSString {
@@ -207,31 +270,13 @@ impl Clone for JSString {
 
 impl Debug for JSString {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let max_len = unsafe { JSStringGetMaximumUTF8CStringSize(self.inner) };
-        let mut buffer = vec![0u8; max_len];
-        let new_size = unsafe {
-            JSStringGetUTF8CString(self.inner, buffer.as_mut_ptr() as *mut i8, max_len)
-        };
-        unsafe {
-            buffer.set_len(new_size - 1);
-        };
-        let s = String::from_utf8(buffer).map_err(|_| std::fmt::Error)?;
-        write!(fmt, "{:?}", s)
+        write!(fmt, "{:?}", utf8_string(self.inner))
     }
 }
 
 impl std::fmt::Display for JSString {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let max_len = unsafe { JSStringGetMaximumUTF8CStringSize(self.inner) };
-        let mut buffer = vec![0u8; max_len];
-        let new_size = unsafe {
-            JSStringGetUTF8CString(self.inner, buffer.as_mut_ptr() as *mut i8, max_len)
-        };
-        unsafe {
-            buffer.set_len(new_size - 1);
-        };
-        let s = String::from_utf8(buffer).map_err(|_| std::fmt::Error)?;
-        write!(fmt, "{}", s)
+        fmt.write_str(&utf8_string(self.inner))
     }
 }
 
@@ -245,7 +290,7 @@ impl Drop for JSString {
 
 #[cfg(test)]
 mod tests {
-    use crate::{JSString, JSStringRetain};
+    use crate::{JSRetainedString, JSString};
 
     #[test]
     fn test_js_string() {
@@ -254,6 +299,27 @@ mod tests {
         assert_eq!(s.to_string(), "Hello, World!");
     }
 
+    #[test]
+    fn test_js_string_utf16() {
+        let s = JSString::from_utf16(&[0x61, 0x00, 0xd800]);
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.to_utf16(), &[0x61, 0x00, 0xd800]);
+        assert!(JSString::from_utf16(&[]).to_utf16().is_empty());
+    }
+
+    #[test]
+    fn test_js_string_into_string() {
+        let s = JSString::from("a€😀");
+        assert_eq!(s.to_string(), "a€😀");
+        let s = s.into_string();
+        assert_eq!(s, "a€😀");
+        assert_eq!(s.capacity(), s.len());
+
+        let s = JSString::from_utf16(&[0x61, 0x00, 0xd800]);
+        assert_eq!(s.into_string(), "a\0\u{fffd}");
+        assert_eq!(JSString::from("").into_string(), "");
+    }
+
     #[test]
     fn test_js_string_eq() {
         let s1 = JSString::from("Hello, World!");
@@ -270,15 +336,15 @@ mod tests {
 
     #[test]
     fn test_js_string_retain_eq_utf8() {
-        let s1 = JSStringRetain::from("Hello, World!");
-        let s2 = JSStringRetain::from("Hello, World!");
-        let s3 = JSStringRetain::from("démonstration.html");
-        let s4 = JSStringRetain::from("こんにちは世界");
-        let s5 = JSStringRetain::from("Привет, мир!");
-        let s6 = JSStringRetain::from("😊👍🏽");
-        let s7 = JSStringRetain::from("");
-        let s8 = JSStringRetain::from("你好，世界！");
-        let s9 = JSStringRetain::from("Bonjour le monde!");
+        let s1 = JSRetainedString::from("Hello, World!");
+        let s2 = JSRetainedString::from("Hello, World!");
+        let s3 = JSRetainedString::from("démonstration.html");
+        let s4 = JSRetainedString::from("こんにちは世界");
+        let s5 = JSRetainedString::from("Привет, мир!");
+        let s6 = JSRetainedString::from("😊👍🏽");
+        let s7 = JSRetainedString::from("");
+        let s8 = JSRetainedString::from("你好，世界！");
+        let s9 = JSRetainedString::from("Bonjour le monde!");
 
         // Test equality with the same content
         assert_eq!(s1.to_string(), s2.to_string());
@@ -388,12 +454,24 @@ mod tests {
 
     #[test]
     fn test_jsstring_retain() {
-        let s = JSStringRetain::from("Hello, World!");
+        let s = JSRetainedString::from("Hello, World!");
         assert_eq!(s.to_string(), "Hello, World!");
 
-        let s1 = JSStringRetain::from("Hello, World!");
-        let s2 = JSStringRetain::from("Hello, World!");
+        let s1 = JSRetainedString::from("Hello, World!");
+        let s2 = JSRetainedString::from("Hello, World!");
         assert_eq!(s1.clone().to_string(), s2.to_string());
         assert_eq!(s1.to_string(), s2.clone().to_string());
+        s1.release();
+
+        let inner = s2.leak_to_engine();
+        assert_eq!(JSString::from(inner).to_string(), "Hello, World!");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_jsstring_retain_alias() {
+        let s: crate::JSStringRetain = JSRetainedString::from("alias");
+        assert_eq!(s.to_string(), "alias");
+        s.release();
     }
 }
@@ -0,0 +1,306 @@
+//! A machine-readable description of the host API registered on the global object,
+//! to document it or compare it across versions.
+
+use crate::{
+    context_data::ContextData, loader::string_literal, JSArray, JSContext, JSResult,
+    JSValue,
+};
+
+/// Lists the own properties of the global object that a fresh context does not
+/// have, and their members, as `[path, kind, type, arity, className]`. Getters are
+/// not called, and objects are listed once.
+const API_SURFACE: &str = r#"(function (global, builtins) {
+    builtins = new Set(builtins);
+    const entries = [];
+    const seen = new Set();
+    const tag = (v) => Object.prototype.toString.call(v).slice(8, -1);
+    const isClass = (f) => {
+        let source = "";
+        try {
+            source = Function.prototype.toString.call(f);
+        } catch (_) {}
+        if (/^class\b/.test(source)) {
+            return true;
+        }
+        const prototype = f.prototype;
+        return (typeof prototype === "object" && prototype !== null &&
+            Object.getOwnPropertyNames(prototype).some((name) => name !== "constructor"));
+    };
+    const statics = new Set(["length", "name", "prototype", "arguments", "caller"]);
+
+    const walk = (target, prefix, skip) => {
+        if (seen.has(target)) {
+            return;
+        }
+        seen.add(target);
+        for (const name of Object.getOwnPropertyNames(target)) {
+            if (skip(name)) {
+                continue;
+            }
+            const path = prefix === "" ? name : `${prefix}.${name}`;
+            const descriptor = Object.getOwnPropertyDescriptor(target, name);
+            if (descriptor === undefined) {
+                continue;
+            }
+            if (!("value" in descriptor)) {
+                entries.push([path, "accessor", null, null, null]);
+                continue;
+            }
+            const value = descriptor.value;
+            const type = value === null ? "null" : typeof value;
+            if (type === "function") {
+                const arity = typeof value.length === "number" ? value.length : 0;
+                if (isClass(value)) {
+                    entries.push([path, "class", type, arity, value.name || name]);
+                    walk(value, path, (key) => statics.has(key));
+                    if (typeof value.prototype === "object" && value.prototype !== null) {
+                        walk(value.prototype, `${path}.prototype`, (key) => key === "constructor");
+                    }
+                } else {
+                    entries.push([path, "function", type, arity, null]);
+                    walk(value, path, (key) => statics.has(key));
+                }
+            } else if (type === "object") {
+                const className = tag(value);
+                entries.push([path, "object", type, null, className === "Object" ? null : className]);
+                walk(value, path, () => false);
+            } else {
+                entries.push([path, "value", type, null, null]);
+            }
+        }
+    };
+    walk(global, "", (name) => builtins.has(name));
+    return entries;
+})"#;
+
+/// What a member of the API is, see [`ApiEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKind {
+    /// A function that is not a class.
+    Function,
+    /// A class, or a function with methods on its prototype.
+    Class,
+    /// An object, such as a namespace of functions.
+    Object,
+    /// A primitive value.
+    Value,
+    /// A property with a getter or a setter, which is not called.
+    Accessor,
+}
+
+impl ApiKind {
+    /// Returns the name of the kind, such as `"function"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Class => "class",
+            Self::Object => "object",
+            Self::Value => "value",
+            Self::Accessor => "accessor",
+        }
+    }
+}
+
+/// A member of the API, listed by [`JSContext::export_api_surface`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiEntry {
+    /// The path of the member from the global object, such as `host.fs.readFile` or
+    /// `Buffer.prototype.slice`.
+    pub path: String,
+    /// What the member is.
+    pub kind: ApiKind,
+    /// The `typeof` of the value, or `None` for an accessor.
+    pub value_type: Option<String>,
+    /// The number of parameters of a function or class, its `length`.
+    pub arity: Option<u32>,
+    /// The name of a class, or the class of an object other than a plain object,
+    /// such as `Map` or the name of a native class.
+    pub class_name: Option<String>,
+}
+
+/// The host API registered on the global object of a context, built by
+/// [`JSContext::export_api_surface`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiSurface {
+    /// The members of the API, in definition order, each object before its members.
+    pub entries: Vec<ApiEntry>,
+}
+
+impl ApiSurface {
+    /// Returns the member at a path, such as `host.log`.
+    pub fn get(&self, path: &str) -> Option<&ApiEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+
+    /// Returns the API as a JSON array of
+    /// `{ "path", "kind", "type", "arity", "className" }` objects, with `null` for the
+    /// fields that do not apply.
+    pub fn to_json(&self) -> String {
+        let optional =
+            |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    r#"{{"path":{},"kind":"{}","type":{},"arity":{},"className":{}}}"#,
+                    string_literal(&entry.path),
+                    entry.kind.as_str(),
+                    optional(entry.value_type.as_deref().map(string_literal)),
+                    optional(entry.arity.map(|arity| arity.to_string())),
+                    optional(entry.class_name.as_deref().map(string_literal)),
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
+}
+
+impl JSContext {
+    /// Describes everything registered on the global object beyond what a fresh
+    /// context has: the names, types, function arities and class names of the host
+    /// API and of its members, e.g. to generate its documentation or to compare it
+    /// across versions with [`ApiSurface::to_json`].
+    ///
+    /// Objects and functions are described with their own properties, classes with
+    /// their static members and the methods of their prototype. Getters are not
+    /// called, symbol keys are ignored, and an object reachable from several paths
+    /// is described under the first one only.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.evaluate_script("globalThis.host = { log(message) {}, version: '1.0' }", None)
+    ///     .unwrap();
+    ///
+    /// let surface = ctx.export_api_surface().unwrap();
+    /// assert_eq!(surface.get("host.log").unwrap().arity, Some(1));
+    /// std::fs::write("api.json", surface.to_json()).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If an exception is thrown while listing the properties, e.g. by a proxy.
+    pub fn export_api_surface(&self) -> JSResult<ApiSurface> {
+        // Values cannot cross context groups, so the names of the fresh context are
+        // copied.
+        let builtins = JSContext::new()
+            .evaluate_script("Object.getOwnPropertyNames(globalThis).join('\\n')", None)?
+            .as_string()?
+            .to_string();
+        let builtins: Vec<JSValue> = builtins
+            .split('\n')
+            .map(|name| JSValue::string(self, name))
+            .collect();
+        let builtins = JSArray::new_array(self, &builtins)?;
+
+        let list = ContextData::shim(self, "apiSurface", API_SURFACE)?;
+        let rows = list
+            .call(None, &[self.global_object().into(), builtins.into()])?
+            .as_object()?;
+        let length = rows.get_property("length")?.as_number()? as u32;
+
+        let mut entries = Vec::with_capacity(length as usize);
+        for index in 0..length {
+            let row = rows.get_property_at_index(index)?.as_object()?;
+            let text = |index: u32| -> JSResult<Option<String>> {
+                let value = row.get_property_at_index(index)?;
+                if value.is_null() {
+                    return Ok(None);
+                }
+                Ok(Some(value.as_string()?.to_string()))
+            };
+            let kind = match text(1)?.as_deref() {
+                Some("function") => ApiKind::Function,
+                Some("class") => ApiKind::Class,
+                Some("object") => ApiKind::Object,
+                Some("accessor") => ApiKind::Accessor,
+                _ => ApiKind::Value,
+            };
+            let arity = row.get_property_at_index(3)?;
+            entries.push(ApiEntry {
+                path: text(0)?.unwrap_or_default(),
+                kind,
+                value_type: text(2)?,
+                arity: match arity.is_null() {
+                    true => None,
+                    false => Some(arity.as_number()? as u32),
+                },
+                class_name: text(4)?,
+            });
+        }
+        Ok(ApiSurface { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_api_surface() {
+        let ctx = JSContext::new();
+        assert!(ctx.export_api_surface().unwrap().entries.is_empty());
+
+        ctx.evaluate_script(
+            "globalThis.host = {
+                 log(level, message) {},
+                 version: '1.0',
+                 get uptime() { throw new Error('not called'); },
+                 registry: new Map(),
+             };
+             host.self = host;
+             globalThis.Counter = class Counter {
+                 constructor(start) {}
+                 static from(value) {}
+                 increment() {}
+             };",
+            None,
+        )
+        .unwrap();
+
+        let surface = ctx.export_api_surface().unwrap();
+        let paths: Vec<&str> = surface
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "host",
+                "host.log",
+                "host.version",
+                "host.uptime",
+                "host.registry",
+                "host.self",
+                "Counter",
+                "Counter.from",
+                "Counter.prototype.increment",
+            ]
+        );
+
+        let log = surface.get("host.log").unwrap();
+        assert_eq!(log.kind, ApiKind::Function);
+        assert_eq!(log.arity, Some(2));
+        let version = surface.get("host.version").unwrap();
+        assert_eq!(version.kind, ApiKind::Value);
+        assert_eq!(version.value_type.as_deref(), Some("string"));
+        assert_eq!(surface.get("host.uptime").unwrap().kind, ApiKind::Accessor);
+        let registry = surface.get("host.registry").unwrap();
+        assert_eq!(registry.class_name.as_deref(), Some("Map"));
+        let counter = surface.get("Counter").unwrap();
+        assert_eq!(counter.kind, ApiKind::Class);
+        assert_eq!(counter.arity, Some(1));
+        assert_eq!(counter.class_name.as_deref(), Some("Counter"));
+
+        let json = surface.to_json();
+        assert!(json.starts_with(
+            r#"[{"path":"host","kind":"object","type":"object","arity":null,"className":null},"#
+        ));
+        assert!(json.contains(
+            r#"{"path":"host.uptime","kind":"accessor","type":null,"arity":null,"className":null}"#
+        ));
+    }
+}
@@ -0,0 +1,643 @@
+use std::{rc::Rc, time::Duration};
+
+use crate::{
+    loader::{LoaderState, MemoryFs},
+    JSArray, JSContext, JSContextGroup, JSResult, JSValue,
+};
+
+type SandboxSetup = Rc<dyn Fn(&JSContext) -> JSResult<()>>;
+
+/// The standard ECMAScript globals a [`SandboxTemplate`] keeps, on top of the ones
+/// it allows explicitly. Host objects of the engine, such as `console`, are removed.
+pub const SANDBOX_GLOBALS: &[&str] = &[
+    "globalThis",
+    "Infinity",
+    "NaN",
+    "undefined",
+    "eval",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "escape",
+    "unescape",
+    "Object",
+    "Function",
+    "Boolean",
+    "Symbol",
+    "Error",
+    "AggregateError",
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+    "Number",
+    "BigInt",
+    "Math",
+    "Date",
+    "String",
+    "RegExp",
+    "Array",
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+    "Map",
+    "Set",
+    "WeakMap",
+    "WeakSet",
+    "ArrayBuffer",
+    "DataView",
+    "JSON",
+    "Promise",
+    "Reflect",
+    "Proxy",
+    "Intl",
+];
+
+/// Removes the globals that are not allowed, including those keyed by symbols, and,
+/// with `lockdown`, freezes the remaining ones and everything reachable from them,
+/// except the global object. Throws a `TypeError` if a global cannot be removed.
+///
+/// Only the crate's own state is kept: it is held under unregistered `rust_jsc.*`
+/// symbols, as non-configurable properties that reveal nothing.
+const SANDBOX: &str = r#"(function (allowed, lockdown) {
+    allowed = new Set(allowed);
+    const isInternal = (key) => Symbol.keyFor(key) === undefined
+        && String(key.description).startsWith("rust_jsc.")
+        && !Reflect.getOwnPropertyDescriptor(globalThis, key).configurable;
+    for (const key of Reflect.ownKeys(globalThis)) {
+        if (typeof key === "symbol" ? isInternal(key) : allowed.has(key)) {
+            continue;
+        }
+        if (!Reflect.deleteProperty(globalThis, key)) {
+            throw new TypeError(`Cannot remove the global ${String(key)}: it is not configurable`);
+        }
+    }
+    if (!lockdown) {
+        return;
+    }
+
+    const seen = new Set([globalThis]);
+    const stack = [];
+    for (const name of allowed) {
+        if (Object.prototype.hasOwnProperty.call(globalThis, name)) {
+            stack.push(globalThis[name]);
+        }
+    }
+    while (stack.length > 0) {
+        const value = stack.pop();
+        if ((typeof value !== "object" && typeof value !== "function") || value === null
+            || seen.has(value)) {
+            continue;
+        }
+        seen.add(value);
+        try {
+            Object.freeze(value);
+        } catch {
+            // Typed arrays with elements cannot be frozen.
+            Object.preventExtensions(value);
+        }
+        stack.push(Object.getPrototypeOf(value));
+        for (const key of Reflect.ownKeys(value)) {
+            const descriptor = Reflect.getOwnPropertyDescriptor(value, key);
+            stack.push(descriptor.value, descriptor.get, descriptor.set);
+        }
+    }
+})"#;
+
+/// A list of scripts that every context created from the template runs before it
+/// is handed out, such as a standard library or an application bundle.
+///
+/// All the contexts share one context group. The engine caches compiled code per
+/// group, keyed by the source, so after [`ContextTemplate::freeze`] has compiled the
+/// bundles once, new contexts run the cached code instead of parsing it again.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::template::ContextTemplate;
+///
+/// let template = ContextTemplate::new()
+///     .add_bundle("globalThis.greet = (name) => `Hello, ${name}!`")
+///     .freeze()
+///     .unwrap();
+///
+/// let ctx = template.new_context().unwrap();
+/// ctx.evaluate_script("greet('tenant')", None).unwrap();
+/// ```
+pub struct ContextTemplate {
+    group: JSContextGroup,
+    bundles: Vec<String>,
+}
+
+/// A [`ContextTemplate`] whose bundles have been compiled and can no longer change.
+pub struct FrozenContextTemplate {
+    group: JSContextGroup,
+    bundles: Vec<String>,
+}
+
+impl ContextTemplate {
+    /// Creates an empty template with a new context group.
+    pub fn new() -> Self {
+        Self {
+            group: JSContextGroup::new(),
+            bundles: Vec::new(),
+        }
+    }
+
+    /// Adds a script to run in every new context, after the scripts added before it.
+    ///
+    /// # Arguments
+    /// - `script`: The source of the script.
+    pub fn add_bundle(mut self, script: impl Into<String>) -> Self {
+        self.bundles.push(script.into());
+        self
+    }
+
+    /// Compiles the bundles by running them once in a scratch context of the group,
+    /// so syntax errors and exceptions surface here rather than on every new context.
+    ///
+    /// # Errors
+    /// The first error thrown while checking or running the bundles.
+    pub fn freeze(self) -> JSResult<FrozenContextTemplate> {
+        let ctx = self.group.new_context();
+        for bundle in &self.bundles {
+            ctx.check_syntax(bundle, 1)?;
+        }
+        for bundle in &self.bundles {
+            ctx.evaluate_script(bundle, None)?;
+        }
+
+        Ok(FrozenContextTemplate {
+            group: self.group,
+            bundles: self.bundles,
+        })
+    }
+}
+
+impl Default for ContextTemplate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrozenContextTemplate {
+    /// Creates a context in the template group and runs the bundles in it.
+    ///
+    /// # Errors
+    /// If a bundle throws in the new context.
+    pub fn new_context(&self) -> JSResult<JSContext> {
+        let ctx = self.group.new_context();
+        for bundle in &self.bundles {
+            ctx.evaluate_script(bundle, None)?;
+        }
+
+        Ok(ctx)
+    }
+
+    /// Returns the context group shared by all the contexts of the template.
+    pub fn group(&self) -> &JSContextGroup {
+        &self.group
+    }
+
+    /// Returns the number of bundles the template runs.
+    pub fn bundles(&self) -> usize {
+        self.bundles.len()
+    }
+}
+
+impl std::fmt::Debug for ContextTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextTemplate")
+            .field("bundles", &self.bundles.len())
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for FrozenContextTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenContextTemplate")
+            .field("bundles", &self.bundles.len())
+            .finish()
+    }
+}
+
+/// A recipe for plugin contexts that expose only a vetted API: the standard
+/// globals of [`SANDBOX_GLOBALS`], the globals the template allows, and the modules
+/// it provides. Everything else of the context is removed before it is handed out.
+///
+/// Each context is set up by running the setup closures and the bundles, which may
+/// use any global, e.g. to capture a host function in a closure and expose only a
+/// wrapper. Then the globals that are not allowed are removed, the allowed ones
+/// are frozen deeply (see [`SandboxTemplate::lockdown`]), and the module loader is
+/// confined to the modules of [`SandboxTemplate::add_module`], with no filesystem.
+///
+/// Every context gets the recursion limit of [`SandboxTemplate::recursion_limit`],
+/// and the budget of [`SandboxTemplate::budget`] bounds its setup and the work run
+/// with [`FrozenSandboxTemplate::run`]. The engine cannot interrupt a script that
+/// never calls into the host, and its API has no heap limit, so both only stop a
+/// plugin at its next native call.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::template::SandboxTemplate;
+///
+/// let template = SandboxTemplate::new()
+///     .add_bundle("globalThis.host = Object.freeze({ version: () => 2 })")
+///     .allow_global("host")
+///     .add_module("@host/math", "export const double = (x) => x * 2;")
+///     .freeze()
+///     .unwrap();
+///
+/// let ctx = template.new_context().unwrap();
+/// ctx.evaluate_module_from_source(
+///     "import { double } from '@host/math'; globalThis.answer = double(host.version());",
+///     "plugin.js",
+///     None,
+/// )
+/// .unwrap();
+/// ```
+pub struct SandboxTemplate {
+    group: JSContextGroup,
+    globals: Vec<String>,
+    setup: Vec<SandboxSetup>,
+    bundles: Vec<String>,
+    modules: Vec<(String, String)>,
+    lockdown: bool,
+    budget: Option<Duration>,
+    recursion_limit: Option<usize>,
+}
+
+/// A [`SandboxTemplate`] that has been checked and can no longer change.
+pub struct FrozenSandboxTemplate {
+    template: SandboxTemplate,
+}
+
+impl SandboxTemplate {
+    /// Creates a template with a new context group that keeps only the standard
+    /// globals and locks them down.
+    pub fn new() -> Self {
+        Self {
+            group: JSContextGroup::new(),
+            globals: SANDBOX_GLOBALS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            setup: Vec::new(),
+            bundles: Vec::new(),
+            modules: Vec::new(),
+            lockdown: true,
+            budget: None,
+            recursion_limit: None,
+        }
+    }
+
+    /// Keeps a global, such as one defined by a bundle or a setup closure.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the global.
+    pub fn allow_global(mut self, name: impl Into<String>) -> Self {
+        self.globals.push(name.into());
+        self
+    }
+
+    /// Adds a closure to run first in every new context, e.g. to define host
+    /// functions.
+    ///
+    /// # Arguments
+    /// - `setup`: The closure.
+    pub fn setup<F>(mut self, setup: F) -> Self
+    where
+        F: Fn(&JSContext) -> JSResult<()> + 'static,
+    {
+        self.setup.push(Rc::new(setup));
+        self
+    }
+
+    /// Adds a script to run in every new context, after the setup closures and the
+    /// scripts added before it.
+    ///
+    /// # Arguments
+    /// - `script`: The source of the script.
+    pub fn add_bundle(mut self, script: impl Into<String>) -> Self {
+        self.bundles.push(script.into());
+        self
+    }
+
+    /// Adds a module that plugins can import, and that is the only kind of module
+    /// they can import.
+    ///
+    /// # Arguments
+    /// - `specifier`: The bare specifier of the module, such as `"@host/api"`.
+    /// - `source`: The source of the module.
+    pub fn add_module(
+        mut self,
+        specifier: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        self.modules.push((specifier.into(), source.into()));
+        self
+    }
+
+    /// Sets whether the allowed globals, and everything reachable from them such as
+    /// `Array.prototype`, are frozen, so a plugin cannot tamper with the intrinsics
+    /// or the host API. On by default.
+    ///
+    /// As everywhere frozen prototypes are used, assigning a property that exists on
+    /// a frozen prototype, such as `toString`, fails unless it is defined with
+    /// `Object.defineProperty`.
+    pub fn lockdown(mut self, lockdown: bool) -> Self {
+        self.lockdown = lockdown;
+        self
+    }
+
+    /// Sets the execution budget of every new context, see [`JSContext::with_budget`].
+    /// It bounds the setup closures and the bundles when a context is created, and
+    /// each call of [`FrozenSandboxTemplate::run`].
+    ///
+    /// # Arguments
+    /// - `budget`: How long each of them may run.
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Limits the depth of native calls in every new context, see
+    /// [`JSContext::set_recursion_limit`].
+    ///
+    /// # Arguments
+    /// - `limit`: The maximum depth.
+    pub fn recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = Some(limit);
+        self
+    }
+
+    /// Checks the template by creating a context from it, so errors in the setup and
+    /// the bundles surface here rather than on every new context.
+    ///
+    /// # Errors
+    /// The first error thrown while checking the bundles or creating the context.
+    pub fn freeze(self) -> JSResult<FrozenSandboxTemplate> {
+        let ctx = self.group.new_context();
+        for bundle in &self.bundles {
+            ctx.check_syntax(bundle, 1)?;
+        }
+        self.stamp()?;
+
+        Ok(FrozenSandboxTemplate { template: self })
+    }
+
+    fn stamp(&self) -> JSResult<JSContext> {
+        let ctx = self.group.new_context();
+        ctx.set_recursion_limit(self.recursion_limit);
+        self.run(&ctx, || -> JSResult<()> {
+            for setup in &self.setup {
+                setup(&ctx)?;
+            }
+            for bundle in &self.bundles {
+                ctx.evaluate_script(bundle, None)?;
+            }
+            Ok(())
+        })?;
+
+        ctx.set_module_fs(MemoryFs::new());
+        let loader = LoaderState::get(&ctx);
+        for (specifier, source) in &self.modules {
+            loader.register_builtin(specifier, source);
+        }
+
+        let globals: Vec<JSValue> = self
+            .globals
+            .iter()
+            .map(|name| JSValue::string(&ctx, name.as_str()))
+            .collect();
+        let allowed = JSArray::new_array(&ctx, &globals)?;
+        let sandbox = ctx.evaluate_script(SANDBOX, None)?.as_object()?;
+        sandbox.call(
+            None,
+            &[allowed.into(), JSValue::boolean(&ctx, self.lockdown)],
+        )?;
+
+        Ok(ctx)
+    }
+
+    fn run<T>(&self, ctx: &JSContext, work: impl FnOnce() -> T) -> T {
+        match self.budget {
+            Some(budget) => ctx.with_budget(budget, work),
+            None => work(),
+        }
+    }
+}
+
+impl Default for SandboxTemplate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrozenSandboxTemplate {
+    /// Creates a plugin context from the template.
+    ///
+    /// # Errors
+    /// If a setup closure or a bundle fails in the new context, or a global that is
+    /// not allowed cannot be removed.
+    pub fn new_context(&self) -> JSResult<JSContext> {
+        self.template.stamp()
+    }
+
+    /// Runs plugin work, such as an evaluation or a call to a hook, in a context of
+    /// the template with the budget of [`SandboxTemplate::budget`], if it has one.
+    ///
+    /// # Arguments
+    /// - `ctx`: A context created by [`FrozenSandboxTemplate::new_context`].
+    /// - `work`: The work.
+    pub fn run<T>(&self, ctx: &JSContext, work: impl FnOnce() -> T) -> T {
+        self.template.run(ctx, work)
+    }
+
+    /// Returns the context group shared by all the contexts of the template.
+    pub fn group(&self) -> &JSContextGroup {
+        &self.template.group
+    }
+}
+
+impl std::fmt::Debug for SandboxTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SandboxTemplate")
+            .field("globals", &self.globals.len())
+            .field("bundles", &self.bundles.len())
+            .field("modules", &self.modules.len())
+            .field("lockdown", &self.lockdown)
+            .field("budget", &self.budget)
+            .field("recursion_limit", &self.recursion_limit)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for FrozenSandboxTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenSandboxTemplate")
+            .field("template", &self.template)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JSFunction, JSObject};
+
+    #[test]
+    fn test_template() {
+        let template = ContextTemplate::new()
+            .add_bundle("globalThis.counter = 0")
+            .add_bundle("globalThis.next = () => ++counter")
+            .freeze()
+            .unwrap();
+        assert_eq!(template.bundles(), 2);
+
+        let first = template.new_context().unwrap();
+        first.evaluate_script("next(); next()", None).unwrap();
+        let second = template.new_context().unwrap();
+        let result = second.evaluate_script("next()", None).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_template_syntax_error() {
+        let result = ContextTemplate::new()
+            .add_bundle("globalThis.ok = 1")
+            .add_bundle("function (")
+            .freeze();
+        let error = result.err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "SyntaxError");
+    }
+
+    #[test]
+    fn test_sandbox_template() {
+        let template = SandboxTemplate::new()
+            .setup(|ctx| {
+                let secret = JSValue::string(ctx, "secret");
+                ctx.global_object().set_property("hostSecret", &secret, Default::default())
+            })
+            .add_bundle("const secret = hostSecret; globalThis.host = { peek: () => secret.length }")
+            .allow_global("host")
+            .add_module("@host/math", "export const double = (x) => x * 2;")
+            .freeze()
+            .unwrap();
+
+        let ctx = template.new_context().unwrap();
+        let result = ctx
+            .evaluate_script(
+                "[typeof hostSecret, typeof console, host.peek(), Object.isFrozen(Array.prototype), \
+                 Object.isFrozen(host), Object.isFrozen(globalThis)].join()",
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result.as_string().unwrap().to_string(),
+            "undefined,undefined,6,true,true,false"
+        );
+
+        ctx.evaluate_module_from_source(
+            "import { double } from '@host/math'; globalThis.answer = double(21);",
+            "plugin.js",
+            None,
+        )
+        .unwrap();
+        let answer = ctx.evaluate_script("answer", None).unwrap();
+        assert_eq!(answer.as_number().unwrap(), 42.0);
+        assert!(ctx.evaluate_module("/etc/hosts").is_err());
+
+        let symbols = ctx
+            .evaluate_script(
+                "Object.getOwnPropertySymbols(globalThis).filter((key) => \
+                 Symbol.keyFor(key) !== undefined || !key.description.startsWith('rust_jsc.')).length",
+                None,
+            )
+            .unwrap();
+        assert_eq!(symbols.as_number().unwrap(), 0.0);
+
+        let pinned = SandboxTemplate::new()
+            .add_bundle(
+                "globalThis[Symbol.for('host.token')] = 1; \
+                 Object.defineProperty(globalThis, 'pinned', { value: 1 });",
+            )
+            .freeze();
+        let error = pinned.err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+        assert!(error.message().unwrap().to_string().contains("pinned"));
+
+        let open = SandboxTemplate::new().lockdown(false).freeze().unwrap();
+        let ctx = open.new_context().unwrap();
+        let result = ctx
+            .evaluate_script("Array.prototype.extra = 1; [].extra", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_sandbox_template_limits() {
+        let template = SandboxTemplate::new()
+            .setup(|ctx| {
+                let native: JSObject = JSFunction::closure(ctx, |ctx, _, arguments| {
+                    let step = ctx.global_object().get_property("step")?.as_object()?;
+                    step.call(None, arguments)
+                })
+                .into();
+                ctx.global_object().set_property(
+                    "native",
+                    &native.into(),
+                    Default::default(),
+                )
+            })
+            .add_bundle(
+                "const call = native; \
+                 globalThis.step = (n) => (n === 0 ? 0 : call(n - 1) + 1); \
+                 globalThis.spin = () => { while (true) { call(0); } };",
+            )
+            .allow_global("step")
+            .allow_global("spin")
+            .budget(Duration::from_millis(20))
+            .recursion_limit(10)
+            .freeze()
+            .unwrap();
+
+        let ctx = template.new_context().unwrap();
+        assert_eq!(ctx.recursion_limit(), Some(10));
+        let result = template
+            .run(&ctx, || ctx.evaluate_script("step(5)", None))
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 5.0);
+        let error = template
+            .run(&ctx, || ctx.evaluate_script("step(50)", None))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "RangeError");
+
+        let error = template
+            .run(&ctx, || ctx.evaluate_script("spin()", None))
+            .unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TimeoutError");
+        assert!(ctx.remaining_budget().is_none());
+
+        let slow = SandboxTemplate::new()
+            .setup(|ctx| ctx.check_budget())
+            .budget(Duration::ZERO)
+            .freeze();
+        let error = slow.err().unwrap();
+        assert_eq!(error.name().unwrap().to_string(), "TimeoutError");
+    }
+}
@@ -0,0 +1,675 @@
+//! Helpers for testing bindings: stable snapshots of values and assertions
+//! comparing the result of a script with JSON, and the [`TEST_MODULE`] that lets
+//! scripts declare tests whose results are reported back to Rust.
+
+use std::{fmt, time::Duration};
+
+use crate::{
+    context::CustomModuleLoader, context_data::ContextData, convert::PlainValue,
+    loader::LoaderState, JSContext, JSError, JSResult, JSValue,
+};
+
+/// The depth up to which [`snapshot`] follows nested arrays and objects.
+pub const SNAPSHOT_DEPTH: usize = 32;
+
+/// Serializes a value to a stable, indented JSON-like text for snapshot tests.
+///
+/// Object keys are sorted, so the snapshot does not depend on the order properties
+/// were added in. Unlike JSON, `undefined` and non-finite numbers are kept, as
+/// `undefined`, `NaN`, `Infinity` and `-Infinity`.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{testing::snapshot, JSContext};
+///
+/// let ctx = JSContext::new();
+/// let value = ctx.evaluate_script("({ b: [1], a: undefined })", None).unwrap();
+/// assert_eq!(snapshot(&value).unwrap(), "{\n  \"a\": undefined,\n  \"b\": [\n    1\n  ]\n}");
+/// ```
+///
+/// # Errors
+/// If an exception is thrown while reading the value, or it is nested deeper than
+/// [`SNAPSHOT_DEPTH`].
+pub fn snapshot(value: &JSValue) -> JSResult<String> {
+    let mut output = String::new();
+    render(&value.to_plain(SNAPSHOT_DEPTH)?, 0, &mut output);
+    Ok(output)
+}
+
+fn render(value: &PlainValue, level: usize, output: &mut String) {
+    let indentation = "  ".repeat(level + 1);
+    match value {
+        PlainValue::Undefined => output.push_str("undefined"),
+        PlainValue::Null => output.push_str("null"),
+        PlainValue::Bool(value) => output.push_str(&value.to_string()),
+        PlainValue::Number(value) if value.is_nan() => output.push_str("NaN"),
+        PlainValue::Number(value) if value.is_infinite() && *value > 0.0 => {
+            output.push_str("Infinity")
+        }
+        PlainValue::Number(value) if value.is_infinite() => output.push_str("-Infinity"),
+        PlainValue::Number(value) => output.push_str(&value.to_string()),
+        PlainValue::String(value) => output.push_str(&format!("{:?}", value)),
+        PlainValue::Array(items) if items.is_empty() => output.push_str("[]"),
+        PlainValue::Array(items) => {
+            output.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+                output.push_str(&indentation);
+                render(item, level + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&"  ".repeat(level));
+            output.push(']');
+        }
+        PlainValue::Object(properties) if properties.is_empty() => output.push_str("{}"),
+        PlainValue::Object(properties) => {
+            output.push_str("{\n");
+            for (index, (name, item)) in properties.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+                output.push_str(&format!("{}{:?}: ", indentation, name));
+                render(item, level + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&"  ".repeat(level));
+            output.push('}');
+        }
+    }
+}
+
+/// Evaluates the expression and panics unless its snapshot equals the snapshot of
+/// the expected JSON. Used by [`assert_js_eq!`](crate::assert_js_eq).
+///
+/// # Panics
+/// If the expression throws, the expected text is not valid JSON, or the values
+/// differ; the message shows both snapshots.
+#[track_caller]
+pub fn assert_js_eq(ctx: &JSContext, expression: &str, expected_json: &str) {
+    let actual = ctx
+        .evaluate_script(expression, None)
+        .and_then(|value| snapshot(&value))
+        .unwrap_or_else(|error| panic!("`{}` threw: {}", expression, describe(&error)));
+
+    let expected = JSValue::from_json(ctx, expected_json);
+    if expected.is_null() && expected_json.trim() != "null" {
+        panic!("expected value is not valid JSON: {}", expected_json);
+    }
+    let expected = snapshot(&expected)
+        .unwrap_or_else(|error| panic!("expected value threw: {}", describe(&error)));
+
+    if actual != expected {
+        panic!(
+            "assertion `{}` == expected failed\n  actual: {}\nexpected: {}",
+            expression,
+            actual.replace('\n', "\n          "),
+            expected.replace('\n', "\n          "),
+        );
+    }
+}
+
+/// Describes an exception for a panic message, falling back to its string value.
+fn describe(error: &JSError) -> String {
+    match error.message() {
+        Ok(message) => format!(
+            "{}: {}",
+            error
+                .name()
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            message
+        ),
+        Err(_) => error
+            .as_string()
+            .map(|value| value.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// The key of the module with the `describe`, `it` (or `test`) and `expect`
+/// functions of the test harness, see [`JSContext::install_test_module`].
+pub const TEST_MODULE: &str = "@rust-jsc/test";
+
+/// Creates the test harness of the context, exposing its API to the [`TEST_MODULE`]
+/// under a global symbol, and evaluates to the object with its `run` function, which
+/// is kept by the crate.
+const TEST_HARNESS: &str = r#"(() => {
+    const key = Symbol.for("rust_jsc.testHarness");
+
+    class AssertionError extends Error {}
+    AssertionError.prototype.name = "AssertionError";
+
+    const newSuite = () => ({ name: undefined, skip: false, entries: [] });
+    let root = newSuite();
+    let current = root;
+
+    function define(name, body, skip) {
+        const suite = { name: String(name), skip: skip || current.skip, entries: [] };
+        current.entries.push(suite);
+        const parent = current;
+        current = suite;
+        try {
+            body();
+        } finally {
+            current = parent;
+        }
+    }
+
+    function describe(name, body) {
+        define(name, body, false);
+    }
+    describe.skip = (name, body) => define(name, body, true);
+
+    function it(name, body) {
+        current.entries.push({ name: String(name), body, skip: current.skip });
+    }
+    it.skip = (name, body) => current.entries.push({ name: String(name), body, skip: true });
+
+    function format(value) {
+        if (typeof value === "string") {
+            return JSON.stringify(value);
+        }
+        if (typeof value === "bigint") {
+            return `${value}n`;
+        }
+        if (typeof value === "function") {
+            return `[Function ${value.name || "anonymous"}]`;
+        }
+        if (value instanceof Error || value instanceof RegExp) {
+            return String(value);
+        }
+        if (typeof value === "object" && value !== null) {
+            try {
+                const json = JSON.stringify(value);
+                if (json !== undefined) {
+                    return json;
+                }
+            } catch {}
+        }
+        return String(value);
+    }
+
+    function describeError(error) {
+        try {
+            return error instanceof Error ? `${error.name}: ${error.message}` : format(error);
+        } catch {
+            return "unknown error";
+        }
+    }
+
+    function equals(actual, expected, seen) {
+        if (Object.is(actual, expected)) {
+            return true;
+        }
+        if (typeof actual !== "object" || typeof expected !== "object"
+            || actual === null || expected === null
+            || Object.getPrototypeOf(actual) !== Object.getPrototypeOf(expected)) {
+            return false;
+        }
+        if (seen.some(([a, b]) => a === actual && b === expected)) {
+            return true;
+        }
+        seen.push([actual, expected]);
+        if (actual instanceof Date) {
+            return actual.getTime() === expected.getTime();
+        }
+        if (actual instanceof RegExp) {
+            return String(actual) === String(expected);
+        }
+        if (actual instanceof Map || actual instanceof Set) {
+            return equals([...actual], [...expected], seen);
+        }
+        const keys = Object.keys(actual);
+        return keys.length === Object.keys(expected).length
+            && keys.every((key) => Object.prototype.hasOwnProperty.call(expected, key)
+                && equals(actual[key], expected[key], seen));
+    }
+
+    function expect(actual) {
+        const matchers = (negated) => {
+            const check = (pass, description) => {
+                if (pass === negated) {
+                    const not = negated ? " not" : "";
+                    throw new AssertionError(`expected ${format(actual)}${not} ${description}`);
+                }
+            };
+            return {
+                toBe: (expected) => check(Object.is(actual, expected), `to be ${format(expected)}`),
+                toEqual: (expected) =>
+                    check(equals(actual, expected, []), `to equal ${format(expected)}`),
+                toBeTruthy: () => check(!!actual, "to be truthy"),
+                toBeFalsy: () => check(!actual, "to be falsy"),
+                toBeNull: () => check(actual === null, "to be null"),
+                toBeUndefined: () => check(actual === undefined, "to be undefined"),
+                toBeDefined: () => check(actual !== undefined, "to be defined"),
+                toBeNaN: () => check(Number.isNaN(actual), "to be NaN"),
+                toBeGreaterThan: (expected) =>
+                    check(actual > expected, `to be greater than ${format(expected)}`),
+                toBeLessThan: (expected) =>
+                    check(actual < expected, `to be less than ${format(expected)}`),
+                toBeInstanceOf: (expected) =>
+                    check(actual instanceof expected, `to be an instance of ${expected.name}`),
+                toContain: (expected) => check(actual != null
+                    && typeof actual.includes === "function" && actual.includes(expected),
+                    `to contain ${format(expected)}`),
+                toHaveLength: (expected) =>
+                    check(actual != null && actual.length === expected, `to have length ${expected}`),
+                toMatch: (expected) => check(typeof actual === "string"
+                    && (expected instanceof RegExp ? expected.test(actual) : actual.includes(expected)),
+                    `to match ${format(expected)}`),
+                toThrow: (expected) => {
+                    let thrown = false;
+                    let error;
+                    try {
+                        actual();
+                    } catch (caught) {
+                        thrown = true;
+                        error = caught;
+                    }
+                    const message = String(error instanceof Error ? error.message : error);
+                    const matches = thrown && (expected === undefined
+                        || (typeof expected === "function" && error instanceof expected)
+                        || (expected instanceof RegExp && expected.test(message))
+                        || (typeof expected === "string" && message.includes(expected)));
+                    check(matches, expected === undefined ? "to throw" : `to throw ${format(expected)}`);
+                },
+            };
+        };
+        return Object.assign(matchers(false), { not: matchers(true) });
+    }
+
+    let running = false;
+
+    async function run() {
+        if (running) {
+            throw new Error("The tests are already running");
+        }
+        running = true;
+        try {
+            return await runSuite();
+        } finally {
+            running = false;
+        }
+    }
+
+    async function runSuite() {
+        const suite = root;
+        root = current = newSuite();
+        const results = [];
+        const visit = async (suite, path) => {
+            for (const entry of suite.entries) {
+                const name = path.concat(entry.name);
+                if (entry.entries !== undefined) {
+                    await visit(entry, name);
+                } else if (entry.skip) {
+                    results.push({ path: name, status: "skipped" });
+                } else {
+                    try {
+                        await entry.body();
+                        results.push({ path: name, status: "passed" });
+                    } catch (error) {
+                        results.push({ path: name, status: "failed", message: describeError(error) });
+                    }
+                }
+            }
+        };
+        await visit(suite, []);
+        return results;
+    }
+
+    const api = Object.freeze({ describe, it, test: it, expect, AssertionError });
+    // Only the API is exposed to scripts; `run` stays with the embedder.
+    Object.defineProperty(globalThis, key, { value: api });
+    return { run };
+})()"#;
+
+/// Marks a context with the [`TEST_MODULE`] installed.
+struct TestModule;
+
+/// The source of [`TEST_MODULE`], which exports the API of the installed harness.
+const TEST_MODULE_SOURCE: &str = r#"const api = globalThis[Symbol.for("rust_jsc.testHarness")];
+export const { describe, it, test, expect, AssertionError } = api;
+export default api;
+"#;
+
+/// How a test of the [`TEST_MODULE`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    /// The test threw, or returned a promise that rejected, with the error
+    /// described as `name: message`.
+    Failed(String),
+    /// The test was declared with `it.skip`, or in a `describe.skip` suite.
+    Skipped,
+}
+
+/// The result of a test of the [`TEST_MODULE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    /// The names of the enclosing `describe` suites, followed by the test name.
+    pub path: Vec<String>,
+    pub outcome: TestOutcome,
+}
+
+impl TestResult {
+    /// Returns the full name of the test, with the suite names separated by `" > "`.
+    pub fn name(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+/// The results of a run of the tests declared with the [`TEST_MODULE`], in the
+/// order they were declared. Produced by [`JSContext::run_tests`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    /// Returns the number of tests that passed.
+    pub fn passed(&self) -> usize {
+        self.count(|outcome| *outcome == TestOutcome::Passed)
+    }
+
+    /// Returns the number of tests that failed.
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Failed(_)))
+    }
+
+    /// Returns the number of tests that were skipped.
+    pub fn skipped(&self) -> usize {
+        self.count(|outcome| *outcome == TestOutcome::Skipped)
+    }
+
+    /// Returns true if no test failed.
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// Returns the tests that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, TestOutcome::Failed(_)))
+    }
+
+    fn count(&self, predicate: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|result| predicate(&result.outcome))
+            .count()
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            match &result.outcome {
+                TestOutcome::Passed => writeln!(f, "ok {}", result.name())?,
+                TestOutcome::Failed(error) => {
+                    writeln!(f, "FAILED {}: {}", result.name(), error)?
+                }
+                TestOutcome::Skipped => writeln!(f, "skipped {}", result.name())?,
+            }
+        }
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped",
+            self.passed(),
+            self.failed(),
+            self.skipped()
+        )
+    }
+}
+
+impl JSContext {
+    /// Makes the [`TEST_MODULE`] importable, so scripts and plugins can declare
+    /// self-tests with `describe`, `it` (or `test`) and `expect`, which the embedder
+    /// runs with [`JSContext::run_tests`], e.g. to validate a plugin when it is loaded.
+    ///
+    /// `expect` has the matchers `toBe`, `toEqual`, `toBeTruthy`, `toBeFalsy`,
+    /// `toBeNull`, `toBeUndefined`, `toBeDefined`, `toBeNaN`, `toBeGreaterThan`,
+    /// `toBeLessThan`, `toBeInstanceOf`, `toContain`, `toHaveLength`, `toMatch` and
+    /// `toThrow`, each negated by `.not`. Tests may be async.
+    ///
+    /// The module is served by the module loader of [`JSContext::set_module_root`],
+    /// so it cannot be installed in a context with a module loader set with
+    /// [`JSContext::set_module_loader`]. Scripts can declare tests, but only the
+    /// embedder can run them.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::time::Duration;
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.install_test_module().unwrap();
+    /// ctx.evaluate_module_from_source(
+    ///     r#"
+    ///     import { describe, it, expect } from "@rust-jsc/test";
+    ///     describe("math", () => {
+    ///         it("adds", () => expect(1 + 1).toBe(2));
+    ///     });
+    ///     "#,
+    ///     "plugin.js",
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let report = ctx.run_tests(Duration::from_secs(1)).unwrap();
+    /// assert!(report.is_success());
+    /// ```
+    ///
+    /// # Errors
+    /// A `TypeError` if a module loader was set with [`JSContext::set_module_loader`],
+    /// or the exception thrown while creating the harness.
+    pub fn install_test_module(&self) -> JSResult<()> {
+        if self.get_extension::<CustomModuleLoader>().is_some() {
+            let message = "The test module needs the module loader of the crate, but \
+                           another one was set with set_module_loader";
+            return Err(JSError::new_typ(self, message)?);
+        }
+
+        ContextData::shim(self, "testHarness", TEST_HARNESS)?;
+        LoaderState::get(self).register_builtin(TEST_MODULE, TEST_MODULE_SOURCE);
+        self.set_extension(TestModule);
+        Ok(())
+    }
+
+    /// Runs the tests declared with the [`TEST_MODULE`] since the last run, one after
+    /// the other, and returns their results. The declared tests are cleared.
+    ///
+    /// # Arguments
+    /// - `budget`: The longest time to wait for async tests, see
+    ///   [`JSContext::run_until_settled`].
+    ///
+    /// # Errors
+    /// A `TypeError` if [`JSContext::install_test_module`] was not called, or a
+    /// `TimeoutError` if the tests do not finish within the budget. A failing test is
+    /// not an error; it is reported in the [`TestReport`].
+    pub fn run_tests(&self, budget: Duration) -> JSResult<TestReport> {
+        if self.get_extension::<TestModule>().is_none() {
+            return Err(JSError::new_typ(self, "The test module is not installed")?);
+        }
+
+        let harness = ContextData::shim(self, "testHarness", TEST_HARNESS)?;
+        let run = harness.get_property("run")?.as_object()?;
+        let promise = run.call(None, &[])?;
+        let results = self.run_until_settled(&promise, budget)?;
+        let results = match results.to_plain(4)? {
+            PlainValue::Array(results) => results,
+            _ => Vec::new(),
+        };
+
+        Ok(TestReport {
+            results: results.iter().filter_map(test_result).collect(),
+        })
+    }
+}
+
+/// Reads a result recorded by the harness, `{ path, status, message }`.
+fn test_result(value: &PlainValue) -> Option<TestResult> {
+    let PlainValue::Object(properties) = value else {
+        return None;
+    };
+    let text = |name: &str| match properties.get(name) {
+        Some(PlainValue::String(text)) => Some(text.clone()),
+        _ => None,
+    };
+
+    let path = match properties.get("path") {
+        Some(PlainValue::Array(names)) => names
+            .iter()
+            .filter_map(|name| match name {
+                PlainValue::String(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let outcome = match text("status")?.as_str() {
+        "passed" => TestOutcome::Passed,
+        "skipped" => TestOutcome::Skipped,
+        _ => TestOutcome::Failed(text("message").unwrap_or_default()),
+    };
+
+    Some(TestResult { path, outcome })
+}
+
+/// Asserts that a script expression evaluates to a value equal to the given JSON,
+/// comparing the [`snapshot`](crate::testing::snapshot)s of both, so object key
+/// order does not matter.
+///
+/// # Examples
+/// ```ignore
+/// use rust_jsc::{assert_js_eq, JSContext};
+///
+/// let ctx = JSContext::new();
+/// assert_js_eq!(ctx, "({ b: 2, a: [1] })", r#"{ "a": [1], "b": 2 }"#);
+/// ```
+#[macro_export]
+macro_rules! assert_js_eq {
+    ($ctx:expr, $expression:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_js_eq(&$ctx, $expression, $expected)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let ctx = JSContext::new();
+        let value = ctx
+            .evaluate_script(
+                "({ z: 'last', a: [1.5, null, undefined, NaN], m: { y: true, x: {} } })",
+                None,
+            )
+            .unwrap();
+        let expected = "{\n  \"a\": [\n    1.5,\n    null,\n    undefined,\n    NaN\n  ],\n  \
+                        \"m\": {\n    \"x\": {},\n    \"y\": true\n  },\n  \"z\": \"last\"\n}";
+        assert_eq!(snapshot(&value).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_assert_js_eq() {
+        let ctx = JSContext::new();
+        assert_js_eq!(
+            ctx,
+            "({ b: 2, a: [1, 'x'] })",
+            r#"{ "a": [1, "x"], "b": 2 }"#
+        );
+        assert_js_eq!(ctx, "null", "null");
+
+        let result = std::panic::catch_unwind(|| {
+            let ctx = JSContext::new();
+            assert_js_eq!(ctx, "({ a: 1 })", r#"{ "a": 2 }"#);
+        });
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("assertion `({ a: 1 })` == expected failed"));
+
+        let result = std::panic::catch_unwind(|| {
+            let ctx = JSContext::new();
+            assert_js_eq!(ctx, "missing", "1");
+        });
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("threw: ReferenceError"));
+    }
+
+    #[test]
+    fn test_test_module() {
+        let ctx = JSContext::new();
+        assert!(ctx.run_tests(Duration::from_secs(1)).is_err());
+        ctx.install_test_module().unwrap();
+        ctx.install_test_module().unwrap();
+        ctx.evaluate_module_from_source(
+            r#"
+            import { describe, it, test, expect } from "@rust-jsc/test";
+            describe("plugin", () => {
+                it("equals", () => expect({ a: [1, 2] }).toEqual({ a: [1, 2] }));
+                test("negates", () => expect(1).not.toBe(2));
+                it("throws", () => expect(() => { throw new TypeError("bad"); }).toThrow(TypeError));
+                it("fails", () => expect([1, 2]).toContain(3));
+                it("awaits", async () => expect(await Promise.resolve(4)).toBe(5));
+                it.skip("skips", () => {});
+            });
+            "#,
+            "plugin.js",
+            None,
+        )
+        .unwrap();
+
+        let report = ctx.run_tests(Duration::from_secs(1)).unwrap();
+        assert_eq!(report.results.len(), 6);
+        assert_eq!(report.results[0].name(), "plugin > equals");
+        assert_eq!(
+            (report.passed(), report.failed(), report.skipped()),
+            (3, 2, 1)
+        );
+        assert!(!report.is_success());
+
+        let failures: Vec<_> = report.failures().collect();
+        assert_eq!(
+            failures[0].outcome,
+            TestOutcome::Failed(
+                "AssertionError: expected [1,2] to contain 3".to_string()
+            )
+        );
+        assert_eq!(
+            failures[1].outcome,
+            TestOutcome::Failed("AssertionError: expected 4 to be 5".to_string())
+        );
+        assert!(report
+            .to_string()
+            .ends_with("3 passed, 2 failed, 1 skipped"));
+
+        let report = ctx.run_tests(Duration::from_secs(1)).unwrap();
+        assert!(report.results.is_empty());
+
+        let run = ctx
+            .evaluate_script(
+                "typeof globalThis[Symbol.for('rust_jsc.testHarness')].run",
+                None,
+            )
+            .unwrap();
+        assert_eq!(run.as_string().unwrap().to_string(), "undefined");
+    }
+
+    #[test]
+    fn test_test_module_keeps_custom_loader() {
+        let ctx = JSContext::new();
+        ctx.set_module_loader(rust_jsc_sys::JSAPIModuleLoader {
+            disableBuiltinFileSystemLoader: true,
+            moduleLoaderResolve: None,
+            moduleLoaderEvaluate: None,
+            moduleLoaderFetch: None,
+            moduleLoaderCreateImportMetaProperties: None,
+        });
+        let error = ctx.install_test_module().unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+}
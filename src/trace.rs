@@ -0,0 +1,203 @@
+//! Tracing of the calls between scripts and native functions, to profile the
+//! overhead of bindings.
+
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust_jsc_sys::{JSObjectGetProperty, JSObjectRef, JSValueRef};
+
+use crate::{JSContext, JSObject, JSString, JSValue};
+
+/// The direction of a traced call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallDirection {
+    /// A script called a native function.
+    JsToNative,
+    /// Native code called a JavaScript function, e.g. with [`JSObject::call`].
+    NativeToJs,
+}
+
+/// A call reported to the hook of [`JSContext::set_call_trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTrace {
+    /// The direction of the call.
+    pub direction: CallDirection,
+    /// The `name` of the function called, or `"anonymous"` when it has none.
+    pub name: String,
+    /// The number of arguments passed.
+    pub arguments: usize,
+    /// How long the call took, including the calls it made.
+    pub duration: Duration,
+}
+
+type CallTraceHook = dyn Fn(&CallTrace);
+
+/// The hook of a context, stored as a context extension.
+struct CallTracer(Rc<CallTraceHook>);
+
+/// Measures a call and reports it when dropped, if the context traces calls.
+#[doc(hidden)]
+pub struct CallSpan {
+    traced: Option<(Rc<CallTraceHook>, CallTrace, Instant)>,
+}
+
+impl CallSpan {
+    /// Starts measuring a call of `function` with `arguments` arguments.
+    pub fn enter(
+        ctx: &JSContext,
+        direction: CallDirection,
+        function: &JSObject,
+        arguments: usize,
+    ) -> Self {
+        Self::enter_ref(ctx, direction, function.inner, arguments)
+    }
+
+    /// Starts measuring a call of the `function` reference, which is only wrapped and
+    /// asked for its name when the context traces calls.
+    pub(crate) fn enter_ref(
+        ctx: &JSContext,
+        direction: CallDirection,
+        function: JSObjectRef,
+        arguments: usize,
+    ) -> Self {
+        let Some(tracer) = ctx.get_extension::<CallTracer>() else {
+            return Self { traced: None };
+        };
+
+        let trace = CallTrace {
+            direction,
+            name: function_name(ctx, function),
+            arguments,
+            duration: Duration::ZERO,
+        };
+
+        Self {
+            traced: Some((tracer.0.clone(), trace, Instant::now())),
+        }
+    }
+}
+
+/// Returns the `name` of a function, or `"anonymous"`. An exception thrown by a
+/// `name` getter is ignored rather than reported to the exception hook.
+fn function_name(ctx: &JSContext, function: JSObjectRef) -> String {
+    let mut exception: JSValueRef = std::ptr::null_mut();
+    let key = JSString::from("name");
+    let name =
+        unsafe { JSObjectGetProperty(ctx.inner, function, key.inner, &mut exception) };
+    if !exception.is_null() {
+        return "anonymous".to_string();
+    }
+
+    let name = JSValue::new(name, ctx.inner);
+    name.is_string()
+        .then(|| name.as_string().ok())
+        .flatten()
+        .map(|name| name.to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "anonymous".to_string())
+}
+
+impl Drop for CallSpan {
+    fn drop(&mut self) {
+        if let Some((hook, mut trace, start)) = self.traced.take() {
+            trace.duration = start.elapsed();
+            hook(&trace);
+        }
+    }
+}
+
+impl JSContext {
+    /// Sets a hook that is called after every call from a script to a native
+    /// function, and from native code to a JavaScript function, with the name of the
+    /// function, the number of arguments and the duration of the call. Replaces the
+    /// previous hook.
+    ///
+    /// Native functions are traced when defined with `#[callback]` or
+    /// `#[constructor]`, or as closures. Calls that scripts make to other JavaScript
+    /// functions are not traced.
+    ///
+    /// # Arguments
+    /// - `hook`: The hook.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::JSContext;
+    ///
+    /// let ctx = JSContext::new();
+    /// ctx.set_call_trace(|call| {
+    ///     println!("{:?} {}({}) took {:?}", call.direction, call.name, call.arguments, call.duration);
+    /// });
+    /// ctx.evaluate_script("host.log('hello')", None);
+    /// ctx.clear_call_trace();
+    /// ```
+    pub fn set_call_trace<F>(&self, hook: F)
+    where
+        F: Fn(&CallTrace) + 'static,
+    {
+        self.set_extension(CallTracer(Rc::new(hook)));
+    }
+
+    /// Removes the hook of [`JSContext::set_call_trace`], so calls are no longer
+    /// measured.
+    pub fn clear_call_trace(&self) {
+        self.remove_extension::<CallTracer>();
+    }
+
+    /// Returns `true` if the context has a hook set with
+    /// [`JSContext::set_call_trace`].
+    pub fn is_tracing_calls(&self) -> bool {
+        self.get_extension::<CallTracer>().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{JSFunction, JSValue};
+
+    #[test]
+    fn test_call_trace() {
+        let ctx = JSContext::new();
+        let add: JSObject = JSFunction::closure(&ctx, |ctx, _, arguments| {
+            let sum = arguments
+                .iter()
+                .map(|value| value.as_number().unwrap_or_default())
+                .sum::<f64>();
+            Ok(JSValue::number(ctx, sum))
+        })
+        .into();
+        ctx.global_object()
+            .set_property("add", &add.into(), Default::default())
+            .unwrap();
+        let twice = ctx
+            .evaluate_script("(function twice(x) { return add(x, x); })", None)
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let recorded = calls.clone();
+        ctx.set_call_trace(move |call| recorded.borrow_mut().push(call.clone()));
+        assert!(ctx.is_tracing_calls());
+
+        let result = twice.call(None, &[JSValue::number(&ctx, 2.0)]).unwrap();
+        assert_eq!(result.as_number().unwrap(), 4.0);
+
+        let calls = calls.take();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].direction, CallDirection::JsToNative);
+        assert_eq!(calls[0].arguments, 2);
+        assert_eq!(calls[1].direction, CallDirection::NativeToJs);
+        assert_eq!(calls[1].name, "twice");
+        assert_eq!(calls[1].arguments, 1);
+        assert!(calls[1].duration >= calls[0].duration);
+
+        ctx.clear_call_trace();
+        assert!(!ctx.is_tracing_calls());
+        twice.call(None, &[JSValue::number(&ctx, 1.0)]).unwrap();
+    }
+}
@@ -49,8 +49,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         let object = JSObject::from_ref(result, ctx.inner);
@@ -106,8 +105,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         if result.is_null() {
@@ -149,8 +147,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(JSTypedArrayType::from_type(_type))
@@ -184,8 +181,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(result)
@@ -219,8 +215,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(result)
@@ -254,8 +249,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(result)
@@ -289,8 +283,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(JSArrayBuffer::from_object(JSObject::from_ref(
@@ -332,8 +325,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         assert!(!result.is_null(), "TypedArray pointer is null");
@@ -372,6 +364,25 @@ impl JSTypedArray {
         Ok(self.bytes::<T>()?.to_vec())
     }
 
+    /// Creates a `Uint8Array` holding a copy of the bytes.
+    pub(crate) fn from_slice(ctx: &JSContext, bytes: &[u8]) -> JSResult<Self> {
+        let typed_array = Self::new(ctx, bytes.len())?;
+        if !bytes.is_empty() {
+            typed_array.bytes::<u8>()?.copy_from_slice(bytes);
+        }
+
+        Ok(typed_array)
+    }
+
+    /// Copies the bytes of the Typed Array, which may be empty.
+    pub(crate) fn to_bytes(&self) -> JSResult<Vec<u8>> {
+        if self.byte_len()? == 0 {
+            return Ok(Vec::new());
+        }
+
+        self.as_vec::<u8>()
+    }
+
     /// Creates a JavaScript Typed Array object from an existing buffer.
     ///
     /// # Arguments
@@ -412,8 +423,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self {
@@ -468,8 +478,7 @@ impl JSTypedArray {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         Ok(Self {
@@ -531,8 +540,7 @@ impl JSArrayBuffer {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         Ok(result)
@@ -567,8 +575,7 @@ impl JSArrayBuffer {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.object.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.object.ctx));
         }
 
         assert!(!result.is_null(), "ArrayBuffer pointer is null");
@@ -624,8 +631,7 @@ impl JSArrayBuffer {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, ctx.inner);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, ctx.inner));
         }
 
         if result.is_null() {
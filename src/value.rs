@@ -1,22 +1,60 @@
+use std::{any::Any, sync::Arc};
+
 use rust_jsc_sys::{
-    JSContextRef, JSObjectRef, JSValueCreateJSONString, JSValueGetType, JSValueIsArray,
-    JSValueIsBoolean, JSValueIsDate, JSValueIsEqual, JSValueIsInstanceOfConstructor,
-    JSValueIsNull, JSValueIsNumber, JSValueIsObject, JSValueIsObjectOfClass,
-    JSValueIsStrictEqual, JSValueIsString, JSValueIsSymbol, JSValueIsUndefined,
-    JSValueMakeBoolean, JSValueMakeFromJSONString, JSValueMakeNull, JSValueMakeNumber,
-    JSValueMakeString, JSValueMakeSymbol, JSValueMakeUndefined, JSValueProtect,
-    JSValueRef, JSValueToBoolean, JSValueToNumber, JSValueToObject, JSValueToStringCopy,
+    JSContextRef, JSObjectGetPrivate, JSObjectMake, JSObjectRef, JSValueCreateJSONString,
+    JSValueGetType, JSValueGetTypedArrayType, JSValueIsArray, JSValueIsBoolean,
+    JSValueIsDate, JSValueIsEqual, JSValueIsInstanceOfConstructor, JSValueIsNull,
+    JSValueIsNumber, JSValueIsObject, JSValueIsObjectOfClass, JSValueIsStrictEqual,
+    JSValueIsString, JSValueIsSymbol, JSValueIsUndefined, JSValueMakeBoolean,
+    JSValueMakeFromJSONString, JSValueMakeNull, JSValueMakeNumber, JSValueMakeString,
+    JSValueMakeSymbol, JSValueMakeUndefined, JSValueProtect, JSValueRef,
+    JSValueToBoolean, JSValueToNumber, JSValueToObject, JSValueToStringCopy,
     JSValueUnprotect,
 };
 
 use crate::{
-    JSClass, JSContext, JSError, JSObject, JSResult, JSString, JSValue, JSValueType,
+    audit, class::StaticClass, context_data::ContextData, JSClass, JSContext, JSError,
+    JSObject, JSResult, JSString, JSTypedArrayType, JSValue, JSValueKind, JSValueType,
+    PrimitiveHint,
 };
 
+/// Returns the `Symbol.toPrimitive` method of an object, read with the symbol of the
+/// context when the shim was created, so scripts replacing `Symbol` do not affect it.
+const TO_PRIMITIVE: &str = r#"(() => {
+    const toPrimitive = Symbol.toPrimitive;
+    return (object) => object[toPrimitive];
+})()"#;
+
+/// The largest integer a double represents exactly, `Number.MAX_SAFE_INTEGER`.
+pub const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+static EXTERNAL_CLASS: StaticClass = StaticClass::new();
+
+fn external_class() -> &'static JSClass {
+    EXTERNAL_CLASS.get_or_init(|| {
+        JSClass::builder("External")
+            .set_finalize(Some(external_finalize))
+            .build()
+            .expect("Failed to create external class")
+    })
+}
+
+unsafe extern "C" fn external_finalize(object: JSObjectRef) {
+    let value = JSObjectGetPrivate(object) as *mut Arc<dyn Any + Send + Sync>;
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
 impl JSValue {
     /// Creates a new `JSValue` object.
     pub fn new(inner: JSValueRef, ctx: JSContextRef) -> Self {
-        Self { inner, ctx }
+        Self {
+            inner,
+            ctx,
+            #[cfg(feature = "value-audit")]
+            tag: audit::tag(ctx),
+        }
     }
 
     /// Creates a JavaScript boolean value.
@@ -97,6 +135,34 @@ impl JSValue {
         Self::new(inner, ctx.inner)
     }
 
+    /// Creates the JavaScript number `NaN`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(JSValue::nan(&ctx).is_nan());
+    /// ```
+    pub fn nan(ctx: &JSContext) -> JSValue {
+        Self::number(ctx, f64::NAN)
+    }
+
+    /// Creates the JavaScript number `Infinity`; negate it with
+    /// `JSValue::number(ctx, f64::NEG_INFINITY)`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = JSValue::infinity(&ctx);
+    /// assert!(value.is_number() && !value.is_finite());
+    /// ```
+    pub fn infinity(ctx: &JSContext) -> JSValue {
+        Self::number(ctx, f64::INFINITY)
+    }
+
     /// Creates a JavaScript string value from a string.
     ///
     /// # Arguments
@@ -139,6 +205,32 @@ impl JSValue {
         Self::new(inner, ctx.inner)
     }
 
+    /// Creates an opaque JavaScript object that carries a host value.
+    /// JavaScript code can pass it around but cannot inspect it; the host gets the
+    /// value back with [`JSValue::as_external`]. The reference is released when the
+    /// object is garbage collected.
+    ///
+    /// # Arguments
+    /// * `value` - The host value.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::sync::Arc;
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = JSValue::external(&ctx, Arc::new(42));
+    /// assert!(value.is_external());
+    /// ```
+    ///
+    /// # Returns
+    /// A JavaScript object wrapping the host value.
+    pub fn external(ctx: &JSContext, value: Arc<dyn Any + Send + Sync>) -> JSValue {
+        let data = Box::into_raw(Box::new(value)) as *mut std::ffi::c_void;
+        let inner = unsafe { JSObjectMake(ctx.inner, external_class().inner, data) };
+        Self::new(inner, ctx.inner)
+    }
+
     /// Creates a JavaScript value from a JSON serialized string.
     ///
     /// # Arguments
@@ -178,20 +270,21 @@ impl JSValue {
     /// # Returns
     /// A JSString with the result of serialization, or JSError if an exception occurs.
     pub fn as_json_string(&self, indent: u32) -> JSResult<JSString> {
+        audit::check_value(self, "JSValue::as_json_string");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let string = unsafe {
             JSValueCreateJSONString(self.ctx, self.inner, indent, &mut exception)
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(string.into())
     }
 
     /// Converts a JavaScript value to a js string and returns the resulting js string.
+    /// This is the `ToString` abstract operation, which throws for symbols.
     ///
     /// # Examples
     /// ```
@@ -205,12 +298,12 @@ impl JSValue {
     /// # Returns
     /// A JavaScript string.
     pub fn as_string(&self) -> JSResult<JSString> {
+        audit::check_value(self, "JSValue::as_string");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let string = unsafe { JSValueToStringCopy(self.ctx, self.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(string.into())
@@ -230,17 +323,45 @@ impl JSValue {
     /// # Returns
     /// A JavaScript object.
     pub fn as_object(&self) -> JSResult<JSObject> {
+        audit::check_value(self, "JSValue::as_object");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let object = unsafe { JSValueToObject(self.ctx, self.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(JSObject::from_ref(object, self.ctx))
     }
 
+    /// Returns the host value wrapped by [`JSValue::external`], if this value is an
+    /// external value holding a `T`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::sync::Arc;
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = JSValue::external(&ctx, Arc::new(String::from("handle")));
+    /// assert_eq!(value.as_external::<String>().unwrap().as_str(), "handle");
+    /// assert!(value.as_external::<i32>().is_none());
+    /// ```
+    ///
+    /// # Returns
+    /// A new reference to the host value, or `None` if the value is not an external
+    /// value or holds a different type.
+    pub fn as_external<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        if !self.is_external() {
+            return None;
+        }
+
+        let value = unsafe { JSObjectGetPrivate(self.inner as JSObjectRef) }
+            as *const Arc<dyn Any + Send + Sync>;
+        let value = unsafe { value.as_ref() }?;
+        value.clone().downcast::<T>().ok()
+    }
+
     /// Converts a JavaScript value to boolean and returns the resulting boolean.
     ///
     /// # Examples
@@ -255,10 +376,12 @@ impl JSValue {
     /// # Returns
     /// A boolean value.
     pub fn as_boolean(&self) -> bool {
+        audit::check_value(self, "JSValue::as_boolean");
         unsafe { JSValueToBoolean(self.ctx, self.inner) }
     }
 
     /// Converts a JavaScript value to number and returns the resulting number.
+    /// This is the `ToNumber` abstract operation, which throws for symbols and BigInts.
     ///
     /// # Examples
     /// ```
@@ -272,17 +395,93 @@ impl JSValue {
     /// # Returns
     /// A number value.
     pub fn as_number(&self) -> JSResult<f64> {
+        audit::check_value(self, "JSValue::as_number");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let number = unsafe { JSValueToNumber(self.ctx, self.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(number)
     }
 
+    /// Converts the value to a primitive value, like the `ToPrimitive` abstract
+    /// operation: primitives are returned as is, objects are converted with their
+    /// `Symbol.toPrimitive` method, or else with `valueOf` and `toString` in the
+    /// order given by the hint.
+    ///
+    /// Together with [`JSValue::as_number`] and [`JSValue::as_string`], which are
+    /// the `ToNumber` and `ToString` operations, this lets host code implement
+    /// operators with the coercions of JavaScript.
+    ///
+    /// # Arguments
+    /// - `hint`: The preferred type of the primitive value.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let date = ctx.evaluate_script("new Date(0)", None).unwrap();
+    /// assert!(date.to_primitive(PrimitiveHint::Number).unwrap().is_number());
+    /// assert!(date.to_primitive(PrimitiveHint::Default).unwrap().is_string());
+    /// ```
+    ///
+    /// # Errors
+    /// If a conversion method throws, or a `TypeError` if the object cannot be
+    /// converted to a primitive value.
+    pub fn to_primitive(&self, hint: PrimitiveHint) -> JSResult<JSValue> {
+        if !self.is_object() {
+            return Ok(self.clone());
+        }
+
+        let ctx = JSContext::from(self.ctx);
+        let object = self.as_object()?;
+        let hint_name = match hint {
+            PrimitiveHint::Default => "default",
+            PrimitiveHint::Number => "number",
+            PrimitiveHint::String => "string",
+        };
+
+        let to_primitive = ContextData::shim(&ctx, "toPrimitive", TO_PRIMITIVE)?
+            .call(None, std::slice::from_ref(self))?;
+        if !to_primitive.is_undefined() && !to_primitive.is_null() {
+            let to_primitive = to_primitive.as_object()?;
+            if !to_primitive.is_function() {
+                let message = "Symbol.toPrimitive is not a function";
+                return Err(JSError::new_typ(&ctx, message)?);
+            }
+
+            let result =
+                to_primitive.call(Some(&object), &[JSValue::string(&ctx, hint_name)])?;
+            if result.is_object() {
+                let message = "Symbol.toPrimitive returned an object";
+                return Err(JSError::new_typ(&ctx, message)?);
+            }
+            return Ok(result);
+        }
+
+        let methods = match hint {
+            PrimitiveHint::String => ["toString", "valueOf"],
+            _ => ["valueOf", "toString"],
+        };
+        for method in methods {
+            let function = object.get_property(method)?;
+            if !function.is_object() || !function.as_object()?.is_function() {
+                continue;
+            }
+
+            let result = function.as_object()?.call(Some(&object), &[])?;
+            if !result.is_object() {
+                return Ok(result);
+            }
+        }
+
+        let message = "Cannot convert object to primitive value";
+        Err(JSError::new_typ(&ctx, message)?)
+    }
+
     /// Checks if the value is undefined.
     ///
     /// # Examples
@@ -351,6 +550,56 @@ impl JSValue {
         unsafe { JSValueIsNumber(self.ctx, self.inner) }
     }
 
+    /// Checks if the value is the number `NaN`, like `Number.isNaN`: other values
+    /// are not converted, so `"abc"` is not `NaN`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(JSValue::nan(&ctx).is_nan());
+    /// assert!(!JSValue::string(&ctx, "abc").is_nan());
+    /// ```
+    pub fn is_nan(&self) -> bool {
+        self.number_value().is_some_and(f64::is_nan)
+    }
+
+    /// Checks if the value is a finite number, like `Number.isFinite`: `NaN`,
+    /// infinities and values that are not numbers are not finite.
+    pub fn is_finite(&self) -> bool {
+        self.number_value().is_some_and(f64::is_finite)
+    }
+
+    /// Checks if the value is an integral number that a double represents exactly,
+    /// like `Number.isSafeInteger`: its magnitude is at most `2^53 - 1`.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert!(JSValue::number(&ctx, 9007199254740991.0).is_safe_integer());
+    /// assert!(!JSValue::number(&ctx, 9007199254740992.0).is_safe_integer());
+    /// assert!(!JSValue::number(&ctx, 1.5).is_safe_integer());
+    /// ```
+    pub fn is_safe_integer(&self) -> bool {
+        self.number_value().is_some_and(|number| {
+            number.is_finite()
+                && number.trunc() == number
+                && number.abs() <= MAX_SAFE_INTEGER
+        })
+    }
+
+    /// Returns the number of a number value, without converting other values.
+    fn number_value(&self) -> Option<f64> {
+        if !self.is_number() {
+            return None;
+        }
+
+        self.as_number().ok()
+    }
+
     /// Checks if the value is a string.
     ///
     /// # Examples
@@ -436,6 +685,68 @@ impl JSValue {
         unsafe { JSValueIsDate(self.ctx, self.inner) }
     }
 
+    /// Checks if the value is a typed array, such as a `Uint8Array`. `ArrayBuffer`s
+    /// are not typed arrays.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("new Uint8Array(4)", None).unwrap();
+    /// assert!(value.is_typed_array());
+    /// ```
+    ///
+    /// # Returns
+    /// A boolean value.
+    pub fn is_typed_array(&self) -> bool {
+        let mut exception: JSValueRef = std::ptr::null_mut();
+        let array_type = JSTypedArrayType::from_type(unsafe {
+            JSValueGetTypedArrayType(self.ctx, self.inner, &mut exception)
+        });
+        !matches!(
+            array_type,
+            JSTypedArrayType::None | JSTypedArrayType::ArrayBuffer
+        )
+    }
+
+    /// Checks if the value is an array-like object: an object other than a function
+    /// whose `length` is an integer from `0` to `Number.MAX_SAFE_INTEGER`, such as an
+    /// array, an `arguments` object or a typed array. Strings are not objects, so
+    /// they are not array-like, but they are iterable, see [`JSValue::is_iterable`].
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("({ length: 2, 0: 'a', 1: 'b' })", None).unwrap();
+    /// assert!(value.is_array_like());
+    /// ```
+    ///
+    /// # Returns
+    /// A boolean value, `false` if reading `length` throws.
+    pub fn is_array_like(&self) -> bool {
+        if !self.is_object() {
+            return false;
+        }
+        let Ok(object) = self.as_object() else {
+            return false;
+        };
+        if object.is_function() {
+            return false;
+        }
+
+        object
+            .get_property("length")
+            .ok()
+            .filter(JSValue::is_number)
+            .and_then(|length| length.as_number().ok())
+            .is_some_and(|length| {
+                length >= 0.0 && length.fract() == 0.0 && length <= MAX_SAFE_INTEGER
+            })
+    }
+
     /// Tests whether a JavaScript value is an object constructed by a given constructor,
     /// as compared by the JS instanceof operator.
     ///
@@ -457,8 +768,7 @@ impl JSValue {
         };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(result)
@@ -485,6 +795,14 @@ impl JSValue {
         return Ok(unsafe { JSValueIsObjectOfClass(self.ctx, self.inner, class.inner) });
     }
 
+    /// Checks if the value was created with [`JSValue::external`].
+    ///
+    /// # Returns
+    /// A boolean value.
+    pub fn is_external(&self) -> bool {
+        unsafe { JSValueIsObjectOfClass(self.ctx, self.inner, external_class().inner) }
+    }
+
     /// Tests whether two JavaScript values are equal, as compared by the JS == operator.
     ///
     /// # Arguments
@@ -503,13 +821,13 @@ impl JSValue {
     /// # Returns
     /// true if the values are equal, otherwise false.
     pub fn is_equal(&self, other: &JSValue) -> JSResult<bool> {
+        audit::check_value(self, "JSValue::is_equal");
         let mut exception: JSValueRef = std::ptr::null_mut();
         let result =
             unsafe { JSValueIsEqual(self.ctx, self.inner, other.inner, &mut exception) };
 
         if !exception.is_null() {
-            let value = JSValue::new(exception, self.ctx);
-            return Err(JSError::from(value));
+            return Err(JSError::from_exception(exception, self.ctx));
         }
 
         Ok(result)
@@ -533,6 +851,7 @@ impl JSValue {
     /// A value may be protected multiple times and must be unprotected an equal number of times
     /// before becoming eligible for garbage collection.
     pub fn protect(&self) {
+        audit::check_value(self, "JSValue::protect");
         unsafe { JSValueProtect(self.ctx, self.inner) };
     }
 
@@ -552,6 +871,7 @@ impl JSValue {
     /// A value may be protected multiple times and must be unprotected an\n
     /// equal number of times before becoming eligible for garbage collection.
     pub fn unprotect(&self) {
+        audit::check_value(self, "JSValue::unprotect");
         unsafe { JSValueUnprotect(self.ctx, self.inner) };
     }
 
@@ -572,6 +892,111 @@ impl JSValue {
         let type_ = unsafe { JSValueGetType(self.ctx, self.inner) };
         JSValueType::from_js_type(type_)
     }
+
+    /// Returns the result of the `typeof` operator for the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// assert_eq!(JSValue::null(&ctx).typeof_str(), "object");
+    /// let value = ctx.evaluate_script("() => {}", None).unwrap();
+    /// assert_eq!(value.typeof_str(), "function");
+    /// let value = ctx.evaluate_script("10n", None).unwrap();
+    /// assert_eq!(value.typeof_str(), "bigint");
+    /// ```
+    ///
+    /// # Returns
+    /// One of `undefined`, `object`, `boolean`, `number`, `bigint`, `string`,
+    /// `symbol` or `function`.
+    pub fn typeof_str(&self) -> &'static str {
+        match self.get_type() {
+            JSValueType::Undefined => "undefined",
+            JSValueType::Null => "object",
+            JSValueType::Boolean => "boolean",
+            JSValueType::Number => "number",
+            JSValueType::String => "string",
+            JSValueType::Symbol => "symbol",
+            // The C API reports BigInts as objects.
+            JSValueType::Object if !self.is_object() => "bigint",
+            JSValueType::Object => match self.as_object() {
+                Ok(object) if object.is_function() => "function",
+                _ => "object",
+            },
+        }
+    }
+
+    /// Returns the kind of the value, telling apart the built-in object types.
+    /// Objects are classified by their internal type where the C API exposes it,
+    /// and otherwise with `instanceof` against the constructors of the context.
+    ///
+    /// # Examples
+    /// ```
+    /// use rust_jsc::*;
+    ///
+    /// let ctx = JSContext::new();
+    /// let value = ctx.evaluate_script("/a+/g", None).unwrap();
+    /// assert_eq!(value.match_type(), JSValueKind::RegExp);
+    /// let value = ctx.evaluate_script("new Float64Array(2)", None).unwrap();
+    /// assert_eq!(
+    ///     value.match_type(),
+    ///     JSValueKind::TypedArray(JSTypedArrayType::Float64Array)
+    /// );
+    /// ```
+    ///
+    /// # Returns
+    /// The kind of the JavaScript value.
+    pub fn match_type(&self) -> JSValueKind {
+        match self.typeof_str() {
+            "undefined" => return JSValueKind::Undefined,
+            "boolean" => return JSValueKind::Boolean,
+            "number" => return JSValueKind::Number,
+            "bigint" => return JSValueKind::BigInt,
+            "string" => return JSValueKind::String,
+            "symbol" => return JSValueKind::Symbol,
+            "function" => return JSValueKind::Function,
+            _ if self.is_null() => return JSValueKind::Null,
+            _ => {}
+        }
+
+        let mut exception: JSValueRef = std::ptr::null_mut();
+        let array_type = JSTypedArrayType::from_type(unsafe {
+            JSValueGetTypedArrayType(self.ctx, self.inner, &mut exception)
+        });
+        match array_type {
+            JSTypedArrayType::None => {}
+            JSTypedArrayType::ArrayBuffer => return JSValueKind::ArrayBuffer,
+            array_type => return JSValueKind::TypedArray(array_type),
+        }
+
+        if self.is_array() {
+            return JSValueKind::Array;
+        }
+
+        if self.is_date() {
+            return JSValueKind::Date;
+        }
+
+        let ctx = JSContext::from(self.ctx);
+        let is_instance_of = |name: &str| {
+            ctx.global_constructor(name)
+                .and_then(|constructor| self.is_instance_of(&constructor.into()))
+                .unwrap_or(false)
+        };
+
+        [
+            ("RegExp", JSValueKind::RegExp),
+            ("Error", JSValueKind::Error),
+            ("Promise", JSValueKind::Promise),
+            ("Map", JSValueKind::Map),
+            ("Set", JSValueKind::Set),
+        ]
+        .into_iter()
+        .find(|(name, _)| is_instance_of(name))
+        .map(|(_, kind)| kind)
+        .unwrap_or(JSValueKind::Object)
+    }
 }
 
 /// This is equivalent to `===` in JavaScript.
@@ -595,7 +1020,130 @@ impl From<JSValue> for JSObjectRef {
 
 #[cfg(test)]
 mod tests {
-    use crate::{JSObject, JSValue};
+    use crate::{
+        JSContext, JSObject, JSTypedArrayType, JSValue, JSValueKind, PrimitiveHint,
+    };
+
+    #[test]
+    fn test_number_helpers() {
+        let ctx = JSContext::new();
+        let nan = JSValue::nan(&ctx);
+        let infinity = JSValue::infinity(&ctx);
+        assert!(nan.is_nan() && !nan.is_finite() && !nan.is_safe_integer());
+        assert!(!infinity.is_nan() && !infinity.is_finite());
+        assert!(!infinity.is_safe_integer());
+
+        let string = JSValue::string(&ctx, "abc");
+        assert!(!string.is_nan() && !string.is_finite());
+        assert!(!JSValue::string(&ctx, "1").is_safe_integer());
+
+        for (script, safe) in [
+            ("Number.MAX_SAFE_INTEGER", true),
+            ("-Number.MAX_SAFE_INTEGER", true),
+            ("Number.MAX_SAFE_INTEGER + 1", false),
+            ("-0", true),
+            ("1.5", false),
+        ] {
+            let value = ctx.evaluate_script(script, None).unwrap();
+            assert_eq!(value.is_safe_integer(), safe, "{}", script);
+            assert!(value.is_finite());
+        }
+
+        let result = ctx
+            .evaluate_script("Number.MAX_SAFE_INTEGER", None)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), super::MAX_SAFE_INTEGER);
+    }
+
+    #[test]
+    fn test_to_primitive() {
+        let ctx = JSContext::new();
+        let number = JSValue::number(&ctx, 1.5);
+        let result = number.to_primitive(PrimitiveHint::String).unwrap();
+        assert_eq!(result, number);
+
+        let date = ctx.evaluate_script("new Date(0)", None).unwrap();
+        let result = date.to_primitive(PrimitiveHint::Number).unwrap();
+        assert_eq!(result.as_number().unwrap(), 0.0);
+        assert!(date
+            .to_primitive(PrimitiveHint::Default)
+            .unwrap()
+            .is_string());
+
+        let object = ctx
+            .evaluate_script(
+                "({ valueOf() { return 2 }, toString() { return 'two' } })",
+                None,
+            )
+            .unwrap();
+        let result = object.to_primitive(PrimitiveHint::Default).unwrap();
+        assert_eq!(result.as_number().unwrap(), 2.0);
+        let result = object.to_primitive(PrimitiveHint::String).unwrap();
+        assert_eq!(result.as_string().unwrap(), "two");
+
+        let hinted = ctx
+            .evaluate_script("({ [Symbol.toPrimitive](hint) { return hint } })", None)
+            .unwrap();
+        let result = hinted.to_primitive(PrimitiveHint::Number).unwrap();
+        assert_eq!(result.as_string().unwrap(), "number");
+        ctx.evaluate_script("globalThis.Symbol = { toPrimitive: 'valueOf' }", None)
+            .unwrap();
+        let result = hinted.to_primitive(PrimitiveHint::String).unwrap();
+        assert_eq!(result.as_string().unwrap(), "string");
+
+        let opaque = ctx.evaluate_script("Object.create(null)", None).unwrap();
+        let error = opaque.to_primitive(PrimitiveHint::Default).unwrap_err();
+        assert_eq!(error.name().unwrap().to_string(), "TypeError");
+    }
+
+    #[test]
+    fn test_typeof_str() {
+        let ctx = JSContext::new();
+        let cases = [
+            ("undefined", "undefined"),
+            ("null", "object"),
+            ("true", "boolean"),
+            ("1.5", "number"),
+            ("10n", "bigint"),
+            ("'a'", "string"),
+            ("Symbol()", "symbol"),
+            ("class A {}", "function"),
+            ("({})", "object"),
+        ];
+
+        for (script, expected) in cases {
+            let value = ctx.evaluate_script(script, None).unwrap();
+            assert_eq!(value.typeof_str(), expected, "typeof {}", script);
+        }
+    }
+
+    #[test]
+    fn test_match_type() {
+        let ctx = JSContext::new();
+        let cases = [
+            ("null", JSValueKind::Null),
+            ("10n", JSValueKind::BigInt),
+            ("Math.max", JSValueKind::Function),
+            ("[1]", JSValueKind::Array),
+            ("new Date()", JSValueKind::Date),
+            ("/a/", JSValueKind::RegExp),
+            ("new RangeError()", JSValueKind::Error),
+            ("Promise.resolve()", JSValueKind::Promise),
+            ("new Map()", JSValueKind::Map),
+            ("new Set()", JSValueKind::Set),
+            ("new ArrayBuffer(1)", JSValueKind::ArrayBuffer),
+            (
+                "new Uint8Array(1)",
+                JSValueKind::TypedArray(JSTypedArrayType::Uint8Array),
+            ),
+            ("({})", JSValueKind::Object),
+        ];
+
+        for (script, expected) in cases {
+            let value = ctx.evaluate_script(script, None).unwrap();
+            assert_eq!(value.match_type(), expected, "{}", script);
+        }
+    }
 
     #[test]
     fn test_boolean() {
@@ -789,4 +1337,20 @@ mod tests {
         let value2 = JSValue::number(&ctx, 42.0);
         assert_eq!(value1, value2);
     }
+
+    #[test]
+    fn test_external() {
+        let ctx = crate::JSContext::new();
+        let handle = std::sync::Arc::new(String::from("handle"));
+        let value = JSValue::external(&ctx, handle.clone());
+        assert!(value.is_external());
+        assert!(value.is_object());
+        assert_eq!(std::sync::Arc::strong_count(&handle), 2);
+
+        let external = value.as_external::<String>().unwrap();
+        assert_eq!(external.as_str(), "handle");
+        assert!(value.as_external::<i32>().is_none());
+        assert!(!JSValue::number(&ctx, 1.0).is_external());
+        assert!(JSValue::number(&ctx, 1.0).as_external::<String>().is_none());
+    }
 }
@@ -0,0 +1,3 @@
+export const answer = 42;
+
+globalThis.embeddedAnswer = `${answer}`;